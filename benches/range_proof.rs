@@ -10,11 +10,13 @@ use curve25519_dalek::scalar::Scalar;
 
 use merlin::Transcript;
 
-use tari_bulletproofs::RangeProof;
 use tari_bulletproofs::{BulletproofGens, PedersenGens};
+use tari_bulletproofs::{RangeProof, RangeProofStatement};
 
 static AGGREGATION_SIZES: [usize; 6] = [1, 2, 4, 8, 16, 32];
 
+static BATCH_SIZES: [usize; 5] = [1, 8, 32, 128, 512];
+
 fn create_aggregated_rangeproof_helper(n: usize, c: &mut Criterion) {
     let label = format!("Aggregated {}-bit rangeproof creation", n);
 
@@ -115,6 +117,58 @@ fn verify_aggregated_rangeproof_n_64(c: &mut Criterion) {
     verify_aggregated_rangeproof_helper(64, c);
 }
 
+fn verify_batch_rangeproof_helper(n: usize, c: &mut Criterion) {
+    let label = format!("Batched {}-bit rangeproof verification", n);
+
+    c.bench_function_over_inputs(
+        &label,
+        move |b, &&batch_size| {
+            let pc_gens = PedersenGens::default();
+            let bp_gens = BulletproofGens::new(n, 1);
+            let mut rng = rand::thread_rng();
+
+            let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
+            let proofs: Vec<_> = (0..batch_size)
+                .map(|_| {
+                    let value = rng.gen_range(min..max);
+                    let blinding = Scalar::random(&mut rng);
+                    let mut transcript = Transcript::new(b"BatchRangeProofBenchmark");
+                    RangeProof::prove_single(
+                        &bp_gens,
+                        &pc_gens,
+                        &mut transcript,
+                        value,
+                        &blinding,
+                        n,
+                    )
+                    .unwrap()
+                })
+                .collect();
+
+            b.iter(|| {
+                // Each proof verification requires a clean transcript.
+                let mut transcripts =
+                    vec![Transcript::new(b"BatchRangeProofBenchmark"); batch_size];
+                let statements = proofs.iter().zip(transcripts.iter_mut()).map(
+                    |((proof, value_commitment), transcript)| RangeProofStatement {
+                        proof,
+                        value_commitments: core::slice::from_ref(value_commitment),
+                        n,
+                        transcript,
+                    },
+                );
+
+                RangeProof::verify_batch(&bp_gens, &pc_gens, statements)
+            });
+        },
+        &BATCH_SIZES,
+    );
+}
+
+fn verify_batch_rangeproof_n_64(c: &mut Criterion) {
+    verify_batch_rangeproof_helper(64, c);
+}
+
 criterion_group! {
     name = create_rp;
     config = Criterion::default().sample_size(10);
@@ -135,4 +189,11 @@ criterion_group! {
     verify_aggregated_rangeproof_n_64,
 }
 
-criterion_main!(create_rp, verify_rp);
+criterion_group! {
+    name = verify_batch_rp;
+    config = Criterion::default().sample_size(10);
+    targets =
+    verify_batch_rangeproof_n_64,
+}
+
+criterion_main!(create_rp, verify_rp, verify_batch_rp);
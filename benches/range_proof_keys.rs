@@ -0,0 +1,70 @@
+#[macro_use]
+extern crate criterion;
+use criterion::Criterion;
+
+use curve25519_dalek::scalar::Scalar;
+
+use merlin::Transcript;
+
+use tari_bulletproofs::{BulletproofGens, PedersenGens, RangeProof, RangeProofVerifierKey};
+
+static AGGREGATION_SIZES: [usize; 4] = [1, 2, 4, 8];
+
+fn verify_with_and_without_key(c: &mut Criterion) {
+    let n = 64;
+
+    c.bench_function_over_inputs(
+        "64-bit rangeproof verification without a key",
+        move |b, &&m| {
+            let (bp_gens, pc_gens, proof, value_commitments) = proof_helper(n, m);
+            b.iter(|| {
+                let mut transcript = Transcript::new(b"RangeProofKeyBenchmark");
+                proof.verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+            })
+        },
+        &AGGREGATION_SIZES,
+    );
+
+    c.bench_function_over_inputs(
+        "64-bit rangeproof verification with a key",
+        move |b, &&m| {
+            let (bp_gens, pc_gens, proof, value_commitments) = proof_helper(n, m);
+            let key = RangeProofVerifierKey::new(&bp_gens, &pc_gens, n, m).unwrap();
+            b.iter(|| {
+                let mut transcript = Transcript::new(b"RangeProofKeyBenchmark");
+                key.verify(&proof, &mut transcript, &value_commitments)
+            })
+        },
+        &AGGREGATION_SIZES,
+    );
+}
+
+fn proof_helper(
+    n: usize,
+    m: usize,
+) -> (
+    BulletproofGens,
+    PedersenGens,
+    RangeProof,
+    Vec<curve25519_dalek::ristretto::CompressedRistretto>,
+) {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, m);
+    let mut rng = rand::thread_rng();
+
+    let values: Vec<u64> = (0..m as u64).collect();
+    let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+    let mut transcript = Transcript::new(b"RangeProofKeyBenchmark");
+    let (proof, value_commitments) =
+        RangeProof::prove_multiple(&bp_gens, &pc_gens, &mut transcript, &values, &blindings, n)
+            .unwrap();
+    (bp_gens, pc_gens, proof, value_commitments)
+}
+
+criterion_group! {
+    name = keys;
+    config = Criterion::default().sample_size(20);
+    targets = verify_with_and_without_key,
+}
+
+criterion_main!(keys);
@@ -0,0 +1,784 @@
+//! Deferred verification equations, which allow the checks of many
+//! proofs to be combined into a single multiscalar multiplication.
+
+#![allow(non_snake_case)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate rand;
+
+#[cfg(feature = "std")]
+use self::rand::thread_rng;
+use alloc::vec::Vec;
+#[cfg(all(feature = "yoloproofs", feature = "std"))]
+use core::borrow::BorrowMut;
+use core::iter;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, VartimeRistrettoPrecomputation};
+use curve25519_dalek::traits::{IsIdentity, VartimePrecomputedMultiscalarMul};
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+use crate::group::{Group, Ristretto, ScalarField};
+use crate::msm::MsmBackend;
+#[cfg(all(feature = "yoloproofs", feature = "std"))]
+use crate::r1cs::{PendingCheck, R1CSError, R1CSProof, Verifier};
+use crate::range_proof::RangeProof;
+use crate::surjection_proof::SurjectionProof;
+
+/// Accumulates the verification equations of proofs received one by
+/// one, to verify all of them at once with [`BatchVerifier::finish`].
+///
+/// Each proof added to the batch is checked for well-formedness and
+/// its transcript is replayed immediately, but its verification
+/// equation is only stored.  When the batch is finished, the equations
+/// are weighted by random scalars and evaluated in a single multiscalar
+/// multiplication, in which the generators shared between the proofs
+/// appear once.
+///
+/// The constraints of constraint system proofs are only flattened into
+/// their equations when the batch is finished.  With the `parallel`
+/// feature, this is done for all of the proofs in parallel, and the
+/// multiscalar multiplication is split across the threads of the rayon
+/// thread pool.
+///
+/// If the batch fails to verify, [`BatchVerifier::find_invalid`]
+/// identifies the invalid proofs by bisecting the batch.  The proofs
+/// are numbered in the order of the calls to the `add_*` methods,
+/// counting the proofs which were rejected when they were added.
+pub struct BatchVerifier<'g, G: Group = Ristretto> {
+    bp_gens: &'g BulletproofGens<G>,
+    pc_gens: ExtendedPedersenGens<G>,
+    /// The proofs in the order they were added, with `None` for those
+    /// which were rejected, so that their indices follow the caller's.
+    entries: Vec<Option<BatchEntry<G>>>,
+}
+
+/// A proof in a [`BatchVerifier`].
+enum BatchEntry<G: Group> {
+    /// A proof whose verification equation is constructed.
+    Ready(DeferredCheck<G>),
+    /// A constraint system proof whose constraints are yet to be
+    /// flattened into its verification equation.
+    #[cfg(all(feature = "yoloproofs", feature = "std"))]
+    Pending(Box<PendingCheck<G>>),
+}
+
+impl<G: Group> BatchEntry<G> {
+    fn into_check(self) -> DeferredCheck<G> {
+        match self {
+            BatchEntry::Ready(check) => check,
+            #[cfg(all(feature = "yoloproofs", feature = "std"))]
+            BatchEntry::Pending(pending) => pending.into_check(),
+        }
+    }
+}
+
+impl<'g> BatchVerifier<'g> {
+    /// Creates an empty batch for proofs over the given generators.
+    pub fn new(bp_gens: &'g BulletproofGens, pc_gens: &PedersenGens) -> Self {
+        BatchVerifier::for_group(bp_gens, pc_gens)
+    }
+
+    /// Creates an empty batch for proofs over the given generators,
+    /// which may include range proofs for extended Pedersen commitments
+    /// of exactly the extension degree of the `pc_gens`.
+    pub fn new_extended(bp_gens: &'g BulletproofGens, pc_gens: &ExtendedPedersenGens) -> Self {
+        BatchVerifier::extended_for_group(bp_gens, pc_gens)
+    }
+
+    /// Adds a surjection proof for the given asset tags to the batch,
+    /// with the given verifier's transcript.
+    pub fn add_surjection_proof<T: RngCore + CryptoRng>(
+        &mut self,
+        proof: &SurjectionProof,
+        transcript: &mut Transcript,
+        input_tags: &[CompressedRistretto],
+        output_tag: &CompressedRistretto,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let check = proof.verification_check(transcript, input_tags, output_tag, rng);
+        self.push(check.map(|check| (BatchEntry::Ready(check), ())))
+    }
+}
+
+impl<'g, G: Group> BatchVerifier<'g, G> {
+    /// Creates an empty batch for proofs in the group `G` over the
+    /// given generators.
+    pub fn for_group(bp_gens: &'g BulletproofGens<G>, pc_gens: &PedersenGens<G>) -> Self {
+        BatchVerifier::extended_for_group(bp_gens, &ExtendedPedersenGens::from(*pc_gens))
+    }
+
+    /// Creates an empty batch for proofs in the group `G` over the
+    /// given extended generators, like [`BatchVerifier::new_extended`].
+    pub fn extended_for_group(
+        bp_gens: &'g BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+    ) -> Self {
+        BatchVerifier {
+            bp_gens,
+            pc_gens: pc_gens.clone(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the number of proofs in the batch, not counting those
+    /// which were rejected when they were added.
+    pub fn len(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.is_some()).count()
+    }
+
+    /// Returns `true` if there are no proofs in the batch.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Records the outcome of adding a proof, keeping the place of a
+    /// rejected proof in the numbering of the batch.
+    fn push<T, E>(&mut self, entry: Result<(BatchEntry<G>, T), E>) -> Result<T, E> {
+        match entry {
+            Ok((entry, value)) => {
+                self.entries.push(Some(entry));
+                Ok(value)
+            }
+            Err(e) => {
+                self.entries.push(None);
+                Err(e)
+            }
+        }
+    }
+
+    /// Adds an aggregated `n`-bit range proof for the given value
+    /// commitments to the batch, with the given verifier's transcript.
+    ///
+    /// The extension degree of the proof must match the generators of
+    /// the batch.
+    pub fn add_range_proof<T: RngCore + CryptoRng>(
+        &mut self,
+        proof: &RangeProof<G>,
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let check = proof.verification_check(
+            self.bp_gens,
+            self.pc_gens.extension_degree(),
+            None,
+            transcript,
+            value_commitments,
+            n,
+            rng,
+        );
+        self.push(check.map(|check| (BatchEntry::Ready(check), ())))
+    }
+
+    /// Adds an aggregated `n`-bit range proof for the given value
+    /// commitments, each of which is made with the value base at the
+    /// same position in `value_bases` instead of the `B` of the batch's
+    /// generators, like
+    /// [`RangeProof::verify_multiple_with_value_bases`].
+    ///
+    /// The extension degree of the proof must match the generators of
+    /// the batch.
+    pub fn add_range_proof_with_value_bases<T: RngCore + CryptoRng>(
+        &mut self,
+        proof: &RangeProof<G>,
+        value_bases: &[G::Point],
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let check = proof.verification_check(
+            self.bp_gens,
+            self.pc_gens.extension_degree(),
+            Some(value_bases),
+            transcript,
+            value_commitments,
+            n,
+            rng,
+        );
+        self.push(check.map(|check| (BatchEntry::Ready(check), ())))
+    }
+
+    /// Adds a constraint system proof to the batch, consuming the
+    /// `verifier` which holds its constraint system, and returns the
+    /// verifier's transcript like
+    /// [`Verifier::verify_and_return_transcript`].
+    #[cfg(all(feature = "yoloproofs", feature = "std"))]
+    pub fn add_r1cs_proof<T: BorrowMut<Transcript>>(
+        &mut self,
+        verifier: Verifier<T, G>,
+        proof: &R1CSProof<G>,
+    ) -> Result<T, R1CSError> {
+        let pending = verifier.pending_check(proof, self.bp_gens);
+        self.push(
+            pending
+                .map(|(pending, transcript)| (BatchEntry::Pending(Box::new(pending)), transcript)),
+        )
+    }
+
+    /// Verifies all of the proofs in the batch at once.
+    ///
+    /// The batch fails if any proof was rejected when it was added, so
+    /// that a proof cannot be accepted by ignoring the error of its
+    /// `add_*` call.
+    pub fn finish_with_rng<T: RngCore + CryptoRng>(self, rng: &mut T) -> Result<(), ProofError> {
+        if self.entries.iter().any(Option::is_none) {
+            return Err(ProofError::VerificationError);
+        }
+        let (bp_gens, pc_gens, checks) = self.into_checks();
+        let checks: Vec<DeferredCheck<G>> = checks.into_iter().flatten().collect();
+        let weights: Vec<G::Scalar> = checks.iter().map(|_| G::Scalar::random(rng)).collect();
+        if verify_batch(weights.into_iter().zip(checks.iter()), bp_gens, &pc_gens) {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Verifies all of the proofs in the batch at once.
+    ///
+    /// This is a convenience wrapper around [`BatchVerifier::finish_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn finish(self) -> Result<(), ProofError> {
+        self.finish_with_rng(&mut thread_rng())
+    }
+
+    /// Verifies all of the proofs in the batch and returns the indices
+    /// of the invalid ones, in increasing order.
+    ///
+    /// The proofs are numbered in the order of the calls to the `add_*`
+    /// methods, and the proofs which were rejected when they were added
+    /// are reported as invalid as well.  If the batch holds, this costs
+    /// no more than [`BatchVerifier::finish_with_rng`]; otherwise the
+    /// batch is bisected to find the invalid proofs, which for a single
+    /// invalid proof takes logarithmically many further batch
+    /// verifications.
+    pub fn find_invalid_with_rng<T: RngCore + CryptoRng>(self, rng: &mut T) -> Vec<usize> {
+        let (bp_gens, pc_gens, entries) = self.into_checks();
+
+        let mut invalid = Vec::new();
+        let mut indices = Vec::new();
+        let mut checks = Vec::new();
+        for (i, check) in entries.into_iter().enumerate() {
+            match check {
+                Some(check) => {
+                    indices.push(i);
+                    checks.push(check);
+                }
+                None => invalid.push(i),
+            }
+        }
+
+        invalid.extend(
+            find_invalid(&checks, bp_gens, &pc_gens, rng)
+                .into_iter()
+                .map(|k| indices[k]),
+        );
+        invalid.sort_unstable();
+        invalid
+    }
+
+    /// Verifies all of the proofs in the batch and returns the indices
+    /// of the invalid ones.
+    ///
+    /// This is a convenience wrapper around [`BatchVerifier::find_invalid_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn find_invalid(self) -> Vec<usize> {
+        self.find_invalid_with_rng(&mut thread_rng())
+    }
+
+    /// Constructs the verification equations of all of the proofs in
+    /// the batch, in the order in which they were added.
+    #[allow(clippy::type_complexity)]
+    fn into_checks(
+        self,
+    ) -> (
+        &'g BulletproofGens<G>,
+        ExtendedPedersenGens<G>,
+        Vec<Option<DeferredCheck<G>>>,
+    ) {
+        let checks = maybe_par_iter!(self.entries)
+            .map(|entry| entry.map(BatchEntry::into_check))
+            .collect();
+        (self.bp_gens, self.pc_gens, checks)
+    }
+}
+
+/// The terms of a verification equation which holds if and only if
+/// the multiscalar multiplication they describe is the identity.
+///
+/// The terms are split into points which are specific to a single
+/// proof, and scalars for the generators which are shared between
+/// all proofs, so that the shared generators only need to appear
+/// once in a batched multiscalar multiplication.
+#[derive(Clone, Debug)]
+pub(crate) struct DeferredCheck<G: Group = Ristretto> {
+    /// Scalars for the points which are specific to this proof.
+    pub(crate) dynamic_scalars: Vec<G::Scalar>,
+    /// Points which are specific to this proof.
+    pub(crate) dynamic_points: Vec<G::Point>,
+    /// Scalar for the Pedersen value base \\(B\\).
+    pub(crate) B_scalar: G::Scalar,
+    /// Scalars for the Pedersen blinding bases \\(\tilde{B}\_1, \dots, \tilde{B}\_d\\).
+    pub(crate) B_blinding_scalars: Vec<G::Scalar>,
+    /// Scalars for the \\(\mathbf{G}\\) generators, indexed by party and then generator.
+    pub(crate) G_scalars: Vec<Vec<G::Scalar>>,
+    /// Scalars for the \\(\mathbf{H}\\) generators, indexed by party and then generator.
+    pub(crate) H_scalars: Vec<Vec<G::Scalar>>,
+}
+
+impl<G: Group> DeferredCheck<G> {
+    /// Evaluates this check on its own.
+    pub(crate) fn verify(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+    ) -> bool {
+        verify_batch(iter::once((G::Scalar::one(), self)), bp_gens, pc_gens)
+    }
+}
+
+impl DeferredCheck {
+    /// Evaluates this check on its own with a precomputation over the
+    /// static points \\(B, \tilde{B}\_1, \dots, \tilde{B}\_d\\), the
+    /// \\(\mathbf{G}\\) generators of each party and then the
+    /// \\(\mathbf{H}\\) generators of each party, which must match the
+    /// shape of the check.
+    pub(crate) fn verify_precomputed(
+        &self,
+        precomputation: &VartimeRistrettoPrecomputation,
+    ) -> bool {
+        let static_scalars = iter::once(&self.B_scalar)
+            .chain(self.B_blinding_scalars.iter())
+            .chain(self.G_scalars.iter().flatten())
+            .chain(self.H_scalars.iter().flatten());
+
+        let mega_check = precomputation.vartime_mixed_multiscalar_mul(
+            static_scalars,
+            self.dynamic_scalars.iter(),
+            self.dynamic_points.iter(),
+        );
+
+        mega_check.is_identity()
+    }
+}
+
+/// Evaluates the sum of the given checks, each multiplied by its
+/// weight, in a single multiscalar multiplication.
+///
+/// The weights must be chosen uniformly at random by the verifier,
+/// otherwise failing checks could cancel each other out.
+pub(crate) fn verify_batch<'a, G, I>(
+    checks: I,
+    bp_gens: &BulletproofGens<G>,
+    pc_gens: &ExtendedPedersenGens<G>,
+) -> bool
+where
+    G: Group,
+    I: IntoIterator<Item = (G::Scalar, &'a DeferredCheck<G>)>,
+{
+    let mut dynamic_scalars = Vec::new();
+    let mut dynamic_points = Vec::new();
+    let mut B_scalar = G::Scalar::zero();
+    let mut B_blinding_scalars = Vec::new();
+    let mut G_scalars: Vec<Vec<G::Scalar>> = Vec::new();
+    let mut H_scalars: Vec<Vec<G::Scalar>> = Vec::new();
+
+    for (weight, check) in checks {
+        dynamic_scalars.extend(check.dynamic_scalars.iter().map(|s| weight * *s));
+        dynamic_points.extend_from_slice(&check.dynamic_points);
+        B_scalar += weight * check.B_scalar;
+        if B_blinding_scalars.len() < check.B_blinding_scalars.len() {
+            B_blinding_scalars.resize(check.B_blinding_scalars.len(), G::Scalar::zero());
+        }
+        for (acc, s) in B_blinding_scalars
+            .iter_mut()
+            .zip(check.B_blinding_scalars.iter())
+        {
+            *acc += weight * *s;
+        }
+        accumulate(&mut G_scalars, &check.G_scalars, &weight);
+        accumulate(&mut H_scalars, &check.H_scalars, &weight);
+    }
+
+    // A check for more blinding bases than the generators provide
+    // cannot hold.
+    if B_blinding_scalars.len() > pc_gens.B_blinding.len() {
+        return false;
+    }
+
+    // The multiscalar multiplication requires exact size hints, which
+    // the flattened iterators over the ragged generator lists lack.
+    let points: Vec<G::Point> = dynamic_points
+        .iter()
+        .chain(iter::once(&pc_gens.B))
+        .chain(pc_gens.B_blinding.iter().take(B_blinding_scalars.len()))
+        .chain(
+            G_scalars
+                .iter()
+                .enumerate()
+                .flat_map(|(j, G_j)| bp_gens.share(j).G(G_j.len())),
+        )
+        .chain(
+            H_scalars
+                .iter()
+                .enumerate()
+                .flat_map(|(j, H_j)| bp_gens.share(j).H(H_j.len())),
+        )
+        .cloned()
+        .collect();
+    let scalars: Vec<G::Scalar> = dynamic_scalars
+        .into_iter()
+        .chain(iter::once(B_scalar))
+        .chain(B_blinding_scalars)
+        .chain(G_scalars.into_iter().flatten())
+        .chain(H_scalars.into_iter().flatten())
+        .collect();
+
+    let mega_check = vartime_multiscalar_mul(bp_gens.msm_backend(), &scalars, &points);
+
+    mega_check == G::identity()
+}
+
+/// Computes a variable-time multiscalar multiplication with `msm`, which
+/// with the `parallel` feature is split into one chunk per thread of the
+/// rayon thread pool.
+fn vartime_multiscalar_mul<G: Group>(
+    msm: &dyn MsmBackend<G>,
+    scalars: &[G::Scalar],
+    points: &[G::Point],
+) -> G::Point {
+    #[cfg(feature = "parallel")]
+    {
+        let threads = rayon::current_num_threads();
+        let chunk_size = scalars.len().div_ceil(threads).max(1);
+        scalars
+            .par_chunks(chunk_size)
+            .zip(points.par_chunks(chunk_size))
+            .map(|(scalars, points)| msm.vartime_multiscalar_mul(scalars, points))
+            .reduce(G::identity, |acc, P| acc + P)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        msm.vartime_multiscalar_mul(scalars, points)
+    }
+}
+
+/// Returns the indices of the failing checks among `checks`, in
+/// increasing order.
+///
+/// The checks are verified together first, and a failing batch is
+/// split in halves which are verified recursively, each time with
+/// fresh random weights.  When the first half of a failing batch holds,
+/// the second half is known to fail without verifying it, so that a
+/// single invalid check among \\(k\\) is found with about
+/// \\(\log\_2 k\\) batch verifications over shrinking subsets.
+pub(crate) fn find_invalid<G: Group, T: RngCore + CryptoRng>(
+    checks: &[DeferredCheck<G>],
+    bp_gens: &BulletproofGens<G>,
+    pc_gens: &ExtendedPedersenGens<G>,
+    rng: &mut T,
+) -> Vec<usize> {
+    let indices: Vec<usize> = (0..checks.len()).collect();
+    let mut invalid = Vec::new();
+    if !indices.is_empty() && !verify_subset(checks, &indices, bp_gens, pc_gens, rng) {
+        bisect(checks, &indices, bp_gens, pc_gens, rng, &mut invalid);
+    }
+    invalid
+}
+
+/// Collects the failing checks among `indices` into `invalid`, given
+/// that the checks at `indices` are known to fail together.
+fn bisect<G: Group, T: RngCore + CryptoRng>(
+    checks: &[DeferredCheck<G>],
+    indices: &[usize],
+    bp_gens: &BulletproofGens<G>,
+    pc_gens: &ExtendedPedersenGens<G>,
+    rng: &mut T,
+    invalid: &mut Vec<usize>,
+) {
+    if indices.len() == 1 {
+        invalid.push(indices[0]);
+        return;
+    }
+
+    let (left, right) = indices.split_at(indices.len() / 2);
+    if verify_subset(checks, left, bp_gens, pc_gens, rng) {
+        bisect(checks, right, bp_gens, pc_gens, rng, invalid);
+    } else {
+        bisect(checks, left, bp_gens, pc_gens, rng, invalid);
+        if !verify_subset(checks, right, bp_gens, pc_gens, rng) {
+            bisect(checks, right, bp_gens, pc_gens, rng, invalid);
+        }
+    }
+}
+
+/// Verifies the checks at `indices` together with fresh random weights.
+fn verify_subset<G: Group, T: RngCore + CryptoRng>(
+    checks: &[DeferredCheck<G>],
+    indices: &[usize],
+    bp_gens: &BulletproofGens<G>,
+    pc_gens: &ExtendedPedersenGens<G>,
+    rng: &mut T,
+) -> bool {
+    let weights: Vec<G::Scalar> = indices.iter().map(|_| G::Scalar::random(rng)).collect();
+    verify_batch(
+        weights.into_iter().zip(indices.iter().map(|&i| &checks[i])),
+        bp_gens,
+        pc_gens,
+    )
+}
+
+/// Adds `weight` times the per-party `scalars` into `acc`, growing
+/// `acc` as needed so that proofs of different sizes can be combined.
+fn accumulate<S: ScalarField>(acc: &mut Vec<Vec<S>>, scalars: &[Vec<S>], weight: &S) {
+    if acc.len() < scalars.len() {
+        acc.resize(scalars.len(), Vec::new());
+    }
+    for (acc_j, scalars_j) in acc.iter_mut().zip(scalars.iter()) {
+        if acc_j.len() < scalars_j.len() {
+            acc_j.resize(scalars_j.len(), S::zero());
+        }
+        for (a, s) in acc_j.iter_mut().zip(scalars_j.iter()) {
+            *a += *weight * *s;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use curve25519_dalek::scalar::Scalar;
+    use curve25519_dalek::traits::Identity;
+
+    use crate::msm::{DalekMsm, PippengerMsm};
+
+    #[test]
+    fn batch_with_value_bases() {
+        let n = 16;
+        let pc_gens = ExtendedPedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, 2);
+        let mut rng = rand::thread_rng();
+
+        let value_bases = [
+            ExtendedPedersenGens::asset_value_base(b"asset A"),
+            ExtendedPedersenGens::asset_value_base(b"asset B"),
+        ];
+        let blindings = [
+            vec![Scalar::random(&mut rng)],
+            vec![Scalar::random(&mut rng)],
+        ];
+        let (multi_asset_proof, multi_asset_commitments) =
+            RangeProof::prove_multiple_with_value_bases(
+                &bp_gens,
+                &pc_gens,
+                &value_bases,
+                &mut Transcript::new(b"BatchValueBasesTest"),
+                &[7, 1 << 15],
+                &blindings,
+                n,
+            )
+            .unwrap();
+        let (single_asset_proof, single_asset_commitments) = RangeProof::prove_multiple_extended(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"BatchValueBasesTest"),
+            &[42, 4242],
+            &blindings,
+            n,
+        )
+        .unwrap();
+
+        let mut batch_helper = |value_bases: &[RistrettoPoint]| {
+            let mut batch = BatchVerifier::new_extended(&bp_gens, &pc_gens);
+            batch
+                .add_range_proof(
+                    &single_asset_proof,
+                    &mut Transcript::new(b"BatchValueBasesTest"),
+                    &single_asset_commitments,
+                    n,
+                    &mut rng,
+                )
+                .unwrap();
+            batch
+                .add_range_proof_with_value_bases(
+                    &multi_asset_proof,
+                    value_bases,
+                    &mut Transcript::new(b"BatchValueBasesTest"),
+                    &multi_asset_commitments,
+                    n,
+                    &mut rng,
+                )
+                .unwrap();
+            batch.finish()
+        };
+
+        assert!(batch_helper(&value_bases).is_ok());
+        assert_eq!(
+            batch_helper(&[value_bases[1], value_bases[0]]),
+            Err(ProofError::VerificationError)
+        );
+
+        // A proof over value bases cannot be added without them, and
+        // keeps its place in the numbering of the invalid proofs
+        let mut batch = BatchVerifier::new_extended(&bp_gens, &pc_gens);
+        assert_eq!(
+            batch.add_range_proof(
+                &multi_asset_proof,
+                &mut Transcript::new(b"BatchValueBasesTest"),
+                &multi_asset_commitments,
+                n,
+                &mut rng,
+            ),
+            Err(ProofError::InvalidValueBases)
+        );
+        for bases in &[[value_bases[1], value_bases[0]], value_bases] {
+            batch
+                .add_range_proof_with_value_bases(
+                    &multi_asset_proof,
+                    bases,
+                    &mut Transcript::new(b"BatchValueBasesTest"),
+                    &multi_asset_commitments,
+                    n,
+                    &mut rng,
+                )
+                .unwrap();
+        }
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch.find_invalid(), vec![0, 1]);
+
+        // A rejected proof makes the batch fail, even if all of the
+        // proofs which were added are valid
+        let mut batch = BatchVerifier::new_extended(&bp_gens, &pc_gens);
+        assert!(batch
+            .add_range_proof(
+                &multi_asset_proof,
+                &mut Transcript::new(b"BatchValueBasesTest"),
+                &multi_asset_commitments,
+                n,
+                &mut rng,
+            )
+            .is_err());
+        batch
+            .add_range_proof(
+                &single_asset_proof,
+                &mut Transcript::new(b"BatchValueBasesTest"),
+                &single_asset_commitments,
+                n,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(batch.finish(), Err(ProofError::VerificationError));
+    }
+
+    #[test]
+    #[cfg(feature = "yoloproofs")]
+    fn r1cs_proofs_in_another_group() {
+        use crate::group::tests::{Fp, ToyGroup};
+        use crate::r1cs::{
+            ConstraintSystem, LinearCombination, Prover, R1CSError, RandomizableConstraintSystem,
+            RandomizedConstraintSystem, Variable,
+        };
+
+        /// Constrains `y` to be a permutation of `x`, with a challenge
+        /// drawn in the randomized phase.
+        fn shuffle_gadget<CS: RandomizableConstraintSystem<Fp>>(
+            cs: &mut CS,
+            x: [Variable; 2],
+            y: [Variable; 2],
+        ) -> Result<(), R1CSError> {
+            cs.specify_randomized_constraints(move |cs| {
+                let z = cs.challenge_scalar(b"shuffle challenge");
+                let minus_z = |v: Variable| -> LinearCombination<Fp> {
+                    vec![(v, Fp::one()), (Variable::One(), -z)]
+                        .into_iter()
+                        .collect()
+                };
+                let (_, _, x_mul) = cs.multiply(minus_z(x[0]), minus_z(x[1]));
+                let (_, _, y_mul) = cs.multiply(minus_z(y[0]), minus_z(y[1]));
+                cs.constrain(LinearCombination::from(x_mul) - y_mul);
+                Ok(())
+            })
+        }
+
+        let pc_gens = PedersenGens::<ToyGroup>::for_group();
+        let bp_gens = BulletproofGens::<ToyGroup>::for_group(8, 1);
+        let mut rng = rand::thread_rng();
+
+        let mut prove = |values: [u64; 4]| {
+            let mut prover = Prover::new(&pc_gens, Transcript::new(b"AnotherGroupR1CSTest"));
+            let (commitments, vars): (Vec<_>, Vec<_>) = values
+                .iter()
+                .map(|v| prover.commit(Fp::from(*v), Fp::random(&mut rng)))
+                .unzip();
+            shuffle_gadget(&mut prover, [vars[0], vars[1]], [vars[2], vars[3]]).unwrap();
+            (prover.prove(&bp_gens).unwrap(), commitments)
+        };
+        let verifier_for = |commitments: &[<ToyGroup as Group>::CompressedPoint]| {
+            let mut verifier = Verifier::for_group(Transcript::new(b"AnotherGroupR1CSTest"));
+            let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+            shuffle_gadget(&mut verifier, [vars[0], vars[1]], [vars[2], vars[3]]).unwrap();
+            verifier
+        };
+
+        let (proof, commitments) = prove([3, 7, 7, 3]);
+        let (bad_proof, bad_commitments) = prove([3, 7, 7, 4]);
+
+        let proof: R1CSProof<ToyGroup> =
+            bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+        assert!(verifier_for(&commitments)
+            .verify(&proof, &pc_gens, &bp_gens)
+            .is_ok());
+        assert_eq!(
+            verifier_for(&bad_commitments).verify(&bad_proof, &pc_gens, &bp_gens),
+            Err(R1CSError::VerificationError)
+        );
+
+        let mut batch = BatchVerifier::for_group(&bp_gens, &pc_gens);
+        batch
+            .add_r1cs_proof(verifier_for(&commitments), &proof)
+            .unwrap();
+        batch
+            .add_r1cs_proof(verifier_for(&bad_commitments), &bad_proof)
+            .unwrap();
+        assert_eq!(batch.find_invalid(), vec![1]);
+    }
+
+    #[test]
+    fn multiscalar_mul_matches_sum_of_products() {
+        let mut rng = rand::thread_rng();
+        for &len in &[0, 1, 2, 7, 64, 129] {
+            let scalars: Vec<Scalar> = (0..len).map(|_| Scalar::random(&mut rng)).collect();
+            let points: Vec<RistrettoPoint> =
+                (0..len).map(|_| RistrettoPoint::random(&mut rng)).collect();
+            let expected = scalars
+                .iter()
+                .zip(points.iter())
+                .fold(RistrettoPoint::identity(), |acc, (s, P)| acc + s * P);
+            for msm in &[&DalekMsm as &dyn MsmBackend, &PippengerMsm::new(5)] {
+                assert_eq!(vartime_multiscalar_mul(*msm, &scalars, &points), expected);
+
+                // Split the multiplication into chunks even on a single core
+                #[cfg(feature = "parallel")]
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(4)
+                    .build()
+                    .unwrap()
+                    .install(|| {
+                        assert_eq!(vartime_multiscalar_mul(*msm, &scalars, &points), expected)
+                    });
+            }
+        }
+    }
+}
@@ -21,14 +21,15 @@ pub enum ProofError {
     #[cfg_attr(feature = "std", error("Wrong number of blinding factors supplied."))]
     WrongNumBlindingFactors,
     /// This error occurs when attempting to create a proof with
-    /// bitsize other than \\(8\\), \\(16\\), \\(32\\), or \\(64\\).
-    #[cfg_attr(feature = "std", error("Invalid bitsize, must have n = 8,16,32,64."))]
+    /// bitsize outside of \\(1 \leq n \leq 128\\), or to rewind
+    /// data which does not fit in a proof of the given bitsize.
+    #[cfg_attr(feature = "std", error("Invalid bitsize, must have 1 <= n <= 128."))]
     InvalidBitsize,
-    /// This error occurs when attempting to create an aggregated
-    /// proof with non-power-of-two aggregation size.
+    /// This error occurs when attempting to create or verify an
+    /// aggregated proof for zero values.
     #[cfg_attr(
         feature = "std",
-        error("Invalid aggregation size, m must be a power of 2.")
+        error("Invalid aggregation size, m must be at least 1.")
     )]
     InvalidAggregation,
     /// This error occurs when there are insufficient generators for the proof.
@@ -57,6 +58,75 @@ pub enum ProofError {
         error("Trying to rewind a proof with the wrong rewind key separator")
     )]
     InvalidRewindKeySeparator,
+    /// This error occurs when attempting to create or verify an
+    /// interval proof whose lower bound exceeds its upper bound.
+    #[cfg_attr(
+        feature = "std",
+        error("Invalid interval, the lower bound must not exceed the upper bound.")
+    )]
+    InvalidInterval,
+    /// This error occurs when attempting to prove that a value lies
+    /// in an interval which does not contain it.
+    #[cfg_attr(
+        feature = "std",
+        error("The value is outside of the interval being proven.")
+    )]
+    ValueOutOfRange,
+    /// This error occurs when attempting to embed a message which is
+    /// longer than a rewindable proof of the given bitsize can hold.
+    #[cfg_attr(
+        feature = "std",
+        error("The proof message is too long for the bitsize of the proof.")
+    )]
+    InvalidProofMessageLength,
+    /// This error results from trying to rewind a proof with rewind
+    /// keys or a nonce generator other than the ones it was created
+    /// with, detected by the tag embedded alongside its value.
+    #[cfg_attr(
+        feature = "std",
+        error("Rewinding the proof failed, the rewind keys do not match")
+    )]
+    InvalidRewindKeys,
+    /// This error occurs when the extension degree of the generators,
+    /// the blinding factors or the proof do not match, or when
+    /// generators with an extension degree of zero are requested.
+    #[cfg_attr(
+        feature = "std",
+        error("Invalid extension degree, the generators, blinding factors and proof must match")
+    )]
+    InvalidExtensionDegree,
+    /// This error occurs when the number of value bases does not match
+    /// the number of values or commitments, or when a proof over
+    /// distinct value bases is verified without them, or vice versa.
+    #[cfg_attr(
+        feature = "std",
+        error("Invalid value bases, there must be one per value commitment")
+    )]
+    InvalidValueBases,
+    /// This error occurs when attempting to prove that an output asset
+    /// tag is a re-blinding of an input asset tag which it is not, or
+    /// of an input which does not exist.
+    #[cfg_attr(
+        feature = "std",
+        error("The output tag is not a re-blinding of the given input tag.")
+    )]
+    InvalidSurjection,
+    /// This error occurs when a prover or verifier key is used with a
+    /// number of values or value commitments other than the
+    /// aggregation size it was built for.
+    #[cfg_attr(
+        feature = "std",
+        error("Wrong number of values, the key is for a different aggregation size.")
+    )]
+    WrongNumValues,
+    /// This error occurs when a party whose proof is meant to be
+    /// rewindable is aggregated with other parties, which would make
+    /// its value, blinding factor and message unrecoverable.
+    #[cfg_attr(
+        feature = "std",
+        error("Rewindable proofs can only be created for a single value.")
+    )]
+    InvalidRewindableAggregation,
 }
 
 impl From<MPCError> for ProofError {
@@ -65,6 +135,9 @@ impl From<MPCError> for ProofError {
             MPCError::InvalidBitsize => ProofError::InvalidBitsize,
             MPCError::InvalidAggregation => ProofError::InvalidAggregation,
             MPCError::InvalidGeneratorsLength => ProofError::InvalidGeneratorsLength,
+            MPCError::InvalidProofMessageLength => ProofError::InvalidProofMessageLength,
+            MPCError::InvalidExtensionDegree => ProofError::InvalidExtensionDegree,
+            MPCError::InvalidRewindableAggregation => ProofError::InvalidRewindableAggregation,
             _ => ProofError::ProvingError(e),
         }
     }
@@ -85,14 +158,14 @@ pub enum MPCError {
     #[cfg_attr(feature = "std", error("Dealer gave a malicious challenge value."))]
     MaliciousDealer,
     /// This error occurs when attempting to create a proof with
-    /// bitsize other than \\(8\\), \\(16\\), \\(32\\), or \\(64\\).
-    #[cfg_attr(feature = "std", error("Invalid bitsize, must have n = 8,16,32,64"))]
+    /// bitsize outside of \\(1 \leq n \leq 128\\).
+    #[cfg_attr(feature = "std", error("Invalid bitsize, must have 1 <= n <= 128"))]
     InvalidBitsize,
     /// This error occurs when attempting to create an aggregated
-    /// proof with non-power-of-two aggregation size.
+    /// proof for zero parties.
     #[cfg_attr(
         feature = "std",
-        error("Invalid aggregation size, m must be a power of 2")
+        error("Invalid aggregation size, m must be at least 1")
     )]
     InvalidAggregation,
     /// This error occurs when there are insufficient generators for the proof.
@@ -123,6 +196,93 @@ pub enum MPCError {
         /// A vector with the indexes of the parties whose shares were malformed.
         bad_shares: Vec<usize>,
     },
+    /// This error occurs when attempting to embed a message which is
+    /// longer than a rewindable proof of the given bitsize can hold.
+    #[cfg_attr(
+        feature = "std",
+        error("The proof message is too long for the bitsize of the proof")
+    )]
+    InvalidProofMessageLength,
+    /// This error occurs when a party is given a number of blinding
+    /// factors other than the extension degree of the generators.
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "Invalid extension degree, the number of blinding factors must match the generators"
+        )
+    )]
+    InvalidExtensionDegree,
+    /// This error occurs when a party whose proof is meant to be
+    /// rewindable is assigned a position other than the first, which
+    /// means that it is aggregated with other parties.
+    #[cfg_attr(
+        feature = "std",
+        error("Rewindable proofs can only be created for a single value")
+    )]
+    InvalidRewindableAggregation,
+}
+
+/// Represents an error while driving the aggregated multiparty
+/// computation protocol over a transport with a
+/// [`session`](crate::range_proof_mpc::session).
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum SessionError {
+    /// This error occurs when a step of the protocol fails.
+    #[error("MPC protocol error: {0}")]
+    ProtocolError(MPCError),
+    /// This error occurs when a frame cannot be parsed, or when a
+    /// message cannot be framed.
+    #[error("Malformed session frame.")]
+    FormatError,
+    /// This error occurs when a frame is received for another session.
+    #[error("Received a frame for another session.")]
+    WrongSession,
+    /// This error occurs when a frame carries a message other than
+    /// the next one of the protocol, or comes from an unexpected
+    /// position.
+    #[error("Received an unexpected message from position {position}")]
+    UnexpectedMessage {
+        /// The position in the aggregation named by the frame.
+        position: usize,
+    },
+    /// This error occurs when no frame arrives before the timeout.
+    #[error("Timed out waiting for a message.")]
+    Timeout,
+    /// This error occurs when the other end of a transport has
+    /// disconnected.
+    #[error("The transport was disconnected.")]
+    Disconnected,
+    /// This error occurs when the transport fails for another reason.
+    #[error("Transport error: {description}")]
+    TransportError {
+        /// The description of the reasons for the error.
+        description: String,
+    },
+}
+
+#[cfg(feature = "std")]
+impl From<MPCError> for SessionError {
+    fn from(e: MPCError) -> SessionError {
+        SessionError::ProtocolError(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for SessionError {
+    fn from(e: std::io::Error) -> SessionError {
+        use std::io::ErrorKind;
+        match e.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => SessionError::Timeout,
+            ErrorKind::UnexpectedEof
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe => SessionError::Disconnected,
+            _ => SessionError::TransportError {
+                description: e.to_string(),
+            },
+        }
+    }
 }
 
 /// Represents an error during the proving or verifying of a constraint system.
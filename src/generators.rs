@@ -6,15 +6,18 @@
 
 extern crate alloc;
 
+use alloc::sync::Arc;
 use alloc::vec::Vec;
-use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
-use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use core::iter;
+use core::marker::PhantomData;
 use curve25519_dalek::ristretto::RistrettoPoint;
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::MultiscalarMul;
-use digest::{ExtendableOutputDirty, Update, XofReader};
+use digest::{Digest, ExtendableOutputDirty, Update, XofReader};
 use sha3::{Sha3XofReader, Sha3_512, Shake256};
 
+use crate::errors::ProofError;
+use crate::group::{Group, Ristretto};
+use crate::msm::{DalekMsm, GroupMsm, MsmBackend};
+
 /// Represents a pair of base points for Pedersen commitments.
 ///
 /// The Bulletproofs implementation and API is designed to support
@@ -26,40 +29,158 @@ use sha3::{Sha3XofReader, Sha3_512, Shake256};
 /// * `B`: the `ristretto255` basepoint;
 /// * `B_blinding`: the result of `ristretto255` SHA3-512
 /// hash-to-group on input `B_bytes`.
+///
+/// The generators of other groups are produced in the same way by
+/// [`PedersenGens::for_group`].
 #[derive(Copy, Clone)]
-pub struct PedersenGens {
+pub struct PedersenGens<G: Group = Ristretto> {
     /// Base for the committed value
-    pub B: RistrettoPoint,
+    pub B: G::Point,
     /// Base for the blinding factor
-    pub B_blinding: RistrettoPoint,
+    pub B_blinding: G::Point,
 }
 
-impl PedersenGens {
+impl<G: Group> PedersenGens<G> {
+    /// Creates the default generators of the group `G`: its basepoint,
+    /// and the result of hashing the compressed basepoint to the group
+    /// with SHA3-512.
+    pub fn for_group() -> Self {
+        let B = G::basepoint();
+        let mut uniform_bytes = [0u8; 64];
+        uniform_bytes.copy_from_slice(&Sha3_512::digest(G::compressed_as_bytes(&G::compress(&B))));
+        PedersenGens {
+            B,
+            B_blinding: G::point_from_uniform_bytes(&uniform_bytes),
+        }
+    }
+
     /// Creates a Pedersen commitment using the value scalar and a blinding factor.
-    pub fn commit(&self, value: Scalar, blinding: Scalar) -> RistrettoPoint {
-        RistrettoPoint::multiscalar_mul(&[value, blinding], &[self.B, self.B_blinding])
+    pub fn commit(&self, value: G::Scalar, blinding: G::Scalar) -> G::Point {
+        G::multiscalar_mul(&[value, blinding], &[self.B, self.B_blinding])
     }
 }
 
 impl Default for PedersenGens {
     fn default() -> Self {
-        PedersenGens {
-            B: RISTRETTO_BASEPOINT_POINT,
-            B_blinding: RistrettoPoint::hash_from_bytes::<Sha3_512>(
-                RISTRETTO_BASEPOINT_COMPRESSED.as_bytes(),
-            ),
+        PedersenGens::for_group()
+    }
+}
+
+/// Represents the base points for extended Pedersen commitments of the
+/// form \\(v B + k\_1 \\tilde{B}\_1 + \\dots + k\_d \\tilde{B}\_d\\),
+/// which hide the value with \\(d\\) blinding factors, where \\(d\\)
+/// is the extension degree.
+///
+/// The default generators extend the default [`PedersenGens`]: the
+/// first blinding base is `B_blinding`, and the others are produced
+/// by a [`GeneratorsChain`] with the label `ExtendedPedersenGens`.
+/// With an extension degree of one, the commitments are the same as
+/// those of [`PedersenGens`].
+///
+/// The generators of other groups are produced in the same way by
+/// [`ExtendedPedersenGens::for_group`].
+#[derive(Clone, Debug)]
+pub struct ExtendedPedersenGens<G: Group = Ristretto> {
+    /// Base for the committed value
+    pub B: G::Point,
+    /// Bases for the blinding factors, one per extension degree
+    pub B_blinding: Vec<G::Point>,
+}
+
+impl ExtendedPedersenGens {
+    /// Creates the default generators for the given extension degree,
+    /// which must be at least one.
+    pub fn new(extension_degree: usize) -> Result<Self, ProofError> {
+        ExtendedPedersenGens::for_group(extension_degree)
+    }
+
+    /// Returns the value base of the asset with the given identifier,
+    /// the result of `ristretto255` SHA3-512 hash-to-group on input
+    /// `asset_id`.
+    pub fn asset_value_base(asset_id: &[u8]) -> RistrettoPoint {
+        RistrettoPoint::hash_from_bytes::<Sha3_512>(asset_id)
+    }
+}
+
+impl<G: Group> ExtendedPedersenGens<G> {
+    /// Creates the default generators of the group `G` for the given
+    /// extension degree, as [`ExtendedPedersenGens::new`] does.
+    pub fn for_group(extension_degree: usize) -> Result<Self, ProofError> {
+        if extension_degree == 0 {
+            return Err(ProofError::InvalidExtensionDegree);
+        }
+        let pc_gens = PedersenGens::<G>::for_group();
+        let B_blinding = iter::once(pc_gens.B_blinding)
+            .chain(GeneratorsChain::<G>::new(b"ExtendedPedersenGens").take(extension_degree - 1))
+            .collect();
+        Ok(ExtendedPedersenGens {
+            B: pc_gens.B,
+            B_blinding,
+        })
+    }
+
+    /// Returns the number of blinding factors of the commitments.
+    pub fn extension_degree(&self) -> usize {
+        self.B_blinding.len()
+    }
+
+    /// Creates an extended Pedersen commitment using the value scalar
+    /// and one blinding factor per extension degree.
+    pub fn commit(
+        &self,
+        value: G::Scalar,
+        blindings: &[G::Scalar],
+    ) -> Result<G::Point, ProofError> {
+        if blindings.len() != self.extension_degree() {
+            return Err(ProofError::InvalidExtensionDegree);
+        }
+        Ok(G::multiscalar_mul(
+            iter::once(&value).chain(blindings.iter()),
+            iter::once(&self.B).chain(self.B_blinding.iter()),
+        ))
+    }
+
+    /// Returns generators with the same blinding bases, which commit
+    /// to values with the given `value_base` instead of `B`.
+    pub fn with_value_base(&self, value_base: G::Point) -> Self {
+        ExtendedPedersenGens {
+            B: value_base,
+            B_blinding: self.B_blinding.clone(),
+        }
+    }
+
+    /// Blinds the value base of an asset into an asset tag
+    /// \(H + r \tilde{B}\_1\), which hides the asset of the
+    /// commitments made with it.
+    pub fn blind_value_base(&self, value_base: &G::Point, blinding: &G::Scalar) -> G::Point {
+        *value_base + self.B_blinding[0] * *blinding
+    }
+}
+
+impl Default for ExtendedPedersenGens {
+    fn default() -> Self {
+        ExtendedPedersenGens::from(PedersenGens::default())
+    }
+}
+
+impl<G: Group> From<PedersenGens<G>> for ExtendedPedersenGens<G> {
+    fn from(pc_gens: PedersenGens<G>) -> Self {
+        ExtendedPedersenGens {
+            B: pc_gens.B,
+            B_blinding: alloc::vec![pc_gens.B_blinding],
         }
     }
 }
 
 /// The `GeneratorsChain` creates an arbitrary-long sequence of
-/// orthogonal generators.  The sequence can be deterministically
-/// produced starting with an arbitrary point.
-struct GeneratorsChain {
+/// orthogonal generators of the group `G`.  The sequence can be
+/// deterministically produced starting with an arbitrary point.
+struct GeneratorsChain<G: Group = Ristretto> {
     reader: Sha3XofReader,
+    group: PhantomData<G>,
 }
 
-impl GeneratorsChain {
+impl<G: Group> GeneratorsChain<G> {
     /// Creates a chain of generators, determined by the hash of `label`.
     fn new(label: &[u8]) -> Self {
         let mut shake = Shake256::default();
@@ -68,6 +189,7 @@ impl GeneratorsChain {
 
         GeneratorsChain {
             reader: shake.finalize_xof_dirty(),
+            group: PhantomData,
         }
     }
 
@@ -82,20 +204,20 @@ impl GeneratorsChain {
     }
 }
 
-impl Default for GeneratorsChain {
+impl<G: Group> Default for GeneratorsChain<G> {
     fn default() -> Self {
         Self::new(&[])
     }
 }
 
-impl Iterator for GeneratorsChain {
-    type Item = RistrettoPoint;
+impl<G: Group> Iterator for GeneratorsChain<G> {
+    type Item = G::Point;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut uniform_bytes = [0u8; 64];
         self.reader.read(&mut uniform_bytes);
 
-        Some(RistrettoPoint::from_uniform_bytes(&uniform_bytes))
+        Some(G::point_from_uniform_bytes(&uniform_bytes))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -114,7 +236,8 @@ impl Iterator for GeneratorsChain {
 ///
 /// To construct an arbitrary-length chain of generators, we apply
 /// SHAKE256 to a domain separator label, and feed each 64 bytes of
-/// XOF output into the `ristretto255` hash-to-group function.
+/// XOF output into the `ristretto255` hash-to-group function, or the
+/// [`Group::point_from_uniform_bytes`] of another group.
 /// Each of the `m` parties' generators are constructed using a
 /// different domain separation label, and proving and verification
 /// uses the first `n` elements of the arbitrary-length chain.
@@ -130,19 +253,24 @@ impl Iterator for GeneratorsChain {
 /// constraint system proofs, since the generators are namespaced by
 /// their party index.
 #[derive(Clone)]
-pub struct BulletproofGens {
+pub struct BulletproofGens<G: Group = Ristretto> {
     /// The maximum number of usable generators for each party.
     pub gens_capacity: usize,
     /// Number of values or parties
     pub party_capacity: usize,
     /// Precomputed \\(\mathbf G\\) generators for each party.
-    G_vec: Vec<Vec<RistrettoPoint>>,
+    G_vec: Vec<Vec<G::Point>>,
     /// Precomputed \\(\mathbf H\\) generators for each party.
-    H_vec: Vec<Vec<RistrettoPoint>>,
+    H_vec: Vec<Vec<G::Point>>,
+    /// The backend for multiscalar multiplications over the generators.
+    msm: Arc<dyn MsmBackend<G>>,
 }
 
 impl BulletproofGens {
-    /// Create a new `BulletproofGens` object.
+    /// Create a new `BulletproofGens` object of the `ristretto255` group.
+    ///
+    /// The generators of other groups are created with
+    /// [`BulletproofGens::for_group`], which takes the same inputs.
     ///
     /// # Inputs
     ///
@@ -155,19 +283,43 @@ impl BulletproofGens {
     /// * `party_capacity` is the maximum number of parties that can
     ///    produce an aggregated proof.
     pub fn new(gens_capacity: usize, party_capacity: usize) -> Self {
+        BulletproofGens::for_group(gens_capacity, party_capacity).with_msm_backend(DalekMsm)
+    }
+}
+
+impl<G: Group> BulletproofGens<G> {
+    /// Create a new `BulletproofGens` object of the group `G`, with
+    /// the same inputs as [`BulletproofGens::new`].
+    pub fn for_group(gens_capacity: usize, party_capacity: usize) -> Self {
         let mut gens = BulletproofGens {
             gens_capacity: 0,
             party_capacity,
             G_vec: (0..party_capacity).map(|_| Vec::new()).collect(),
             H_vec: (0..party_capacity).map(|_| Vec::new()).collect(),
+            msm: Arc::new(GroupMsm),
         };
         gens.increase_capacity(gens_capacity);
         gens
     }
 
+    /// Returns the generators with the multiscalar multiplication
+    /// backend replaced by `backend`.
+    ///
+    /// The backend is used by provers and verifiers for the
+    /// multiscalar multiplications over these generators.
+    pub fn with_msm_backend<M: MsmBackend<G> + 'static>(mut self, backend: M) -> Self {
+        self.msm = Arc::new(backend);
+        self
+    }
+
+    /// Returns the multiscalar multiplication backend of the generators.
+    pub fn msm_backend(&self) -> &dyn MsmBackend<G> {
+        self.msm.as_ref()
+    }
+
     /// Returns j-th share of generators, with an appropriate
     /// slice of vectors G and H for the j-th range proof.
-    pub fn share(&self, j: usize) -> BulletproofGensShare<'_> {
+    pub fn share(&self, j: usize) -> BulletproofGensShare<'_, G> {
         BulletproofGensShare {
             gens: &self,
             share: j,
@@ -188,14 +340,14 @@ impl BulletproofGens {
             let mut label = [b'G', 0, 0, 0, 0];
             LittleEndian::write_u32(&mut label[1..5], party_index);
             self.G_vec[i].extend(
-                &mut GeneratorsChain::new(&label)
+                &mut GeneratorsChain::<G>::new(&label)
                     .fast_forward(self.gens_capacity)
                     .take(new_capacity - self.gens_capacity),
             );
 
             label[0] = b'H';
             self.H_vec[i].extend(
-                &mut GeneratorsChain::new(&label)
+                &mut GeneratorsChain::<G>::new(&label)
                     .fast_forward(self.gens_capacity)
                     .take(new_capacity - self.gens_capacity),
             );
@@ -204,7 +356,7 @@ impl BulletproofGens {
     }
 
     /// Return an iterator over the aggregation of the parties' G generators with given size `n`.
-    pub(crate) fn G(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
+    pub(crate) fn G(&self, n: usize, m: usize) -> impl Iterator<Item = &G::Point> {
         AggregatedGensIter {
             n,
             m,
@@ -215,7 +367,7 @@ impl BulletproofGens {
     }
 
     /// Return an iterator over the aggregation of the parties' H generators with given size `n`.
-    pub(crate) fn H(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
+    pub(crate) fn H(&self, n: usize, m: usize) -> impl Iterator<Item = &G::Point> {
         AggregatedGensIter {
             n,
             m,
@@ -226,16 +378,16 @@ impl BulletproofGens {
     }
 }
 
-struct AggregatedGensIter<'a> {
-    array: &'a Vec<Vec<RistrettoPoint>>,
+struct AggregatedGensIter<'a, P> {
+    array: &'a Vec<Vec<P>>,
     n: usize,
     m: usize,
     party_idx: usize,
     gen_idx: usize,
 }
 
-impl<'a> Iterator for AggregatedGensIter<'a> {
-    type Item = &'a RistrettoPoint;
+impl<'a, P> Iterator for AggregatedGensIter<'a, P> {
+    type Item = &'a P;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.gen_idx >= self.n {
@@ -266,29 +418,47 @@ impl<'a> Iterator for AggregatedGensIter<'a> {
 /// provides a view of the generators for one of the `m` parties' shares.
 ///
 /// The `BulletproofGensShare` is produced by [`BulletproofGens::share()`].
-#[derive(Copy, Clone)]
-pub struct BulletproofGensShare<'a> {
+pub struct BulletproofGensShare<'a, G: Group = Ristretto> {
     /// The parent object that this is a view into
-    gens: &'a BulletproofGens,
+    gens: &'a BulletproofGens<G>,
     /// Which share we are
     share: usize,
 }
 
-impl<'a> BulletproofGensShare<'a> {
+impl<'a, G: Group> Clone for BulletproofGensShare<'a, G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, G: Group> Copy for BulletproofGensShare<'a, G> {}
+
+impl<'a, G: Group> BulletproofGensShare<'a, G> {
     /// Return an iterator over this party's G generators with given size `n`.
-    pub(crate) fn G(&self, n: usize) -> impl Iterator<Item = &'a RistrettoPoint> {
+    pub(crate) fn G(&self, n: usize) -> impl Iterator<Item = &'a G::Point> {
         self.gens.G_vec[self.share].iter().take(n)
     }
 
     /// Return an iterator over this party's H generators with given size `n`.
-    pub(crate) fn H(&self, n: usize) -> impl Iterator<Item = &'a RistrettoPoint> {
+    pub(crate) fn H(&self, n: usize) -> impl Iterator<Item = &'a G::Point> {
         self.gens.H_vec[self.share].iter().take(n)
     }
+
+    /// Return this party's first `n` G generators as a slice.
+    pub(crate) fn G_slice(&self, n: usize) -> &'a [G::Point] {
+        &self.gens.G_vec[self.share][..n]
+    }
+
+    /// Return this party's first `n` H generators as a slice.
+    pub(crate) fn H_slice(&self, n: usize) -> &'a [G::Point] {
+        &self.gens.H_vec[self.share][..n]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use curve25519_dalek::scalar::Scalar;
 
     #[test]
     fn aggregated_gens_iter_matches_flat_map() {
@@ -331,6 +501,28 @@ mod tests {
         helper(16, 1);
     }
 
+    #[test]
+    fn extended_gens_extend_pedersen_gens() {
+        let pc_gens = PedersenGens::default();
+        let value = Scalar::from(42u64);
+        let blinding = Scalar::from(7u64);
+
+        assert_eq!(
+            ExtendedPedersenGens::new(1)
+                .unwrap()
+                .commit(value, &[blinding])
+                .unwrap(),
+            pc_gens.commit(value, blinding)
+        );
+
+        let extended_gens = ExtendedPedersenGens::new(3).unwrap();
+        assert_eq!(extended_gens.extension_degree(), 3);
+        assert_eq!(extended_gens.B_blinding[0], pc_gens.B_blinding);
+        assert_ne!(extended_gens.B_blinding[1], extended_gens.B_blinding[2]);
+        assert!(extended_gens.commit(value, &[blinding]).is_err());
+        assert!(ExtendedPedersenGens::new(0).is_err());
+    }
+
     #[test]
     fn resizing_small_gens_matches_creating_bigger_gens() {
         let gens = BulletproofGens::new(64, 8);
@@ -353,4 +545,26 @@ mod tests {
         helper(32, 8);
         helper(16, 8);
     }
+
+    #[test]
+    fn generators_for_another_group() {
+        use crate::group::tests::{Fp, ToyGroup};
+
+        let pc_gens = PedersenGens::<ToyGroup>::for_group();
+        assert_eq!(pc_gens.B, ToyGroup::basepoint());
+        assert_ne!(pc_gens.B, pc_gens.B_blinding);
+        assert_eq!(
+            pc_gens.commit(Fp::from(3u64), Fp::from(5u64)),
+            pc_gens.B * Fp::from(3u64) + pc_gens.B_blinding * Fp::from(5u64)
+        );
+
+        let gens = BulletproofGens::<ToyGroup>::for_group(16, 2);
+        let mut resized = BulletproofGens::<ToyGroup>::for_group(8, 2);
+        resized.increase_capacity(16);
+        assert_eq!(
+            gens.G(16, 2).collect::<Vec<_>>(),
+            resized.G(16, 2).collect::<Vec<_>>()
+        );
+        assert_eq!(gens.share(1).H_slice(16), resized.share(1).H_slice(16));
+    }
 }
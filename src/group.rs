@@ -0,0 +1,511 @@
+//! The `group` module contains the traits abstracting the prime-order
+//! group, and its field of scalars, in which proofs are made.
+//!
+//! The generators, the inner product argument and the proof types are
+//! generic over a [`Group`], and default to [`Ristretto`], the
+//! `ristretto255` group of `curve25519-dalek`, so that code written for
+//! the Ristretto types keeps working unchanged.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::convert::TryInto;
+use core::fmt::Debug;
+use core::iter::Sum;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, IsIdentity, MultiscalarMul, VartimeMultiscalarMul};
+use rand_core::{CryptoRng, RngCore};
+use subtle::ConditionallySelectable;
+use zeroize::Zeroize;
+
+/// The field of scalars of a prime-order [`Group`].
+pub trait ScalarField:
+    Copy
+    + Debug
+    + Default
+    + Eq
+    + Send
+    + Sync
+    + Zeroize
+    + From<u64>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + Sum
+{
+    /// Returns the additive identity.
+    fn zero() -> Self {
+        Self::from(0u64)
+    }
+
+    /// Returns the multiplicative identity.
+    fn one() -> Self {
+        Self::from(1u64)
+    }
+
+    /// Returns the multiplicative inverse of a nonzero scalar.
+    fn invert(&self) -> Self;
+
+    /// Inverts each of the nonzero `scalars` in place, and returns the
+    /// inverse of their product.
+    ///
+    /// The default implementation uses Montgomery's trick, computing a
+    /// single inversion.
+    fn batch_invert(scalars: &mut [Self]) -> Self {
+        let mut products = Vec::with_capacity(scalars.len());
+        let mut acc = Self::one();
+        for scalar in scalars.iter() {
+            products.push(acc);
+            acc *= *scalar;
+        }
+        let allinv = acc.invert();
+        let mut acc = allinv;
+        for (scalar, product) in scalars.iter_mut().zip(products).rev() {
+            let inv = acc * product;
+            acc *= *scalar;
+            *scalar = inv;
+        }
+        allinv
+    }
+
+    /// Returns a scalar drawn uniformly at random from `rng`.
+    fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self;
+
+    /// Reduces 64 uniformly random bytes to a scalar, such as the
+    /// challenges drawn from a transcript.
+    fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self;
+
+    /// Decodes a scalar from its canonical little-endian encoding, or
+    /// returns `None` if `bytes` is not the canonical encoding of a
+    /// scalar, of [`Group::SCALAR_SIZE`] bytes.
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self>;
+
+    /// Returns the canonical little-endian encoding of the scalar, of
+    /// [`Group::SCALAR_SIZE`] bytes.
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+/// A prime-order group in which proofs are made.
+///
+/// The implementing type is only a marker naming the group: its
+/// scalars, points and compressed points are the associated types.
+pub trait Group: Copy + Debug + Default + Eq + Send + Sync + 'static {
+    /// The scalars of the group.
+    type Scalar: ScalarField;
+
+    /// The points of the group.
+    type Point: Copy
+        + Debug
+        + Eq
+        + Send
+        + Sync
+        + Add<Output = Self::Point>
+        + Sub<Output = Self::Point>
+        + Neg<Output = Self::Point>
+        + AddAssign
+        + SubAssign
+        + Mul<Self::Scalar, Output = Self::Point>
+        + Sum
+        + ConditionallySelectable;
+
+    /// The compressed encodings of points, which are hashed into
+    /// transcripts and serialized into proofs.
+    type CompressedPoint: Copy + Debug + Eq + Send + Sync;
+
+    /// The length in bytes of a compressed point.  A block of this many
+    /// `0xff` bytes must not be the compression of any point, as it marks
+    /// the encodings of range proofs which are not plain proofs.
+    const COMPRESSED_POINT_SIZE: usize;
+
+    /// The length in bytes of an encoded scalar.
+    const SCALAR_SIZE: usize;
+
+    /// Returns the identity of the group.
+    fn identity() -> Self::Point;
+
+    /// Returns the standard basepoint of the group.
+    fn basepoint() -> Self::Point;
+
+    /// Maps 64 uniformly random bytes to a point, with no known
+    /// discrete logarithm relative to any other point.
+    fn point_from_uniform_bytes(bytes: &[u8; 64]) -> Self::Point;
+
+    /// Compresses a point.
+    fn compress(point: &Self::Point) -> Self::CompressedPoint;
+
+    /// Decompresses a point, or returns `None` if it is not the
+    /// encoding of a point of the group.
+    fn decompress(point: &Self::CompressedPoint) -> Option<Self::Point>;
+
+    /// Returns the bytes of a compressed point.
+    fn compressed_as_bytes(point: &Self::CompressedPoint) -> &[u8];
+
+    /// Reads a compressed point from [`Group::COMPRESSED_POINT_SIZE`]
+    /// bytes, without checking that it decompresses, or returns `None`
+    /// if the slice has another length.
+    fn compressed_from_slice(bytes: &[u8]) -> Option<Self::CompressedPoint>;
+
+    /// Returns whether the compressed point is the encoding of the identity.
+    fn is_identity(point: &Self::CompressedPoint) -> bool;
+
+    /// Computes the multiscalar multiplication \\(\sum\_i s\_i P\_i\\)
+    /// in constant time.
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> Self::Point
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Self::Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self::Point>;
+
+    /// Computes the multiscalar multiplication \\(\sum\_i s\_i P\_i\\)
+    /// in variable time.
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> Self::Point
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Self::Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self::Point>;
+}
+
+/// The `ristretto255` group of `curve25519-dalek`, which is the default
+/// group of the proofs.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ristretto;
+
+impl ScalarField for Scalar {
+    fn zero() -> Self {
+        Scalar::zero()
+    }
+
+    fn one() -> Self {
+        Scalar::one()
+    }
+
+    fn invert(&self) -> Self {
+        Scalar::invert(self)
+    }
+
+    fn batch_invert(scalars: &mut [Self]) -> Self {
+        Scalar::batch_invert(scalars)
+    }
+
+    fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Scalar::random(rng)
+    }
+
+    fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self {
+        Scalar::from_bytes_mod_order_wide(bytes)
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: [u8; 32] = bytes.try_into().ok()?;
+        Scalar::from_canonical_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        Scalar::to_bytes(self).to_vec()
+    }
+}
+
+impl Group for Ristretto {
+    type Scalar = Scalar;
+    type Point = RistrettoPoint;
+    type CompressedPoint = CompressedRistretto;
+
+    const COMPRESSED_POINT_SIZE: usize = 32;
+    const SCALAR_SIZE: usize = 32;
+
+    fn identity() -> RistrettoPoint {
+        RistrettoPoint::identity()
+    }
+
+    fn basepoint() -> RistrettoPoint {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn point_from_uniform_bytes(bytes: &[u8; 64]) -> RistrettoPoint {
+        RistrettoPoint::from_uniform_bytes(bytes)
+    }
+
+    fn compress(point: &RistrettoPoint) -> CompressedRistretto {
+        point.compress()
+    }
+
+    fn decompress(point: &CompressedRistretto) -> Option<RistrettoPoint> {
+        point.decompress()
+    }
+
+    fn compressed_as_bytes(point: &CompressedRistretto) -> &[u8] {
+        point.as_bytes()
+    }
+
+    fn compressed_from_slice(bytes: &[u8]) -> Option<CompressedRistretto> {
+        if bytes.len() == Self::COMPRESSED_POINT_SIZE {
+            Some(CompressedRistretto::from_slice(bytes))
+        } else {
+            None
+        }
+    }
+
+    fn is_identity(point: &CompressedRistretto) -> bool {
+        point.is_identity()
+    }
+
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> RistrettoPoint
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<RistrettoPoint>,
+    {
+        RistrettoPoint::multiscalar_mul(scalars, points)
+    }
+
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> RistrettoPoint
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<RistrettoPoint>,
+    {
+        RistrettoPoint::vartime_multiscalar_mul(scalars, points)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// The modulus \\(2^{61} - 1\\) of the toy group.
+    const P: u64 = (1 << 61) - 1;
+
+    /// An integer modulo the prime `P`.
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    pub(crate) struct Fp(u64);
+
+    impl Fp {
+        fn reduce(x: u128) -> Fp {
+            Fp((x % P as u128) as u64)
+        }
+
+        fn pow(self, mut e: u64) -> Fp {
+            let mut base = self;
+            let mut acc = Fp(1);
+            while e > 0 {
+                if e & 1 == 1 {
+                    acc *= base;
+                }
+                base *= base;
+                e >>= 1;
+            }
+            acc
+        }
+    }
+
+    impl From<u64> for Fp {
+        fn from(x: u64) -> Fp {
+            Fp::reduce(x.into())
+        }
+    }
+
+    impl Add for Fp {
+        type Output = Fp;
+        fn add(self, other: Fp) -> Fp {
+            Fp::reduce(self.0 as u128 + other.0 as u128)
+        }
+    }
+
+    impl Sub for Fp {
+        type Output = Fp;
+        fn sub(self, other: Fp) -> Fp {
+            self + -other
+        }
+    }
+
+    impl Mul for Fp {
+        type Output = Fp;
+        fn mul(self, other: Fp) -> Fp {
+            Fp::reduce(self.0 as u128 * other.0 as u128)
+        }
+    }
+
+    impl Neg for Fp {
+        type Output = Fp;
+        fn neg(self) -> Fp {
+            Fp::reduce((P - self.0) as u128)
+        }
+    }
+
+    impl AddAssign for Fp {
+        fn add_assign(&mut self, other: Fp) {
+            *self = *self + other;
+        }
+    }
+
+    impl SubAssign for Fp {
+        fn sub_assign(&mut self, other: Fp) {
+            *self = *self - other;
+        }
+    }
+
+    impl MulAssign for Fp {
+        fn mul_assign(&mut self, other: Fp) {
+            *self = *self * other;
+        }
+    }
+
+    impl Sum for Fp {
+        fn sum<I: Iterator<Item = Fp>>(iter: I) -> Fp {
+            iter.fold(Fp(0), Add::add)
+        }
+    }
+
+    impl ConditionallySelectable for Fp {
+        fn conditional_select(a: &Fp, b: &Fp, choice: subtle::Choice) -> Fp {
+            Fp(u64::conditional_select(&a.0, &b.0, choice))
+        }
+    }
+
+    impl Zeroize for Fp {
+        fn zeroize(&mut self) {
+            self.0.zeroize();
+        }
+    }
+
+    impl ScalarField for Fp {
+        fn invert(&self) -> Fp {
+            self.pow(P - 2)
+        }
+
+        fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Fp {
+            Fp::from(rng.next_u64())
+        }
+
+        fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Fp {
+            bytes
+                .iter()
+                .rev()
+                .fold(Fp(0), |acc, byte| acc * Fp(256) + Fp(*byte as u64))
+        }
+
+        fn from_canonical_bytes(bytes: &[u8]) -> Option<Fp> {
+            let x = u64::from_le_bytes(bytes.try_into().ok()?);
+            if x < P {
+                Some(Fp(x))
+            } else {
+                None
+            }
+        }
+
+        fn to_bytes(&self) -> Vec<u8> {
+            self.0.to_le_bytes().to_vec()
+        }
+    }
+
+    /// The additive group of the integers modulo `P`, whose points are
+    /// compressed to 8 bytes.
+    ///
+    /// It has prime order, but its discrete logarithms are trivial, so
+    /// it is only useful for testing the code which is generic over the
+    /// group.
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    pub(crate) struct ToyGroup;
+
+    impl Group for ToyGroup {
+        type Scalar = Fp;
+        type Point = Fp;
+        type CompressedPoint = [u8; 8];
+
+        const COMPRESSED_POINT_SIZE: usize = 8;
+        const SCALAR_SIZE: usize = 8;
+
+        fn identity() -> Fp {
+            Fp(0)
+        }
+
+        fn basepoint() -> Fp {
+            Fp(1)
+        }
+
+        fn point_from_uniform_bytes(bytes: &[u8; 64]) -> Fp {
+            Fp::from_bytes_mod_order_wide(bytes)
+        }
+
+        fn compress(point: &Fp) -> [u8; 8] {
+            point.0.to_le_bytes()
+        }
+
+        fn decompress(point: &[u8; 8]) -> Option<Fp> {
+            let x = u64::from_le_bytes(*point);
+            if x < P {
+                Some(Fp(x))
+            } else {
+                None
+            }
+        }
+
+        fn compressed_as_bytes(point: &[u8; 8]) -> &[u8] {
+            point
+        }
+
+        fn compressed_from_slice(bytes: &[u8]) -> Option<[u8; 8]> {
+            let mut point = [0u8; 8];
+            if bytes.len() != point.len() {
+                return None;
+            }
+            point.copy_from_slice(bytes);
+            Some(point)
+        }
+
+        fn is_identity(point: &[u8; 8]) -> bool {
+            *point == [0u8; 8]
+        }
+
+        fn multiscalar_mul<I, J>(scalars: I, points: J) -> Fp
+        where
+            I: IntoIterator,
+            I::Item: Borrow<Fp>,
+            J: IntoIterator,
+            J::Item: Borrow<Fp>,
+        {
+            scalars
+                .into_iter()
+                .zip(points)
+                .map(|(s, p)| *s.borrow() * *p.borrow())
+                .sum()
+        }
+
+        fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> Fp
+        where
+            I: IntoIterator,
+            I::Item: Borrow<Fp>,
+            J: IntoIterator,
+            J::Item: Borrow<Fp>,
+        {
+            Self::multiscalar_mul(scalars, points)
+        }
+    }
+
+    #[test]
+    fn default_batch_invert() {
+        let mut rng = rand::thread_rng();
+        let scalars: Vec<Fp> = (0..5).map(|_| Fp::random(&mut rng)).collect();
+
+        let mut inverses = scalars.clone();
+        let allinv = Fp::batch_invert(&mut inverses);
+        let product = scalars.iter().fold(Fp(1), |acc, scalar| acc * *scalar);
+        assert_eq!(allinv * product, Fp(1));
+        for (scalar, inverse) in scalars.iter().zip(inverses.iter()) {
+            assert_eq!(*scalar * *inverse, Fp(1));
+        }
+    }
+}
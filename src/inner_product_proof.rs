@@ -7,23 +7,25 @@ use alloc::borrow::Borrow;
 use alloc::vec::Vec;
 
 use core::iter;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::VartimeMultiscalarMul;
+use core::ops::Range;
 use merlin::Transcript;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use crate::errors::ProofError;
+use crate::group::{Group, Ristretto, ScalarField};
+use crate::msm::{vartime_multiscalar_mul, MsmBackend};
 use crate::transcript::TranscriptProtocol;
 
 #[derive(Clone, Debug)]
-pub struct InnerProductProof {
-    pub(crate) L_vec: Vec<CompressedRistretto>,
-    pub(crate) R_vec: Vec<CompressedRistretto>,
-    pub(crate) a: Scalar,
-    pub(crate) b: Scalar,
+pub struct InnerProductProof<G: Group = Ristretto> {
+    pub(crate) L_vec: Vec<G::CompressedPoint>,
+    pub(crate) R_vec: Vec<G::CompressedPoint>,
+    pub(crate) a: G::Scalar,
+    pub(crate) b: G::Scalar,
 }
 
-impl InnerProductProof {
+impl<G: Group> InnerProductProof<G> {
     /// Create an inner-product proof.
     ///
     /// The proof is created with respect to the bases \\(G\\), \\(H'\\),
@@ -35,31 +37,32 @@ impl InnerProductProof {
     ///
     /// The lengths of the vectors must all be the same, and must all be
     /// either 0 or a power of 2.
+    ///
+    /// The generators and the vectors \\(\mathbf{a}\\), \\(\mathbf{b}\\)
+    /// are borrowed, so that the parties' vectors of an aggregated proof
+    /// need not be concatenated: the first round reads them in place, and
+    /// only the halved vectors it folds them into are allocated.
+    ///
+    /// The multiscalar multiplications are computed with `msm`.
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
+        msm: &dyn MsmBackend<G>,
         transcript: &mut Transcript,
-        Q: &RistrettoPoint,
-        G_factors: &[Scalar],
-        H_factors: &[Scalar],
-        mut G_vec: Vec<RistrettoPoint>,
-        mut H_vec: Vec<RistrettoPoint>,
-        mut a_vec: Vec<Scalar>,
-        mut b_vec: Vec<Scalar>,
-    ) -> InnerProductProof {
-        // Create slices G, H, a, b backed by their respective
-        // vectors.  This lets us reslice as we compress the lengths
-        // of the vectors in the main loop below.
-        let mut G = &mut G_vec[..];
-        let mut H = &mut H_vec[..];
-        let mut a = &mut a_vec[..];
-        let mut b = &mut b_vec[..];
-
-        let mut n = G.len();
+        Q: &G::Point,
+        G_factors: &[G::Scalar],
+        H_factors: &[G::Scalar],
+        G_vec: Chunked<'_, G::Point>,
+        H_vec: Chunked<'_, G::Point>,
+        a_vec: Chunked<'_, G::Scalar>,
+        b_vec: Chunked<'_, G::Scalar>,
+    ) -> InnerProductProof<G> {
+        let mut n = G_vec.len();
 
         // All of the input vectors must have the same length.
-        assert_eq!(G.len(), n);
-        assert_eq!(H.len(), n);
-        assert_eq!(a.len(), n);
-        assert_eq!(b.len(), n);
+        assert_eq!(G_vec.len(), n);
+        assert_eq!(H_vec.len(), n);
+        assert_eq!(a_vec.len(), n);
+        assert_eq!(b_vec.len(), n);
         assert_eq!(G_factors.len(), n);
         assert_eq!(H_factors.len(), n);
 
@@ -72,73 +75,72 @@ impl InnerProductProof {
         let mut L_vec = Vec::with_capacity(lg_n);
         let mut R_vec = Vec::with_capacity(lg_n);
 
-        // If it's the first iteration, unroll the Hprime = H*y_inv scalar mults
-        // into multiscalar muls, for performance.
-        if n != 1 {
-            n = n / 2;
-            let (a_L, a_R) = a.split_at_mut(n);
-            let (b_L, b_R) = b.split_at_mut(n);
-            let (G_L, G_R) = G.split_at_mut(n);
-            let (H_L, H_R) = H.split_at_mut(n);
-
-            let c_L = inner_product(&a_L, &b_R);
-            let c_R = inner_product(&a_R, &b_L);
+        if n == 1 {
+            return InnerProductProof {
+                L_vec,
+                R_vec,
+                a: *a_vec.get(0),
+                b: *b_vec.get(0),
+            };
+        }
 
-            let L = RistrettoPoint::vartime_multiscalar_mul(
-                a_L.iter()
-                    .zip(G_factors[n..2 * n].into_iter())
-                    .map(|(a_L_i, g)| a_L_i * g)
-                    .chain(
-                        b_R.iter()
-                            .zip(H_factors[0..n].into_iter())
-                            .map(|(b_R_i, h)| b_R_i * h),
-                    )
-                    .chain(iter::once(c_L)),
-                G_R.iter().chain(H_L.iter()).chain(iter::once(Q)),
-            )
-            .compress();
-
-            let R = RistrettoPoint::vartime_multiscalar_mul(
-                a_R.iter()
-                    .zip(G_factors[0..n].into_iter())
-                    .map(|(a_R_i, g)| a_R_i * g)
-                    .chain(
-                        b_L.iter()
-                            .zip(H_factors[n..2 * n].into_iter())
-                            .map(|(b_L_i, h)| b_L_i * h),
-                    )
-                    .chain(iter::once(c_R)),
-                G_L.iter().chain(H_R.iter()).chain(iter::once(Q)),
-            )
-            .compress();
+        // The first round reads the borrowed vectors and unrolls the
+        // Hprime = H*y_inv scalar mults into multiscalar muls, for
+        // performance.  The folded vectors are the first to be allocated.
+        //
+        // In each round, Q is multiplied apart from the multiscalar
+        // multiplication, whose power of two length fits the buffers
+        // `curve25519-dalek` grows by doubling while collecting its inputs.
+        n = n / 2;
+
+        let c_L: G::Scalar = (0..n).map(|i| *a_vec.get(i) * *b_vec.get(n + i)).sum();
+        let c_R: G::Scalar = (0..n).map(|i| *a_vec.get(n + i) * *b_vec.get(i)).sum();
+
+        let (L, R) = join(
+            || {
+                G::compress(
+                    &(vartime_multiscalar_mul(
+                        msm,
+                        (0..n)
+                            .map(|i| *a_vec.get(i) * G_factors[n + i])
+                            .chain((0..n).map(|i| *b_vec.get(n + i) * H_factors[i])),
+                        G_vec.range(n..2 * n).chain(H_vec.range(0..n)),
+                    ) + *Q * c_L),
+                )
+            },
+            || {
+                G::compress(
+                    &(vartime_multiscalar_mul(
+                        msm,
+                        (0..n)
+                            .map(|i| *a_vec.get(n + i) * G_factors[i])
+                            .chain((0..n).map(|i| *b_vec.get(i) * H_factors[n + i])),
+                        G_vec.range(0..n).chain(H_vec.range(n..2 * n)),
+                    ) + *Q * c_R),
+                )
+            },
+        );
 
-            L_vec.push(L);
-            R_vec.push(R);
+        L_vec.push(L);
+        R_vec.push(R);
 
-            transcript.append_point(b"L", &L);
-            transcript.append_point(b"R", &R);
+        transcript.append_group_point::<G>(b"L", &L);
+        transcript.append_group_point::<G>(b"R", &R);
 
-            let u = transcript.challenge_scalar(b"u");
-            let u_inv = u.invert();
+        let u = transcript.challenge_group_scalar::<G>(b"u");
+        let u_inv = u.invert();
 
-            for i in 0..n {
-                a_L[i] = a_L[i] * u + u_inv * a_R[i];
-                b_L[i] = b_L[i] * u_inv + u * b_R[i];
-                G_L[i] = RistrettoPoint::vartime_multiscalar_mul(
-                    &[u_inv * G_factors[i], u * G_factors[n + i]],
-                    &[G_L[i], G_R[i]],
-                );
-                H_L[i] = RistrettoPoint::vartime_multiscalar_mul(
-                    &[u * H_factors[i], u_inv * H_factors[n + i]],
-                    &[H_L[i], H_R[i]],
-                )
-            }
+        let mut a_vec = a_vec.fold(n, u, u_inv);
+        let mut b_vec = b_vec.fold(n, u_inv, u);
+        let mut G_vec =
+            G_vec.fold_points::<G, _>(n, |i| (u_inv * G_factors[i], u * G_factors[n + i]));
+        let mut H_vec =
+            H_vec.fold_points::<G, _>(n, |i| (u * H_factors[i], u_inv * H_factors[n + i]));
 
-            a = a_L;
-            b = b_L;
-            G = G_L;
-            H = H_L;
-        }
+        let mut G = &mut G_vec[..];
+        let mut H = &mut H_vec[..];
+        let mut a = &mut a_vec[..];
+        let mut b = &mut b_vec[..];
 
         while n != 1 {
             n = n / 2;
@@ -150,33 +152,40 @@ impl InnerProductProof {
             let c_L = inner_product(&a_L, &b_R);
             let c_R = inner_product(&a_R, &b_L);
 
-            let L = RistrettoPoint::vartime_multiscalar_mul(
-                a_L.iter().chain(b_R.iter()).chain(iter::once(&c_L)),
-                G_R.iter().chain(H_L.iter()).chain(iter::once(Q)),
-            )
-            .compress();
-
-            let R = RistrettoPoint::vartime_multiscalar_mul(
-                a_R.iter().chain(b_L.iter()).chain(iter::once(&c_R)),
-                G_L.iter().chain(H_R.iter()).chain(iter::once(Q)),
-            )
-            .compress();
+            let (L, R) = join(
+                || {
+                    G::compress(
+                        &(vartime_multiscalar_mul(
+                            msm,
+                            a_L.iter().chain(b_R.iter()),
+                            G_R.iter().chain(H_L.iter()),
+                        ) + *Q * c_L),
+                    )
+                },
+                || {
+                    G::compress(
+                        &(vartime_multiscalar_mul(
+                            msm,
+                            a_R.iter().chain(b_L.iter()),
+                            G_L.iter().chain(H_R.iter()),
+                        ) + *Q * c_R),
+                    )
+                },
+            );
 
             L_vec.push(L);
             R_vec.push(R);
 
-            transcript.append_point(b"L", &L);
-            transcript.append_point(b"R", &R);
+            transcript.append_group_point::<G>(b"L", &L);
+            transcript.append_group_point::<G>(b"R", &R);
 
-            let u = transcript.challenge_scalar(b"u");
+            let u = transcript.challenge_group_scalar::<G>(b"u");
             let u_inv = u.invert();
 
-            for i in 0..n {
-                a_L[i] = a_L[i] * u + u_inv * a_R[i];
-                b_L[i] = b_L[i] * u_inv + u * b_R[i];
-                G_L[i] = RistrettoPoint::vartime_multiscalar_mul(&[u_inv, u], &[G_L[i], G_R[i]]);
-                H_L[i] = RistrettoPoint::vartime_multiscalar_mul(&[u, u_inv], &[H_L[i], H_R[i]]);
-            }
+            fold_scalars(a_L, a_R, u, u_inv);
+            fold_scalars(b_L, b_R, u_inv, u);
+            fold_points::<G>(G_L, G_R, u_inv, u);
+            fold_points::<G>(H_L, H_R, u, u_inv);
 
             a = a_L;
             b = b_L;
@@ -199,7 +208,7 @@ impl InnerProductProof {
         &self,
         n: usize,
         transcript: &mut Transcript,
-    ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>), ProofError> {
+    ) -> Result<(Vec<G::Scalar>, Vec<G::Scalar>, Vec<G::Scalar>), ProofError> {
         let lg_n = self.L_vec.len();
         if lg_n >= 32 {
             // 4 billion multiplications should be enough for anyone
@@ -216,15 +225,15 @@ impl InnerProductProof {
 
         let mut challenges = Vec::with_capacity(lg_n);
         for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
-            transcript.validate_and_append_point(b"L", L)?;
-            transcript.validate_and_append_point(b"R", R)?;
-            challenges.push(transcript.challenge_scalar(b"u"));
+            transcript.validate_and_append_group_point::<G>(b"L", L)?;
+            transcript.validate_and_append_group_point::<G>(b"R", R)?;
+            challenges.push(transcript.challenge_group_scalar::<G>(b"u"));
         }
 
         // 2. Compute 1/(u_k...u_1) and 1/u_k, ..., 1/u_1
 
         let mut challenges_inv = challenges.clone();
-        let allinv = Scalar::batch_invert(&mut challenges_inv);
+        let allinv = G::Scalar::batch_invert(&mut challenges_inv);
 
         // 3. Compute u_i^2 and (1/u_i)^2
 
@@ -263,23 +272,23 @@ impl InnerProductProof {
         transcript: &mut Transcript,
         G_factors: IG,
         H_factors: IH,
-        P: &RistrettoPoint,
-        Q: &RistrettoPoint,
-        G: &[RistrettoPoint],
-        H: &[RistrettoPoint],
+        P: &G::Point,
+        Q: &G::Point,
+        G: &[G::Point],
+        H: &[G::Point],
     ) -> Result<(), ProofError>
     where
         IG: IntoIterator,
-        IG::Item: Borrow<Scalar>,
+        IG::Item: Borrow<G::Scalar>,
         IH: IntoIterator,
-        IH::Item: Borrow<Scalar>,
+        IH::Item: Borrow<G::Scalar>,
     {
         let (u_sq, u_inv_sq, s) = self.verification_scalars(n, transcript)?;
 
         let g_times_a_times_s = G_factors
             .into_iter()
             .zip(s.iter())
-            .map(|(g_i, s_i)| (self.a * s_i) * g_i.borrow())
+            .map(|(g_i, s_i)| (self.a * *s_i) * *g_i.borrow())
             .take(G.len());
 
         // 1/s[i] is s[!i], and !i runs from n-1 to 0 as i runs from 0 to n-1
@@ -288,24 +297,24 @@ impl InnerProductProof {
         let h_times_b_div_s = H_factors
             .into_iter()
             .zip(inv_s)
-            .map(|(h_i, s_i_inv)| (self.b * s_i_inv) * h_i.borrow());
+            .map(|(h_i, s_i_inv)| (self.b * *s_i_inv) * *h_i.borrow());
 
-        let neg_u_sq = u_sq.iter().map(|ui| -ui);
-        let neg_u_inv_sq = u_inv_sq.iter().map(|ui| -ui);
+        let neg_u_sq = u_sq.iter().map(|ui| -*ui);
+        let neg_u_inv_sq = u_inv_sq.iter().map(|ui| -*ui);
 
         let Ls = self
             .L_vec
             .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
+            .map(|p| G::decompress(p).ok_or(ProofError::VerificationError))
             .collect::<Result<Vec<_>, _>>()?;
 
         let Rs = self
             .R_vec
             .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
+            .map(|p| G::decompress(p).ok_or(ProofError::VerificationError))
             .collect::<Result<Vec<_>, _>>()?;
 
-        let expect_P = RistrettoPoint::vartime_multiscalar_mul(
+        let expect_P = G::vartime_multiscalar_mul(
             iter::once(self.a * self.b)
                 .chain(g_times_a_times_s)
                 .chain(h_times_b_div_s)
@@ -329,23 +338,26 @@ impl InnerProductProof {
     /// product proof.
     ///
     /// For vectors of length `n` the proof size is
-    /// \\(32 \cdot (2\lg n+2)\\) bytes.
+    /// \\(32 \cdot (2\lg n+2)\\) bytes, for compressed points of 32 bytes.
     pub fn serialized_size(&self) -> usize {
-        (self.L_vec.len() * 2 + 2) * 32
+        self.L_vec.len() * 2 * G::COMPRESSED_POINT_SIZE + 2 * G::SCALAR_SIZE
     }
 
     /// Serializes the proof into a byte array of \\(2n+2\\) 32-byte elements.
     /// The layout of the inner product proof is:
     /// * \\(n\\) pairs of compressed Ristretto points \\(L_0, R_0 \dots, L_{n-1}, R_{n-1}\\),
     /// * two scalars \\(a, b\\).
+    ///
+    /// The points and scalars of other groups take
+    /// [`Group::COMPRESSED_POINT_SIZE`] and [`Group::SCALAR_SIZE`] bytes.
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(self.serialized_size());
         for (l, r) in self.L_vec.iter().zip(self.R_vec.iter()) {
-            buf.extend_from_slice(l.as_bytes());
-            buf.extend_from_slice(r.as_bytes());
+            buf.extend_from_slice(G::compressed_as_bytes(l));
+            buf.extend_from_slice(G::compressed_as_bytes(r));
         }
-        buf.extend_from_slice(self.a.as_bytes());
-        buf.extend_from_slice(self.b.as_bytes());
+        buf.extend_from_slice(&self.a.to_bytes());
+        buf.extend_from_slice(&self.b.to_bytes());
         buf
     }
 
@@ -358,10 +370,14 @@ impl InnerProductProof {
         self.L_vec
             .iter()
             .zip(self.R_vec.iter())
-            .flat_map(|(l, r)| l.as_bytes().iter().chain(r.as_bytes()))
-            .chain(self.a.as_bytes())
-            .chain(self.b.as_bytes())
+            .flat_map(|(l, r)| {
+                G::compressed_as_bytes(l)
+                    .iter()
+                    .chain(G::compressed_as_bytes(r))
+            })
             .copied()
+            .chain(self.a.to_bytes())
+            .chain(self.b.to_bytes())
     }
 
     /// Deserializes the proof from a byte slice.
@@ -370,50 +386,157 @@ impl InnerProductProof {
     /// * \\(n\\) is larger or equal to 32 (proof is too big),
     /// * any of \\(2n\\) points are not valid compressed Ristretto points,
     /// * any of 2 scalars are not canonical scalars modulo Ristretto group order.
-    pub fn from_bytes(slice: &[u8]) -> Result<InnerProductProof, ProofError> {
+    pub fn from_bytes(slice: &[u8]) -> Result<InnerProductProof<G>, ProofError> {
+        let point_size = G::COMPRESSED_POINT_SIZE;
+        let scalar_size = G::SCALAR_SIZE;
         let b = slice.len();
-        if b % 32 != 0 {
-            return Err(ProofError::FormatError);
-        }
-        let num_elements = b / 32;
-        if num_elements < 2 {
+        if b < 2 * scalar_size {
             return Err(ProofError::FormatError);
         }
-        if (num_elements - 2) % 2 != 0 {
+        if (b - 2 * scalar_size) % (2 * point_size) != 0 {
             return Err(ProofError::FormatError);
         }
-        let lg_n = (num_elements - 2) / 2;
+        let lg_n = (b - 2 * scalar_size) / (2 * point_size);
         if lg_n >= 32 {
             return Err(ProofError::FormatError);
         }
 
-        use crate::util::read32;
-
-        let mut L_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
-        let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        let read_point = |pos: usize| {
+            G::compressed_from_slice(&slice[pos..pos + point_size]).ok_or(ProofError::FormatError)
+        };
+        let mut L_vec: Vec<G::CompressedPoint> = Vec::with_capacity(lg_n);
+        let mut R_vec: Vec<G::CompressedPoint> = Vec::with_capacity(lg_n);
         for i in 0..lg_n {
-            let pos = 2 * i * 32;
-            L_vec.push(CompressedRistretto(read32(&slice[pos..])));
-            R_vec.push(CompressedRistretto(read32(&slice[pos + 32..])));
+            let pos = 2 * i * point_size;
+            L_vec.push(read_point(pos)?);
+            R_vec.push(read_point(pos + point_size)?);
         }
 
-        let pos = 2 * lg_n * 32;
-        let a =
-            Scalar::from_canonical_bytes(read32(&slice[pos..])).ok_or(ProofError::FormatError)?;
-        let b = Scalar::from_canonical_bytes(read32(&slice[pos + 32..]))
+        let pos = 2 * lg_n * point_size;
+        let a = G::Scalar::from_canonical_bytes(&slice[pos..pos + scalar_size])
+            .ok_or(ProofError::FormatError)?;
+        let b = G::Scalar::from_canonical_bytes(&slice[pos + scalar_size..])
             .ok_or(ProofError::FormatError)?;
 
         Ok(InnerProductProof { L_vec, R_vec, a, b })
     }
 }
 
+/// A vector borrowed as consecutive chunks of equal length, such as
+/// the generators or the vectors of the parties to an aggregated proof.
+pub struct Chunked<'a, T> {
+    chunks: &'a [&'a [T]],
+    chunk_len: usize,
+}
+
+impl<'a, T> Clone for Chunked<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Chunked<'a, T> {}
+
+impl<'a, T> Chunked<'a, T> {
+    /// Borrows the concatenation of `chunks`, which must all have the
+    /// same length.
+    pub fn new(chunks: &'a [&'a [T]]) -> Self {
+        let chunk_len = chunks.first().map_or(0, |chunk| chunk.len());
+        assert!(chunks.iter().all(|chunk| chunk.len() == chunk_len));
+        Chunked { chunks, chunk_len }
+    }
+
+    fn len(&self) -> usize {
+        self.chunks.len() * self.chunk_len
+    }
+
+    fn get(&self, i: usize) -> &'a T {
+        &self.chunks[i / self.chunk_len][i % self.chunk_len]
+    }
+
+    fn range(self, range: Range<usize>) -> impl Iterator<Item = &'a T> {
+        range.map(move |i| self.get(i))
+    }
+}
+
+impl<'a, S: ScalarField> Chunked<'a, S> {
+    /// Folds the vector of length \\(2n\\) into a new vector,
+    /// \\(x'\_i = x\_i \cdot l + x\_{n+i} \cdot r\\).
+    fn fold(self, n: usize, l: S, r: S) -> Vec<S> {
+        maybe_par_iter!(0..n)
+            .map(|i| *self.get(i) * l + *self.get(n + i) * r)
+            .collect()
+    }
+}
+
+impl<'a, P: Copy + Send + Sync> Chunked<'a, P> {
+    /// Folds the vector of length \\(2n\\) of points of the group `G` into
+    /// a new vector, \\(P'\_i = l\_i \cdot P\_i + r\_i \cdot P\_{n+i}\\), where
+    /// `factors(i)` returns \\((l\_i, r\_i)\\).
+    fn fold_points<G, F>(self, n: usize, factors: F) -> Vec<P>
+    where
+        G: Group<Point = P>,
+        F: Fn(usize) -> (G::Scalar, G::Scalar) + Send + Sync,
+    {
+        maybe_par_iter!(0..n)
+            .map(|i| {
+                let (l, r) = factors(i);
+                G::vartime_multiscalar_mul(&[l, r], &[*self.get(i), *self.get(n + i)])
+            })
+            .collect()
+    }
+}
+
+/// Runs the two closures, in parallel on the rayon thread pool with
+/// the `parallel` feature.
+fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        rayon::join(a, b)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (a(), b())
+    }
+}
+
+/// Folds the right half of a scalar vector into the left half,
+/// setting \\(x\_{L,i} \gets x\_{L,i} \cdot l + x\_{R,i} \cdot r\\).
+fn fold_scalars<S: ScalarField>(x_L: &mut [S], x_R: &[S], l: S, r: S) {
+    let fold = |(x_L_i, x_R_i): (&mut S, &S)| *x_L_i = *x_L_i * l + r * *x_R_i;
+
+    #[cfg(feature = "parallel")]
+    x_L.par_iter_mut().zip(x_R.par_iter()).for_each(fold);
+    #[cfg(not(feature = "parallel"))]
+    x_L.iter_mut().zip(x_R.iter()).for_each(fold);
+}
+
+/// Folds the right half of a point vector into the left half, setting
+/// \\(P\_{L,i} \gets l \cdot P\_{L,i} + r \cdot P\_{R,i}\\).
+fn fold_points<G: Group>(P_L: &mut [G::Point], P_R: &[G::Point], l: G::Scalar, r: G::Scalar) {
+    let fold = |(P_L_i, P_R_i): (&mut G::Point, &G::Point)| {
+        *P_L_i = G::vartime_multiscalar_mul(&[l, r], &[*P_L_i, *P_R_i]);
+    };
+
+    #[cfg(feature = "parallel")]
+    P_L.par_iter_mut().zip(P_R.par_iter()).for_each(fold);
+    #[cfg(not(feature = "parallel"))]
+    P_L.iter_mut().zip(P_R.iter()).for_each(fold);
+}
+
 /// Computes an inner product of two vectors
 /// \\[
 ///    {\langle {\mathbf{a}}, {\mathbf{b}} \rangle} = \sum\_{i=0}^{n-1} a\_i \cdot b\_i.
 /// \\]
 /// Panics if the lengths of \\(\mathbf{a}\\) and \\(\mathbf{b}\\) are not equal.
-pub fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
-    let mut out = Scalar::zero();
+pub fn inner_product<S: ScalarField>(a: &[S], b: &[S]) -> S {
+    let mut out = S::zero();
     if a.len() != b.len() {
         panic!("inner_product(a,b): lengths of vectors do not match");
     }
@@ -427,64 +550,88 @@ pub fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
 mod tests {
     use super::*;
 
-    use crate::util;
-    use sha3::Sha3_512;
+    use crate::generators::BulletproofGens;
+    use crate::group::tests::ToyGroup;
+    use curve25519_dalek::scalar::Scalar;
 
-    fn test_helper_create(n: usize) {
+    fn test_helper_create<G: Group>(n: usize) {
         let mut rng = rand::thread_rng();
 
-        use crate::generators::BulletproofGens;
-        let bp_gens = BulletproofGens::new(n, 1);
-        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
-        let H: Vec<RistrettoPoint> = bp_gens.share(0).H(n).cloned().collect();
+        let bp_gens = BulletproofGens::<G>::for_group(n, 1);
+        let G: Vec<G::Point> = bp_gens.share(0).G(n).cloned().collect();
+        let H: Vec<G::Point> = bp_gens.share(0).H(n).cloned().collect();
 
         // Q would be determined upstream in the protocol, so we pick a random one.
-        let Q = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"test point");
+        let Q = G::point_from_uniform_bytes(&[7u8; 64]);
 
         // a and b are the vectors for which we want to prove c = <a,b>
-        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
-        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let a: Vec<_> = (0..n).map(|_| G::Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| G::Scalar::random(&mut rng)).collect();
         let c = inner_product(&a, &b);
 
-        let G_factors: Vec<Scalar> = iter::repeat(Scalar::one()).take(n).collect();
+        let G_factors: Vec<G::Scalar> = iter::repeat(G::Scalar::one()).take(n).collect();
 
         // y_inv is (the inverse of) a random challenge
-        let y_inv = Scalar::random(&mut rng);
-        let H_factors: Vec<Scalar> = util::exp_iter(y_inv).take(n).collect();
+        let y_inv = G::Scalar::random(&mut rng);
+        let H_factors: Vec<G::Scalar> =
+            iter::successors(Some(G::Scalar::one()), |y| Some(*y * y_inv))
+                .take(n)
+                .collect();
 
         // P would be determined upstream, but we need a correct P to check the proof.
         //
         // To generate P = <a,G> + <b,H'> + <a,b> Q, compute
         //             P = <a,G> + <b',H> + <a,b> Q,
         // where b' = b \circ y^(-n)
-        let b_prime = b.iter().zip(util::exp_iter(y_inv)).map(|(bi, yi)| bi * yi);
+        let b_prime = b.iter().zip(H_factors.iter()).map(|(bi, yi)| *bi * *yi);
         // a.iter() has Item=&Scalar, need Item=Scalar to chain with b_prime
         let a_prime = a.iter().cloned();
 
-        let P = RistrettoPoint::vartime_multiscalar_mul(
+        let P = G::vartime_multiscalar_mul(
             a_prime.chain(b_prime).chain(iter::once(c)),
             G.iter().chain(H.iter()).chain(iter::once(&Q)),
         );
 
         let mut verifier = Transcript::new(b"innerproducttest");
-        let proof = InnerProductProof::create(
+        let proof = InnerProductProof::<G>::create(
+            bp_gens.msm_backend(),
             &mut verifier,
             &Q,
             &G_factors,
             &H_factors,
-            G.clone(),
-            H.clone(),
-            a.clone(),
-            b.clone(),
+            Chunked::new(&[&G]),
+            Chunked::new(&[&H]),
+            Chunked::new(&[&a]),
+            Chunked::new(&[&b]),
         );
 
+        // The same proof is created from vectors borrowed in several chunks
+        let chunk_len = core::cmp::max(n / 4, 1);
+        let G_chunks: Vec<&[G::Point]> = G.chunks(chunk_len).collect();
+        let H_chunks: Vec<&[G::Point]> = H.chunks(chunk_len).collect();
+        let a_chunks: Vec<&[G::Scalar]> = a.chunks(chunk_len).collect();
+        let b_chunks: Vec<&[G::Scalar]> = b.chunks(chunk_len).collect();
+        let chunked_proof = InnerProductProof::<G>::create(
+            bp_gens.msm_backend(),
+            &mut Transcript::new(b"innerproducttest"),
+            &Q,
+            &G_factors,
+            &H_factors,
+            Chunked::new(&G_chunks),
+            Chunked::new(&H_chunks),
+            Chunked::new(&a_chunks),
+            Chunked::new(&b_chunks),
+        );
+        assert_eq!(chunked_proof.to_bytes(), proof.to_bytes());
+        assert_eq!(proof.to_bytes().len(), proof.serialized_size());
+
         let mut verifier = Transcript::new(b"innerproducttest");
         assert!(proof
             .verify(
                 n,
                 &mut verifier,
-                iter::repeat(Scalar::one()).take(n),
-                util::exp_iter(y_inv).take(n),
+                iter::repeat(G::Scalar::one()).take(n),
+                H_factors.iter(),
                 &P,
                 &Q,
                 &G,
@@ -492,14 +639,14 @@ mod tests {
             )
             .is_ok());
 
-        let proof = InnerProductProof::from_bytes(proof.to_bytes().as_slice()).unwrap();
+        let proof = InnerProductProof::<G>::from_bytes(proof.to_bytes().as_slice()).unwrap();
         let mut verifier = Transcript::new(b"innerproducttest");
         assert!(proof
             .verify(
                 n,
                 &mut verifier,
-                iter::repeat(Scalar::one()).take(n),
-                util::exp_iter(y_inv).take(n),
+                iter::repeat(G::Scalar::one()).take(n),
+                H_factors.iter(),
                 &P,
                 &Q,
                 &G,
@@ -510,27 +657,34 @@ mod tests {
 
     #[test]
     fn make_ipp_1() {
-        test_helper_create(1);
+        test_helper_create::<Ristretto>(1);
     }
 
     #[test]
     fn make_ipp_2() {
-        test_helper_create(2);
+        test_helper_create::<Ristretto>(2);
     }
 
     #[test]
     fn make_ipp_4() {
-        test_helper_create(4);
+        test_helper_create::<Ristretto>(4);
     }
 
     #[test]
     fn make_ipp_32() {
-        test_helper_create(32);
+        test_helper_create::<Ristretto>(32);
     }
 
     #[test]
     fn make_ipp_64() {
-        test_helper_create(64);
+        test_helper_create::<Ristretto>(64);
+    }
+
+    #[test]
+    fn make_ipp_in_another_group() {
+        for n in &[1, 2, 4, 32] {
+            test_helper_create::<ToyGroup>(*n);
+        }
     }
 
     #[test]
@@ -11,6 +11,7 @@ extern crate alloc;
 #[macro_use]
 extern crate serde_derive;
 
+#[macro_use]
 mod util;
 
 #[cfg_attr(feature = "docs", doc = include_str!("../docs/notes-intro.md"))]
@@ -23,23 +24,41 @@ mod notes {
     mod r1cs_proof {}
 }
 
+mod batch;
 mod errors;
 mod generators;
+mod group;
 mod inner_product_proof;
+mod msm;
 // TODO: Do not expose `range_proof` publicly
 pub mod range_proof;
+pub mod range_proof_plus;
+pub mod surjection_proof;
 mod transcript;
 
+pub use crate::batch::BatchVerifier;
 pub use crate::errors::ProofError;
-pub use crate::generators::{BulletproofGens, BulletproofGensShare, PedersenGens};
-pub use crate::range_proof::RangeProof;
+pub use crate::generators::{
+    BulletproofGens, BulletproofGensShare, ExtendedPedersenGens, PedersenGens,
+};
+pub use crate::group::{Group, Ristretto, ScalarField};
+pub use crate::msm::{DalekMsm, GroupMsm, MsmBackend, PippengerMsm};
+pub use crate::range_proof::{
+    RangeProof, RangeProofProverKey, RangeProofStatement, RangeProofVerifierKey,
+};
+pub use crate::range_proof_plus::RangeProofPlus;
+pub use crate::surjection_proof::{SurjectionProof, SurjectionProofStatement};
 
 #[cfg_attr(feature = "docs", doc = include_str!("../docs/aggregation-api.md"))]
 pub mod range_proof_mpc {
     pub use crate::errors::MPCError;
+    #[cfg(feature = "std")]
+    pub use crate::errors::SessionError;
     pub use crate::range_proof::dealer;
     pub use crate::range_proof::messages;
     pub use crate::range_proof::party;
+    #[cfg(feature = "std")]
+    pub use crate::range_proof::session;
 }
 
 #[cfg(feature = "yoloproofs")]
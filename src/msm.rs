@@ -0,0 +1,270 @@
+//! The `msm` module contains the multiscalar multiplication backends
+//! used by the provers and verifiers.
+//!
+//! The backend of a [`BulletproofGens`](crate::BulletproofGens) is used
+//! for all of the multiscalar multiplications over its generators, which
+//! dominate the cost of creating and verifying proofs.  It defaults to
+//! [`DalekMsm`], or to [`GroupMsm`] for the generators of groups other
+//! than [`Ristretto`], and can be replaced with
+//! [`BulletproofGens::with_msm_backend`](crate::BulletproofGens::with_msm_backend).
+
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::borrow::Borrow;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, VartimeMultiscalarMul};
+use zeroize::Zeroize;
+
+use crate::group::{Group, Ristretto};
+
+/// A backend computing multiscalar multiplications
+/// \\(\sum\_i s\_i P\_i\\) of equal length slices of scalars and points
+/// of the group `G`.
+pub trait MsmBackend<G: Group = Ristretto>: Send + Sync {
+    /// Computes the multiscalar multiplication in variable time.
+    ///
+    /// This is used by verifiers, and for the rounds of the inner
+    /// product proof.
+    fn vartime_multiscalar_mul(&self, scalars: &[G::Scalar], points: &[G::Point]) -> G::Point;
+
+    /// Computes the multiscalar multiplication of iterators in variable
+    /// time.
+    ///
+    /// The default implementation collects the iterators for
+    /// [`MsmBackend::vartime_multiscalar_mul`]; a backend which can
+    /// consume them directly saves the copies of the points.
+    fn vartime_multiscalar_mul_iter(
+        &self,
+        scalars: &mut dyn Iterator<Item = G::Scalar>,
+        points: &mut dyn Iterator<Item = &G::Point>,
+    ) -> G::Point {
+        let scalars: Vec<G::Scalar> = scalars.collect();
+        let points: Vec<G::Point> = points.cloned().collect();
+        self.vartime_multiscalar_mul(&scalars, &points)
+    }
+
+    /// Computes the multiscalar multiplication in constant time.
+    ///
+    /// This is used by provers, whose scalars are secret, so an
+    /// implementation must not branch on or index memory by the scalars.
+    /// The default implementation uses the constant time algorithm of
+    /// the group, which is that of `curve25519-dalek` for [`Ristretto`].
+    fn multiscalar_mul(&self, scalars: &[G::Scalar], points: &[G::Point]) -> G::Point {
+        G::multiscalar_mul(scalars, points)
+    }
+}
+
+/// The default backend, which uses the multiscalar multiplication
+/// algorithms of `curve25519-dalek`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DalekMsm;
+
+impl MsmBackend for DalekMsm {
+    fn vartime_multiscalar_mul(
+        &self,
+        scalars: &[Scalar],
+        points: &[RistrettoPoint],
+    ) -> RistrettoPoint {
+        RistrettoPoint::vartime_multiscalar_mul(scalars, points)
+    }
+
+    fn vartime_multiscalar_mul_iter(
+        &self,
+        scalars: &mut dyn Iterator<Item = Scalar>,
+        points: &mut dyn Iterator<Item = &RistrettoPoint>,
+    ) -> RistrettoPoint {
+        RistrettoPoint::vartime_multiscalar_mul(scalars, points)
+    }
+}
+
+/// The default backend of the groups other than [`Ristretto`], which
+/// uses the multiscalar multiplication algorithms of the [`Group`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GroupMsm;
+
+impl<G: Group> MsmBackend<G> for GroupMsm {
+    fn vartime_multiscalar_mul(&self, scalars: &[G::Scalar], points: &[G::Point]) -> G::Point {
+        G::vartime_multiscalar_mul(scalars, points)
+    }
+
+    fn vartime_multiscalar_mul_iter(
+        &self,
+        scalars: &mut dyn Iterator<Item = G::Scalar>,
+        points: &mut dyn Iterator<Item = &G::Point>,
+    ) -> G::Point {
+        G::vartime_multiscalar_mul(scalars, points)
+    }
+}
+
+/// A backend computing variable time multiscalar multiplications with
+/// the bucket method of Pippenger, using a fixed window of bits.
+///
+/// Each window of the scalars costs one addition per point and about
+/// \\(2^{w+1}\\) additions over the buckets, so larger windows suit
+/// larger multiplications.  Constant time multiplications use the
+/// default implementation of [`MsmBackend::multiscalar_mul`].
+#[derive(Copy, Clone, Debug)]
+pub struct PippengerMsm {
+    window: usize,
+}
+
+impl PippengerMsm {
+    /// Constructs a backend with a window of `window` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is not between 1 and 16.
+    pub fn new(window: usize) -> Self {
+        assert!(
+            (1..=16).contains(&window),
+            "window must be between 1 and 16 bits"
+        );
+        PippengerMsm { window }
+    }
+
+    /// Returns the window of the backend, in bits.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
+
+impl MsmBackend for PippengerMsm {
+    fn vartime_multiscalar_mul(
+        &self,
+        scalars: &[Scalar],
+        points: &[RistrettoPoint],
+    ) -> RistrettoPoint {
+        assert_eq!(scalars.len(), points.len());
+
+        let w = self.window;
+        let scalars: Vec<[u8; 32]> = scalars.iter().map(|s| s.to_bytes()).collect();
+        let mut buckets = vec![RistrettoPoint::identity(); (1 << w) - 1];
+
+        let mut result = RistrettoPoint::identity();
+        for i in (0..=255 / w).rev() {
+            for _ in 0..w {
+                result += result;
+            }
+
+            for bucket in buckets.iter_mut() {
+                *bucket = RistrettoPoint::identity();
+            }
+            for (s, P) in scalars.iter().zip(points) {
+                let d = digit(s, i * w, w);
+                if d != 0 {
+                    buckets[d - 1] += P;
+                }
+            }
+
+            // Sum the buckets weighted by their digits, as the running sums
+            // of the buckets from the highest digit down.
+            let mut running_sum = RistrettoPoint::identity();
+            for bucket in buckets.iter().rev() {
+                running_sum += bucket;
+                result += running_sum;
+            }
+        }
+        result
+    }
+}
+
+/// Computes a constant time multiscalar multiplication of iterators
+/// with `msm`, clearing the collected secret scalars afterwards.
+pub(crate) fn multiscalar_mul<G, I, J>(msm: &dyn MsmBackend<G>, scalars: I, points: J) -> G::Point
+where
+    G: Group,
+    I: IntoIterator,
+    I::Item: Borrow<G::Scalar>,
+    J: IntoIterator,
+    J::Item: Borrow<G::Point>,
+{
+    let mut scalars: Vec<G::Scalar> = scalars.into_iter().map(|s| *s.borrow()).collect();
+    let points: Vec<G::Point> = points.into_iter().map(|P| *P.borrow()).collect();
+    let result = msm.multiscalar_mul(&scalars, &points);
+    scalars.zeroize();
+    result
+}
+
+/// Computes a variable time multiscalar multiplication of iterators
+/// with `msm`.
+pub(crate) fn vartime_multiscalar_mul<'a, G, I, J>(
+    msm: &dyn MsmBackend<G>,
+    scalars: I,
+    points: J,
+) -> G::Point
+where
+    G: Group,
+    I: IntoIterator,
+    I::Item: Borrow<G::Scalar>,
+    J: IntoIterator<Item = &'a G::Point>,
+{
+    msm.vartime_multiscalar_mul_iter(
+        &mut scalars.into_iter().map(|s| *s.borrow()),
+        &mut points.into_iter(),
+    )
+}
+
+/// Returns the `w` bits of the little-endian `bytes` starting at bit `offset`.
+fn digit(bytes: &[u8; 32], offset: usize, w: usize) -> usize {
+    (0..w)
+        .map(|k| offset + k)
+        .filter(|&bit| bit < 256)
+        .map(|bit| (((bytes[bit / 8] >> (bit % 8)) & 1) as usize) << (bit - offset))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::iter;
+
+    #[test]
+    fn pippenger_matches_dalek() {
+        let mut rng = rand::thread_rng();
+        for &len in &[0, 1, 7, 64] {
+            let scalars: Vec<Scalar> = iter::once(-Scalar::one())
+                .chain(iter::once(Scalar::zero()))
+                .chain((2..len).map(|_| Scalar::random(&mut rng)))
+                .take(len)
+                .collect();
+            let points: Vec<RistrettoPoint> =
+                (0..len).map(|_| RistrettoPoint::random(&mut rng)).collect();
+
+            let expected = DalekMsm.vartime_multiscalar_mul(&scalars, &points);
+            assert_eq!(DalekMsm.multiscalar_mul(&scalars, &points), expected);
+            assert_eq!(
+                MsmBackend::<Ristretto>::vartime_multiscalar_mul(&GroupMsm, &scalars, &points),
+                expected
+            );
+            assert_eq!(
+                vartime_multiscalar_mul(&DalekMsm, &scalars, &points),
+                expected
+            );
+            for window in 1..=8 {
+                let pippenger = PippengerMsm::new(window);
+                assert_eq!(
+                    pippenger.vartime_multiscalar_mul(&scalars, &points),
+                    expected
+                );
+                assert_eq!(pippenger.multiscalar_mul(&scalars, &points), expected);
+                assert_eq!(
+                    vartime_multiscalar_mul(&pippenger, &scalars, &points),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn pippenger_rejects_empty_window() {
+        PippengerMsm::new(0);
+    }
+}
@@ -1,6 +1,7 @@
 //! Definition of the constraint system trait.
 
 use super::{LinearCombination, R1CSError, Variable};
+use crate::group::ScalarField;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 
@@ -16,7 +17,11 @@ use merlin::Transcript;
 /// verifier, gadgets for the constraint system should be written
 /// using the `ConstraintSystem` trait, so that the prover and
 /// verifier share the logic for specifying constraints.
-pub trait ConstraintSystem {
+///
+/// The assignments and the coefficients of the constraints are scalars
+/// of the field `S`, which defaults to that of
+/// [`Ristretto`](crate::group::Ristretto).
+pub trait ConstraintSystem<S: ScalarField = Scalar> {
     /// Leases the proof transcript to the user, so they can
     /// add extra data to which the proof must be bound, but which
     /// is not available before creation of the constraint system.
@@ -38,8 +43,8 @@ pub trait ConstraintSystem {
     /// Returns `(left, right, out)` for use in further constraints.
     fn multiply(
         &mut self,
-        left: LinearCombination,
-        right: LinearCombination,
+        left: LinearCombination<S>,
+        right: LinearCombination<S>,
     ) -> (Variable, Variable, Variable);
 
     /// Allocate a single variable.
@@ -52,7 +57,7 @@ pub trait ConstraintSystem {
     /// has the `right` assigned to zero and all its variables committed.
     ///
     /// Returns unconstrained `Variable` for use in further constraints.
-    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError>;
+    fn allocate(&mut self, assignment: Option<S>) -> Result<Variable, R1CSError>;
 
     /// Allocate variables `left`, `right`, and `out`
     /// with the implicit constraint that
@@ -63,17 +68,29 @@ pub trait ConstraintSystem {
     /// Returns `(left, right, out)` for use in further constraints.
     fn allocate_multiplier(
         &mut self,
-        input_assignments: Option<(Scalar, Scalar)>,
+        input_assignments: Option<(S, S)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError>;
 
     /// Counts the amount of constraints in the constraint system.
+    ///
+    /// The callbacks of
+    /// [`specify_randomized_constraints`](RandomizableConstraintSystem::specify_randomized_constraints)
+    /// are only run when the proof is created or verified, so the
+    /// multipliers and constraints they allocate are not counted in
+    /// the `multipliers` and `constraints` of the metrics outside of
+    /// them; each callback counts as one of the
+    /// `phase_two_constraints`.  The estimators
+    /// [`R1CSProof::serialized_size_for`](crate::r1cs::R1CSProof::serialized_size_for)
+    /// and
+    /// [`R1CSProof::verification_msm_size`](crate::r1cs::R1CSProof::verification_msm_size)
+    /// take the number of randomized multipliers separately.
     fn metrics(&self) -> crate::r1cs::Metrics;
 
     /// Enforce the explicit constraint that
     /// ```text
     /// lc = 0
     /// ```
-    fn constrain(&mut self, lc: LinearCombination);
+    fn constrain(&mut self, lc: LinearCombination<S>);
 }
 
 /// An extension to the constraint system trait that permits randomized constraints.
@@ -81,9 +98,9 @@ pub trait ConstraintSystem {
 /// while gadgets that need randomization should use trait bound `CS: RandomizedConstraintSystem`.
 /// Gadgets generally _should not_ use this trait as a bound on the CS argument: it should be used
 /// by the higher-order protocol that composes gadgets together.
-pub trait RandomizableConstraintSystem: ConstraintSystem {
+pub trait RandomizableConstraintSystem<S: ScalarField = Scalar>: ConstraintSystem<S> {
     /// Represents a concrete type for the CS in a randomization phase.
-    type RandomizedCS: RandomizedConstraintSystem;
+    type RandomizedCS: RandomizedConstraintSystem<S>;
 
     /// Specify additional variables and constraints randomized using a challenge scalar
     /// bound to the assignments of the non-randomized variables.
@@ -114,7 +131,7 @@ pub trait RandomizableConstraintSystem: ConstraintSystem {
 ///
 /// Note: this trait also includes `ConstraintSystem` trait
 /// in order to allow composition of gadgets: e.g. a shuffle gadget can be used in both phases.
-pub trait RandomizedConstraintSystem: ConstraintSystem {
+pub trait RandomizedConstraintSystem<S: ScalarField = Scalar>: ConstraintSystem<S> {
     /// Generates a challenge scalar.
     ///
     /// ### Usage
@@ -131,5 +148,5 @@ pub trait RandomizedConstraintSystem: ConstraintSystem {
     ///     // ...
     /// })
     /// ```
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> S;
 }
@@ -2,6 +2,8 @@
 
 use curve25519_dalek::scalar::Scalar;
 use std::iter::FromIterator;
+
+use crate::group::ScalarField;
 use std::ops::{Add, Mul, Neg, Sub};
 
 /// Represents a variable in a constraint system.
@@ -19,10 +21,10 @@ pub enum Variable {
     One(),
 }
 
-impl From<Variable> for LinearCombination {
-    fn from(v: Variable) -> LinearCombination {
+impl<S: ScalarField> From<Variable> for LinearCombination<S> {
+    fn from(v: Variable) -> LinearCombination<S> {
         LinearCombination {
-            terms: vec![(v, Scalar::one())],
+            terms: vec![(v, S::one())],
         }
     }
 }
@@ -105,22 +107,23 @@ impl Mul<Variable> for Scalar {
 
 /// Represents a linear combination of
 /// [`Variables`](::r1cs::Variable).  Each term is represented by a
-/// `(Variable, Scalar)` pair.
+/// `(Variable, Scalar)` pair, with a scalar of the field `S`, which
+/// defaults to that of [`Ristretto`](crate::group::Ristretto).
 #[derive(Clone, Debug, PartialEq)]
-pub struct LinearCombination {
-    pub(super) terms: Vec<(Variable, Scalar)>,
+pub struct LinearCombination<S: ScalarField = Scalar> {
+    pub(super) terms: Vec<(Variable, S)>,
 }
 
-impl Default for LinearCombination {
+impl<S: ScalarField> Default for LinearCombination<S> {
     fn default() -> Self {
         LinearCombination { terms: Vec::new() }
     }
 }
 
-impl FromIterator<(Variable, Scalar)> for LinearCombination {
+impl<S: ScalarField> FromIterator<(Variable, S)> for LinearCombination<S> {
     fn from_iter<T>(iter: T) -> Self
     where
-        T: IntoIterator<Item = (Variable, Scalar)>,
+        T: IntoIterator<Item = (Variable, S)>,
     {
         LinearCombination {
             terms: iter.into_iter().collect(),
@@ -128,10 +131,10 @@ impl FromIterator<(Variable, Scalar)> for LinearCombination {
     }
 }
 
-impl<'a> FromIterator<&'a (Variable, Scalar)> for LinearCombination {
+impl<'a, S: ScalarField> FromIterator<&'a (Variable, S)> for LinearCombination<S> {
     fn from_iter<T>(iter: T) -> Self
     where
-        T: IntoIterator<Item = &'a (Variable, Scalar)>,
+        T: IntoIterator<Item = &'a (Variable, S)>,
     {
         LinearCombination {
             terms: iter.into_iter().cloned().collect(),
@@ -141,7 +144,7 @@ impl<'a> FromIterator<&'a (Variable, Scalar)> for LinearCombination {
 
 // Arithmetic on linear combinations
 
-impl<L: Into<LinearCombination>> Add<L> for LinearCombination {
+impl<S: ScalarField, L: Into<LinearCombination<S>>> Add<L> for LinearCombination<S> {
     type Output = Self;
 
     fn add(mut self, rhs: L) -> Self::Output {
@@ -150,12 +153,12 @@ impl<L: Into<LinearCombination>> Add<L> for LinearCombination {
     }
 }
 
-impl<L: Into<LinearCombination>> Sub<L> for LinearCombination {
+impl<S: ScalarField, L: Into<LinearCombination<S>>> Sub<L> for LinearCombination<S> {
     type Output = Self;
 
     fn sub(mut self, rhs: L) -> Self::Output {
         self.terms
-            .extend(rhs.into().terms.iter().map(|(var, coeff)| (*var, -coeff)));
+            .extend(rhs.into().terms.iter().map(|(var, coeff)| (*var, -*coeff)));
         LinearCombination { terms: self.terms }
     }
 }
@@ -173,7 +176,7 @@ impl Mul<LinearCombination> for Scalar {
     }
 }
 
-impl Neg for LinearCombination {
+impl<S: ScalarField> Neg for LinearCombination<S> {
     type Output = Self;
 
     fn neg(mut self) -> Self::Output {
@@ -184,10 +187,10 @@ impl Neg for LinearCombination {
     }
 }
 
-impl<S: Into<Scalar>> Mul<S> for LinearCombination {
+impl<S: ScalarField, T: Into<S>> Mul<T> for LinearCombination<S> {
     type Output = Self;
 
-    fn mul(mut self, other: S) -> Self::Output {
+    fn mul(mut self, other: T) -> Self::Output {
         let other = other.into();
         for (_, s) in self.terms.iter_mut() {
             *s *= other
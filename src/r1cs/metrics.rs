@@ -11,4 +11,26 @@ pub struct Metrics {
     pub phase_one_constraints: usize,
     /// Number of linear constraints added in the randomization phase.
     pub phase_two_constraints: usize,
+    /// Number of high-level variables committed to with Pedersen commitments.
+    pub commitments: usize,
+}
+
+impl Metrics {
+    /// Creates the metrics of a constraint system from its counts, for
+    /// estimating the size of its proofs without building it.
+    pub fn new(
+        multipliers: usize,
+        constraints: usize,
+        phase_one_constraints: usize,
+        phase_two_constraints: usize,
+        commitments: usize,
+    ) -> Metrics {
+        Metrics {
+            multipliers,
+            constraints,
+            phase_one_constraints,
+            phase_two_constraints,
+            commitments,
+        }
+    }
 }
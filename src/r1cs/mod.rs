@@ -17,6 +17,7 @@ pub use self::linear_combination::{LinearCombination, Variable};
 pub use self::metrics::Metrics;
 pub use self::proof::R1CSProof;
 pub use self::prover::Prover;
+pub(crate) use self::verifier::PendingCheck;
 pub use self::verifier::Verifier;
 
 pub use crate::errors::R1CSError;
@@ -1,13 +1,12 @@
 #![allow(non_snake_case)]
 //! Definition of the proof struct.
 
-use curve25519_dalek::ristretto::CompressedRistretto;
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::{Identity, IsIdentity};
+use core::marker::PhantomData;
 
 use crate::errors::R1CSError;
+use crate::group::{Group, Ristretto, ScalarField};
 use crate::inner_product_proof::InnerProductProof;
-use crate::util;
+use crate::r1cs::Metrics;
 
 use serde::de::Visitor;
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
@@ -31,43 +30,49 @@ const TWO_PHASE_COMMITMENTS: u8 = 1;
 /// the constraint system using
 /// [`VerifierCS::verify`](::r1cs::VerifierCS::verify) to verify the
 /// proof.
+///
+/// The proof is generic over the prime-order [`Group`] of its
+/// commitments, which defaults to [`Ristretto`].  The
+/// [`Prover`](::r1cs::Prover) takes the generators of the group, and
+/// the [`Verifier`](::r1cs::Verifier) of another group is constructed
+/// with [`Verifier::for_group`](::r1cs::Verifier::for_group).
 #[derive(Clone, Debug)]
 #[allow(non_snake_case)]
-pub struct R1CSProof {
+pub struct R1CSProof<G: Group = Ristretto> {
     /// Commitment to the values of input wires in the first phase.
-    pub(super) A_I1: CompressedRistretto,
+    pub(super) A_I1: G::CompressedPoint,
     /// Commitment to the values of output wires in the first phase.
-    pub(super) A_O1: CompressedRistretto,
+    pub(super) A_O1: G::CompressedPoint,
     /// Commitment to the blinding factors in the first phase.
-    pub(super) S1: CompressedRistretto,
+    pub(super) S1: G::CompressedPoint,
     /// Commitment to the values of input wires in the second phase.
-    pub(super) A_I2: CompressedRistretto,
+    pub(super) A_I2: G::CompressedPoint,
     /// Commitment to the values of output wires in the second phase.
-    pub(super) A_O2: CompressedRistretto,
+    pub(super) A_O2: G::CompressedPoint,
     /// Commitment to the blinding factors in the second phase.
-    pub(super) S2: CompressedRistretto,
+    pub(super) S2: G::CompressedPoint,
     /// Commitment to the \\(t_1\\) coefficient of \\( t(x) \\)
-    pub(super) T_1: CompressedRistretto,
+    pub(super) T_1: G::CompressedPoint,
     /// Commitment to the \\(t_3\\) coefficient of \\( t(x) \\)
-    pub(super) T_3: CompressedRistretto,
+    pub(super) T_3: G::CompressedPoint,
     /// Commitment to the \\(t_4\\) coefficient of \\( t(x) \\)
-    pub(super) T_4: CompressedRistretto,
+    pub(super) T_4: G::CompressedPoint,
     /// Commitment to the \\(t_5\\) coefficient of \\( t(x) \\)
-    pub(super) T_5: CompressedRistretto,
+    pub(super) T_5: G::CompressedPoint,
     /// Commitment to the \\(t_6\\) coefficient of \\( t(x) \\)
-    pub(super) T_6: CompressedRistretto,
+    pub(super) T_6: G::CompressedPoint,
     /// Evaluation of the polynomial \\(t(x)\\) at the challenge point \\(x\\)
-    pub(super) t_x: Scalar,
+    pub(super) t_x: G::Scalar,
     /// Blinding factor for the synthetic commitment to \\( t(x) \\)
-    pub(super) t_x_blinding: Scalar,
+    pub(super) t_x_blinding: G::Scalar,
     /// Blinding factor for the synthetic commitment to the
     /// inner-product arguments
-    pub(super) e_blinding: Scalar,
+    pub(super) e_blinding: G::Scalar,
     /// Proof data for the inner-product argument.
-    pub(super) ipp_proof: InnerProductProof,
+    pub(super) ipp_proof: InnerProductProof<G>,
 }
 
-impl R1CSProof {
+impl<G: Group> R1CSProof<G> {
     /// Serializes the proof into a byte array of 1 version byte + \\((13 or 16) + 2k\\) 32-byte elements,
     /// where \\(k=\lceil \log_2(n) \rceil\\) and \\(n\\) is the number of multiplication gates.
     ///
@@ -80,106 +85,105 @@ impl R1CSProof {
     /// * three scalars \\(t_x, \tilde{t}_x, \tilde{e}\\),
     /// * \\(k\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{k-1},R_{k-1}\\),
     /// * two scalars \\(a, b\\).
+    ///
+    /// The points of other groups take [`Group::COMPRESSED_POINT_SIZE`] bytes.
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(self.serialized_size());
         if self.missing_phase2_commitments() {
             buf.push(ONE_PHASE_COMMITMENTS);
-            buf.extend_from_slice(self.A_I1.as_bytes());
-            buf.extend_from_slice(self.A_O1.as_bytes());
-            buf.extend_from_slice(self.S1.as_bytes());
+            buf.extend_from_slice(G::compressed_as_bytes(&self.A_I1));
+            buf.extend_from_slice(G::compressed_as_bytes(&self.A_O1));
+            buf.extend_from_slice(G::compressed_as_bytes(&self.S1));
         } else {
             buf.push(TWO_PHASE_COMMITMENTS);
-            buf.extend_from_slice(self.A_I1.as_bytes());
-            buf.extend_from_slice(self.A_O1.as_bytes());
-            buf.extend_from_slice(self.S1.as_bytes());
-            buf.extend_from_slice(self.A_I2.as_bytes());
-            buf.extend_from_slice(self.A_O2.as_bytes());
-            buf.extend_from_slice(self.S2.as_bytes());
+            buf.extend_from_slice(G::compressed_as_bytes(&self.A_I1));
+            buf.extend_from_slice(G::compressed_as_bytes(&self.A_O1));
+            buf.extend_from_slice(G::compressed_as_bytes(&self.S1));
+            buf.extend_from_slice(G::compressed_as_bytes(&self.A_I2));
+            buf.extend_from_slice(G::compressed_as_bytes(&self.A_O2));
+            buf.extend_from_slice(G::compressed_as_bytes(&self.S2));
         }
-        buf.extend_from_slice(self.T_1.as_bytes());
-        buf.extend_from_slice(self.T_3.as_bytes());
-        buf.extend_from_slice(self.T_4.as_bytes());
-        buf.extend_from_slice(self.T_5.as_bytes());
-        buf.extend_from_slice(self.T_6.as_bytes());
-        buf.extend_from_slice(self.t_x.as_bytes());
-        buf.extend_from_slice(self.t_x_blinding.as_bytes());
-        buf.extend_from_slice(self.e_blinding.as_bytes());
+        buf.extend_from_slice(G::compressed_as_bytes(&self.T_1));
+        buf.extend_from_slice(G::compressed_as_bytes(&self.T_3));
+        buf.extend_from_slice(G::compressed_as_bytes(&self.T_4));
+        buf.extend_from_slice(G::compressed_as_bytes(&self.T_5));
+        buf.extend_from_slice(G::compressed_as_bytes(&self.T_6));
+        buf.extend_from_slice(&self.t_x.to_bytes());
+        buf.extend_from_slice(&self.t_x_blinding.to_bytes());
+        buf.extend_from_slice(&self.e_blinding.to_bytes());
         buf.extend(self.ipp_proof.to_bytes_iter());
         buf
     }
 
     /// Returns the size in bytes required to serialize the `R1CSProof`.
     pub fn serialized_size(&self) -> usize {
-        // version tag + (11 or 14) elements + the ipp
-        let elements = if self.missing_phase2_commitments() {
-            11
+        // version tag + (8 or 11) points + 3 scalars + the ipp
+        let points = if self.missing_phase2_commitments() {
+            8
         } else {
-            14
+            11
         };
-        1 + elements * 32 + self.ipp_proof.serialized_size()
+        1 + points * G::COMPRESSED_POINT_SIZE
+            + 3 * G::SCALAR_SIZE
+            + self.ipp_proof.serialized_size()
     }
 
     fn missing_phase2_commitments(&self) -> bool {
-        self.A_I2.is_identity() && self.A_O2.is_identity() && self.S2.is_identity()
+        G::is_identity(&self.A_I2) && G::is_identity(&self.A_O2) && G::is_identity(&self.S2)
     }
 
-    /// Deserializes the proof from a byte slice.
-    ///
-    /// Returns an error if the byte slice cannot be parsed into a `R1CSProof`.
-    pub fn from_bytes(slice: &[u8]) -> Result<R1CSProof, R1CSError> {
+    /// Deserializes the proof from a byte slice, as
+    /// [`R1CSProof::from_bytes`] does for the proofs of any group.
+    fn read_bytes(slice: &[u8]) -> Result<Self, R1CSError> {
         if slice.len() < 1 {
             return Err(R1CSError::FormatError);
         }
         let version = slice[0];
         let mut slice = &slice[1..];
 
-        if slice.len() % 32 != 0 {
-            return Err(R1CSError::FormatError);
-        }
-
-        let minlength = match version {
-            ONE_PHASE_COMMITMENTS => 11 * 32,
-            TWO_PHASE_COMMITMENTS => 14 * 32,
+        let points = match version {
+            ONE_PHASE_COMMITMENTS => 8,
+            TWO_PHASE_COMMITMENTS => 11,
             _ => return Err(R1CSError::FormatError),
         };
 
-        if slice.len() < minlength {
+        if slice.len() < points * G::COMPRESSED_POINT_SIZE + 3 * G::SCALAR_SIZE {
             return Err(R1CSError::FormatError);
         }
 
-        // This macro takes care of counting bytes in the slice
-        macro_rules! read32 {
+        // These macros take care of counting bytes in the slice
+        macro_rules! read_point {
+            () => {{
+                let (point, rest) = slice.split_at(G::COMPRESSED_POINT_SIZE);
+                slice = rest;
+                G::compressed_from_slice(point).ok_or(R1CSError::FormatError)?
+            }};
+        }
+        macro_rules! read_scalar {
             () => {{
-                let tmp = util::read32(slice);
-                slice = &slice[32..];
-                tmp
+                let (scalar, rest) = slice.split_at(G::SCALAR_SIZE);
+                slice = rest;
+                G::Scalar::from_canonical_bytes(scalar).ok_or(R1CSError::FormatError)?
             }};
         }
 
-        let A_I1 = CompressedRistretto(read32!());
-        let A_O1 = CompressedRistretto(read32!());
-        let S1 = CompressedRistretto(read32!());
+        let A_I1 = read_point!();
+        let A_O1 = read_point!();
+        let S1 = read_point!();
         let (A_I2, A_O2, S2) = if version == ONE_PHASE_COMMITMENTS {
-            (
-                CompressedRistretto::identity(),
-                CompressedRistretto::identity(),
-                CompressedRistretto::identity(),
-            )
+            let identity = G::compress(&G::identity());
+            (identity, identity, identity)
         } else {
-            (
-                CompressedRistretto(read32!()),
-                CompressedRistretto(read32!()),
-                CompressedRistretto(read32!()),
-            )
+            (read_point!(), read_point!(), read_point!())
         };
-        let T_1 = CompressedRistretto(read32!());
-        let T_3 = CompressedRistretto(read32!());
-        let T_4 = CompressedRistretto(read32!());
-        let T_5 = CompressedRistretto(read32!());
-        let T_6 = CompressedRistretto(read32!());
-        let t_x = Scalar::from_canonical_bytes(read32!()).ok_or(R1CSError::FormatError)?;
-        let t_x_blinding = Scalar::from_canonical_bytes(read32!()).ok_or(R1CSError::FormatError)?;
-        let e_blinding = Scalar::from_canonical_bytes(read32!()).ok_or(R1CSError::FormatError)?;
+        let T_1 = read_point!();
+        let T_3 = read_point!();
+        let T_4 = read_point!();
+        let T_5 = read_point!();
+        let T_6 = read_point!();
+        let t_x = read_scalar!();
+        let t_x_blinding = read_scalar!();
+        let e_blinding = read_scalar!();
 
         // XXX: IPPProof from_bytes gives ProofError.
         let ipp_proof = InnerProductProof::from_bytes(slice).map_err(|_| R1CSError::FormatError)?;
@@ -204,7 +208,51 @@ impl R1CSProof {
     }
 }
 
-impl Serialize for R1CSProof {
+impl R1CSProof {
+    /// Returns the size in bytes of the serialization of a proof for a
+    /// constraint system with the given `metrics`, which matches the
+    /// length of [`R1CSProof::to_bytes`].
+    ///
+    /// The `metrics` are those of the constraint system before its
+    /// randomized constraints are run, as returned by
+    /// [`ConstraintSystem::metrics`](crate::r1cs::ConstraintSystem::metrics)
+    /// once all of the gadgets are built, and `randomized_multipliers`
+    /// is the number of multipliers which the randomized constraints
+    /// allocate.  The proof only carries the commitments of the second
+    /// phase if `randomized_multipliers` is not zero.
+    pub fn serialized_size_for(metrics: &Metrics, randomized_multipliers: usize) -> usize {
+        let elements = if randomized_multipliers == 0 { 11 } else { 14 };
+        let multipliers = metrics.multipliers + randomized_multipliers;
+        let lg_n = multipliers.next_power_of_two().trailing_zeros() as usize;
+        // version tag + (11 or 14) elements + the ipp
+        1 + elements * 32 + (2 * lg_n + 2) * 32
+    }
+
+    /// Returns the number of terms of the multiscalar multiplication
+    /// which verifies a proof for a constraint system with the given
+    /// `metrics` and `randomized_multipliers`, counted as for
+    /// [`R1CSProof::serialized_size_for`].
+    ///
+    /// The multiplication has a point and a scalar for each of the
+    /// commitments of the proof and the high-level variables, and for
+    /// two generators per padded multiplier.
+    pub fn verification_msm_size(metrics: &Metrics, randomized_multipliers: usize) -> usize {
+        let padded_n = (metrics.multipliers + randomized_multipliers).next_power_of_two();
+        let lg_n = padded_n.trailing_zeros() as usize;
+        // A_I1, A_O1, S1, A_I2, A_O2, S2, V, T_1, T_3, T_4, T_5, T_6,
+        // B, B_blinding, G, H, L, R
+        6 + metrics.commitments + 5 + 2 + 2 * padded_n + 2 * lg_n
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `R1CSProof`.
+    pub fn from_bytes(slice: &[u8]) -> Result<R1CSProof, R1CSError> {
+        R1CSProof::read_bytes(slice)
+    }
+}
+
+impl<G: Group> Serialize for R1CSProof<G> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -213,35 +261,35 @@ impl Serialize for R1CSProof {
     }
 }
 
-impl<'de> Deserialize<'de> for R1CSProof {
+impl<'de, G: Group> Deserialize<'de> for R1CSProof<G> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct R1CSProofVisitor;
+        struct R1CSProofVisitor<G>(PhantomData<G>);
 
-        impl<'de> Visitor<'de> for R1CSProofVisitor {
-            type Value = R1CSProof;
+        impl<'de, G: Group> Visitor<'de> for R1CSProofVisitor<G> {
+            type Value = R1CSProof<G>;
 
             fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                 formatter.write_str("a valid R1CSProof")
             }
 
-            fn visit_bytes<E>(self, v: &[u8]) -> Result<R1CSProof, E>
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<R1CSProof<G>, E>
             where
                 E: serde::de::Error,
             {
                 // Using Error::custom requires T: Display, which our error
                 // type only implements when it implements std::error::Error.
                 #[cfg(feature = "std")]
-                return R1CSProof::from_bytes(v).map_err(serde::de::Error::custom);
+                return R1CSProof::read_bytes(v).map_err(serde::de::Error::custom);
                 // In no-std contexts, drop the error message.
                 #[cfg(not(feature = "std"))]
-                return R1CSProof::from_bytes(v)
+                return R1CSProof::read_bytes(v)
                     .map_err(|_| serde::de::Error::custom("deserialization error"));
             }
         }
 
-        deserializer.deserialize_bytes(R1CSProofVisitor)
+        deserializer.deserialize_bytes(R1CSProofVisitor(PhantomData))
     }
 }
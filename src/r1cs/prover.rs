@@ -2,9 +2,6 @@
 
 use core::borrow::BorrowMut;
 use core::mem;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::{Identity, MultiscalarMul};
 use merlin::Transcript;
 use zeroize::Zeroize;
 
@@ -15,7 +12,9 @@ use super::{
 
 use crate::errors::R1CSError;
 use crate::generators::{BulletproofGens, PedersenGens};
-use crate::inner_product_proof::InnerProductProof;
+use crate::group::{Group, Ristretto, ScalarField};
+use crate::inner_product_proof::{Chunked, InnerProductProof};
+use crate::msm;
 use crate::r1cs::Metrics;
 use crate::transcript::TranscriptProtocol;
 
@@ -28,18 +27,19 @@ use crate::transcript::TranscriptProtocol;
 /// When all constraints are added, the proving code calls `prove`
 /// which consumes the `Prover` instance, samples random challenges
 /// that instantiate the randomized constraints, and creates a complete proof.
-pub struct Prover<'g, T: BorrowMut<Transcript>> {
+pub struct Prover<'g, T: BorrowMut<Transcript>, G: Group = Ristretto> {
     transcript: T,
-    pc_gens: &'g PedersenGens,
+    pc_gens: &'g PedersenGens<G>,
     /// The constraints accumulated so far.
-    constraints: Vec<LinearCombination>,
+    constraints: Vec<LinearCombination<G::Scalar>>,
     /// Secret data
-    secrets: Secrets,
+    secrets: Secrets<G::Scalar>,
 
     /// This list holds closures that will be called in the second phase of the protocol,
     /// when non-randomized variables are committed.
+    #[allow(clippy::type_complexity)]
     deferred_constraints:
-        Vec<Box<dyn FnOnce(&mut RandomizingProver<'g, T>) -> Result<(), R1CSError>>>,
+        Vec<Box<dyn FnOnce(&mut RandomizingProver<'g, T, G>) -> Result<(), R1CSError>>>,
 
     /// Index of a pending multiplier that's not fully assigned yet.
     pending_multiplier: Option<usize>,
@@ -47,17 +47,17 @@ pub struct Prover<'g, T: BorrowMut<Transcript>> {
 
 /// Separate struct to implement Drop trait for (for zeroing),
 /// so that compiler does not prohibit us from moving the Transcript out of `prove()`.
-struct Secrets {
+struct Secrets<S: ScalarField> {
     /// Stores assignments to the "left" of multiplication gates
-    a_L: Vec<Scalar>,
+    a_L: Vec<S>,
     /// Stores assignments to the "right" of multiplication gates
-    a_R: Vec<Scalar>,
+    a_R: Vec<S>,
     /// Stores assignments to the "output" of multiplication gates
-    a_O: Vec<Scalar>,
+    a_O: Vec<S>,
     /// High-level witness data (value openings to V commitments)
-    v: Vec<Scalar>,
+    v: Vec<S>,
     /// High-level witness data (blinding openings to V commitments)
-    v_blinding: Vec<Scalar>,
+    v_blinding: Vec<S>,
 }
 
 /// Prover in the randomizing phase.
@@ -67,12 +67,12 @@ struct Secrets {
 /// monomorphize the closures for the proving and verifying code.
 /// However, this type cannot be instantiated by the user and therefore can only be used within
 /// the callback provided to `specify_randomized_constraints`.
-pub struct RandomizingProver<'g, T: BorrowMut<Transcript>> {
-    prover: Prover<'g, T>,
+pub struct RandomizingProver<'g, T: BorrowMut<Transcript>, G: Group = Ristretto> {
+    prover: Prover<'g, T, G>,
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
-impl Drop for Secrets {
+impl<S: ScalarField> Drop for Secrets<S> {
     fn drop(&mut self) {
         self.v.zeroize();
         self.v_blinding.zeroize();
@@ -89,15 +89,15 @@ impl Drop for Secrets {
     }
 }
 
-impl<'g, T: BorrowMut<Transcript>> ConstraintSystem for Prover<'g, T> {
+impl<'g, T: BorrowMut<Transcript>, G: Group> ConstraintSystem<G::Scalar> for Prover<'g, T, G> {
     fn transcript(&mut self) -> &mut Transcript {
         self.transcript.borrow_mut()
     }
 
     fn multiply(
         &mut self,
-        mut left: LinearCombination,
-        mut right: LinearCombination,
+        mut left: LinearCombination<G::Scalar>,
+        mut right: LinearCombination<G::Scalar>,
     ) -> (Variable, Variable, Variable) {
         // Synthesize the assignments for l,r,o
         let l = self.eval(&left);
@@ -114,15 +114,15 @@ impl<'g, T: BorrowMut<Transcript>> ConstraintSystem for Prover<'g, T> {
         self.secrets.a_O.push(o);
 
         // Constrain l,r,o:
-        left.terms.push((l_var, -Scalar::one()));
-        right.terms.push((r_var, -Scalar::one()));
+        left.terms.push((l_var, -G::Scalar::one()));
+        right.terms.push((r_var, -G::Scalar::one()));
         self.constrain(left);
         self.constrain(right);
 
         (l_var, r_var, o_var)
     }
 
-    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError> {
+    fn allocate(&mut self, assignment: Option<G::Scalar>) -> Result<Variable, R1CSError> {
         let scalar = assignment.ok_or(R1CSError::MissingAssignment)?;
 
         match self.pending_multiplier {
@@ -130,8 +130,8 @@ impl<'g, T: BorrowMut<Transcript>> ConstraintSystem for Prover<'g, T> {
                 let i = self.secrets.a_L.len();
                 self.pending_multiplier = Some(i);
                 self.secrets.a_L.push(scalar);
-                self.secrets.a_R.push(Scalar::zero());
-                self.secrets.a_O.push(Scalar::zero());
+                self.secrets.a_R.push(G::Scalar::zero());
+                self.secrets.a_O.push(G::Scalar::zero());
                 Ok(Variable::MultiplierLeft(i))
             }
             Some(i) => {
@@ -145,7 +145,7 @@ impl<'g, T: BorrowMut<Transcript>> ConstraintSystem for Prover<'g, T> {
 
     fn allocate_multiplier(
         &mut self,
-        input_assignments: Option<(Scalar, Scalar)>,
+        input_assignments: Option<(G::Scalar, G::Scalar)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError> {
         let (l, r) = input_assignments.ok_or(R1CSError::MissingAssignment)?;
         let o = l * r;
@@ -168,18 +168,21 @@ impl<'g, T: BorrowMut<Transcript>> ConstraintSystem for Prover<'g, T> {
             constraints: self.constraints.len() + self.deferred_constraints.len(),
             phase_one_constraints: self.constraints.len(),
             phase_two_constraints: self.deferred_constraints.len(),
+            commitments: self.secrets.v.len(),
         }
     }
 
-    fn constrain(&mut self, lc: LinearCombination) {
+    fn constrain(&mut self, lc: LinearCombination<G::Scalar>) {
         // TODO: check that the linear combinations are valid
         // (e.g. that variables are valid, that the linear combination evals to 0 for prover, etc).
         self.constraints.push(lc);
     }
 }
 
-impl<'g, T: BorrowMut<Transcript>> RandomizableConstraintSystem for Prover<'g, T> {
-    type RandomizedCS = RandomizingProver<'g, T>;
+impl<'g, T: BorrowMut<Transcript>, G: Group> RandomizableConstraintSystem<G::Scalar>
+    for Prover<'g, T, G>
+{
+    type RandomizedCS = RandomizingProver<'g, T, G>;
 
     fn specify_randomized_constraints<F>(&mut self, callback: F) -> Result<(), R1CSError>
     where
@@ -190,26 +193,28 @@ impl<'g, T: BorrowMut<Transcript>> RandomizableConstraintSystem for Prover<'g, T
     }
 }
 
-impl<'g, T: BorrowMut<Transcript>> ConstraintSystem for RandomizingProver<'g, T> {
+impl<'g, T: BorrowMut<Transcript>, G: Group> ConstraintSystem<G::Scalar>
+    for RandomizingProver<'g, T, G>
+{
     fn transcript(&mut self) -> &mut Transcript {
         self.prover.transcript.borrow_mut()
     }
 
     fn multiply(
         &mut self,
-        left: LinearCombination,
-        right: LinearCombination,
+        left: LinearCombination<G::Scalar>,
+        right: LinearCombination<G::Scalar>,
     ) -> (Variable, Variable, Variable) {
         self.prover.multiply(left, right)
     }
 
-    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError> {
+    fn allocate(&mut self, assignment: Option<G::Scalar>) -> Result<Variable, R1CSError> {
         self.prover.allocate(assignment)
     }
 
     fn allocate_multiplier(
         &mut self,
-        input_assignments: Option<(Scalar, Scalar)>,
+        input_assignments: Option<(G::Scalar, G::Scalar)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError> {
         self.prover.allocate_multiplier(input_assignments)
     }
@@ -218,18 +223,23 @@ impl<'g, T: BorrowMut<Transcript>> ConstraintSystem for RandomizingProver<'g, T>
         self.prover.metrics()
     }
 
-    fn constrain(&mut self, lc: LinearCombination) {
+    fn constrain(&mut self, lc: LinearCombination<G::Scalar>) {
         self.prover.constrain(lc)
     }
 }
 
-impl<'g, T: BorrowMut<Transcript>> RandomizedConstraintSystem for RandomizingProver<'g, T> {
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
-        self.prover.transcript.borrow_mut().challenge_scalar(label)
+impl<'g, T: BorrowMut<Transcript>, G: Group> RandomizedConstraintSystem<G::Scalar>
+    for RandomizingProver<'g, T, G>
+{
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> G::Scalar {
+        self.prover
+            .transcript
+            .borrow_mut()
+            .challenge_group_scalar::<G>(label)
     }
 }
 
-impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
+impl<'g, T: BorrowMut<Transcript>, G: Group> Prover<'g, T, G> {
     /// Construct an empty constraint system with specified external
     /// input variables.
     ///
@@ -250,7 +260,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
     /// # Returns
     ///
     /// Returns a new `Prover` instance.
-    pub fn new(pc_gens: &'g PedersenGens, mut transcript: T) -> Self {
+    pub fn new(pc_gens: &'g PedersenGens<G>, mut transcript: T) -> Self {
         transcript.borrow_mut().r1cs_domain_sep();
 
         Prover {
@@ -284,16 +294,22 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
     ///
     /// # Returns
     ///
-    /// Returns a pair of a Pedersen commitment (as a compressed Ristretto point),
+    /// Returns a pair of a Pedersen commitment (as a compressed point of the group),
     /// and a [`Variable`] corresponding to it, which can be used to form constraints.
-    pub fn commit(&mut self, v: Scalar, v_blinding: Scalar) -> (CompressedRistretto, Variable) {
+    pub fn commit(
+        &mut self,
+        v: G::Scalar,
+        v_blinding: G::Scalar,
+    ) -> (G::CompressedPoint, Variable) {
         let i = self.secrets.v.len();
         self.secrets.v.push(v);
         self.secrets.v_blinding.push(v_blinding);
 
         // Add the commitment to the transcript.
-        let V = self.pc_gens.commit(v, v_blinding).compress();
-        self.transcript.borrow_mut().append_point(b"V", &V);
+        let V = G::compress(&self.pc_gens.commit(v, v_blinding));
+        self.transcript
+            .borrow_mut()
+            .append_group_point::<G>(b"V", &V);
 
         (V, Variable::Committed(i))
     }
@@ -309,57 +325,63 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
     /// (wL, wR, wO, wV)
     /// ```
     /// where `w{L,R,O}` is \\( z \cdot z^Q \cdot W_{L,R,O} \\).
+    #[allow(clippy::type_complexity)]
     fn flattened_constraints(
         &mut self,
-        z: &Scalar,
-    ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Vec<Scalar>) {
+        z: &G::Scalar,
+    ) -> (
+        Vec<G::Scalar>,
+        Vec<G::Scalar>,
+        Vec<G::Scalar>,
+        Vec<G::Scalar>,
+    ) {
         let n = self.secrets.a_L.len();
         let m = self.secrets.v.len();
 
-        let mut wL = vec![Scalar::zero(); n];
-        let mut wR = vec![Scalar::zero(); n];
-        let mut wO = vec![Scalar::zero(); n];
-        let mut wV = vec![Scalar::zero(); m];
+        let mut wL = vec![G::Scalar::zero(); n];
+        let mut wR = vec![G::Scalar::zero(); n];
+        let mut wO = vec![G::Scalar::zero(); n];
+        let mut wV = vec![G::Scalar::zero(); m];
 
         let mut exp_z = *z;
         for lc in self.constraints.iter() {
             for (var, coeff) in &lc.terms {
                 match var {
                     Variable::MultiplierLeft(i) => {
-                        wL[*i] += exp_z * coeff;
+                        wL[*i] += exp_z * *coeff;
                     }
                     Variable::MultiplierRight(i) => {
-                        wR[*i] += exp_z * coeff;
+                        wR[*i] += exp_z * *coeff;
                     }
                     Variable::MultiplierOutput(i) => {
-                        wO[*i] += exp_z * coeff;
+                        wO[*i] += exp_z * *coeff;
                     }
                     Variable::Committed(i) => {
-                        wV[*i] -= exp_z * coeff;
+                        wV[*i] -= exp_z * *coeff;
                     }
                     Variable::One() => {
                         // The prover doesn't need to handle constant terms
                     }
                 }
             }
-            exp_z *= z;
+            exp_z *= *z;
         }
 
         (wL, wR, wO, wV)
     }
 
     /// Returns the secret value of the linear combination.
-    pub fn eval(&self, lc: &LinearCombination) -> Scalar {
+    pub fn eval(&self, lc: &LinearCombination<G::Scalar>) -> G::Scalar {
         lc.terms
             .iter()
             .map(|(var, coeff)| {
-                coeff
+                *coeff
                     * match var {
                         Variable::MultiplierLeft(i) => self.secrets.a_L[*i],
                         Variable::MultiplierRight(i) => self.secrets.a_R[*i],
                         Variable::MultiplierOutput(i) => self.secrets.a_O[*i],
                         Variable::Committed(i) => self.secrets.v[*i],
-                        Variable::One() => Scalar::one(),
+                        Variable::One() => G::Scalar::one(),
                     }
             })
             .sum()
@@ -389,7 +411,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
     }
 
     /// Consume this `ConstraintSystem` to produce a proof.
-    pub fn prove(self, bp_gens: &BulletproofGens) -> Result<R1CSProof, R1CSError> {
+    pub fn prove(self, bp_gens: &BulletproofGens<G>) -> Result<R1CSProof<G>, R1CSError> {
         self.prove_and_return_transcript(bp_gens)
             .map(|(proof, _transcript)| proof)
     }
@@ -397,8 +419,8 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
     /// Consume this `ConstraintSystem` to produce a proof. Returns the proof and the transcript passed in `Prover::new`.
     pub fn prove_and_return_transcript(
         mut self,
-        bp_gens: &BulletproofGens,
-    ) -> Result<(R1CSProof, T), R1CSError> {
+        bp_gens: &BulletproofGens<G>,
+    ) -> Result<(R1CSProof<G>, T), R1CSError> {
         use crate::util;
         use std::iter;
 
@@ -428,7 +450,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
 
             // Commit the blinding factors for the input wires
             for v_b in &self.secrets.v_blinding {
-                builder = builder.rekey_with_witness_bytes(b"v_blinding", v_b.as_bytes());
+                builder = builder.rekey_with_witness_bytes(b"v_blinding", &v_b.to_bytes());
             }
 
             use rand::thread_rng;
@@ -445,46 +467,46 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         // We are performing a single-party circuit proof, so party index is 0.
         let gens = bp_gens.share(0);
 
-        let i_blinding1 = Scalar::random(&mut rng);
-        let o_blinding1 = Scalar::random(&mut rng);
-        let s_blinding1 = Scalar::random(&mut rng);
+        let i_blinding1 = G::Scalar::random(&mut rng);
+        let o_blinding1 = G::Scalar::random(&mut rng);
+        let s_blinding1 = G::Scalar::random(&mut rng);
 
-        let mut s_L1: Vec<Scalar> = (0..n1).map(|_| Scalar::random(&mut rng)).collect();
-        let mut s_R1: Vec<Scalar> = (0..n1).map(|_| Scalar::random(&mut rng)).collect();
+        let mut s_L1: Vec<G::Scalar> = (0..n1).map(|_| G::Scalar::random(&mut rng)).collect();
+        let mut s_R1: Vec<G::Scalar> = (0..n1).map(|_| G::Scalar::random(&mut rng)).collect();
 
         // A_I = <a_L, G> + <a_R, H> + i_blinding * B_blinding
-        let A_I1 = RistrettoPoint::multiscalar_mul(
+        let A_I1 = G::compress(&msm::multiscalar_mul(
+            bp_gens.msm_backend(),
             iter::once(&i_blinding1)
                 .chain(self.secrets.a_L.iter())
                 .chain(self.secrets.a_R.iter()),
             iter::once(&self.pc_gens.B_blinding)
                 .chain(gens.G(n1))
                 .chain(gens.H(n1)),
-        )
-        .compress();
+        ));
 
         // A_O = <a_O, G> + o_blinding * B_blinding
-        let A_O1 = RistrettoPoint::multiscalar_mul(
+        let A_O1 = G::compress(&msm::multiscalar_mul(
+            bp_gens.msm_backend(),
             iter::once(&o_blinding1).chain(self.secrets.a_O.iter()),
             iter::once(&self.pc_gens.B_blinding).chain(gens.G(n1)),
-        )
-        .compress();
+        ));
 
         // S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
-        let S1 = RistrettoPoint::multiscalar_mul(
+        let S1 = G::compress(&msm::multiscalar_mul(
+            bp_gens.msm_backend(),
             iter::once(&s_blinding1)
                 .chain(s_L1.iter())
                 .chain(s_R1.iter()),
             iter::once(&self.pc_gens.B_blinding)
                 .chain(gens.G(n1))
                 .chain(gens.H(n1)),
-        )
-        .compress();
+        ));
 
         let transcript = self.transcript.borrow_mut();
-        transcript.append_point(b"A_I1", &A_I1);
-        transcript.append_point(b"A_O1", &A_O1);
-        transcript.append_point(b"S1", &S1);
+        transcript.append_group_point::<G>(b"A_I1", &A_I1);
+        transcript.append_group_point::<G>(b"A_O1", &A_O1);
+        transcript.append_group_point::<G>(b"S1", &S1);
 
         // Process the remaining constraints.
         self = self.create_randomized_constraints()?;
@@ -507,74 +529,71 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
 
         let (i_blinding2, o_blinding2, s_blinding2) = if has_2nd_phase_commitments {
             (
-                Scalar::random(&mut rng),
-                Scalar::random(&mut rng),
-                Scalar::random(&mut rng),
+                G::Scalar::random(&mut rng),
+                G::Scalar::random(&mut rng),
+                G::Scalar::random(&mut rng),
             )
         } else {
-            (Scalar::zero(), Scalar::zero(), Scalar::zero())
+            (G::Scalar::zero(), G::Scalar::zero(), G::Scalar::zero())
         };
 
-        let mut s_L2: Vec<Scalar> = (0..n2).map(|_| Scalar::random(&mut rng)).collect();
-        let mut s_R2: Vec<Scalar> = (0..n2).map(|_| Scalar::random(&mut rng)).collect();
+        let mut s_L2: Vec<G::Scalar> = (0..n2).map(|_| G::Scalar::random(&mut rng)).collect();
+        let mut s_R2: Vec<G::Scalar> = (0..n2).map(|_| G::Scalar::random(&mut rng)).collect();
 
         let (A_I2, A_O2, S2) = if has_2nd_phase_commitments {
             (
                 // A_I = <a_L, G> + <a_R, H> + i_blinding * B_blinding
-                RistrettoPoint::multiscalar_mul(
+                G::compress(&msm::multiscalar_mul(
+                    bp_gens.msm_backend(),
                     iter::once(&i_blinding2)
                         .chain(self.secrets.a_L.iter().skip(n1))
                         .chain(self.secrets.a_R.iter().skip(n1)),
                     iter::once(&self.pc_gens.B_blinding)
                         .chain(gens.G(n).skip(n1))
                         .chain(gens.H(n).skip(n1)),
-                )
-                .compress(),
+                )),
                 // A_O = <a_O, G> + o_blinding * B_blinding
-                RistrettoPoint::multiscalar_mul(
+                G::compress(&msm::multiscalar_mul(
+                    bp_gens.msm_backend(),
                     iter::once(&o_blinding2).chain(self.secrets.a_O.iter().skip(n1)),
                     iter::once(&self.pc_gens.B_blinding).chain(gens.G(n).skip(n1)),
-                )
-                .compress(),
+                )),
                 // S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
-                RistrettoPoint::multiscalar_mul(
+                G::compress(&msm::multiscalar_mul(
+                    bp_gens.msm_backend(),
                     iter::once(&s_blinding2)
                         .chain(s_L2.iter())
                         .chain(s_R2.iter()),
                     iter::once(&self.pc_gens.B_blinding)
                         .chain(gens.G(n).skip(n1))
                         .chain(gens.H(n).skip(n1)),
-                )
-                .compress(),
+                )),
             )
         } else {
             // Since we are using zero blinding factors and
             // there are no variables to commit,
             // the commitments _must_ be identity points,
             // so we can hardcode them saving 3 mults+compressions.
-            (
-                CompressedRistretto::identity(),
-                CompressedRistretto::identity(),
-                CompressedRistretto::identity(),
-            )
+            let identity = G::compress(&G::identity());
+            (identity, identity, identity)
         };
 
         let transcript = self.transcript.borrow_mut();
-        transcript.append_point(b"A_I2", &A_I2);
-        transcript.append_point(b"A_O2", &A_O2);
-        transcript.append_point(b"S2", &S2);
+        transcript.append_group_point::<G>(b"A_I2", &A_I2);
+        transcript.append_group_point::<G>(b"A_O2", &A_O2);
+        transcript.append_group_point::<G>(b"S2", &S2);
 
         // 4. Compute blinded vector polynomials l(x) and r(x)
 
-        let y = transcript.challenge_scalar(b"y");
-        let z = transcript.challenge_scalar(b"z");
+        let y = transcript.challenge_group_scalar::<G>(b"y");
+        let z = transcript.challenge_group_scalar::<G>(b"z");
 
         let (wL, wR, wO, wV) = self.flattened_constraints(&z);
 
         let mut l_poly = util::VecPoly3::zero(n);
         let mut r_poly = util::VecPoly3::zero(n);
 
-        let mut exp_y = Scalar::one(); // y^n starting at n=0
+        let mut exp_y = G::Scalar::one(); // y^n starting at n=0
         let y_inv = y.invert();
         let exp_y_inv = util::exp_iter(y_inv).take(padded_n).collect::<Vec<_>>();
 
@@ -596,41 +615,41 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
             r_poly.1[i] = exp_y * self.secrets.a_R[i] + wL[i];
             // r_poly.2 = 0
             // r_poly.3 = y^n * s_R
-            r_poly.3[i] = exp_y * sr;
+            r_poly.3[i] = exp_y * *sr;
 
             exp_y = exp_y * y; // y^i -> y^(i+1)
         }
 
         let t_poly = util::VecPoly3::special_inner_product(&l_poly, &r_poly);
 
-        let t_1_blinding = Scalar::random(&mut rng);
-        let t_3_blinding = Scalar::random(&mut rng);
-        let t_4_blinding = Scalar::random(&mut rng);
-        let t_5_blinding = Scalar::random(&mut rng);
-        let t_6_blinding = Scalar::random(&mut rng);
+        let t_1_blinding = G::Scalar::random(&mut rng);
+        let t_3_blinding = G::Scalar::random(&mut rng);
+        let t_4_blinding = G::Scalar::random(&mut rng);
+        let t_5_blinding = G::Scalar::random(&mut rng);
+        let t_6_blinding = G::Scalar::random(&mut rng);
 
-        let T_1 = self.pc_gens.commit(t_poly.t1, t_1_blinding).compress();
-        let T_3 = self.pc_gens.commit(t_poly.t3, t_3_blinding).compress();
-        let T_4 = self.pc_gens.commit(t_poly.t4, t_4_blinding).compress();
-        let T_5 = self.pc_gens.commit(t_poly.t5, t_5_blinding).compress();
-        let T_6 = self.pc_gens.commit(t_poly.t6, t_6_blinding).compress();
+        let T_1 = G::compress(&self.pc_gens.commit(t_poly.t1, t_1_blinding));
+        let T_3 = G::compress(&self.pc_gens.commit(t_poly.t3, t_3_blinding));
+        let T_4 = G::compress(&self.pc_gens.commit(t_poly.t4, t_4_blinding));
+        let T_5 = G::compress(&self.pc_gens.commit(t_poly.t5, t_5_blinding));
+        let T_6 = G::compress(&self.pc_gens.commit(t_poly.t6, t_6_blinding));
 
         let transcript = self.transcript.borrow_mut();
-        transcript.append_point(b"T_1", &T_1);
-        transcript.append_point(b"T_3", &T_3);
-        transcript.append_point(b"T_4", &T_4);
-        transcript.append_point(b"T_5", &T_5);
-        transcript.append_point(b"T_6", &T_6);
+        transcript.append_group_point::<G>(b"T_1", &T_1);
+        transcript.append_group_point::<G>(b"T_3", &T_3);
+        transcript.append_group_point::<G>(b"T_4", &T_4);
+        transcript.append_group_point::<G>(b"T_5", &T_5);
+        transcript.append_group_point::<G>(b"T_6", &T_6);
 
-        let u = transcript.challenge_scalar(b"u");
-        let x = transcript.challenge_scalar(b"x");
+        let u = transcript.challenge_group_scalar::<G>(b"u");
+        let x = transcript.challenge_group_scalar::<G>(b"x");
 
         // t_2_blinding = <z*z^Q, W_V * v_blinding>
         // in the t_x_blinding calculations, line 76.
         let t_2_blinding = wV
             .iter()
             .zip(self.secrets.v_blinding.iter())
-            .map(|(c, v_blinding)| c * v_blinding)
+            .map(|(c, v_blinding)| *c * *v_blinding)
             .sum();
 
         let t_blinding_poly = util::Poly6 {
@@ -645,10 +664,10 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         let t_x = t_poly.eval(x);
         let t_x_blinding = t_blinding_poly.eval(x);
         let mut l_vec = l_poly.eval(x);
-        l_vec.append(&mut vec![Scalar::zero(); pad]);
+        l_vec.append(&mut vec![G::Scalar::zero(); pad]);
 
         let mut r_vec = r_poly.eval(x);
-        r_vec.append(&mut vec![Scalar::zero(); pad]);
+        r_vec.append(&mut vec![G::Scalar::zero(); pad]);
 
         // XXX this should refer to the notes to explain why this is correct
         for i in n..padded_n {
@@ -662,33 +681,34 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
 
         let e_blinding = x * (i_blinding + x * (o_blinding + x * s_blinding));
 
-        transcript.append_scalar(b"t_x", &t_x);
-        transcript.append_scalar(b"t_x_blinding", &t_x_blinding);
-        transcript.append_scalar(b"e_blinding", &e_blinding);
+        transcript.append_group_scalar::<G>(b"t_x", &t_x);
+        transcript.append_group_scalar::<G>(b"t_x_blinding", &t_x_blinding);
+        transcript.append_group_scalar::<G>(b"e_blinding", &e_blinding);
 
         // Get a challenge value to combine statements for the IPP
-        let w = transcript.challenge_scalar(b"w");
-        let Q = w * self.pc_gens.B;
+        let w = transcript.challenge_group_scalar::<G>(b"w");
+        let Q = self.pc_gens.B * w;
 
-        let G_factors = iter::repeat(Scalar::one())
+        let G_factors = iter::repeat(G::Scalar::one())
             .take(n1)
             .chain(iter::repeat(u).take(n2 + pad))
             .collect::<Vec<_>>();
         let H_factors = exp_y_inv
             .into_iter()
             .zip(G_factors.iter())
-            .map(|(y, u_or_1)| y * u_or_1)
+            .map(|(y, u_or_1)| y * *u_or_1)
             .collect::<Vec<_>>();
 
         let ipp_proof = InnerProductProof::create(
+            bp_gens.msm_backend(),
             transcript,
             &Q,
             &G_factors,
             &H_factors,
-            gens.G(padded_n).cloned().collect(),
-            gens.H(padded_n).cloned().collect(),
-            l_vec,
-            r_vec,
+            Chunked::new(&[gens.G_slice(padded_n)]),
+            Chunked::new(&[gens.H_slice(padded_n)]),
+            Chunked::new(&[&l_vec]),
+            Chunked::new(&[&r_vec]),
         );
 
         for scalar in s_L1
@@ -2,9 +2,6 @@
 
 use core::borrow::BorrowMut;
 use core::mem;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::VartimeMultiscalarMul;
 use merlin::Transcript;
 
 use super::{
@@ -12,8 +9,10 @@ use super::{
     RandomizedConstraintSystem, Variable,
 };
 
+use crate::batch::DeferredCheck;
 use crate::errors::R1CSError;
-use crate::generators::{BulletproofGens, PedersenGens};
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+use crate::group::{Group, Ristretto, ScalarField};
 use crate::r1cs::Metrics;
 use crate::transcript::TranscriptProtocol;
 
@@ -26,9 +25,9 @@ use crate::transcript::TranscriptProtocol;
 /// When all constraints are added, the verifying code calls `verify`
 /// which consumes the `Verifier` instance, samples random challenges
 /// that instantiate the randomized constraints, and verifies the proof.
-pub struct Verifier<T: BorrowMut<Transcript>> {
+pub struct Verifier<T: BorrowMut<Transcript>, G: Group = Ristretto> {
     transcript: T,
-    constraints: Vec<LinearCombination>,
+    constraints: Vec<LinearCombination<G::Scalar>>,
 
     /// Records the number of low-level variables allocated in the
     /// constraint system.
@@ -38,14 +37,15 @@ pub struct Verifier<T: BorrowMut<Transcript>> {
     /// `Missing`), so the `num_vars` isn't kept implicitly in the
     /// variable assignments.
     num_vars: usize,
-    V: Vec<CompressedRistretto>,
+    V: Vec<G::CompressedPoint>,
 
     /// This list holds closures that will be called in the second phase of the protocol,
     /// when non-randomized variables are committed.
     /// After that, the option will flip to None and additional calls to `randomize_constraints`
     /// will invoke closures immediately.
+    #[allow(clippy::type_complexity)]
     deferred_constraints:
-        Vec<Box<dyn FnOnce(&mut RandomizingVerifier<T>) -> Result<(), R1CSError>>>,
+        Vec<Box<dyn FnOnce(&mut RandomizingVerifier<T, G>) -> Result<(), R1CSError>>>,
 
     /// Index of a pending multiplier that's not fully assigned yet.
     pending_multiplier: Option<usize>,
@@ -58,19 +58,19 @@ pub struct Verifier<T: BorrowMut<Transcript>> {
 /// monomorphize the closures for the proving and verifying code.
 /// However, this type cannot be instantiated by the user and therefore can only be used within
 /// the callback provided to `specify_randomized_constraints`.
-pub struct RandomizingVerifier<T: BorrowMut<Transcript>> {
-    verifier: Verifier<T>,
+pub struct RandomizingVerifier<T: BorrowMut<Transcript>, G: Group = Ristretto> {
+    verifier: Verifier<T, G>,
 }
 
-impl<T: BorrowMut<Transcript>> ConstraintSystem for Verifier<T> {
+impl<T: BorrowMut<Transcript>, G: Group> ConstraintSystem<G::Scalar> for Verifier<T, G> {
     fn transcript(&mut self) -> &mut Transcript {
         self.transcript.borrow_mut()
     }
 
     fn multiply(
         &mut self,
-        mut left: LinearCombination,
-        mut right: LinearCombination,
+        mut left: LinearCombination<G::Scalar>,
+        mut right: LinearCombination<G::Scalar>,
     ) -> (Variable, Variable, Variable) {
         let var = self.num_vars;
         self.num_vars += 1;
@@ -81,15 +81,15 @@ impl<T: BorrowMut<Transcript>> ConstraintSystem for Verifier<T> {
         let o_var = Variable::MultiplierOutput(var);
 
         // Constrain l,r,o:
-        left.terms.push((l_var, -Scalar::one()));
-        right.terms.push((r_var, -Scalar::one()));
+        left.terms.push((l_var, -G::Scalar::one()));
+        right.terms.push((r_var, -G::Scalar::one()));
         self.constrain(left);
         self.constrain(right);
 
         (l_var, r_var, o_var)
     }
 
-    fn allocate(&mut self, _: Option<Scalar>) -> Result<Variable, R1CSError> {
+    fn allocate(&mut self, _: Option<G::Scalar>) -> Result<Variable, R1CSError> {
         match self.pending_multiplier {
             None => {
                 let i = self.num_vars;
@@ -106,7 +106,7 @@ impl<T: BorrowMut<Transcript>> ConstraintSystem for Verifier<T> {
 
     fn allocate_multiplier(
         &mut self,
-        _: Option<(Scalar, Scalar)>,
+        _: Option<(G::Scalar, G::Scalar)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError> {
         let var = self.num_vars;
         self.num_vars += 1;
@@ -125,10 +125,11 @@ impl<T: BorrowMut<Transcript>> ConstraintSystem for Verifier<T> {
             constraints: self.constraints.len() + self.deferred_constraints.len(),
             phase_one_constraints: self.constraints.len(),
             phase_two_constraints: self.deferred_constraints.len(),
+            commitments: self.V.len(),
         }
     }
 
-    fn constrain(&mut self, lc: LinearCombination) {
+    fn constrain(&mut self, lc: LinearCombination<G::Scalar>) {
         // TODO: check that the linear combinations are valid
         // (e.g. that variables are valid, that the linear combination
         // evals to 0 for prover, etc).
@@ -136,8 +137,10 @@ impl<T: BorrowMut<Transcript>> ConstraintSystem for Verifier<T> {
     }
 }
 
-impl<T: BorrowMut<Transcript>> RandomizableConstraintSystem for Verifier<T> {
-    type RandomizedCS = RandomizingVerifier<T>;
+impl<T: BorrowMut<Transcript>, G: Group> RandomizableConstraintSystem<G::Scalar>
+    for Verifier<T, G>
+{
+    type RandomizedCS = RandomizingVerifier<T, G>;
 
     fn specify_randomized_constraints<F>(&mut self, callback: F) -> Result<(), R1CSError>
     where
@@ -148,26 +151,26 @@ impl<T: BorrowMut<Transcript>> RandomizableConstraintSystem for Verifier<T> {
     }
 }
 
-impl<T: BorrowMut<Transcript>> ConstraintSystem for RandomizingVerifier<T> {
+impl<T: BorrowMut<Transcript>, G: Group> ConstraintSystem<G::Scalar> for RandomizingVerifier<T, G> {
     fn transcript(&mut self) -> &mut Transcript {
         self.verifier.transcript.borrow_mut()
     }
 
     fn multiply(
         &mut self,
-        left: LinearCombination,
-        right: LinearCombination,
+        left: LinearCombination<G::Scalar>,
+        right: LinearCombination<G::Scalar>,
     ) -> (Variable, Variable, Variable) {
         self.verifier.multiply(left, right)
     }
 
-    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError> {
+    fn allocate(&mut self, assignment: Option<G::Scalar>) -> Result<Variable, R1CSError> {
         self.verifier.allocate(assignment)
     }
 
     fn allocate_multiplier(
         &mut self,
-        input_assignments: Option<(Scalar, Scalar)>,
+        input_assignments: Option<(G::Scalar, G::Scalar)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError> {
         self.verifier.allocate_multiplier(input_assignments)
     }
@@ -176,17 +179,19 @@ impl<T: BorrowMut<Transcript>> ConstraintSystem for RandomizingVerifier<T> {
         self.verifier.metrics()
     }
 
-    fn constrain(&mut self, lc: LinearCombination) {
+    fn constrain(&mut self, lc: LinearCombination<G::Scalar>) {
         self.verifier.constrain(lc)
     }
 }
 
-impl<T: BorrowMut<Transcript>> RandomizedConstraintSystem for RandomizingVerifier<T> {
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+impl<T: BorrowMut<Transcript>, G: Group> RandomizedConstraintSystem<G::Scalar>
+    for RandomizingVerifier<T, G>
+{
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> G::Scalar {
         self.verifier
             .transcript
             .borrow_mut()
-            .challenge_scalar(label)
+            .challenge_group_scalar::<G>(label)
     }
 }
 
@@ -216,7 +221,15 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
     ///
     /// The second element is a list of [`Variable`]s corresponding to
     /// the external inputs, which can be used to form constraints.
-    pub fn new(mut transcript: T) -> Self {
+    pub fn new(transcript: T) -> Self {
+        Verifier::for_group(transcript)
+    }
+}
+
+impl<T: BorrowMut<Transcript>, G: Group> Verifier<T, G> {
+    /// Construct an empty constraint system for proofs in the group
+    /// `G`, like [`Verifier::new`] does for [`Ristretto`].
+    pub fn for_group(mut transcript: T) -> Self {
         transcript.borrow_mut().r1cs_domain_sep();
 
         Verifier {
@@ -241,73 +254,20 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
     ///
     /// # Returns
     ///
-    /// Returns a pair of a Pedersen commitment (as a compressed Ristretto point),
-    /// and a [`Variable`] corresponding to it, which can be used to form constraints.
-    pub fn commit(&mut self, commitment: CompressedRistretto) -> Variable {
+    /// Returns a [`Variable`] corresponding to the commitment, which
+    /// can be used to form constraints.
+    pub fn commit(&mut self, commitment: G::CompressedPoint) -> Variable {
         let i = self.V.len();
         self.V.push(commitment);
 
         // Add the commitment to the transcript.
-        self.transcript.borrow_mut().append_point(b"V", &commitment);
+        self.transcript
+            .borrow_mut()
+            .append_group_point::<G>(b"V", &commitment);
 
         Variable::Committed(i)
     }
 
-    /// Use a challenge, `z`, to flatten the constraints in the
-    /// constraint system into vectors used for proving and
-    /// verification.
-    ///
-    /// # Output
-    ///
-    /// Returns a tuple of
-    /// ```text
-    /// (wL, wR, wO, wV, wc)
-    /// ```
-    /// where `w{L,R,O}` is \\( z \cdot z^Q \cdot W_{L,R,O} \\).
-    ///
-    /// This has the same logic as `ProverCS::flattened_constraints()`
-    /// but also computes the constant terms (which the prover skips
-    /// because they're not needed to construct the proof).
-    fn flattened_constraints(
-        &mut self,
-        z: &Scalar,
-    ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Scalar) {
-        let n = self.num_vars;
-        let m = self.V.len();
-
-        let mut wL = vec![Scalar::zero(); n];
-        let mut wR = vec![Scalar::zero(); n];
-        let mut wO = vec![Scalar::zero(); n];
-        let mut wV = vec![Scalar::zero(); m];
-        let mut wc = Scalar::zero();
-
-        let mut exp_z = *z;
-        for lc in self.constraints.iter() {
-            for (var, coeff) in &lc.terms {
-                match var {
-                    Variable::MultiplierLeft(i) => {
-                        wL[*i] += exp_z * coeff;
-                    }
-                    Variable::MultiplierRight(i) => {
-                        wR[*i] += exp_z * coeff;
-                    }
-                    Variable::MultiplierOutput(i) => {
-                        wO[*i] += exp_z * coeff;
-                    }
-                    Variable::Committed(i) => {
-                        wV[*i] -= exp_z * coeff;
-                    }
-                    Variable::One() => {
-                        wc -= exp_z * coeff;
-                    }
-                }
-            }
-            exp_z *= z;
-        }
-
-        (wL, wR, wO, wV, wc)
-    }
-
     /// Calls all remembered callbacks with an API that
     /// allows generating challenge scalars.
     fn create_randomized_constraints(mut self) -> Result<Self, R1CSError> {
@@ -339,20 +299,50 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
     /// be added into the constraint system.
     pub fn verify(
         self,
-        proof: &R1CSProof,
-        pc_gens: &PedersenGens,
-        bp_gens: &BulletproofGens,
+        proof: &R1CSProof<G>,
+        pc_gens: &PedersenGens<G>,
+        bp_gens: &BulletproofGens<G>,
     ) -> Result<(), R1CSError> {
         self.verify_and_return_transcript(proof, pc_gens, bp_gens)
             .map(|_| ())
     }
     /// Same as `verify`, but also returns the transcript back to the user.
     pub fn verify_and_return_transcript(
-        mut self,
-        proof: &R1CSProof,
-        pc_gens: &PedersenGens,
-        bp_gens: &BulletproofGens,
+        self,
+        proof: &R1CSProof<G>,
+        pc_gens: &PedersenGens<G>,
+        bp_gens: &BulletproofGens<G>,
     ) -> Result<T, R1CSError> {
+        let (check, transcript) = self.verification_check(proof, bp_gens)?;
+
+        if check.verify(bp_gens, &ExtendedPedersenGens::from(*pc_gens)) {
+            Ok(transcript)
+        } else {
+            Err(R1CSError::VerificationError)
+        }
+    }
+
+    /// Consumes this `VerifierCS`, replaying the transcript of the
+    /// supplied `proof` to construct its verification equation without
+    /// evaluating it.  Returns the equation and the transcript.
+    pub(crate) fn verification_check(
+        self,
+        proof: &R1CSProof<G>,
+        bp_gens: &BulletproofGens<G>,
+    ) -> Result<(DeferredCheck<G>, T), R1CSError> {
+        let (pending, transcript) = self.pending_check(proof, bp_gens)?;
+        Ok((pending.into_check(), transcript))
+    }
+
+    /// Consumes this `VerifierCS`, replaying the transcript of the
+    /// supplied `proof` to derive all of its challenges.  Returns the
+    /// [`PendingCheck`], which no longer refers to the transcript or
+    /// the deferred constraints, and the transcript.
+    pub(crate) fn pending_check(
+        mut self,
+        proof: &R1CSProof<G>,
+        bp_gens: &BulletproofGens<G>,
+    ) -> Result<(PendingCheck<G>, T), R1CSError> {
         // Commit a length _suffix_ for the number of high-level variables.
         // We cannot do this in advance because user can commit variables one-by-one,
         // but this suffix provides safe disambiguation because each variable
@@ -361,9 +351,9 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
         transcript.append_u64(b"m", self.V.len() as u64);
 
         let n1 = self.num_vars;
-        transcript.validate_and_append_point(b"A_I1", &proof.A_I1)?;
-        transcript.validate_and_append_point(b"A_O1", &proof.A_O1)?;
-        transcript.validate_and_append_point(b"S1", &proof.S1)?;
+        transcript.validate_and_append_group_point::<G>(b"A_I1", &proof.A_I1)?;
+        transcript.validate_and_append_group_point::<G>(b"A_O1", &proof.A_O1)?;
+        transcript.validate_and_append_group_point::<G>(b"S1", &proof.S1)?;
 
         // Process the remaining constraints.
         self = self.create_randomized_constraints()?;
@@ -372,44 +362,35 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
 
         // If the number of multiplications is not 0 or a power of 2, then pad the circuit.
         let n = self.num_vars;
-        let n2 = n - n1;
         let padded_n = self.num_vars.next_power_of_two();
-        let pad = padded_n - n;
 
-        use crate::inner_product_proof::inner_product;
-        use crate::util;
         use std::iter;
 
         if bp_gens.gens_capacity < padded_n {
             return Err(R1CSError::InvalidGeneratorsLength);
         }
-        // We are performing a single-party circuit proof, so party index is 0.
-        let gens = bp_gens.share(0);
-
         // These points are the identity in the 1-phase unrandomized case.
-        transcript.append_point(b"A_I2", &proof.A_I2);
-        transcript.append_point(b"A_O2", &proof.A_O2);
-        transcript.append_point(b"S2", &proof.S2);
-
-        let y = transcript.challenge_scalar(b"y");
-        let z = transcript.challenge_scalar(b"z");
+        transcript.append_group_point::<G>(b"A_I2", &proof.A_I2);
+        transcript.append_group_point::<G>(b"A_O2", &proof.A_O2);
+        transcript.append_group_point::<G>(b"S2", &proof.S2);
 
-        transcript.validate_and_append_point(b"T_1", &proof.T_1)?;
-        transcript.validate_and_append_point(b"T_3", &proof.T_3)?;
-        transcript.validate_and_append_point(b"T_4", &proof.T_4)?;
-        transcript.validate_and_append_point(b"T_5", &proof.T_5)?;
-        transcript.validate_and_append_point(b"T_6", &proof.T_6)?;
+        let y = transcript.challenge_group_scalar::<G>(b"y");
+        let z = transcript.challenge_group_scalar::<G>(b"z");
 
-        let u = transcript.challenge_scalar(b"u");
-        let x = transcript.challenge_scalar(b"x");
+        transcript.validate_and_append_group_point::<G>(b"T_1", &proof.T_1)?;
+        transcript.validate_and_append_group_point::<G>(b"T_3", &proof.T_3)?;
+        transcript.validate_and_append_group_point::<G>(b"T_4", &proof.T_4)?;
+        transcript.validate_and_append_group_point::<G>(b"T_5", &proof.T_5)?;
+        transcript.validate_and_append_group_point::<G>(b"T_6", &proof.T_6)?;
 
-        transcript.append_scalar(b"t_x", &proof.t_x);
-        transcript.append_scalar(b"t_x_blinding", &proof.t_x_blinding);
-        transcript.append_scalar(b"e_blinding", &proof.e_blinding);
+        let u = transcript.challenge_group_scalar::<G>(b"u");
+        let x = transcript.challenge_group_scalar::<G>(b"x");
 
-        let w = transcript.challenge_scalar(b"w");
+        transcript.append_group_scalar::<G>(b"t_x", &proof.t_x);
+        transcript.append_group_scalar::<G>(b"t_x_blinding", &proof.t_x_blinding);
+        transcript.append_group_scalar::<G>(b"e_blinding", &proof.e_blinding);
 
-        let (wL, wR, wO, wV, wc) = self.flattened_constraints(&z);
+        let w = transcript.challenge_group_scalar::<G>(b"w");
 
         // Get IPP variables
         let (u_sq, u_inv_sq, s) = proof
@@ -417,23 +398,190 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
             .verification_scalars(padded_n, self.transcript.borrow_mut())
             .map_err(|_| R1CSError::VerificationError)?;
 
-        let a = proof.ipp_proof.a;
-        let b = proof.ipp_proof.b;
+        // Create a `TranscriptRng` from the transcript. The verifier
+        // has no witness data to commit, so this just mixes external
+        // randomness into the existing transcript.
+        use rand::thread_rng;
+        let mut rng = self
+            .transcript
+            .borrow_mut()
+            .build_rng()
+            .finalize(&mut thread_rng());
+        let r = G::Scalar::random(&mut rng);
+
+        let T_points = [proof.T_1, proof.T_3, proof.T_4, proof.T_5, proof.T_6];
+
+        let dynamic_points = iter::once(&proof.A_I1)
+            .chain(iter::once(&proof.A_O1))
+            .chain(iter::once(&proof.S1))
+            .chain(iter::once(&proof.A_I2))
+            .chain(iter::once(&proof.A_O2))
+            .chain(iter::once(&proof.S2))
+            .chain(self.V.iter())
+            .chain(T_points.iter())
+            .chain(proof.ipp_proof.L_vec.iter())
+            .chain(proof.ipp_proof.R_vec.iter())
+            .map(|P| G::decompress(P).ok_or(R1CSError::VerificationError))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let pending = PendingCheck {
+            constraints: self.constraints,
+            n1,
+            n,
+            m: self.V.len(),
+            y,
+            z,
+            u,
+            x,
+            w,
+            r,
+            t_x: proof.t_x,
+            t_x_blinding: proof.t_x_blinding,
+            e_blinding: proof.e_blinding,
+            a: proof.ipp_proof.a,
+            b: proof.ipp_proof.b,
+            u_sq,
+            u_inv_sq,
+            s,
+            dynamic_points,
+        };
+
+        Ok((pending, self.transcript))
+    }
+}
+
+/// The verification equation of a constraint system proof whose
+/// transcript has been replayed, before its constraints are flattened.
+///
+/// Unlike the [`Verifier`], it can be sent to another thread, so that
+/// the constraints of many proofs can be flattened in parallel.
+pub(crate) struct PendingCheck<G: Group = Ristretto> {
+    constraints: Vec<LinearCombination<G::Scalar>>,
+    /// Number of multipliers allocated before the randomization phase
+    n1: usize,
+    /// Number of multipliers
+    n: usize,
+    /// Number of high-level commitments
+    m: usize,
+    y: G::Scalar,
+    z: G::Scalar,
+    u: G::Scalar,
+    x: G::Scalar,
+    w: G::Scalar,
+    /// Random challenge for combining the checks of \(t(x)\) and the inner product proof
+    r: G::Scalar,
+    t_x: G::Scalar,
+    t_x_blinding: G::Scalar,
+    e_blinding: G::Scalar,
+    a: G::Scalar,
+    b: G::Scalar,
+    u_sq: Vec<G::Scalar>,
+    u_inv_sq: Vec<G::Scalar>,
+    s: Vec<G::Scalar>,
+    dynamic_points: Vec<G::Point>,
+}
+
+impl<G: Group> PendingCheck<G> {
+    /// Use a challenge, `z`, to flatten the constraints in the
+    /// constraint system into vectors used for proving and
+    /// verification.
+    ///
+    /// # Output
+    ///
+    /// Returns a tuple of
+    /// ```text
+    /// (wL, wR, wO, wV, wc)
+    /// ```
+    /// where `w{L,R,O}` is \\( z \cdot z^Q \cdot W_{L,R,O} \\).
+    ///
+    /// This has the same logic as `ProverCS::flattened_constraints()`
+    /// but also computes the constant terms (which the prover skips
+    /// because they're not needed to construct the proof).
+    #[allow(clippy::type_complexity)]
+    fn flattened_constraints(
+        &self,
+        z: &G::Scalar,
+    ) -> (
+        Vec<G::Scalar>,
+        Vec<G::Scalar>,
+        Vec<G::Scalar>,
+        Vec<G::Scalar>,
+        G::Scalar,
+    ) {
+        let n = self.n;
+        let m = self.m;
+
+        let mut wL = vec![G::Scalar::zero(); n];
+        let mut wR = vec![G::Scalar::zero(); n];
+        let mut wO = vec![G::Scalar::zero(); n];
+        let mut wV = vec![G::Scalar::zero(); m];
+        let mut wc = G::Scalar::zero();
+
+        let mut exp_z = *z;
+        for lc in self.constraints.iter() {
+            for (var, coeff) in &lc.terms {
+                match var {
+                    Variable::MultiplierLeft(i) => {
+                        wL[*i] += exp_z * *coeff;
+                    }
+                    Variable::MultiplierRight(i) => {
+                        wR[*i] += exp_z * *coeff;
+                    }
+                    Variable::MultiplierOutput(i) => {
+                        wO[*i] += exp_z * *coeff;
+                    }
+                    Variable::Committed(i) => {
+                        wV[*i] -= exp_z * *coeff;
+                    }
+                    Variable::One() => {
+                        wc -= exp_z * *coeff;
+                    }
+                }
+            }
+            exp_z *= *z;
+        }
+
+        (wL, wR, wO, wV, wc)
+    }
+
+    /// Flattens the constraints to construct the verification equation.
+    pub(crate) fn into_check(self) -> DeferredCheck<G> {
+        use crate::inner_product_proof::inner_product;
+        use crate::util;
+        use std::iter;
+
+        let (wL, wR, wO, wV, wc) = self.flattened_constraints(&self.z);
+
+        let PendingCheck {
+            n1,
+            n,
+            y,
+            u,
+            x,
+            w,
+            r,
+            a,
+            b,
+            ..
+        } = self;
+        let padded_n = n.next_power_of_two();
+        let n2 = n - n1;
+        let pad = padded_n - n;
 
         let y_inv = y.invert();
         let y_inv_vec = util::exp_iter(y_inv)
             .take(padded_n)
-            .collect::<Vec<Scalar>>();
+            .collect::<Vec<G::Scalar>>();
         let yneg_wR = wR
             .into_iter()
             .zip(y_inv_vec.iter())
-            .map(|(wRi, exp_y_inv)| wRi * exp_y_inv)
-            .chain(iter::repeat(Scalar::zero()).take(pad))
-            .collect::<Vec<Scalar>>();
+            .map(|(wRi, exp_y_inv)| wRi * *exp_y_inv)
+            .chain(iter::repeat(G::Scalar::zero()).take(pad))
+            .collect::<Vec<G::Scalar>>();
 
         let delta = inner_product(&yneg_wR[0..n], &wL);
 
-        let u_for_g = iter::repeat(Scalar::one())
+        let u_for_g = iter::repeat(G::Scalar::one())
             .take(n1)
             .chain(iter::repeat(u).take(n2 + pad));
         let u_for_h = u_for_g.clone();
@@ -442,78 +590,50 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
         let g_scalars = yneg_wR
             .iter()
             .zip(u_for_g)
-            .zip(s.iter().take(padded_n))
-            .map(|((yneg_wRi, u_or_1), s_i)| u_or_1 * (x * yneg_wRi - a * s_i));
+            .zip(self.s.iter().take(padded_n))
+            .map(|((yneg_wRi, u_or_1), s_i)| u_or_1 * (x * *yneg_wRi - a * *s_i));
 
         let h_scalars = y_inv_vec
             .iter()
             .zip(u_for_h)
-            .zip(s.iter().rev().take(padded_n))
-            .zip(wL.into_iter().chain(iter::repeat(Scalar::zero()).take(pad)))
-            .zip(wO.into_iter().chain(iter::repeat(Scalar::zero()).take(pad)))
+            .zip(self.s.iter().rev().take(padded_n))
+            .zip(
+                wL.into_iter()
+                    .chain(iter::repeat(G::Scalar::zero()).take(pad)),
+            )
+            .zip(
+                wO.into_iter()
+                    .chain(iter::repeat(G::Scalar::zero()).take(pad)),
+            )
             .map(|((((y_inv_i, u_or_1), s_i_inv), wLi), wOi)| {
-                u_or_1 * (y_inv_i * (x * wLi + wOi - b * s_i_inv) - Scalar::one())
+                u_or_1 * (*y_inv_i * (x * wLi + wOi - b * *s_i_inv) - G::Scalar::one())
             });
 
-        // Create a `TranscriptRng` from the transcript. The verifier
-        // has no witness data to commit, so this just mixes external
-        // randomness into the existing transcript.
-        use rand::thread_rng;
-        let mut rng = self
-            .transcript
-            .borrow_mut()
-            .build_rng()
-            .finalize(&mut thread_rng());
-        let r = Scalar::random(&mut rng);
-
         let xx = x * x;
         let rxx = r * xx;
         let xxx = x * xx;
 
         // group the T_scalars and T_points together
         let T_scalars = [r * x, rxx * x, rxx * xx, rxx * xxx, rxx * xx * xx];
-        let T_points = [proof.T_1, proof.T_3, proof.T_4, proof.T_5, proof.T_6];
 
-        let mega_check = RistrettoPoint::optional_multiscalar_mul(
-            iter::once(x) // A_I1
+        DeferredCheck {
+            dynamic_scalars: iter::once(x) // A_I1
                 .chain(iter::once(xx)) // A_O1
                 .chain(iter::once(xxx)) // S1
                 .chain(iter::once(u * x)) // A_I2
                 .chain(iter::once(u * xx)) // A_O2
                 .chain(iter::once(u * xxx)) // S2
-                .chain(wV.iter().map(|wVi| wVi * rxx)) // V
+                .chain(wV.iter().map(|wVi| *wVi * rxx)) // V
                 .chain(T_scalars.iter().cloned()) // T_points
-                .chain(iter::once(
-                    w * (proof.t_x - a * b) + r * (xx * (wc + delta) - proof.t_x),
-                )) // B
-                .chain(iter::once(-proof.e_blinding - r * proof.t_x_blinding)) // B_blinding
-                .chain(g_scalars) // G
-                .chain(h_scalars) // H
-                .chain(u_sq.iter().cloned()) // ipp_proof.L_vec
-                .chain(u_inv_sq.iter().cloned()), // ipp_proof.R_vec
-            iter::once(proof.A_I1.decompress())
-                .chain(iter::once(proof.A_O1.decompress()))
-                .chain(iter::once(proof.S1.decompress()))
-                .chain(iter::once(proof.A_I2.decompress()))
-                .chain(iter::once(proof.A_O2.decompress()))
-                .chain(iter::once(proof.S2.decompress()))
-                .chain(self.V.iter().map(|V_i| V_i.decompress()))
-                .chain(T_points.iter().map(|T_i| T_i.decompress()))
-                .chain(iter::once(Some(pc_gens.B)))
-                .chain(iter::once(Some(pc_gens.B_blinding)))
-                .chain(gens.G(padded_n).map(|&G_i| Some(G_i)))
-                .chain(gens.H(padded_n).map(|&H_i| Some(H_i)))
-                .chain(proof.ipp_proof.L_vec.iter().map(|L_i| L_i.decompress()))
-                .chain(proof.ipp_proof.R_vec.iter().map(|R_i| R_i.decompress())),
-        )
-        .ok_or_else(|| R1CSError::VerificationError)?;
-
-        use curve25519_dalek::traits::IsIdentity;
-
-        if !mega_check.is_identity() {
-            return Err(R1CSError::VerificationError);
+                .chain(self.u_sq.iter().cloned()) // ipp_proof.L_vec
+                .chain(self.u_inv_sq.iter().cloned()) // ipp_proof.R_vec
+                .collect(),
+            dynamic_points: self.dynamic_points,
+            B_scalar: w * (self.t_x - a * b) + r * (xx * (wc + delta) - self.t_x),
+            B_blinding_scalars: vec![-self.e_blinding - r * self.t_x_blinding],
+            // We are performing a single-party circuit proof, so party index is 0.
+            G_scalars: vec![g_scalars.collect()],
+            H_scalars: vec![h_scalars.collect()],
         }
-
-        Ok(self.transcript)
     }
 }
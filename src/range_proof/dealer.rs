@@ -10,14 +10,13 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 
-use curve25519_dalek::ristretto::RistrettoPoint;
-use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 
 use crate::errors::MPCError;
-use crate::generators::{BulletproofGens, PedersenGens};
-use crate::inner_product_proof;
-use crate::range_proof::RangeProof;
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+use crate::group::{Group, Ristretto, ScalarField};
+use crate::inner_product_proof::{self, Chunked};
+use crate::range_proof::{padded_bitsize, RangeProof};
 use crate::transcript::TranscriptProtocol;
 
 use rand_core::{CryptoRng, RngCore};
@@ -34,23 +33,84 @@ pub struct Dealer {}
 
 impl Dealer {
     /// Creates a new dealer coordinating `m` parties proving `n`-bit ranges.
-    pub fn new<'a, 'b>(
-        bp_gens: &'b BulletproofGens,
-        pc_gens: &'b PedersenGens,
+    ///
+    /// If `m` is not a power of two, the dealer pads the aggregation
+    /// with dummy parties committing to the value zero with a zero
+    /// blinding factor, whose messages it computes by itself.  The
+    /// dummy parties take the positions after the `m` real parties.
+    pub fn new<'a, 'b, G: Group>(
+        bp_gens: &'b BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
         transcript: &'a mut Transcript,
         n: usize,
         m: usize,
-    ) -> Result<DealerAwaitingBitCommitments<'a, 'b>, MPCError> {
-        if !(n == 8 || n == 16 || n == 32 || n == 64) {
-            return Err(MPCError::InvalidBitsize);
-        }
-        if !m.is_power_of_two() {
+    ) -> Result<DealerAwaitingBitCommitments<'a, 'b, G>, MPCError> {
+        Dealer::new_extended(
+            bp_gens,
+            &ExtendedPedersenGens::from(*pc_gens),
+            transcript,
+            n,
+            m,
+        )
+    }
+
+    /// Creates a new dealer coordinating `m` parties proving `n`-bit
+    /// ranges of values in extended Pedersen commitments, whose
+    /// blinding factors are counted by the extension degree of the
+    /// `pc_gens`.
+    pub fn new_extended<'a, 'b, G: Group>(
+        bp_gens: &'b BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        transcript: &'a mut Transcript,
+        n: usize,
+        m: usize,
+    ) -> Result<DealerAwaitingBitCommitments<'a, 'b, G>, MPCError> {
+        Dealer::new_internal(bp_gens, pc_gens, None, transcript, n, m)
+    }
+
+    /// Creates a new dealer coordinating one party per value base,
+    /// proving `n`-bit ranges of values committed to with the value
+    /// base of their position instead of the `B` of the `pc_gens`,
+    /// such as the value bases of different assets or blinded asset
+    /// tags.  The parties are created with
+    /// [`Party::new_with_value_base`](super::party::Party::new_with_value_base).
+    ///
+    /// The value bases are bound to the transcript, and must be known
+    /// to the verifier.  The dummy parties padding the aggregation
+    /// commit with `B`.
+    pub fn new_with_value_bases<'a, 'b, G: Group>(
+        bp_gens: &'b BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        value_bases: &[G::Point],
+        transcript: &'a mut Transcript,
+        n: usize,
+    ) -> Result<DealerAwaitingBitCommitments<'a, 'b, G>, MPCError> {
+        Dealer::new_internal(
+            bp_gens,
+            pc_gens,
+            Some(value_bases.to_vec()),
+            transcript,
+            n,
+            value_bases.len(),
+        )
+    }
+
+    fn new_internal<'a, 'b, G: Group>(
+        bp_gens: &'b BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        value_bases: Option<Vec<G::Point>>,
+        transcript: &'a mut Transcript,
+        n: usize,
+        m: usize,
+    ) -> Result<DealerAwaitingBitCommitments<'a, 'b, G>, MPCError> {
+        let padded_n = padded_bitsize(n).ok_or(MPCError::InvalidBitsize)?;
+        if m == 0 {
             return Err(MPCError::InvalidAggregation);
         }
-        if bp_gens.gens_capacity < n {
+        if bp_gens.gens_capacity < padded_n {
             return Err(MPCError::InvalidGeneratorsLength);
         }
-        if bp_gens.party_capacity < m {
+        if bp_gens.party_capacity < m.next_power_of_two() {
             return Err(MPCError::InvalidGeneratorsLength);
         }
 
@@ -72,7 +132,8 @@ impl Dealer {
 
         Ok(DealerAwaitingBitCommitments {
             bp_gens,
-            pc_gens,
+            pc_gens: pc_gens.clone(),
+            value_bases,
             transcript,
             initial_transcript,
             n,
@@ -82,9 +143,11 @@ impl Dealer {
 }
 
 /// A dealer waiting for the parties to send their [`BitCommitment`]s.
-pub struct DealerAwaitingBitCommitments<'a, 'b> {
-    bp_gens: &'b BulletproofGens,
-    pc_gens: &'b PedersenGens,
+pub struct DealerAwaitingBitCommitments<'a, 'b, G: Group = Ristretto> {
+    bp_gens: &'b BulletproofGens<G>,
+    pc_gens: ExtendedPedersenGens<G>,
+    /// The value bases of the parties, if they differ from `B`
+    value_bases: Option<Vec<G::Point>>,
     transcript: &'a mut Transcript,
     /// The dealer keeps a copy of the initial transcript state, so
     /// that it can attempt to verify the aggregated proof at the end.
@@ -93,30 +156,48 @@ pub struct DealerAwaitingBitCommitments<'a, 'b> {
     m: usize,
 }
 
-impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
+impl<'a, 'b, G: Group> DealerAwaitingBitCommitments<'a, 'b, G> {
     /// Receive each party's [`BitCommitment`]s and compute the [`BitChallenge`].
     pub fn receive_bit_commitments(
         self,
-        bit_commitments: Vec<BitCommitment>,
-    ) -> Result<(DealerAwaitingPolyCommitments<'a, 'b>, BitChallenge), MPCError> {
+        bit_commitments: Vec<BitCommitment<G>>,
+    ) -> Result<(DealerAwaitingPolyCommitments<'a, 'b, G>, BitChallenge<G>), MPCError> {
         if self.m != bit_commitments.len() {
             return Err(MPCError::WrongNumBitCommitments);
         }
 
-        // Commit each V_j individually
+        // Commit each V_j individually, including the identity
+        // commitments of the dummy parties
         for vc in bit_commitments.iter() {
-            self.transcript.append_point(b"V", &vc.V_j);
+            self.transcript.append_group_point::<G>(b"V", &vc.V_j);
+        }
+        for _ in self.m..self.m.next_power_of_two() {
+            self.transcript
+                .append_group_point::<G>(b"V", &G::compress(&G::identity()));
+        }
+        if let Some(value_bases) = &self.value_bases {
+            for value_base in value_bases.iter() {
+                self.transcript
+                    .append_group_point::<G>(b"value_base", &G::compress(value_base));
+            }
         }
 
-        // Commit aggregated A_j, S_j
-        let A: RistrettoPoint = bit_commitments.iter().map(|vc| vc.A_j).sum();
-        self.transcript.append_point(b"A", &A.compress());
+        // Commit aggregated A_j, S_j.  The dummy parties have
+        // S_j = 0, but contribute A_j = -<1, H_j>.
+        let A_dummy: G::Point = (self.m..self.m.next_power_of_two())
+            .flat_map(|j| self.bp_gens.share(j).H(self.n.next_power_of_two()))
+            .copied()
+            .sum();
+        let A: G::Point = bit_commitments.iter().map(|vc| vc.A_j).sum::<G::Point>() - A_dummy;
+        self.transcript
+            .append_group_point::<G>(b"A", &G::compress(&A));
 
-        let S: RistrettoPoint = bit_commitments.iter().map(|vc| vc.S_j).sum();
-        self.transcript.append_point(b"S", &S.compress());
+        let S: G::Point = bit_commitments.iter().map(|vc| vc.S_j).sum();
+        self.transcript
+            .append_group_point::<G>(b"S", &G::compress(&S));
 
-        let y = self.transcript.challenge_scalar(b"y");
-        let z = self.transcript.challenge_scalar(b"z");
+        let y = self.transcript.challenge_group_scalar::<G>(b"y");
+        let z = self.transcript.challenge_group_scalar::<G>(b"z");
         let bit_challenge = BitChallenge { y, z };
 
         Ok((
@@ -127,6 +208,7 @@ impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
                 initial_transcript: self.initial_transcript,
                 bp_gens: self.bp_gens,
                 pc_gens: self.pc_gens,
+                value_bases: self.value_bases,
                 bit_challenge,
                 bit_commitments,
                 A,
@@ -139,40 +221,44 @@ impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
 
 /// A dealer which has sent the [`BitChallenge`] to the parties and
 /// is waiting for their [`PolyCommitment`]s.
-pub struct DealerAwaitingPolyCommitments<'a, 'b> {
+pub struct DealerAwaitingPolyCommitments<'a, 'b, G: Group = Ristretto> {
     n: usize,
     m: usize,
     transcript: &'a mut Transcript,
     initial_transcript: Transcript,
-    bp_gens: &'b BulletproofGens,
-    pc_gens: &'b PedersenGens,
-    bit_challenge: BitChallenge,
-    bit_commitments: Vec<BitCommitment>,
+    bp_gens: &'b BulletproofGens<G>,
+    pc_gens: ExtendedPedersenGens<G>,
+    value_bases: Option<Vec<G::Point>>,
+    bit_challenge: BitChallenge<G>,
+    bit_commitments: Vec<BitCommitment<G>>,
     /// Aggregated commitment to the parties' bits
-    A: RistrettoPoint,
+    A: G::Point,
     /// Aggregated commitment to the parties' bit blindings
-    S: RistrettoPoint,
+    S: G::Point,
 }
 
-impl<'a, 'b> DealerAwaitingPolyCommitments<'a, 'b> {
+impl<'a, 'b, G: Group> DealerAwaitingPolyCommitments<'a, 'b, G> {
     /// Receive [`PolyCommitment`]s from the parties and compute the
     /// [`PolyChallenge`].
     pub fn receive_poly_commitments(
         self,
-        poly_commitments: Vec<PolyCommitment>,
-    ) -> Result<(DealerAwaitingProofShares<'a, 'b>, PolyChallenge), MPCError> {
+        poly_commitments: Vec<PolyCommitment<G>>,
+    ) -> Result<(DealerAwaitingProofShares<'a, 'b, G>, PolyChallenge<G>), MPCError> {
         if self.m != poly_commitments.len() {
             return Err(MPCError::WrongNumPolyCommitments);
         }
 
-        // Commit sums of T_1_j's and T_2_j's
-        let T_1: RistrettoPoint = poly_commitments.iter().map(|pc| pc.T_1_j).sum();
-        let T_2: RistrettoPoint = poly_commitments.iter().map(|pc| pc.T_2_j).sum();
+        // Commit sums of T_1_j's and T_2_j's (the dummy parties
+        // have T_1_j = T_2_j = 0)
+        let T_1: G::Point = poly_commitments.iter().map(|pc| pc.T_1_j).sum();
+        let T_2: G::Point = poly_commitments.iter().map(|pc| pc.T_2_j).sum();
 
-        self.transcript.append_point(b"T_1", &T_1.compress());
-        self.transcript.append_point(b"T_2", &T_2.compress());
+        self.transcript
+            .append_group_point::<G>(b"T_1", &G::compress(&T_1));
+        self.transcript
+            .append_group_point::<G>(b"T_2", &G::compress(&T_2));
 
-        let x = self.transcript.challenge_scalar(b"x");
+        let x = self.transcript.challenge_group_scalar::<G>(b"x");
         let poly_challenge = PolyChallenge { x };
 
         Ok((
@@ -183,6 +269,7 @@ impl<'a, 'b> DealerAwaitingPolyCommitments<'a, 'b> {
                 initial_transcript: self.initial_transcript,
                 bp_gens: self.bp_gens,
                 pc_gens: self.pc_gens,
+                value_bases: self.value_bases,
                 bit_challenge: self.bit_challenge,
                 bit_commitments: self.bit_commitments,
                 A: self.A,
@@ -200,30 +287,34 @@ impl<'a, 'b> DealerAwaitingPolyCommitments<'a, 'b> {
 /// A dealer which has sent the [`PolyChallenge`] to the parties and
 /// is waiting to aggregate their [`ProofShare`]s into a
 /// [`RangeProof`].
-pub struct DealerAwaitingProofShares<'a, 'b> {
+pub struct DealerAwaitingProofShares<'a, 'b, G: Group = Ristretto> {
     n: usize,
     m: usize,
     transcript: &'a mut Transcript,
     initial_transcript: Transcript,
-    bp_gens: &'b BulletproofGens,
-    pc_gens: &'b PedersenGens,
-    bit_challenge: BitChallenge,
-    bit_commitments: Vec<BitCommitment>,
-    poly_challenge: PolyChallenge,
-    poly_commitments: Vec<PolyCommitment>,
-    A: RistrettoPoint,
-    S: RistrettoPoint,
-    T_1: RistrettoPoint,
-    T_2: RistrettoPoint,
+    bp_gens: &'b BulletproofGens<G>,
+    pc_gens: ExtendedPedersenGens<G>,
+    value_bases: Option<Vec<G::Point>>,
+    bit_challenge: BitChallenge<G>,
+    bit_commitments: Vec<BitCommitment<G>>,
+    poly_challenge: PolyChallenge<G>,
+    poly_commitments: Vec<PolyCommitment<G>>,
+    A: G::Point,
+    S: G::Point,
+    T_1: G::Point,
+    T_2: G::Point,
 }
 
-impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
+impl<'a, 'b, G: Group> DealerAwaitingProofShares<'a, 'b, G> {
     /// Assembles proof shares into an `RangeProof`.
     ///
     /// Used as a helper function by `receive_trusted_shares` (which
     /// just hands back the result) and `receive_shares` (which
     /// validates the proof shares.
-    fn assemble_shares(&mut self, proof_shares: &[ProofShare]) -> Result<RangeProof, MPCError> {
+    fn assemble_shares(
+        &mut self,
+        proof_shares: &[ProofShare<G>],
+    ) -> Result<RangeProof<G>, MPCError> {
         if self.m != proof_shares.len() {
             return Err(MPCError::WrongNumProofShares);
         }
@@ -232,7 +323,12 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
         let mut bad_shares = Vec::<usize>::new(); // no allocations until we append
         for (j, share) in proof_shares.iter().enumerate() {
             share
-                .check_size(self.n, &self.bp_gens, j)
+                .check_size(
+                    self.n.next_power_of_two(),
+                    self.pc_gens.extension_degree(),
+                    &self.bp_gens,
+                    j,
+                )
                 .unwrap_or_else(|_| {
                     bad_shares.push(j);
                 });
@@ -242,52 +338,93 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
             return Err(MPCError::MalformedProofShares { bad_shares });
         }
 
-        let t_x: Scalar = proof_shares.iter().map(|ps| ps.t_x).sum();
-        let t_x_blinding: Scalar = proof_shares.iter().map(|ps| ps.t_x_blinding).sum();
-        let e_blinding: Scalar = proof_shares.iter().map(|ps| ps.e_blinding).sum();
+        let dummy_shares: Vec<ProofShare<G>> = (self.m..self.m.next_power_of_two())
+            .map(|j| {
+                ProofShare::dummy(
+                    self.n,
+                    j,
+                    self.pc_gens.extension_degree(),
+                    &self.bit_challenge,
+                )
+            })
+            .collect();
+        let proof_shares: Vec<&ProofShare<G>> =
+            proof_shares.iter().chain(dummy_shares.iter()).collect();
+        let padded_n = self.n.next_power_of_two();
+        let padded_m = proof_shares.len();
+
+        let t_x: G::Scalar = proof_shares.iter().map(|ps| ps.t_x).sum();
+        let extension_degree = self.pc_gens.extension_degree();
+        let t_x_blinding: Vec<G::Scalar> = (0..extension_degree)
+            .map(|k| proof_shares.iter().map(|ps| ps.t_x_blinding[k]).sum())
+            .collect();
+        let e_blinding: Vec<G::Scalar> = (0..extension_degree)
+            .map(|k| proof_shares.iter().map(|ps| ps.e_blinding[k]).sum())
+            .collect();
 
-        self.transcript.append_scalar(b"t_x", &t_x);
-        self.transcript
-            .append_scalar(b"t_x_blinding", &t_x_blinding);
-        self.transcript.append_scalar(b"e_blinding", &e_blinding);
+        self.transcript.append_group_scalar::<G>(b"t_x", &t_x);
+        for t_x_blinding_k in t_x_blinding.iter() {
+            self.transcript
+                .append_group_scalar::<G>(b"t_x_blinding", t_x_blinding_k);
+        }
+        for e_blinding_k in e_blinding.iter() {
+            self.transcript
+                .append_group_scalar::<G>(b"e_blinding", e_blinding_k);
+        }
+        // With distinct value bases, the verifier needs each party's
+        // evaluation of t(x) to check it against its own value base
+        let t_x_shares: Vec<G::Scalar> = match self.value_bases {
+            Some(_) => proof_shares[..self.m].iter().map(|ps| ps.t_x).collect(),
+            None => Vec::new(),
+        };
+        for t_x_share in t_x_shares.iter() {
+            self.transcript
+                .append_group_scalar::<G>(b"t_x_share", t_x_share);
+        }
 
         // Get a challenge value to combine statements for the IPP
-        let w = self.transcript.challenge_scalar(b"w");
-        let Q = w * self.pc_gens.B;
+        let w = self.transcript.challenge_group_scalar::<G>(b"w");
+        let Q = self.pc_gens.B * w;
 
-        let G_factors: Vec<Scalar> = iter::repeat(Scalar::one()).take(self.n * self.m).collect();
-        let H_factors: Vec<Scalar> = util::exp_iter(self.bit_challenge.y.invert())
-            .take(self.n * self.m)
+        let G_factors: Vec<G::Scalar> = iter::repeat(G::Scalar::one())
+            .take(padded_n * padded_m)
+            .collect();
+        let H_factors: Vec<G::Scalar> = util::exp_iter(self.bit_challenge.y.invert())
+            .take(padded_n * padded_m)
             .collect();
 
-        let l_vec: Vec<Scalar> = proof_shares
-            .iter()
-            .flat_map(|ps| ps.l_vec.clone().into_iter())
+        // The inner product proof borrows the parties' generators and
+        // vectors rather than concatenating them
+        let G: Vec<_> = (0..padded_m)
+            .map(|j| self.bp_gens.share(j).G_slice(padded_n))
             .collect();
-        let r_vec: Vec<Scalar> = proof_shares
-            .iter()
-            .flat_map(|ps| ps.r_vec.clone().into_iter())
+        let H: Vec<_> = (0..padded_m)
+            .map(|j| self.bp_gens.share(j).H_slice(padded_n))
             .collect();
+        let l_vec: Vec<_> = proof_shares.iter().map(|ps| &ps.l_vec[..]).collect();
+        let r_vec: Vec<_> = proof_shares.iter().map(|ps| &ps.r_vec[..]).collect();
 
         let ipp_proof = inner_product_proof::InnerProductProof::create(
+            self.bp_gens.msm_backend(),
             self.transcript,
             &Q,
             &G_factors,
             &H_factors,
-            self.bp_gens.G(self.n, self.m).cloned().collect(),
-            self.bp_gens.H(self.n, self.m).cloned().collect(),
-            l_vec,
-            r_vec,
+            Chunked::new(&G),
+            Chunked::new(&H),
+            Chunked::new(&l_vec),
+            Chunked::new(&r_vec),
         );
 
         Ok(RangeProof {
-            A: self.A.compress(),
-            S: self.S.compress(),
-            T_1: self.T_1.compress(),
-            T_2: self.T_2.compress(),
+            A: G::compress(&self.A),
+            S: G::compress(&self.S),
+            T_1: G::compress(&self.T_1),
+            T_2: G::compress(&self.T_2),
             t_x,
             t_x_blinding,
             e_blinding,
+            t_x_shares,
             ipp_proof,
         })
     }
@@ -299,7 +436,7 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     /// This is a convenience wrapper around receive_shares_with_rng
     ///
     #[cfg(feature = "std")]
-    pub fn receive_shares(self, proof_shares: &[ProofShare]) -> Result<RangeProof, MPCError> {
+    pub fn receive_shares(self, proof_shares: &[ProofShare<G>]) -> Result<RangeProof<G>, MPCError> {
         self.receive_shares_with_rng(proof_shares, &mut thread_rng())
     }
 
@@ -318,27 +455,48 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     /// saves time by skipping verification of the aggregated proof.
     pub fn receive_shares_with_rng<T: RngCore + CryptoRng>(
         mut self,
-        proof_shares: &[ProofShare],
+        proof_shares: &[ProofShare<G>],
         rng: &mut T,
-    ) -> Result<RangeProof, MPCError> {
+    ) -> Result<RangeProof<G>, MPCError> {
         let proof = self.assemble_shares(proof_shares)?;
 
         let Vs: Vec<_> = self.bit_commitments.iter().map(|vc| vc.V_j).collect();
 
         // See comment in `Dealer::new` for why we use `initial_transcript`
         let transcript = &mut self.initial_transcript;
-        if proof
-            .verify_multiple_with_rng(self.bp_gens, self.pc_gens, transcript, &Vs, self.n, rng)
-            .is_ok()
-        {
+        let verified = match &self.value_bases {
+            Some(value_bases) => proof.verify_multiple_with_value_bases_with_rng(
+                self.bp_gens,
+                &self.pc_gens,
+                value_bases,
+                transcript,
+                &Vs,
+                self.n,
+                rng,
+            ),
+            None => proof.verify_multiple_extended_with_rng(
+                self.bp_gens,
+                &self.pc_gens,
+                transcript,
+                &Vs,
+                self.n,
+                rng,
+            ),
+        };
+        if verified.is_ok() {
             Ok(proof)
         } else {
             // Proof verification failed. Now audit the parties:
             let mut bad_shares = Vec::new();
             for j in 0..self.m {
+                let pc_gens = match &self.value_bases {
+                    Some(value_bases) => self.pc_gens.with_value_base(value_bases[j]),
+                    None => self.pc_gens.clone(),
+                };
                 match proof_shares[j].audit_share(
                     &self.bp_gens,
-                    &self.pc_gens,
+                    &pc_gens,
+                    self.n,
                     j,
                     &self.bit_commitments[j],
                     &self.bit_challenge,
@@ -369,8 +527,8 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     /// detects which party(ies) submitted malformed shares.
     pub fn receive_trusted_shares(
         mut self,
-        proof_shares: &[ProofShare],
-    ) -> Result<RangeProof, MPCError> {
+        proof_shares: &[ProofShare<G>],
+    ) -> Result<RangeProof<G>, MPCError> {
         self.assemble_shares(proof_shares)
     }
 }
@@ -0,0 +1,332 @@
+//! The `keys` module contains prover and verifier keys for range
+//! proofs of a fixed bitsize and aggregation size.
+//!
+//! A verifier which checks many proofs of the same shape can build a
+//! [`RangeProofVerifierKey`] once, which holds the padded powers of two
+//! and a variable-time precomputation over the generators the proofs
+//! use, so that each verification only pays for the points which are
+//! specific to the proof.
+
+#![allow(non_snake_case)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate rand;
+
+#[cfg(feature = "std")]
+use self::rand::thread_rng;
+use alloc::vec::Vec;
+use core::iter;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, VartimeRistrettoPrecomputation};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimePrecomputedMultiscalarMul;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use super::{padded_bitsize, padded_powers_of_2, RangeProof};
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+
+/// Checks that proofs of bitsize `n` aggregating `m` values fit in
+/// the generators, returning the padded bitsize and aggregation size.
+fn padded_shape(
+    bp_gens: &BulletproofGens,
+    n: usize,
+    m: usize,
+) -> Result<(usize, usize), ProofError> {
+    let padded_n = padded_bitsize(n).ok_or(ProofError::InvalidBitsize)?;
+    if m == 0 {
+        return Err(ProofError::InvalidAggregation);
+    }
+    let padded_m = m.next_power_of_two();
+    if bp_gens.gens_capacity < padded_n || bp_gens.party_capacity < padded_m {
+        return Err(ProofError::InvalidGeneratorsLength);
+    }
+    Ok((padded_n, padded_m))
+}
+
+/// A key for creating range proofs of bitsize `n` aggregating `m`
+/// values.
+///
+/// The key only validates the shape of the proofs against the
+/// generators once, and then checks that each call proves `m` values;
+/// it precomputes nothing.  Unlike the [`RangeProofVerifierKey`], it
+/// cannot hold tables over the generators, since the prover multiplies
+/// them by secret scalars, which must not be done in variable time.
+pub struct RangeProofProverKey<'a> {
+    bp_gens: &'a BulletproofGens,
+    pc_gens: ExtendedPedersenGens,
+    n: usize,
+    m: usize,
+}
+
+impl<'a> RangeProofProverKey<'a> {
+    /// Constructs a key for proofs of values committed to with the
+    /// given Pedersen generators.
+    pub fn new(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &PedersenGens,
+        n: usize,
+        m: usize,
+    ) -> Result<Self, ProofError> {
+        RangeProofProverKey::new_extended(bp_gens, &ExtendedPedersenGens::from(*pc_gens), n, m)
+    }
+
+    /// Constructs a key for proofs of values committed to with the
+    /// given extended Pedersen generators.
+    pub fn new_extended(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &ExtendedPedersenGens,
+        n: usize,
+        m: usize,
+    ) -> Result<Self, ProofError> {
+        padded_shape(bp_gens, n, m)?;
+        Ok(RangeProofProverKey {
+            bp_gens,
+            pc_gens: pc_gens.clone(),
+            n,
+            m,
+        })
+    }
+
+    /// Returns the bitsize of the proofs created with this key.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the number of values aggregated in the proofs created
+    /// with this key.
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    /// Creates a proof for the given values, each of which has one
+    /// blinding factor per extension degree of the key.
+    pub fn prove_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        transcript: &mut Transcript,
+        values: &[u128],
+        blindings: &[Vec<Scalar>],
+        rng: &mut T,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        if values.len() != self.m {
+            return Err(ProofError::WrongNumValues);
+        }
+        RangeProof::prove_multiple_extended_with_rng(
+            self.bp_gens,
+            &self.pc_gens,
+            transcript,
+            values,
+            blindings,
+            self.n,
+            rng,
+        )
+    }
+
+    /// Creates a proof for the given values.
+    ///
+    /// This is a convenience wrapper around [`RangeProofProverKey::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove(
+        &self,
+        transcript: &mut Transcript,
+        values: &[u128],
+        blindings: &[Vec<Scalar>],
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        self.prove_with_rng(transcript, values, blindings, &mut thread_rng())
+    }
+}
+
+/// A key for verifying range proofs of bitsize `n` aggregating `m`
+/// values.
+///
+/// The key holds a precomputation over the Pedersen generators and
+/// the bulletproof generators of the first `m` parties, rounded up to
+/// a power of two.  This makes the verification of proofs of one or
+/// two values about 15-20% faster; for larger aggregations the
+/// generators dominate the multiscalar multiplication, which is then
+/// about as fast without a precomputation.  Building the key costs
+/// about as much as a few verifications, so it pays off when it is
+/// kept for the lifetime of the verifier.
+pub struct RangeProofVerifierKey {
+    extension_degree: usize,
+    n: usize,
+    m: usize,
+    powers_of_2: Vec<Scalar>,
+    precomputation: VartimeRistrettoPrecomputation,
+}
+
+impl RangeProofVerifierKey {
+    /// Constructs a key for proofs of values committed to with the
+    /// given Pedersen generators.
+    pub fn new(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        n: usize,
+        m: usize,
+    ) -> Result<Self, ProofError> {
+        RangeProofVerifierKey::new_extended(bp_gens, &ExtendedPedersenGens::from(*pc_gens), n, m)
+    }
+
+    /// Constructs a key for proofs of values committed to with the
+    /// given extended Pedersen generators.
+    pub fn new_extended(
+        bp_gens: &BulletproofGens,
+        pc_gens: &ExtendedPedersenGens,
+        n: usize,
+        m: usize,
+    ) -> Result<Self, ProofError> {
+        let (padded_n, padded_m) = padded_shape(bp_gens, n, m)?;
+
+        // The points are in the order of the scalars of a deferred check
+        let static_points = iter::once(&pc_gens.B)
+            .chain(pc_gens.B_blinding.iter())
+            .chain((0..padded_m).flat_map(|j| bp_gens.share(j).G(padded_n)))
+            .chain((0..padded_m).flat_map(|j| bp_gens.share(j).H(padded_n)));
+
+        Ok(RangeProofVerifierKey {
+            extension_degree: pc_gens.extension_degree(),
+            n,
+            m,
+            powers_of_2: padded_powers_of_2(n).collect(),
+            precomputation: VartimeRistrettoPrecomputation::new(static_points),
+        })
+    }
+
+    /// Returns the bitsize of the proofs verified with this key.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the number of values aggregated in the proofs verified
+    /// with this key.
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    /// Verifies a proof for the given value commitments, of which there
+    /// must be `m`.
+    pub fn verify_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        proof: &RangeProof,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        if value_commitments.len() != self.m {
+            return Err(ProofError::WrongNumValues);
+        }
+
+        let check = proof.verification_check_with_powers_of_2(
+            &self.powers_of_2,
+            self.extension_degree,
+            None,
+            transcript,
+            value_commitments,
+            self.n,
+            rng,
+        )?;
+
+        if check.verify_precomputed(&self.precomputation) {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Verifies a proof for the given value commitments.
+    ///
+    /// This is a convenience wrapper around [`RangeProofVerifierKey::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify(
+        &self,
+        proof: &RangeProof,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+    ) -> Result<(), ProofError> {
+        self.verify_with_rng(proof, transcript, value_commitments, &mut thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    #[test]
+    fn prove_and_verify_with_keys() {
+        let bp_gens = BulletproofGens::new(64, 4);
+        let mut rng = rand::thread_rng();
+
+        for &(n, m, extension_degree) in &[(32, 1, 1), (64, 3, 1), (8, 4, 2), (20, 2, 3)] {
+            let pc_gens = ExtendedPedersenGens::new(extension_degree).unwrap();
+            let prover_key = RangeProofProverKey::new_extended(&bp_gens, &pc_gens, n, m).unwrap();
+            let verifier_key =
+                RangeProofVerifierKey::new_extended(&bp_gens, &pc_gens, n, m).unwrap();
+
+            let values: Vec<u128> = (0..m as u128).map(|j| (1 << (n - 1)) + j).collect();
+            let blindings: Vec<Vec<Scalar>> = (0..m)
+                .map(|_| {
+                    (0..extension_degree)
+                        .map(|_| Scalar::random(&mut rng))
+                        .collect()
+                })
+                .collect();
+            let (proof, value_commitments) = prover_key
+                .prove(&mut Transcript::new(b"KeyTest"), &values, &blindings)
+                .unwrap();
+
+            // The proof verifies with and without the verifier key
+            assert!(verifier_key
+                .verify(&proof, &mut Transcript::new(b"KeyTest"), &value_commitments)
+                .is_ok());
+            assert!(proof
+                .verify_multiple_extended(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut Transcript::new(b"KeyTest"),
+                    &value_commitments,
+                    n,
+                )
+                .is_ok());
+
+            // A different transcript does not verify
+            assert_eq!(
+                verifier_key.verify(&proof, &mut Transcript::new(b"Other"), &value_commitments),
+                Err(ProofError::VerificationError)
+            );
+        }
+    }
+
+    #[test]
+    fn keys_check_shape() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 2);
+
+        assert!(RangeProofVerifierKey::new(&bp_gens, &pc_gens, 64, 1).is_err());
+        assert!(RangeProofVerifierKey::new(&bp_gens, &pc_gens, 32, 3).is_err());
+        assert!(RangeProofVerifierKey::new(&bp_gens, &pc_gens, 32, 0).is_err());
+        assert!(RangeProofProverKey::new(&bp_gens, &pc_gens, 0, 1).is_err());
+
+        let prover_key = RangeProofProverKey::new(&bp_gens, &pc_gens, 16, 2).unwrap();
+        let verifier_key = RangeProofVerifierKey::new(&bp_gens, &pc_gens, 16, 1).unwrap();
+        let blindings = vec![vec![Scalar::one()]; 2];
+        assert_eq!(
+            prover_key
+                .prove(&mut Transcript::new(b"KeyTest"), &[7], &blindings[..1])
+                .unwrap_err(),
+            ProofError::WrongNumValues
+        );
+        let (proof, value_commitments) = prover_key
+            .prove(&mut Transcript::new(b"KeyTest"), &[7, 9], &blindings)
+            .unwrap();
+        assert_eq!(
+            verifier_key.verify(&proof, &mut Transcript::new(b"KeyTest"), &value_commitments),
+            Err(ProofError::WrongNumValues)
+        );
+    }
+}
@@ -6,60 +6,238 @@
 
 extern crate alloc;
 
+use alloc::vec;
 use alloc::vec::Vec;
 use core::iter;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
 
-use crate::generators::{BulletproofGens, PedersenGens};
+use serde::{Deserializer, Serializer};
+
+use crate::generators::{BulletproofGens, ExtendedPedersenGens};
+use crate::group::{Group, Ristretto, ScalarField};
 
 /// A commitment to the bits of a party's value.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct BitCommitment {
-    pub(super) V_j: CompressedRistretto,
-    pub(super) A_j: RistrettoPoint,
-    pub(super) S_j: RistrettoPoint,
+#[serde(bound(
+    serialize = "G::Point: serde::Serialize, G::CompressedPoint: serde::Serialize",
+    deserialize = "G::Point: serde::Deserialize<'de>, G::CompressedPoint: serde::Deserialize<'de>"
+))]
+pub struct BitCommitment<G: Group = Ristretto> {
+    pub(super) V_j: G::CompressedPoint,
+    pub(super) A_j: G::Point,
+    pub(super) S_j: G::Point,
 }
 
 /// Challenge values derived from all parties' [`BitCommitment`]s.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct BitChallenge {
-    pub(super) y: Scalar,
-    pub(super) z: Scalar,
+#[serde(bound(
+    serialize = "G::Scalar: serde::Serialize",
+    deserialize = "G::Scalar: serde::Deserialize<'de>"
+))]
+pub struct BitChallenge<G: Group = Ristretto> {
+    pub(super) y: G::Scalar,
+    pub(super) z: G::Scalar,
 }
 
 /// A commitment to a party's polynomial coefficents.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct PolyCommitment {
-    pub(super) T_1_j: RistrettoPoint,
-    pub(super) T_2_j: RistrettoPoint,
+#[serde(bound(
+    serialize = "G::Point: serde::Serialize",
+    deserialize = "G::Point: serde::Deserialize<'de>"
+))]
+pub struct PolyCommitment<G: Group = Ristretto> {
+    pub(super) T_1_j: G::Point,
+    pub(super) T_2_j: G::Point,
 }
 
 /// Challenge values derived from all parties' [`PolyCommitment`]s.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct PolyChallenge {
-    pub(super) x: Scalar,
+#[serde(bound(
+    serialize = "G::Scalar: serde::Serialize",
+    deserialize = "G::Scalar: serde::Deserialize<'de>"
+))]
+pub struct PolyChallenge<G: Group = Ristretto> {
+    pub(super) x: G::Scalar,
 }
 
 /// A party's proof share, ready for aggregation into the final
 /// [`RangeProof`](::RangeProof).
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct ProofShare {
-    pub(super) t_x: Scalar,
-    pub(super) t_x_blinding: Scalar,
-    pub(super) e_blinding: Scalar,
-    pub(super) l_vec: Vec<Scalar>,
-    pub(super) r_vec: Vec<Scalar>,
+///
+/// A `ProofShare` is serialized with a single scalar for each of its
+/// blinding factors, as for plain commitments, and its serialization
+/// fails for extended commitments: send those shares as an
+/// [`ExtendedProofShare`] instead.
+#[derive(Clone, Debug)]
+pub struct ProofShare<G: Group = Ristretto> {
+    pub(super) t_x: G::Scalar,
+    pub(super) t_x_blinding: Vec<G::Scalar>,
+    pub(super) e_blinding: Vec<G::Scalar>,
+    pub(super) l_vec: Vec<G::Scalar>,
+    pub(super) r_vec: Vec<G::Scalar>,
+}
+
+/// A [`ProofShare`] for commitments of any extension degree, whose
+/// serialization carries all of its blinding factors.
+#[derive(Clone, Debug)]
+pub struct ExtendedProofShare<G: Group = Ristretto>(pub ProofShare<G>);
+
+/// The serialized layout of a [`ProofShare`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "ProofShare")]
+struct ProofShareLayout<S, V> {
+    t_x: S,
+    t_x_blinding: S,
+    e_blinding: S,
+    l_vec: V,
+    r_vec: V,
+}
+
+/// The serialized layout of an [`ExtendedProofShare`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "ExtendedProofShare")]
+struct ExtendedProofShareLayout<S, V> {
+    t_x: S,
+    t_x_blinding: V,
+    e_blinding: V,
+    l_vec: V,
+    r_vec: V,
+}
+
+impl<G: Group> serde::Serialize for ProofShare<G>
+where
+    G::Scalar: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.t_x_blinding.len() != 1 || self.e_blinding.len() != 1 {
+            return Err(serde::ser::Error::custom(
+                "the proof share of extended commitments must be sent as an ExtendedProofShare",
+            ));
+        }
+        let layout = ProofShareLayout {
+            t_x: &self.t_x,
+            t_x_blinding: &self.t_x_blinding[0],
+            e_blinding: &self.e_blinding[0],
+            l_vec: &self.l_vec[..],
+            r_vec: &self.r_vec[..],
+        };
+        serde::Serialize::serialize(&layout, serializer)
+    }
+}
+
+impl<'de, G: Group> serde::Deserialize<'de> for ProofShare<G>
+where
+    G::Scalar: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let layout: ProofShareLayout<G::Scalar, Vec<G::Scalar>> =
+            serde::Deserialize::deserialize(deserializer)?;
+        Ok(ProofShare {
+            t_x: layout.t_x,
+            t_x_blinding: vec![layout.t_x_blinding],
+            e_blinding: vec![layout.e_blinding],
+            l_vec: layout.l_vec,
+            r_vec: layout.r_vec,
+        })
+    }
+}
+
+impl<G: Group> serde::Serialize for ExtendedProofShare<G>
+where
+    G::Scalar: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let layout = ExtendedProofShareLayout {
+            t_x: &self.0.t_x,
+            t_x_blinding: &self.0.t_x_blinding[..],
+            e_blinding: &self.0.e_blinding[..],
+            l_vec: &self.0.l_vec[..],
+            r_vec: &self.0.r_vec[..],
+        };
+        serde::Serialize::serialize(&layout, serializer)
+    }
+}
+
+impl<'de, G: Group> serde::Deserialize<'de> for ExtendedProofShare<G>
+where
+    G::Scalar: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let layout: ExtendedProofShareLayout<G::Scalar, Vec<G::Scalar>> =
+            serde::Deserialize::deserialize(deserializer)?;
+        Ok(ExtendedProofShare(ProofShare {
+            t_x: layout.t_x,
+            t_x_blinding: layout.t_x_blinding,
+            e_blinding: layout.e_blinding,
+            l_vec: layout.l_vec,
+            r_vec: layout.r_vec,
+        }))
+    }
 }
 
-impl ProofShare {
+impl<G: Group> ProofShare<G> {
+    /// Computes the proof share of a dummy party at position `j`,
+    /// which commits to the value zero with all blinding factors set
+    /// to zero.  Dummy parties are used by the dealer to pad the
+    /// aggregation size to a power of two.
+    pub(super) fn dummy(
+        n: usize,
+        j: usize,
+        extension_degree: usize,
+        bit_challenge: &BitChallenge<G>,
+    ) -> ProofShare<G> {
+        use crate::inner_product_proof::inner_product;
+        use crate::range_proof::padded_powers_of_2;
+        use crate::util;
+
+        let padded_n = n.next_power_of_two();
+        let (y, z) = (&bit_challenge.y, &bit_challenge.z);
+        let offset_y = util::scalar_exp_vartime(y, (j * padded_n) as u64);
+        let offset_zz = *z * *z * util::scalar_exp_vartime(z, j as u64);
+
+        // With a_L = 0, a_R = -1 and s_L = s_R = 0, the polynomials
+        // l(x) and r(x) are constant.
+        let l_vec: Vec<G::Scalar> = vec![-*z; padded_n];
+        let r_vec: Vec<G::Scalar> = util::exp_iter(*y)
+            .zip(padded_powers_of_2(n))
+            .map(|(exp_y, exp_2)| offset_y * exp_y * (*z - G::Scalar::one()) + offset_zz * exp_2)
+            .collect();
+
+        ProofShare {
+            t_x: inner_product(&l_vec, &r_vec),
+            t_x_blinding: vec![G::Scalar::zero(); extension_degree],
+            e_blinding: vec![G::Scalar::zero(); extension_degree],
+            l_vec,
+            r_vec,
+        }
+    }
+
     /// Checks consistency of all sizes in the proof share and returns the size of the l/r vector.
     pub(super) fn check_size(
         &self,
         expected_n: usize,
-        bp_gens: &BulletproofGens,
+        extension_degree: usize,
+        bp_gens: &BulletproofGens<G>,
         j: usize,
     ) -> Result<(), ()> {
+        if self.t_x_blinding.len() != extension_degree {
+            return Err(());
+        }
+
+        if self.e_blinding.len() != extension_degree {
+            return Err(());
+        }
+
         if self.l_vec.len() != expected_n {
             return Err(());
         }
@@ -81,33 +259,35 @@ impl ProofShare {
 
     /// Audit an individual proof share to determine whether it is
     /// malformed.
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn audit_share(
         &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        n: usize,
         j: usize,
-        bit_commitment: &BitCommitment,
-        bit_challenge: &BitChallenge,
-        poly_commitment: &PolyCommitment,
-        poly_challenge: &PolyChallenge,
+        bit_commitment: &BitCommitment<G>,
+        bit_challenge: &BitChallenge<G>,
+        poly_commitment: &PolyCommitment<G>,
+        poly_challenge: &PolyChallenge<G>,
     ) -> Result<(), ()> {
-        use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
-
         use crate::inner_product_proof::inner_product;
+        use crate::msm;
+        use crate::range_proof::padded_powers_of_2;
         use crate::util;
 
-        let n = self.l_vec.len();
+        let padded_n = n.next_power_of_two();
 
-        self.check_size(n, bp_gens, j)?;
+        self.check_size(padded_n, pc_gens.extension_degree(), bp_gens, j)?;
 
         let (y, z) = (&bit_challenge.y, &bit_challenge.z);
         let x = &poly_challenge.x;
 
         // Precompute some variables
-        let zz = z * z;
-        let minus_z = -z;
+        let zz = *z * *z;
+        let minus_z = -*z;
         let z_j = util::scalar_exp_vartime(z, j as u64); // z^j
-        let y_jn = util::scalar_exp_vartime(y, (j * n) as u64); // y^(j*n)
+        let y_jn = util::scalar_exp_vartime(y, (j * padded_n) as u64); // y^(j*n)
         let y_jn_inv = y_jn.invert(); // y^(-j*n)
         let y_inv = y.invert(); // y^(-1)
 
@@ -115,51 +295,52 @@ impl ProofShare {
             return Err(());
         }
 
-        let g = self.l_vec.iter().map(|l_i| minus_z - l_i);
+        let g = self.l_vec.iter().map(|l_i| minus_z - *l_i);
         let h = self
             .r_vec
             .iter()
-            .zip(util::exp_iter(Scalar::from(2u64)))
+            .zip(padded_powers_of_2(n))
             .zip(util::exp_iter(y_inv))
             .map(|((r_i, exp_2), exp_y_inv)| {
-                z + exp_y_inv * y_jn_inv * (-r_i) + exp_y_inv * y_jn_inv * (zz * z_j * exp_2)
+                *z + exp_y_inv * y_jn_inv * (-*r_i) + exp_y_inv * y_jn_inv * (zz * z_j * exp_2)
             });
 
-        let P_check = RistrettoPoint::vartime_multiscalar_mul(
-            iter::once(Scalar::one())
+        let P_check = msm::vartime_multiscalar_mul(
+            bp_gens.msm_backend(),
+            iter::once(G::Scalar::one())
                 .chain(iter::once(*x))
-                .chain(iter::once(-self.e_blinding))
+                .chain(self.e_blinding.iter().map(|e| -*e))
                 .chain(g)
                 .chain(h),
             iter::once(&bit_commitment.A_j)
                 .chain(iter::once(&bit_commitment.S_j))
-                .chain(iter::once(&pc_gens.B_blinding))
-                .chain(bp_gens.share(j).G(n))
-                .chain(bp_gens.share(j).H(n)),
+                .chain(pc_gens.B_blinding.iter())
+                .chain(bp_gens.share(j).G(padded_n))
+                .chain(bp_gens.share(j).H(padded_n)),
         );
-        if !P_check.is_identity() {
+        if P_check != G::identity() {
             return Err(());
         }
 
-        let V_j = bit_commitment.V_j.decompress().ok_or(())?;
+        let V_j = G::decompress(&bit_commitment.V_j).ok_or(())?;
 
-        let sum_of_powers_y = util::sum_of_powers(&y, n);
-        let sum_of_powers_2 = util::sum_of_powers(&Scalar::from(2u64), n);
-        let delta = (z - zz) * sum_of_powers_y * y_jn - z * zz * sum_of_powers_2 * z_j;
-        let t_check = RistrettoPoint::vartime_multiscalar_mul(
+        let sum_of_powers_y = util::sum_of_powers(y, padded_n);
+        let sum_of_powers_2 = util::sum_of_powers(&G::Scalar::from(2u64), n);
+        let delta = (*z - zz) * sum_of_powers_y * y_jn - *z * zz * sum_of_powers_2 * z_j;
+        let t_check = G::vartime_multiscalar_mul(
             iter::once(zz * z_j)
                 .chain(iter::once(*x))
-                .chain(iter::once(x * x))
+                .chain(iter::once(*x * *x))
                 .chain(iter::once(delta - self.t_x))
-                .chain(iter::once(-self.t_x_blinding)),
+                .chain(self.t_x_blinding.iter().map(|t| -*t)),
             iter::once(&V_j)
                 .chain(iter::once(&poly_commitment.T_1_j))
                 .chain(iter::once(&poly_commitment.T_2_j))
                 .chain(iter::once(&pc_gens.B))
-                .chain(iter::once(&pc_gens.B_blinding)),
+                .chain(pc_gens.B_blinding.iter()),
         );
 
-        if t_check.is_identity() {
+        if t_check == G::identity() {
             Ok(())
         } else {
             Err(())
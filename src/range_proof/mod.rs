@@ -10,21 +10,23 @@ use self::rand::thread_rng;
 use alloc::vec::Vec;
 
 use core::iter;
+use core::marker::PhantomData;
 
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
 use merlin::Transcript;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+use crate::batch::{self, DeferredCheck};
 use crate::errors::ProofError;
-use crate::generators::{BulletproofGens, PedersenGens};
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+use crate::group::{Group, Ristretto, ScalarField};
 use crate::inner_product_proof::InnerProductProof;
 use crate::transcript::TranscriptProtocol;
 use crate::util;
-use blake2::{Blake2b, Digest};
 
-use crate::util::{add_bytes_to_word, bytes_to_usize, xor_32_bytes};
-use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_TABLE};
+use crate::util::{add_bytes_to_word, xor_32_bytes};
 use rand_core::{CryptoRng, RngCore};
 use serde::de::Visitor;
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
@@ -32,8 +34,22 @@ use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
 // Modules for MPC protocol
 
 pub mod dealer;
+pub mod keys;
 pub mod messages;
 pub mod party;
+pub mod rewind;
+pub mod rng;
+pub mod scanner;
+#[cfg(feature = "std")]
+pub mod session;
+
+pub use self::keys::{RangeProofProverKey, RangeProofVerifierKey};
+use self::rewind::{derive_extension_nonces, extract_message, has_rewind_tag};
+pub use self::rewind::{
+    Blake2bNonceGenerator, NonceGenerator, ProofMessage, PublicRewindKeys, RewindKeys, RewindNonces,
+};
+pub use self::rng::SeededRng;
+pub use self::scanner::{RecoveredOutput, RewindScanner};
 
 /// The `RangeProof` struct represents a proof that one or more values
 /// are in a range.
@@ -46,11 +62,30 @@ pub mod party;
 /// the values, are not included in the proof, and must be known to
 /// the verifier.
 ///
-/// This implementation requires that both the bitsize `n` and the
-/// aggregation size `m` be powers of two, so that `n = 8, 16, 32, 64`
-/// and `m = 1, 2, 4, 8, 16, ...`.  Note that the aggregation size is
-/// not given as an explicit parameter, but is determined by the
-/// number of values or commitments passed to the prover or verifier.
+/// The bitsize `n` can be any number from 1 to 128: bitsizes which
+/// are not a power of two are padded internally with bits which are
+/// always zero, while the transcript remains bound to the true `n`,
+/// so that a value of \\(2^n\\) or more cannot be proven to be in
+/// range.  Values of more than 64 bits are proven with the `u128`
+/// variants of the prover functions.  The aggregation size `m` can be
+/// any positive number: aggregations whose size is not a power of two
+/// are padded internally with dummy commitments to zero, which both
+/// the prover and the verifier reconstruct.  Note that the aggregation
+/// size is not given as an explicit parameter, but is determined by
+/// the number of values or commitments passed to the prover or verifier.
+///
+/// The values may also be committed to in extended Pedersen
+/// commitments with several blinding factors, using the `extended`
+/// variants of the prover and verifier functions with
+/// [`ExtendedPedersenGens`].  The proof then holds one of each of its
+/// blinding scalars \\(\tilde{t}\_x, \tilde{e}\\) per extension degree.
+///
+/// For multi-asset ledgers, each value may be committed to with its
+/// own value base instead of \\(B\\), such as the value base of an
+/// asset or a blinded asset tag, using the `with_value_bases` variants
+/// of the prover and verifier functions.  The proof then also holds
+/// each value's share of \\(t(x)\\), so that one proof can cover
+/// the values of different assets.
 ///
 /// # Note
 ///
@@ -58,27 +93,56 @@ pub mod party;
 /// protocol locally.  That API is exposed in the [`aggregation`](::range_proof_mpc)
 /// module and can be used to perform online aggregation between
 /// parties without revealing secret values to each other.
+///
+/// # Groups
+///
+/// The proof is generic over the prime-order [`Group`] of its
+/// commitments, which defaults to [`Ristretto`].  The provers and
+/// verifiers take the generators of the group; only the rewinding of
+/// proofs, and the constructors which cannot infer the group, such as
+/// [`RangeProof::from_bytes`], are implemented for Ristretto alone.
 #[derive(Clone, Debug)]
-pub struct RangeProof {
+pub struct RangeProof<G: Group = Ristretto> {
     /// Commitment to the bits of the value
-    A: CompressedRistretto,
+    A: G::CompressedPoint,
     /// Commitment to the blinding factors
-    S: CompressedRistretto,
+    S: G::CompressedPoint,
     /// Commitment to the \\(t_1\\) coefficient of \\( t(x) \\)
-    T_1: CompressedRistretto,
+    T_1: G::CompressedPoint,
     /// Commitment to the \\(t_2\\) coefficient of \\( t(x) \\)
-    T_2: CompressedRistretto,
+    T_2: G::CompressedPoint,
     /// Evaluation of the polynomial \\(t(x)\\) at the challenge point \\(x\\)
-    t_x: Scalar,
-    /// Blinding factor for the synthetic commitment to \\(t(x)\\)
-    t_x_blinding: Scalar,
-    /// Blinding factor for the synthetic commitment to the inner-product arguments
-    e_blinding: Scalar,
+    t_x: G::Scalar,
+    /// Blinding factors for the synthetic commitment to \\(t(x)\\),
+    /// one per extension degree
+    t_x_blinding: Vec<G::Scalar>,
+    /// Blinding factors for the synthetic commitment to the
+    /// inner-product arguments, one per extension degree
+    e_blinding: Vec<G::Scalar>,
+    /// Evaluations of each party's share of \\(t(x)\\), for proofs
+    /// over distinct value bases, and empty otherwise
+    t_x_shares: Vec<G::Scalar>,
     /// Proof data for the inner-product argument.
-    ipp_proof: InnerProductProof,
+    ipp_proof: InnerProductProof<G>,
 }
 
-impl RangeProof {
+/// A single statement in a batch of rangeproofs to be verified with
+/// [`RangeProof::verify_batch`].
+///
+/// Each statement carries its own transcript, which must be in the
+/// same state as the one used by the prover.
+pub struct RangeProofStatement<'a, G: Group = Ristretto> {
+    /// The rangeproof to verify.
+    pub proof: &'a RangeProof<G>,
+    /// The commitments to the values in the range.
+    pub value_commitments: &'a [G::CompressedPoint],
+    /// The bitsize of the range.
+    pub n: usize,
+    /// The verifier's transcript for this proof.
+    pub transcript: &'a mut Transcript,
+}
+
+impl<G: Group> RangeProof<G> {
     /// Create a rangeproof for a given pair of value `v` and
     /// blinding scalar `v_blinding`.
     /// This is a convenience wrapper around [`RangeProof::prove_multiple`].
@@ -136,14 +200,14 @@ impl RangeProof {
     /// # }
     /// ```
     pub fn prove_single_with_rng<T: RngCore + CryptoRng>(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
         transcript: &mut Transcript,
         v: u64,
-        v_blinding: &Scalar,
+        v_blinding: &G::Scalar,
         n: usize,
         rng: &mut T,
-    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
         let (p, Vs) = RangeProof::prove_multiple_with_rng(
             bp_gens,
             pc_gens,
@@ -156,176 +220,19 @@ impl RangeProof {
         Ok((p, Vs[0]))
     }
 
-    /// Create a rangeproof for a given pair of value `v` and
-    /// blinding scalar `v_blinding`, passing in a rewind key to
-    /// enable rangeproof rewinding with 23 bytes worth of extra
-    /// data that can be embedded.
-    /// This is a convenience wrapper around [`RangeProof::prove_multiple`].
-    ///
-    /// # Example
-    /// ```
-    /// extern crate rand;
-    /// use rand::thread_rng;
-    ///
-    /// extern crate curve25519_dalek;
-    /// use curve25519_dalek::scalar::Scalar;
-    ///
-    /// extern crate merlin;
-    /// use merlin::Transcript;
-    ///
-    /// extern crate tari_bulletproofs;
-    /// use tari_bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
-    ///
-    /// # fn main() {
-    /// // Generators for Pedersen commitments.  These can be selected
-    /// // independently of the Bulletproofs generators.
-    /// use curve25519_dalek::ristretto::RistrettoPoint;
-    /// use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
-    /// use tari_bulletproofs::range_proof::{get_rewind_nonce_from_pub_key, get_secret_nonce_from_pvt_key};
-    /// let pc_gens = PedersenGens::default();
-    ///
-    /// // Generators for Bulletproofs, valid for proofs up to bitsize 64
-    /// // and aggregation size up to 1.
-    /// let bp_gens = BulletproofGens::new(64, 1);
-    ///
-    /// // A secret value we want to prove lies in the range [0, 2^32)
-    /// let confidential_value = 1037578891u64;
-    ///
-    /// // The API takes a blinding factor for the commitment.
-    /// let blinding_factor = Scalar::random(&mut thread_rng());
-    ///
-    /// // The private keys for range proof rewinding; these may be based on a wallet's private root key
-    /// let pvt_rewind_key = Scalar::random(&mut thread_rng());
-    /// let pvt_blinding_key = Scalar::random(&mut thread_rng());
-    ///
-    /// // Up to 23 bytes extra data may be embedded in the range proof meta data
-    /// let proof_message: [u8; 23] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17,
-    ///         18, 19, 20, 21, 22, 23];
-    ///
-    /// // The proof can be chained to an existing transcript.
-    /// // Here we create a transcript with a doctest domain separator.
-    /// let mut prover_transcript = Transcript::new(b"doctest example");
-    ///
-    /// // Create a 32-bit rangeproof.
-    /// let (proof, committed_value) = RangeProof::prove_single_with_rewind_key(
-    ///     &bp_gens,
-    ///     &pc_gens,
-    ///     &mut prover_transcript,
-    ///     confidential_value,
-    ///     &blinding_factor,
-    ///     32,
-    ///     &pvt_rewind_key,
-    ///     &pvt_blinding_key,
-    ///     &proof_message,
-    /// ).expect("A real program could handle errors");
-    ///
-    /// // Verification requires a transcript with identical initial state:
-    /// let mut verifier_transcript = Transcript::new(b"doctest example");
-    /// assert!(
-    ///     proof
-    ///         .verify_single(&bp_gens, &pc_gens, &mut verifier_transcript, &committed_value, 32)
-    ///         .is_ok()
-    /// );
-    ///
-    /// // A third party may have access to the public keys and extra data for range proof rewinding
-    /// let pub_rewind_key_1 = RistrettoPoint::from(&pvt_rewind_key * &RISTRETTO_BASEPOINT_TABLE).compress();
-    /// let pub_rewind_key_2 = RistrettoPoint::from(&pvt_blinding_key * &RISTRETTO_BASEPOINT_TABLE).compress();
-    ///
-    /// // The rewind nonce is necessary to rewind the range proof, which is uniquely bound to the commitment
-    /// let rewind_nonce_1 = get_rewind_nonce_from_pub_key(&pub_rewind_key_1, &committed_value);
-    /// let rewind_nonce_2 = get_rewind_nonce_from_pub_key(&pub_rewind_key_2, &committed_value);
-    ///
-    /// // A owner or third party can extract the value and extra data; if it is the wrong combination
-    /// // garbage data will be extracted
-    /// let mut rewind_transcript = Transcript::new(b"doctest example");
-    /// assert_eq!(
-    ///     proof.rewind_single_get_value_only(
-    ///         &bp_gens,
-    ///         &mut rewind_transcript,
-    ///         &committed_value,
-    ///         32,
-    ///         &rewind_nonce_1,
-    ///         &rewind_nonce_2,
-    ///     ),
-    ///     Ok((confidential_value, proof_message))
-    /// );
-    ///
-    /// // The two blinding nonces are necessary to rewind the range proof fully, which are also
-    /// // uniquely bound to the commitment
-    /// let blinding_nonce_1 = get_secret_nonce_from_pvt_key(&pvt_rewind_key, &committed_value);
-    /// let blinding_nonce_2 = get_secret_nonce_from_pvt_key(&pvt_blinding_key, &committed_value);
-    ///
-    /// // The owner or trusted party can extract the value, extra data and blinding factor; if it is the
-    /// // wrong combination an error will be returned
-    /// let mut rewind_transcript = Transcript::new(b"doctest example");
-    /// assert_eq!(
-    ///     proof.rewind_single_get_commitment_data(
-    ///         &bp_gens,
-    ///         &pc_gens,
-    ///         &mut rewind_transcript,
-    ///         &committed_value,
-    ///         32,
-    ///         &rewind_nonce_1,
-    ///         &rewind_nonce_2,
-    ///         &blinding_nonce_1,
-    ///         &blinding_nonce_2,
-    ///     ),
-    ///     Ok((confidential_value, blinding_factor, proof_message))
-    /// );
-    ///
-    /// # }
-    /// ```
-    pub fn prove_single_with_rng_and_rewind_key<T: RngCore + CryptoRng>(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
-        v: u64,
-        v_blinding: &Scalar,
-        n: usize,
-        rng: &mut T,
-        pvt_rewind_key: &Scalar,
-        pvt_blinding_key: &Scalar,
-        proof_message: &[u8; 23],
-    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
-        let values = &[v];
-        let blindings = &[*v_blinding];
-        // Temporarily borrow the blindings array to pass the additional parameters
-        // into the next function
-        let mut blindings = blindings.to_vec();
-        blindings.push(RangeProof::get_rewind_key_separator());
-        blindings.push(*pvt_rewind_key);
-        blindings.push(*pvt_blinding_key);
-        blindings.push(Scalar::from_bits(add_bytes_to_word(
-            [0u8; 32],
-            proof_message,
-            8,
-        )));
-
-        let (p, Vs) = RangeProof::prove_multiple_with_rng(
-            bp_gens,
-            pc_gens,
-            transcript,
-            values,
-            &blindings[0..blindings.len()],
-            n,
-            rng,
-        )?;
-        Ok((p, Vs[0]))
-    }
-
     /// Create a rangeproof for a given pair of value `v` and
     /// blinding scalar `v_blinding`.
     /// This is a convenience wrapper around [`RangeProof::prove_single_with_rng`],
     /// passing in a threadsafe RNG.
     #[cfg(feature = "std")]
     pub fn prove_single(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
         transcript: &mut Transcript,
         v: u64,
-        v_blinding: &Scalar,
+        v_blinding: &G::Scalar,
         n: usize,
-    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
         RangeProof::prove_single_with_rng(
             bp_gens,
             pc_gens,
@@ -337,26 +244,44 @@ impl RangeProof {
         )
     }
 
-    /// Create a rangeproof for a given pair of value `v` and
-    /// blinding scalar `v_blinding`, passing in a rewind key to
-    /// enable rangeproof rewinding with 23 bytes worth of extra
-    /// data that can be embedded.
-    /// This is a convenience wrapper around
-    /// [`RangeProof::prove_single_with_rng_and_rewind_key`],
+    /// Create a rangeproof for a given pair of 128-bit value `v` and
+    /// blinding scalar `v_blinding`, for bitsizes `n` up to 128.
+    /// This is a convenience wrapper around [`RangeProof::prove_multiple_u128_with_rng`].
+    pub fn prove_single_u128_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &G::Scalar,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
+        let (p, Vs) = RangeProof::prove_multiple_u128_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            &[v],
+            &[*v_blinding],
+            n,
+            rng,
+        )?;
+        Ok((p, Vs[0]))
+    }
+
+    /// Create a rangeproof for a given pair of 128-bit value `v` and
+    /// blinding scalar `v_blinding`, for bitsizes `n` up to 128.
+    /// This is a convenience wrapper around [`RangeProof::prove_single_u128_with_rng`],
     /// passing in a threadsafe RNG.
     #[cfg(feature = "std")]
-    pub fn prove_single_with_rewind_key(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+    pub fn prove_single_u128(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
         transcript: &mut Transcript,
-        v: u64,
-        v_blinding: &Scalar,
+        v: u128,
+        v_blinding: &G::Scalar,
         n: usize,
-        pvt_rewind_key: &Scalar,
-        pvt_blinding_key: &Scalar,
-        proof_message: &[u8; 23],
-    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
-        RangeProof::prove_single_with_rng_and_rewind_key(
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
+        RangeProof::prove_single_u128_with_rng(
             bp_gens,
             pc_gens,
             transcript,
@@ -364,9 +289,6 @@ impl RangeProof {
             v_blinding,
             n,
             &mut thread_rng(),
-            pvt_rewind_key,
-            pvt_blinding_key,
-            proof_message,
         )
     }
 
@@ -425,703 +347,4385 @@ impl RangeProof {
     /// # }
     /// ```
     pub fn prove_multiple_with_rng<T: RngCore + CryptoRng>(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
         transcript: &mut Transcript,
         values: &[u64],
-        blindings: &[Scalar],
+        blindings: &[G::Scalar],
         n: usize,
         rng: &mut T,
-    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
-        use self::dealer::*;
-        use self::party::*;
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        let values: Vec<u128> = values.iter().map(|&v| v.into()).collect();
+        RangeProof::prove_multiple_u128_with_rng(
+            bp_gens, pc_gens, transcript, &values, blindings, n, rng,
+        )
+    }
 
-        //Extract the rewind key and extra bytes from the blindings vector where it was temporarily assigned
-        let (pvt_rewind_key, pvt_blinding_key, proof_message, blindings) =
-            if values.len() + 4 == blindings.len() {
-                if blindings[blindings.len() - 4] != RangeProof::get_rewind_key_separator() {
-                    return Err(ProofError::InvalidRewindKeySeparator);
-                }
-                let rewind_key = blindings[blindings.len() - 3].to_owned();
-                let blinding_key = blindings[blindings.len() - 2].to_owned();
-                let data = blindings[blindings.len() - 1].to_owned();
-                (
-                    rewind_key,
-                    blinding_key,
-                    data,
-                    &blindings[0..blindings.len() - 4],
-                )
-            } else {
-                (
-                    Scalar::default().to_owned(),
-                    Scalar::default().to_owned(),
-                    Scalar::default().to_owned(),
-                    &blindings[0..blindings.len()],
-                )
-            };
+    /// Create a rangeproof for a set of 128-bit values, for bitsizes
+    /// `n` up to 128.
+    ///
+    /// The generators must have a capacity of at least `n` rounded up
+    /// to a power of two, so proving 128-bit ranges requires
+    /// `BulletproofGens::new(128, m)`.
+    pub fn prove_multiple_u128_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        values: &[u128],
+        blindings: &[G::Scalar],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        use self::party::*;
 
         if values.len() != blindings.len() {
             return Err(ProofError::WrongNumBlindingFactors);
         }
 
-        let dealer = Dealer::new(bp_gens, pc_gens, transcript, n, values.len())?;
-
         let parties: Vec<_> = values
             .iter()
             .zip(blindings.iter())
             .map(|(&v, &v_blinding)| {
-                Party::new(
+                Party::new_extended(
                     bp_gens,
-                    pc_gens,
+                    &ExtendedPedersenGens::from(*pc_gens),
                     v,
-                    v_blinding,
+                    &[v_blinding],
                     n,
-                    pvt_rewind_key,
-                    pvt_blinding_key,
-                    proof_message,
                 )
             })
             // Collect the iterator of Results into a Result<Vec>, then unwrap it
             .collect::<Result<Vec<_>, _>>()?;
 
-        let (parties, bit_commitments): (Vec<_>, Vec<_>) = parties
-            .into_iter()
-            .enumerate()
-            .map(|(j, p)| {
-                p.assign_position_with_rng(j, rng)
-                    .expect("We already checked the parameters, so this should never happen")
-            })
-            .unzip();
-
-        let value_commitments: Vec<_> = bit_commitments.iter().map(|c| c.V_j).collect();
-
-        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments)?;
-
-        let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
-            .into_iter()
-            .map(|p| p.apply_challenge_with_rng(&bit_challenge, rng))
-            .unzip();
-
-        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments)?;
-
-        let proof_shares: Vec<_> = parties
-            .into_iter()
-            .map(|p| p.apply_challenge(&poly_challenge))
-            // Collect the iterator of Results into a Result<Vec>, then unwrap it
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let proof = dealer.receive_trusted_shares(&proof_shares)?;
-
-        Ok((proof, value_commitments))
-    }
-
-    /// Create a rangeproof for a set of values.
-    /// This is a convenience wrapper around [`RangeProof::prove_multiple_with_rng`],
-    /// passing in a threadsafe RNG.
-    #[cfg(feature = "std")]
-    pub fn prove_multiple(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
-        values: &[u64],
-        blindings: &[Scalar],
-        n: usize,
-    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
-        RangeProof::prove_multiple_with_rng(
+        RangeProof::prove_with_parties(
             bp_gens,
-            pc_gens,
+            &ExtendedPedersenGens::from(*pc_gens),
+            None,
             transcript,
-            values,
-            blindings,
+            parties,
             n,
-            &mut thread_rng(),
+            rng,
         )
     }
 
-    /// Uniquely identify-able scalar used as a pvt_rewind_key separator
-    fn get_rewind_key_separator() -> Scalar {
-        Scalar::from_bits(*RISTRETTO_BASEPOINT_COMPRESSED.as_bytes())
-    }
-
-    /// Verifies a rangeproof for a given value commitment \\(V\\).
+    /// Create a rangeproof for a set of 128-bit values in extended
+    /// Pedersen commitments, each of which has one blinding factor per
+    /// extension degree of the `pc_gens`.
     ///
-    /// This is a convenience wrapper around `verify_multiple` for the `m=1` case.
-    pub fn verify_single_with_rng<T: RngCore + CryptoRng>(
-        &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// use rand::thread_rng;
+    ///
+    /// extern crate curve25519_dalek;
+    /// use curve25519_dalek::scalar::Scalar;
+    ///
+    /// extern crate merlin;
+    /// use merlin::Transcript;
+    ///
+    /// extern crate tari_bulletproofs;
+    /// use tari_bulletproofs::{BulletproofGens, ExtendedPedersenGens, RangeProof};
+    ///
+    /// # fn main() {
+    /// // Generators for commitments with two blinding factors each.
+    /// let pc_gens = ExtendedPedersenGens::new(2).unwrap();
+    /// let bp_gens = BulletproofGens::new(64, 2);
+    ///
+    /// let secrets = [4242344947u128, 3718732727u128];
+    /// let blindings: Vec<Vec<Scalar>> = (0..2)
+    ///     .map(|_| (0..2).map(|_| Scalar::random(&mut thread_rng())).collect())
+    ///     .collect();
+    ///
+    /// let mut prover_transcript = Transcript::new(b"doctest example");
+    /// let (proof, commitments) = RangeProof::prove_multiple_extended(
+    ///     &bp_gens,
+    ///     &pc_gens,
+    ///     &mut prover_transcript,
+    ///     &secrets,
+    ///     &blindings,
+    ///     32,
+    /// ).expect("A real program could handle errors");
+    ///
+    /// let mut verifier_transcript = Transcript::new(b"doctest example");
+    /// assert!(
+    ///     proof
+    ///         .verify_multiple_extended(&bp_gens, &pc_gens, &mut verifier_transcript, &commitments, 32)
+    ///         .is_ok()
+    /// );
+    /// # }
+    /// ```
+    pub fn prove_multiple_extended_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
         transcript: &mut Transcript,
-        V: &CompressedRistretto,
+        values: &[u128],
+        blindings: &[Vec<G::Scalar>],
         n: usize,
         rng: &mut T,
-    ) -> Result<(), ProofError> {
-        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, &[*V], n, rng)
-    }
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        if values.len() != blindings.len() {
+            return Err(ProofError::WrongNumBlindingFactors);
+        }
 
-    /// Verifies a rangeproof for a given value commitment \\(V\\).
-    ///
-    /// This is a convenience wrapper around [`RangeProof::verify_single_with_rng`],
-    /// passing in a threadsafe RNG.
-    #[cfg(feature = "std")]
-    pub fn verify_single(
-        &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
-        V: &CompressedRistretto,
-        n: usize,
-    ) -> Result<(), ProofError> {
-        self.verify_single_with_rng(bp_gens, pc_gens, transcript, V, n, &mut thread_rng())
+        let parties: Vec<_> = values
+            .iter()
+            .zip(blindings.iter())
+            .map(|(&v, v_blinding)| party::Party::new_extended(bp_gens, pc_gens, v, v_blinding, n))
+            // Collect the iterator of Results into a Result<Vec>, then unwrap it
+            .collect::<Result<Vec<_>, _>>()?;
+
+        RangeProof::prove_with_parties(bp_gens, pc_gens, None, transcript, parties, n, rng)
     }
 
-    /// Verifies an aggregated rangeproof for the given value commitments.
-    pub fn verify_multiple_with_rng<T: RngCore + CryptoRng>(
-        &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+    /// Create a rangeproof for a set of 128-bit values, each of which
+    /// is committed to with its own value base instead of the `B` of
+    /// the `pc_gens`, such as the value base of an asset or a blinded
+    /// asset tag from [`ExtendedPedersenGens::blind_value_base`].  This
+    /// lets one proof cover the values of different assets.
+    ///
+    /// The value bases are bound to the transcript, and must be given
+    /// to [`RangeProof::verify_multiple_with_value_bases_with_rng`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_multiple_with_value_bases_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        value_bases: &[G::Point],
         transcript: &mut Transcript,
-        value_commitments: &[CompressedRistretto],
+        values: &[u128],
+        blindings: &[Vec<G::Scalar>],
         n: usize,
         rng: &mut T,
-    ) -> Result<(), ProofError> {
-        let m = value_commitments.len();
-
-        // First, replay the "interactive" protocol using the proof
-        // data to recompute all challenges.
-        if !(n == 8 || n == 16 || n == 32 || n == 64) {
-            return Err(ProofError::InvalidBitsize);
-        }
-        if bp_gens.gens_capacity < n {
-            return Err(ProofError::InvalidGeneratorsLength);
-        }
-        if bp_gens.party_capacity < m {
-            return Err(ProofError::InvalidGeneratorsLength);
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        if values.len() != blindings.len() {
+            return Err(ProofError::WrongNumBlindingFactors);
         }
-
-        transcript.rangeproof_domain_sep(n as u64, m as u64);
-
-        for V in value_commitments.iter() {
-            // Allow the commitments to be zero (0 value, 0 blinding)
-            // See https://github.com/dalek-cryptography/bulletproofs/pull/248#discussion_r255167177
-            transcript.append_point(b"V", V);
+        if values.len() != value_bases.len() {
+            return Err(ProofError::InvalidValueBases);
         }
 
-        transcript.validate_and_append_point(b"A", &self.A)?;
-        transcript.validate_and_append_point(b"S", &self.S)?;
-
-        let y = transcript.challenge_scalar(b"y");
-        let z = transcript.challenge_scalar(b"z");
-        let zz = z * z;
-        let minus_z = -z;
+        let parties: Vec<_> = values
+            .iter()
+            .zip(blindings.iter())
+            .zip(value_bases.iter())
+            .map(|((&v, v_blinding), value_base)| {
+                party::Party::new_with_value_base(bp_gens, pc_gens, *value_base, v, v_blinding, n)
+            })
+            // Collect the iterator of Results into a Result<Vec>, then unwrap it
+            .collect::<Result<Vec<_>, _>>()?;
 
-        transcript.validate_and_append_point(b"T_1", &self.T_1)?;
-        transcript.validate_and_append_point(b"T_2", &self.T_2)?;
+        RangeProof::prove_with_parties(
+            bp_gens,
+            pc_gens,
+            Some(value_bases),
+            transcript,
+            parties,
+            n,
+            rng,
+        )
+    }
 
-        let x = transcript.challenge_scalar(b"x");
+    /// Create a rangeproof for a given 128-bit value `v` in an extended
+    /// Pedersen commitment with the blinding factors `v_blinding`.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_multiple_extended_with_rng`].
+    pub fn prove_single_extended_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &[G::Scalar],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
+        let (p, Vs) = RangeProof::prove_multiple_extended_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            &[v],
+            &[v_blinding.to_vec()],
+            n,
+            rng,
+        )?;
+        Ok((p, Vs[0]))
+    }
 
-        transcript.append_scalar(b"t_x", &self.t_x);
-        transcript.append_scalar(b"t_x_blinding", &self.t_x_blinding);
-        transcript.append_scalar(b"e_blinding", &self.e_blinding);
+    /// Runs the aggregation protocol between a dealer and the given
+    /// `parties`, returning the proof and the value commitments.
+    fn prove_with_parties<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        value_bases: Option<&[G::Point]>,
+        transcript: &mut Transcript,
+        parties: Vec<party::PartyAwaitingPosition<G>>,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        // Each party draws its randomness from its own generator, seeded
+        // in order from `rng`, so that the proof is the same whether the
+        // parties run one after the other or in parallel.  The generators
+        // are bound to the transcript, and the parties further hedge them
+        // with their values and blinding factors, so that a broken `rng`
+        // does not leak the witness, and the proof is deterministic for a
+        // deterministic `rng`.
+        let parties: Vec<_> = parties
+            .into_iter()
+            .enumerate()
+            .map(|(j, p)| {
+                let party_rng = transcript
+                    .build_rng()
+                    .rekey_with_witness_bytes(b"party_index", &(j as u64).to_le_bytes())
+                    .finalize(rng);
+                (j, p, party_rng)
+            })
+            .collect();
 
-        let w = transcript.challenge_scalar(b"w");
+        let dealer = match value_bases {
+            Some(value_bases) => {
+                dealer::Dealer::new_with_value_bases(bp_gens, pc_gens, value_bases, transcript, n)?
+            }
+            None => dealer::Dealer::new_extended(bp_gens, pc_gens, transcript, n, parties.len())?,
+        };
 
-        // Challenge value for batching statements to be verified
-        let c = Scalar::random(rng);
+        let (parties, bit_commitments): (Vec<_>, Vec<_>) = maybe_par_iter!(parties)
+            .map(|(j, p, mut party_rng)| {
+                let (p, bit_commitment) = p
+                    .assign_position_with_rng(j, &mut party_rng)
+                    .expect("We already checked the parameters, so this should never happen");
+                ((p, party_rng), bit_commitment)
+            })
+            .unzip();
 
-        let (x_sq, x_inv_sq, s) = self.ipp_proof.verification_scalars(n * m, transcript)?;
-        let s_inv = s.iter().rev();
+        let value_commitments: Vec<_> = bit_commitments.iter().map(|c| c.V_j).collect();
 
-        let a = self.ipp_proof.a;
-        let b = self.ipp_proof.b;
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments)?;
 
-        // Construct concat_z_and_2, an iterator of the values of
-        // z^0 * \vec(2)^n || z^1 * \vec(2)^n || ... || z^(m-1) * \vec(2)^n
-        let powers_of_2: Vec<Scalar> = util::exp_iter(Scalar::from(2u64)).take(n).collect();
-        let concat_z_and_2: Vec<Scalar> = util::exp_iter(z)
-            .take(m)
-            .flat_map(|exp_z| powers_of_2.iter().map(move |exp_2| exp_2 * exp_z))
-            .collect();
+        let (parties, poly_commitments): (Vec<_>, Vec<_>) = maybe_par_iter!(parties)
+            .map(|(p, mut party_rng)| p.apply_challenge_with_rng(&bit_challenge, &mut party_rng))
+            .unzip();
 
-        let g = s.iter().map(|s_i| minus_z - a * s_i);
-        let h = s_inv
-            .zip(util::exp_iter(y.invert()))
-            .zip(concat_z_and_2.iter())
-            .map(|((s_i_inv, exp_y_inv), z_and_2)| z + exp_y_inv * (zz * z_and_2 - b * s_i_inv));
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments)?;
 
-        let value_commitment_scalars = util::exp_iter(z).take(m).map(|z_exp| c * zz * z_exp);
-        let basepoint_scalar = w * (self.t_x - a * b) + c * (delta(n, m, &y, &z) - self.t_x);
+        let proof_shares: Vec<_> = maybe_par_iter!(parties)
+            .map(|p| p.apply_challenge(&poly_challenge))
+            // Collect the iterator of Results into a Result<Vec>, then unwrap it
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let mega_check = RistrettoPoint::optional_multiscalar_mul(
-            iter::once(Scalar::one())
-                .chain(iter::once(x))
-                .chain(iter::once(c * x))
-                .chain(iter::once(c * x * x))
-                .chain(x_sq.iter().cloned())
-                .chain(x_inv_sq.iter().cloned())
-                .chain(iter::once(-self.e_blinding - c * self.t_x_blinding))
-                .chain(iter::once(basepoint_scalar))
-                .chain(g)
-                .chain(h)
-                .chain(value_commitment_scalars),
-            iter::once(self.A.decompress())
-                .chain(iter::once(self.S.decompress()))
-                .chain(iter::once(self.T_1.decompress()))
-                .chain(iter::once(self.T_2.decompress()))
-                .chain(self.ipp_proof.L_vec.iter().map(|L| L.decompress()))
-                .chain(self.ipp_proof.R_vec.iter().map(|R| R.decompress()))
-                .chain(iter::once(Some(pc_gens.B_blinding)))
-                .chain(iter::once(Some(pc_gens.B)))
-                .chain(bp_gens.G(n, m).map(|&x| Some(x)))
-                .chain(bp_gens.H(n, m).map(|&x| Some(x)))
-                .chain(value_commitments.iter().map(|V| V.decompress())),
-        )
-        .ok_or_else(|| ProofError::VerificationError)?;
+        let proof = dealer.receive_trusted_shares(&proof_shares)?;
 
-        if mega_check.is_identity() {
-            Ok(())
-        } else {
-            Err(ProofError::VerificationError)
-        }
+        Ok((proof, value_commitments))
     }
 
-    /// Verifies an aggregated rangeproof for the given value commitments.
-    /// This is a convenience wrapper around [`RangeProof::verify_multiple_with_rng`],
+    /// Create a rangeproof for a set of values.
+    /// This is a convenience wrapper around [`RangeProof::prove_multiple_with_rng`],
     /// passing in a threadsafe RNG.
     #[cfg(feature = "std")]
-    pub fn verify_multiple(
-        &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+    pub fn prove_multiple(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
         transcript: &mut Transcript,
-        value_commitments: &[CompressedRistretto],
+        values: &[u64],
+        blindings: &[G::Scalar],
         n: usize,
-    ) -> Result<(), ProofError> {
-        self.verify_multiple_with_rng(
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        RangeProof::prove_multiple_with_rng(
             bp_gens,
             pc_gens,
             transcript,
-            value_commitments,
+            values,
+            blindings,
             n,
             &mut thread_rng(),
         )
     }
 
-    /// Serializes the proof into a byte array of \\(2 \lg n + 9\\)
-    /// 32-byte elements, where \\(n\\) is the number of secret bits.
-    ///
-    /// # Layout
-    ///
-    /// The layout of the range proof encoding is:
-    ///
-    /// * four compressed Ristretto points \\(A,S,T_1,T_2\\),
-    /// * three scalars \\(t_x, \tilde{t}_x, \tilde{e}\\),
-    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{n-1},R_{n-1}\\),
-    /// * two scalars \\(a, b\\).
-    pub fn to_bytes(&self) -> Vec<u8> {
-        // 7 elements: points A, S, T1, T2, scalars tx, tx_bl, e_bl.
-        let mut buf = Vec::with_capacity(7 * 32 + self.ipp_proof.serialized_size());
-        buf.extend_from_slice(self.A.as_bytes());
-        buf.extend_from_slice(self.S.as_bytes());
-        buf.extend_from_slice(self.T_1.as_bytes());
-        buf.extend_from_slice(self.T_2.as_bytes());
-        buf.extend_from_slice(self.t_x.as_bytes());
-        buf.extend_from_slice(self.t_x_blinding.as_bytes());
-        buf.extend_from_slice(self.e_blinding.as_bytes());
-        buf.extend(self.ipp_proof.to_bytes_iter());
-        buf
-    }
-
-    /// Deserializes the proof from a byte slice.
-    ///
-    /// Returns an error if the byte slice cannot be parsed into a `RangeProof`.
-    pub fn from_bytes(slice: &[u8]) -> Result<RangeProof, ProofError> {
-        if slice.len() % 32 != 0 {
-            return Err(ProofError::FormatError);
-        }
-        if slice.len() < 7 * 32 {
-            return Err(ProofError::FormatError);
-        }
-
-        use crate::util::read32;
-
-        #[allow(clippy::erasing_op)]
-        let A = CompressedRistretto(read32(&slice[0 * 32..]));
-        let S = CompressedRistretto(read32(&slice[1 * 32..]));
-        let T_1 = CompressedRistretto(read32(&slice[2 * 32..]));
-        let T_2 = CompressedRistretto(read32(&slice[3 * 32..]));
-
-        let t_x = Scalar::from_canonical_bytes(read32(&slice[4 * 32..]))
-            .ok_or(ProofError::FormatError)?;
-        let t_x_blinding = Scalar::from_canonical_bytes(read32(&slice[5 * 32..]))
-            .ok_or(ProofError::FormatError)?;
-        let e_blinding = Scalar::from_canonical_bytes(read32(&slice[6 * 32..]))
-            .ok_or(ProofError::FormatError)?;
-
-        let ipp_proof = InnerProductProof::from_bytes(&slice[7 * 32..])?;
-
-        Ok(RangeProof {
-            A,
-            S,
-            T_1,
-            T_2,
-            t_x,
-            t_x_blinding,
-            e_blinding,
-            ipp_proof,
-        })
-    }
-
-    /// Rewinds a rangeproof for a given value commitment \\(V\\),
-    /// returning the value, blinding factor and 23 bytes extra data
-    /// upon success.
-    pub fn rewind_single_get_commitment_data(
-        &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+    /// Create a rangeproof for a set of 128-bit values, for bitsizes
+    /// `n` up to 128.
+    /// This is a convenience wrapper around [`RangeProof::prove_multiple_u128_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_multiple_u128(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
         transcript: &mut Transcript,
-        value_commitment: &CompressedRistretto,
+        values: &[u128],
+        blindings: &[G::Scalar],
         n: usize,
-        rewind_nonce_1: &Scalar,
-        rewind_nonce_2: &Scalar,
-        blinding_nonce_1: &Scalar,
-        blinding_nonce_2: &Scalar,
-    ) -> Result<(u64, Scalar, [u8; 23]), ProofError> {
-        let result = self.rewind_single_get_commitment_value(
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        RangeProof::prove_multiple_u128_with_rng(
             bp_gens,
+            pc_gens,
             transcript,
-            value_commitment,
+            values,
+            blindings,
             n,
-            &rewind_nonce_1,
-            &rewind_nonce_2,
-        )?;
-        let value = result.0;
-        let proof_message = result.1;
-        let x = result.2;
-        let z = result.3;
-
-        // Extract the blinding factor:
-        //   t_x_blinding = z^2 * v_blinding + x * t_1_blinding + x^2 * t_2_blinding
-        //   v_blinding = (1 / z^2) * (t_x_blinding - x * t_1_blinding - x^2 * t_2_blinding)
-        //   t_1_blinding: replaced by blinding_nonce_1
-        //   t_2_blinding: replaced by blinding_nonce_2
-        let v_blinding = z.invert()
-            * z.invert()
-            * (self.t_x_blinding - x * blinding_nonce_1 - x * x * blinding_nonce_2);
-
-        //Verify if the correct value and blinding factor was extracted
-        let value_commitment_calculated = pc_gens.commit(value.into(), v_blinding).compress();
-        if value_commitment.as_bytes() != value_commitment_calculated.as_bytes() {
-            return Err(ProofError::InvalidCommitmentExtracted);
-        } else {
-            Ok((value, v_blinding, proof_message))
-        }
+            &mut thread_rng(),
+        )
     }
 
-    /// Rewinds a rangeproof for a given value commitment \\(V\\)
-    /// to get the value and 23 bytes extra data only. If the wrong
-    /// rewind_nonce is provided, garbage data will be returned.
+    /// Create a rangeproof for a set of 128-bit values in extended
+    /// Pedersen commitments.
+    /// This is a convenience wrapper around [`RangeProof::prove_multiple_extended_with_rng`],
+    /// passing in a threadsafe RNG.
     #[cfg(feature = "std")]
-    pub fn rewind_single_get_value_only(
+    pub fn prove_multiple_extended(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        transcript: &mut Transcript,
+        values: &[u128],
+        blindings: &[Vec<G::Scalar>],
+        n: usize,
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        RangeProof::prove_multiple_extended_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            values,
+            blindings,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create a rangeproof for a set of 128-bit values committed to
+    /// with their own value bases.
+    /// This is a convenience wrapper around [`RangeProof::prove_multiple_with_value_bases_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_multiple_with_value_bases(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        value_bases: &[G::Point],
+        transcript: &mut Transcript,
+        values: &[u128],
+        blindings: &[Vec<G::Scalar>],
+        n: usize,
+    ) -> Result<(RangeProof<G>, Vec<G::CompressedPoint>), ProofError> {
+        RangeProof::prove_multiple_with_value_bases_with_rng(
+            bp_gens,
+            pc_gens,
+            value_bases,
+            transcript,
+            values,
+            blindings,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create a rangeproof for a given 128-bit value `v` in an extended
+    /// Pedersen commitment with the blinding factors `v_blinding`.
+    /// This is a convenience wrapper around [`RangeProof::prove_single_extended_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_single_extended(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &[G::Scalar],
+        n: usize,
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
+        RangeProof::prove_single_extended_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Verifies a rangeproof for a given value commitment \\(V\\).
+    ///
+    /// This is a convenience wrapper around `verify_multiple` for the `m=1` case.
+    pub fn verify_single_with_rng<T: RngCore + CryptoRng>(
         &self,
-        bp_gens: &BulletproofGens,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
         transcript: &mut Transcript,
-        V: &CompressedRistretto,
+        V: &G::CompressedPoint,
         n: usize,
-        rewind_nonce_1: &Scalar,
-        rewind_nonce_2: &Scalar,
-    ) -> Result<(u64, [u8; 23]), ProofError> {
-        let result = self.rewind_single_get_commitment_value(
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, &[*V], n, rng)
+    }
+
+    /// Verifies a rangeproof for a given value commitment \\(V\\).
+    ///
+    /// This is a convenience wrapper around [`RangeProof::verify_single_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_single(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        V: &G::CompressedPoint,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_single_with_rng(bp_gens, pc_gens, transcript, V, n, &mut thread_rng())
+    }
+
+    /// Verifies an aggregated rangeproof for the given value commitments.
+    pub fn verify_multiple_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_extended_with_rng(
             bp_gens,
+            &ExtendedPedersenGens::from(*pc_gens),
             transcript,
-            V,
+            value_commitments,
             n,
-            &rewind_nonce_1,
-            &rewind_nonce_2,
-        );
-        let result: Result<(u64, [u8; 23]), ProofError> =
-            Ok((result.clone().unwrap().0, result.clone().unwrap().1));
-        result
+            rng,
+        )
     }
 
-    /// Rewinds a rangeproof for a given value commitment \\(V\\)
-    /// to retrieve the value, challenge scalars x and y and 23
-    /// bytes extra data.
-    fn rewind_single_get_commitment_value(
+    /// Verifies a rangeproof for a given extended Pedersen commitment \\(V\\).
+    ///
+    /// This is a convenience wrapper around `verify_multiple_extended` for the `m=1` case.
+    pub fn verify_single_extended_with_rng<T: RngCore + CryptoRng>(
         &self,
-        bp_gens: &BulletproofGens,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
         transcript: &mut Transcript,
-        value_commitment: &CompressedRistretto,
+        V: &G::CompressedPoint,
         n: usize,
-        rewind_nonce_1: &Scalar,
-        rewind_nonce_2: &Scalar,
-    ) -> Result<(u64, [u8; 23], Scalar, Scalar), ProofError> {
-        // First, replay the "interactive" protocol using the proof
-        // data to recompute all challenges.
-        if !(n == 8 || n == 16 || n == 32 || n == 64) {
-            return Err(ProofError::InvalidBitsize);
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_extended_with_rng(bp_gens, pc_gens, transcript, &[*V], n, rng)
+    }
+
+    /// Verifies a rangeproof for a given extended Pedersen commitment \\(V\\).
+    ///
+    /// This is a convenience wrapper around [`RangeProof::verify_single_extended_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_single_extended(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        transcript: &mut Transcript,
+        V: &G::CompressedPoint,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_single_extended_with_rng(bp_gens, pc_gens, transcript, V, n, &mut thread_rng())
+    }
+
+    /// Verifies an aggregated rangeproof for the given extended
+    /// Pedersen commitments.  The extension degree of the proof must
+    /// match that of the `pc_gens`.
+    pub fn verify_multiple_extended_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let check = self.verification_check(
+            bp_gens,
+            pc_gens.extension_degree(),
+            None,
+            transcript,
+            value_commitments,
+            n,
+            rng,
+        )?;
+
+        if check.verify(bp_gens, pc_gens) {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Verifies an aggregated rangeproof for the given value
+    /// commitments, each of which is made with the value base at the
+    /// same position in `value_bases` instead of the `B` of the
+    /// `pc_gens`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_multiple_with_value_bases_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        value_bases: &[G::Point],
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let check = self.verification_check(
+            bp_gens,
+            pc_gens.extension_degree(),
+            Some(value_bases),
+            transcript,
+            value_commitments,
+            n,
+            rng,
+        )?;
+
+        if check.verify(bp_gens, pc_gens) {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Verifies an aggregated rangeproof for the given value
+    /// commitments made with their own value bases.
+    /// This is a convenience wrapper around [`RangeProof::verify_multiple_with_value_bases_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_multiple_with_value_bases(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        value_bases: &[G::Point],
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_with_value_bases_with_rng(
+            bp_gens,
+            pc_gens,
+            value_bases,
+            transcript,
+            value_commitments,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Replays the proof transcript and constructs the verification
+    /// equation for this proof, without evaluating it.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn verification_check<T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        extension_degree: usize,
+        value_bases: Option<&[G::Point]>,
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<DeferredCheck<G>, ProofError> {
+        let m = value_commitments.len();
+        let padded_n = padded_bitsize(n).ok_or(ProofError::InvalidBitsize)?;
+        if m == 0 {
+            return Err(ProofError::InvalidAggregation);
         }
-        if bp_gens.gens_capacity < n {
+        if bp_gens.gens_capacity < padded_n {
             return Err(ProofError::InvalidGeneratorsLength);
         }
-        if bp_gens.party_capacity < 1 {
+        if bp_gens.party_capacity < m.next_power_of_two() {
             return Err(ProofError::InvalidGeneratorsLength);
         }
 
-        transcript.rangeproof_domain_sep(n as u64, 1u64);
-        transcript.append_point(b"V", value_commitment);
-        transcript.validate_and_append_point(b"A", &self.A)?;
-        transcript.validate_and_append_point(b"S", &self.S)?;
-        transcript.challenge_scalar(b"y");
-        let z = transcript.challenge_scalar(b"z");
-        transcript.validate_and_append_point(b"T_1", &self.T_1)?;
-        transcript.validate_and_append_point(b"T_2", &self.T_2)?;
-        let x = transcript.challenge_scalar(b"x");
+        let powers_of_2: Vec<G::Scalar> = padded_powers_of_2(n).collect();
+        self.verification_check_with_powers_of_2(
+            &powers_of_2,
+            extension_degree,
+            value_bases,
+            transcript,
+            value_commitments,
+            n,
+            rng,
+        )
+    }
 
-        // Extract s_blinding:
-        //   e_blinding = a_blinding + x * s_blinding
-        //   s_blinding = (e_blinding - a_blinding) * (1/x)
-        //   a_blinding: replaced by rewind_nonce_1
-        let s_blinding = (self.e_blinding - rewind_nonce_1) * x.invert();
-        // Extract the value and extra data
-        let xor_s_blinding = xor_32_bytes(&rewind_nonce_2.as_bytes(), &s_blinding.as_bytes());
-        let value = bytes_to_usize(&xor_s_blinding, 1, 8) as u64;
-        let mut proof_message = [0u8; 23];
-        for (place, element) in proof_message.iter_mut().zip(xor_s_blinding.iter().skip(8)) {
-            *place = *element;
+    /// Constructs the verification equation like
+    /// [`RangeProof::verification_check`], with the powers of two
+    /// padded to the bitsize given by the caller, who must also have
+    /// checked the bitsize and aggregation size against the generators.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn verification_check_with_powers_of_2<T: RngCore + CryptoRng>(
+        &self,
+        powers_of_2: &[G::Scalar],
+        extension_degree: usize,
+        value_bases: Option<&[G::Point]>,
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<DeferredCheck<G>, ProofError> {
+        let m = value_commitments.len();
+        // Aggregations of any size are padded to a power of two with
+        // dummy parties committing to zero with a zero blinding factor.
+        let padded_m = m.next_power_of_two();
+        let padded_n = powers_of_2.len();
+
+        // First, replay the "interactive" protocol using the proof
+        // data to recompute all challenges.
+        if self.extension_degree() != extension_degree {
+            return Err(ProofError::InvalidExtensionDegree);
+        }
+        let value_bases = value_bases.unwrap_or(&[]);
+        if value_bases.len() != self.t_x_shares.len()
+            || !(value_bases.is_empty() || value_bases.len() == m)
+        {
+            return Err(ProofError::InvalidValueBases);
+        }
+
+        transcript.rangeproof_domain_sep(n as u64, m as u64);
+
+        for V in value_commitments.iter() {
+            // Allow the commitments to be zero (0 value, 0 blinding)
+            // See https://github.com/dalek-cryptography/bulletproofs/pull/248#discussion_r255167177
+            transcript.append_group_point::<G>(b"V", V);
+        }
+        for _ in m..padded_m {
+            transcript.append_group_point::<G>(b"V", &G::compress(&G::identity()));
+        }
+        for value_base in value_bases.iter() {
+            transcript.append_group_point::<G>(b"value_base", &G::compress(value_base));
+        }
+
+        transcript.validate_and_append_group_point::<G>(b"A", &self.A)?;
+        transcript.validate_and_append_group_point::<G>(b"S", &self.S)?;
+
+        let y = transcript.challenge_group_scalar::<G>(b"y");
+        let z = transcript.challenge_group_scalar::<G>(b"z");
+        let zz = z * z;
+        let minus_z = -z;
+
+        transcript.validate_and_append_group_point::<G>(b"T_1", &self.T_1)?;
+        transcript.validate_and_append_group_point::<G>(b"T_2", &self.T_2)?;
+
+        let x = transcript.challenge_group_scalar::<G>(b"x");
+
+        self.append_proof_scalars(transcript);
+
+        let w = transcript.challenge_group_scalar::<G>(b"w");
+
+        // Challenge value for batching statements to be verified
+        let c = G::Scalar::random(rng);
+
+        let (x_sq, x_inv_sq, s) = self
+            .ipp_proof
+            .verification_scalars(padded_n * padded_m, transcript)?;
+        let s_inv = s.iter().rev();
+
+        let a = self.ipp_proof.a;
+        let b = self.ipp_proof.b;
+
+        // Construct concat_z_and_2, an iterator of the values of
+        // z^0 * \vec(2)^n || z^1 * \vec(2)^n || ... || z^(padded_m-1) * \vec(2)^n,
+        // where each \vec(2)^n is padded with zeros to the padded bitsize
+        let concat_z_and_2: Vec<G::Scalar> = util::exp_iter(z)
+            .take(padded_m)
+            .flat_map(|exp_z| powers_of_2.iter().map(move |exp_2| *exp_2 * exp_z))
+            .collect();
+
+        let g: Vec<G::Scalar> = s.iter().map(|s_i| minus_z - a * *s_i).collect();
+        let h: Vec<G::Scalar> = s_inv
+            .zip(util::exp_iter(y.invert()))
+            .zip(concat_z_and_2.iter())
+            .map(|((s_i_inv, exp_y_inv), z_and_2)| z + exp_y_inv * (zz * *z_and_2 - b * *s_i_inv))
+            .collect();
+
+        let value_commitment_scalars = util::exp_iter(z).take(m).map(|z_exp| c * zz * z_exp);
+        let mut basepoint_scalar =
+            w * (self.t_x - a * b) + c * (delta(n, padded_m, &y, &z) - self.t_x);
+
+        // With distinct value bases, the check of t(x) is split by
+        // party: the terms of each real party move from B to its own
+        // value base, while the dummy parties stay on B.
+        let value_base_scalars: Vec<G::Scalar> = self
+            .t_x_shares
+            .iter()
+            .enumerate()
+            .map(|(j, t_x_share)| c * (party_delta(n, j, &y, &z) - *t_x_share))
+            .collect();
+        for value_base_scalar in value_base_scalars.iter() {
+            basepoint_scalar -= *value_base_scalar;
+        }
+
+        let dynamic_points = iter::once(&self.A)
+            .chain(iter::once(&self.S))
+            .chain(iter::once(&self.T_1))
+            .chain(iter::once(&self.T_2))
+            .chain(self.ipp_proof.L_vec.iter())
+            .chain(self.ipp_proof.R_vec.iter())
+            .chain(value_commitments.iter())
+            .map(|P| G::decompress(P).ok_or(ProofError::VerificationError))
+            .chain(value_bases.iter().map(|P| Ok(*P)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DeferredCheck {
+            dynamic_scalars: iter::once(G::Scalar::one())
+                .chain(iter::once(x))
+                .chain(iter::once(c * x))
+                .chain(iter::once(c * x * x))
+                .chain(x_sq.iter().cloned())
+                .chain(x_inv_sq.iter().cloned())
+                .chain(value_commitment_scalars)
+                .chain(value_base_scalars)
+                .collect(),
+            dynamic_points,
+            B_scalar: basepoint_scalar,
+            B_blinding_scalars: self
+                .e_blinding
+                .iter()
+                .zip(self.t_x_blinding.iter())
+                .map(|(e_blinding, t_x_blinding)| -*e_blinding - c * *t_x_blinding)
+                .collect(),
+            G_scalars: g.chunks(padded_n).map(|g_j| g_j.to_vec()).collect(),
+            H_scalars: h.chunks(padded_n).map(|h_j| h_j.to_vec()).collect(),
+        })
+    }
+
+    /// Verifies a batch of independent rangeproofs at once.
+    ///
+    /// The verification equation of each proof is weighted by a
+    /// random scalar, and the terms for the generators shared between
+    /// the proofs are merged, so that the whole batch is checked with
+    /// a single multiscalar multiplication.  The proofs in the batch
+    /// may use different bitsizes and aggregation sizes.
+    ///
+    /// With the `parallel` feature, the transcripts of the statements
+    /// are replayed in parallel, and the multiscalar multiplication is
+    /// split across the threads of the rayon thread pool.
+    ///
+    /// If the batch fails to verify,
+    /// [`RangeProof::find_invalid_in_batch_with_rng`] identifies the
+    /// invalid statements by bisecting the batch.
+    pub fn verify_batch_with_rng<'a, I, T>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        statements: I,
+        rng: &mut T,
+    ) -> Result<(), ProofError>
+    where
+        I: IntoIterator<Item = RangeProofStatement<'a, G>>,
+        T: RngCore + CryptoRng,
+    {
+        let checks = RangeProof::batch_checks(bp_gens, statements, rng)
+            .into_iter()
+            .map(|check| Ok((G::Scalar::random(rng), check?)))
+            .collect::<Result<Vec<_>, ProofError>>()?;
+
+        if batch::verify_batch(
+            checks.iter().map(|(weight, check)| (*weight, check)),
+            bp_gens,
+            &ExtendedPedersenGens::from(*pc_gens),
+        ) {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
         }
+    }
 
-        Ok((value, proof_message, x, z))
+    /// Verifies a batch of independent rangeproofs at once.
+    ///
+    /// This is a convenience wrapper around [`RangeProof::verify_batch_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_batch<'a, I>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        statements: I,
+    ) -> Result<(), ProofError>
+    where
+        I: IntoIterator<Item = RangeProofStatement<'a, G>>,
+    {
+        RangeProof::verify_batch_with_rng(bp_gens, pc_gens, statements, &mut thread_rng())
+    }
+
+    /// Replays the transcripts of a batch of statements to construct
+    /// their verification equations, in parallel with the `parallel`
+    /// feature.
+    ///
+    /// Each statement draws its randomness from its own generator,
+    /// seeded in order from `rng`, so that the equations are the same
+    /// whether the statements are processed in parallel or not.
+    fn batch_checks<'a, I, T>(
+        bp_gens: &BulletproofGens<G>,
+        statements: I,
+        rng: &mut T,
+    ) -> Vec<Result<DeferredCheck<G>, ProofError>>
+    where
+        I: IntoIterator<Item = RangeProofStatement<'a, G>>,
+        T: RngCore + CryptoRng,
+    {
+        let statements: Vec<_> = statements
+            .into_iter()
+            .map(|statement| {
+                let statement_rng = statement.transcript.build_rng().finalize(rng);
+                (statement, statement_rng)
+            })
+            .collect();
+
+        maybe_par_iter!(statements)
+            .map(|(statement, mut statement_rng)| {
+                statement.proof.verification_check(
+                    bp_gens,
+                    1,
+                    None,
+                    statement.transcript,
+                    statement.value_commitments,
+                    statement.n,
+                    &mut statement_rng,
+                )
+            })
+            .collect()
+    }
+
+    /// Verifies a batch of independent rangeproofs and returns the
+    /// indices of the invalid statements, in increasing order.
+    ///
+    /// Malformed statements are reported without entering the batch.
+    /// The well-formed ones are verified at once like
+    /// [`RangeProof::verify_batch_with_rng`], and if that fails the
+    /// batch is bisected to find the invalid statements, which costs
+    /// logarithmically many further batch verifications when only one
+    /// of them is invalid.
+    pub fn find_invalid_in_batch_with_rng<'a, I, T>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        statements: I,
+        rng: &mut T,
+    ) -> Vec<usize>
+    where
+        I: IntoIterator<Item = RangeProofStatement<'a, G>>,
+        T: RngCore + CryptoRng,
+    {
+        let mut invalid = Vec::new();
+        let mut indices = Vec::new();
+        let mut checks = Vec::new();
+        for (i, check) in RangeProof::batch_checks(bp_gens, statements, rng)
+            .into_iter()
+            .enumerate()
+        {
+            match check {
+                Ok(check) => {
+                    indices.push(i);
+                    checks.push(check);
+                }
+                Err(_) => invalid.push(i),
+            }
+        }
+
+        let pc_gens = ExtendedPedersenGens::from(*pc_gens);
+        invalid.extend(
+            batch::find_invalid(&checks, bp_gens, &pc_gens, rng)
+                .into_iter()
+                .map(|k| indices[k]),
+        );
+        invalid.sort_unstable();
+        invalid
+    }
+
+    /// Verifies a batch of independent rangeproofs and returns the
+    /// indices of the invalid statements.
+    ///
+    /// This is a convenience wrapper around [`RangeProof::find_invalid_in_batch_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn find_invalid_in_batch<'a, I>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        statements: I,
+    ) -> Vec<usize>
+    where
+        I: IntoIterator<Item = RangeProofStatement<'a, G>>,
+    {
+        RangeProof::find_invalid_in_batch_with_rng(bp_gens, pc_gens, statements, &mut thread_rng())
+    }
+
+    /// Verifies an aggregated rangeproof for the given value commitments.
+    /// This is a convenience wrapper around [`RangeProof::verify_multiple_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_multiple(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Verifies an aggregated rangeproof for the given extended Pedersen commitments.
+    /// This is a convenience wrapper around [`RangeProof::verify_multiple_extended_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_multiple_extended(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        transcript: &mut Transcript,
+        value_commitments: &[G::CompressedPoint],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_extended_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Appends the scalar \\(t\_x\\), the blinding scalars
+    /// \\(\tilde{t}\_x, \tilde{e}\\) of every extension degree and the
+    /// shares of \\(t\_x\\) to the `transcript`.
+    fn append_proof_scalars(&self, transcript: &mut Transcript) {
+        transcript.append_group_scalar::<G>(b"t_x", &self.t_x);
+        for t_x_blinding in self.t_x_blinding.iter() {
+            transcript.append_group_scalar::<G>(b"t_x_blinding", t_x_blinding);
+        }
+        for e_blinding in self.e_blinding.iter() {
+            transcript.append_group_scalar::<G>(b"e_blinding", e_blinding);
+        }
+        for t_x_share in self.t_x_shares.iter() {
+            transcript.append_group_scalar::<G>(b"t_x_share", t_x_share);
+        }
+    }
+
+    /// Create a rangeproof that the value `v` committed to with
+    /// blinding scalar `v_blinding` lies in the interval `[min, max]`.
+    ///
+    /// The proof is an aggregated `n`-bit rangeproof for the shifted
+    /// commitments \\(V - \text{min} \cdot B\\) and \\(\text{max} \cdot B - V\\),
+    /// where `n` is the smallest bitsize that fits `max - min`.
+    /// Returns the proof and the commitment \\(V\\) to `v`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_interval_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &G::Scalar,
+        min: u64,
+        max: u64,
+        rng: &mut T,
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
+        let n = interval_bitsize(min, max)?;
+        if v < min || v > max {
+            return Err(ProofError::ValueOutOfRange);
+        }
+
+        transcript.interval_domain_sep(min, max);
+
+        let (proof, _) = RangeProof::prove_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            &[v - min, max - v],
+            &[*v_blinding, -*v_blinding],
+            n,
+            rng,
+        )?;
+        Ok((
+            proof,
+            G::compress(&pc_gens.commit(G::Scalar::from(v), *v_blinding)),
+        ))
+    }
+
+    /// Create a rangeproof that the value `v` committed to with
+    /// blinding scalar `v_blinding` lies in the interval `[min, max]`.
+    /// This is a convenience wrapper around [`RangeProof::prove_interval_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_interval(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &G::Scalar,
+        min: u64,
+        max: u64,
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
+        RangeProof::prove_interval_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            min,
+            max,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Verifies a rangeproof that the value committed to in \\(V\\)
+    /// lies in the interval `[min, max]`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_interval_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        V: &G::CompressedPoint,
+        min: u64,
+        max: u64,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let n = interval_bitsize(min, max)?;
+        let V = G::decompress(V).ok_or(ProofError::VerificationError)?;
+
+        transcript.interval_domain_sep(min, max);
+
+        let value_commitments = [
+            G::compress(&(V - pc_gens.B * G::Scalar::from(min))),
+            G::compress(&(pc_gens.B * G::Scalar::from(max) - V)),
+        ];
+        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, &value_commitments, n, rng)
+    }
+
+    /// Verifies a rangeproof that the value committed to in \\(V\\)
+    /// lies in the interval `[min, max]`.
+    /// This is a convenience wrapper around [`RangeProof::verify_interval_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_interval(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        V: &G::CompressedPoint,
+        min: u64,
+        max: u64,
+    ) -> Result<(), ProofError> {
+        self.verify_interval_with_rng(bp_gens, pc_gens, transcript, V, min, max, &mut thread_rng())
+    }
+
+    /// Create an `n`-bit rangeproof that the value `v` committed to
+    /// with blinding scalar `v_blinding` is at least
+    /// `minimum_value_promise`, i.e. that
+    /// \\(v - \text{minimum\\_value\\_promise} \in [0, 2^n)\\).
+    ///
+    /// Returns the proof and the commitment \\(V\\) to `v`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_minimum_value_promise_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &G::Scalar,
+        minimum_value_promise: u64,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
+        if v < minimum_value_promise || (n < 64 && v - minimum_value_promise >= 1 << n) {
+            return Err(ProofError::ValueOutOfRange);
+        }
+
+        transcript.minimum_value_domain_sep(minimum_value_promise, n as u64);
+
+        let (proof, _) = RangeProof::prove_single_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v - minimum_value_promise,
+            v_blinding,
+            n,
+            rng,
+        )?;
+        Ok((
+            proof,
+            G::compress(&pc_gens.commit(G::Scalar::from(v), *v_blinding)),
+        ))
+    }
+
+    /// Create an `n`-bit rangeproof that the value `v` committed to
+    /// with blinding scalar `v_blinding` is at least `minimum_value_promise`.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_with_minimum_value_promise_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_with_minimum_value_promise(
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &G::Scalar,
+        minimum_value_promise: u64,
+        n: usize,
+    ) -> Result<(RangeProof<G>, G::CompressedPoint), ProofError> {
+        RangeProof::prove_with_minimum_value_promise_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            minimum_value_promise,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Verifies an `n`-bit rangeproof that the value committed to in
+    /// \\(V\\) is at least `minimum_value_promise`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_with_minimum_value_promise_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        V: &G::CompressedPoint,
+        minimum_value_promise: u64,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let V = G::decompress(V).ok_or(ProofError::VerificationError)?;
+
+        transcript.minimum_value_domain_sep(minimum_value_promise, n as u64);
+
+        let shifted_V = G::compress(&(V - pc_gens.B * G::Scalar::from(minimum_value_promise)));
+        self.verify_single_with_rng(bp_gens, pc_gens, transcript, &shifted_V, n, rng)
+    }
+
+    /// Verifies an `n`-bit rangeproof that the value committed to in
+    /// \\(V\\) is at least `minimum_value_promise`.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::verify_with_minimum_value_promise_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_with_minimum_value_promise(
+        &self,
+        bp_gens: &BulletproofGens<G>,
+        pc_gens: &PedersenGens<G>,
+        transcript: &mut Transcript,
+        V: &G::CompressedPoint,
+        minimum_value_promise: u64,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_with_minimum_value_promise_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            V,
+            minimum_value_promise,
+            n,
+            &mut thread_rng(),
+        )
+    }
+}
+
+impl RangeProof {
+    /// Create a rangeproof for a given pair of value `v` and
+    /// blinding scalar `v_blinding`, passing in a rewind key to
+    /// enable rangeproof rewinding with 23 bytes worth of extra
+    /// data that can be embedded.  The bitsize `n` must be at most
+    /// 64; use [`RangeProof::prove_single_u128_with_rng_and_rewind_key`]
+    /// for wider ranges.
+    /// This is a convenience wrapper around [`RangeProof::prove_multiple`].
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// use rand::thread_rng;
+    ///
+    /// extern crate curve25519_dalek;
+    /// use curve25519_dalek::scalar::Scalar;
+    ///
+    /// extern crate merlin;
+    /// use merlin::Transcript;
+    ///
+    /// extern crate tari_bulletproofs;
+    /// use tari_bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+    ///
+    /// # fn main() {
+    /// // Generators for Pedersen commitments.  These can be selected
+    /// // independently of the Bulletproofs generators.
+    /// use curve25519_dalek::ristretto::RistrettoPoint;
+    /// use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+    /// use tari_bulletproofs::range_proof::{get_rewind_nonce_from_pub_key, get_secret_nonce_from_pvt_key};
+    /// let pc_gens = PedersenGens::default();
+    ///
+    /// // Generators for Bulletproofs, valid for proofs up to bitsize 64
+    /// // and aggregation size up to 1.
+    /// let bp_gens = BulletproofGens::new(64, 1);
+    ///
+    /// // A secret value we want to prove lies in the range [0, 2^32)
+    /// let confidential_value = 1037578891u64;
+    ///
+    /// // The API takes a blinding factor for the commitment.
+    /// let blinding_factor = Scalar::random(&mut thread_rng());
+    ///
+    /// // The private keys for range proof rewinding; these may be based on a wallet's private root key
+    /// let pvt_rewind_key = Scalar::random(&mut thread_rng());
+    /// let pvt_blinding_key = Scalar::random(&mut thread_rng());
+    ///
+    /// // Up to 23 bytes extra data may be embedded in the range proof meta data
+    /// let proof_message: [u8; 23] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17,
+    ///         18, 19, 20, 21, 22, 23];
+    ///
+    /// // The proof can be chained to an existing transcript.
+    /// // Here we create a transcript with a doctest domain separator.
+    /// let mut prover_transcript = Transcript::new(b"doctest example");
+    ///
+    /// // Create a 32-bit rangeproof.
+    /// let (proof, committed_value) = RangeProof::prove_single_with_rewind_key(
+    ///     &bp_gens,
+    ///     &pc_gens,
+    ///     &mut prover_transcript,
+    ///     confidential_value,
+    ///     &blinding_factor,
+    ///     32,
+    ///     &pvt_rewind_key,
+    ///     &pvt_blinding_key,
+    ///     &proof_message,
+    /// ).expect("A real program could handle errors");
+    ///
+    /// // Verification requires a transcript with identical initial state:
+    /// let mut verifier_transcript = Transcript::new(b"doctest example");
+    /// assert!(
+    ///     proof
+    ///         .verify_single(&bp_gens, &pc_gens, &mut verifier_transcript, &committed_value, 32)
+    ///         .is_ok()
+    /// );
+    ///
+    /// // A third party may have access to the public keys and extra data for range proof rewinding
+    /// let pub_rewind_key_1 = RistrettoPoint::from(&pvt_rewind_key * &RISTRETTO_BASEPOINT_TABLE).compress();
+    /// let pub_rewind_key_2 = RistrettoPoint::from(&pvt_blinding_key * &RISTRETTO_BASEPOINT_TABLE).compress();
+    ///
+    /// // The rewind nonce is necessary to rewind the range proof, which is uniquely bound to the commitment
+    /// let rewind_nonce_1 = get_rewind_nonce_from_pub_key(&pub_rewind_key_1, &committed_value);
+    /// let rewind_nonce_2 = get_rewind_nonce_from_pub_key(&pub_rewind_key_2, &committed_value);
+    ///
+    /// // A owner or third party can extract the value and extra data; if it is the wrong combination
+    /// // garbage data will be extracted
+    /// let mut rewind_transcript = Transcript::new(b"doctest example");
+    /// assert_eq!(
+    ///     proof.rewind_single_get_value_only(
+    ///         &bp_gens,
+    ///         &mut rewind_transcript,
+    ///         &committed_value,
+    ///         32,
+    ///         &rewind_nonce_1,
+    ///         &rewind_nonce_2,
+    ///     ),
+    ///     Ok((confidential_value, proof_message))
+    /// );
+    ///
+    /// // The two blinding nonces are necessary to rewind the range proof fully, which are also
+    /// // uniquely bound to the commitment
+    /// let blinding_nonce_1 = get_secret_nonce_from_pvt_key(&pvt_rewind_key, &committed_value);
+    /// let blinding_nonce_2 = get_secret_nonce_from_pvt_key(&pvt_blinding_key, &committed_value);
+    ///
+    /// // The owner or trusted party can extract the value, extra data and blinding factor; if it is the
+    /// // wrong combination an error will be returned
+    /// let mut rewind_transcript = Transcript::new(b"doctest example");
+    /// assert_eq!(
+    ///     proof.rewind_single_get_commitment_data(
+    ///         &bp_gens,
+    ///         &pc_gens,
+    ///         &mut rewind_transcript,
+    ///         &committed_value,
+    ///         32,
+    ///         &rewind_nonce_1,
+    ///         &rewind_nonce_2,
+    ///         &blinding_nonce_1,
+    ///         &blinding_nonce_2,
+    ///     ),
+    ///     Ok((confidential_value, blinding_factor, proof_message))
+    /// );
+    ///
+    /// # }
+    /// ```
+    pub fn prove_single_with_rng_and_rewind_key<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        n: usize,
+        rng: &mut T,
+        pvt_rewind_key: &Scalar,
+        pvt_blinding_key: &Scalar,
+        proof_message: &[u8; 23],
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        // Above 64 bits, the value takes up part of the extra data
+        if n > 64 {
+            return Err(ProofError::InvalidBitsize);
+        }
+        RangeProof::prove_single_with_legacy_rewind_data(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v.into(),
+            v_blinding,
+            n,
+            rng,
+            pvt_rewind_key,
+            pvt_blinding_key,
+            add_bytes_to_word([0u8; 32], proof_message, 8),
+        )
+    }
+
+    /// Create a rangeproof for a given pair of value `v` and
+    /// blinding scalar `v_blinding`, passing in a rewind key to
+    /// enable rangeproof rewinding with 23 bytes worth of extra
+    /// data that can be embedded.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_single_with_rng_and_rewind_key`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_single_with_rewind_key(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        n: usize,
+        pvt_rewind_key: &Scalar,
+        pvt_blinding_key: &Scalar,
+        proof_message: &[u8; 23],
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        RangeProof::prove_single_with_rng_and_rewind_key(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            n,
+            &mut thread_rng(),
+            pvt_rewind_key,
+            pvt_blinding_key,
+            proof_message,
+        )
+    }
+
+    /// Create a rangeproof for a given pair of 128-bit value `v` and
+    /// blinding scalar `v_blinding`, for bitsizes `n` up to 128,
+    /// passing in a rewind key to enable rangeproof rewinding with
+    /// 15 bytes worth of extra data that can be embedded.
+    ///
+    /// The value and extra data can be recovered with
+    /// [`RangeProof::rewind_single_u128_get_commitment_data`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_single_u128_with_rng_and_rewind_key<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &Scalar,
+        n: usize,
+        rng: &mut T,
+        pvt_rewind_key: &Scalar,
+        pvt_blinding_key: &Scalar,
+        proof_message: &[u8; 15],
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        // The extra data follows the 16 bytes reserved for the value
+        RangeProof::prove_single_with_legacy_rewind_data(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            n,
+            rng,
+            pvt_rewind_key,
+            pvt_blinding_key,
+            add_bytes_to_word([0u8; 32], proof_message, 16),
+        )
+    }
+
+    /// Creates a rewindable rangeproof with the unauthenticated layout of
+    /// [`RangeProof::prove_single_with_rng_and_rewind_key`], in which the
+    /// value is followed directly by the given extra `data`.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_single_with_legacy_rewind_data<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &Scalar,
+        n: usize,
+        rng: &mut T,
+        pvt_rewind_key: &Scalar,
+        pvt_blinding_key: &Scalar,
+        data: [u8; 32],
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let party = party::Party::new_u128(
+            bp_gens,
+            pc_gens,
+            v,
+            *v_blinding,
+            n,
+            *pvt_rewind_key,
+            *pvt_blinding_key,
+            Scalar::from_bits(data),
+        )?;
+        let (p, Vs) = RangeProof::prove_with_parties(
+            bp_gens,
+            &ExtendedPedersenGens::from(*pc_gens),
+            None,
+            transcript,
+            alloc::vec![party],
+            n,
+            rng,
+        )?;
+        Ok((p, Vs[0]))
+    }
+
+    /// Create a rangeproof for a given pair of 128-bit value `v` and
+    /// blinding scalar `v_blinding`, passing in a rewind key to
+    /// enable rangeproof rewinding with 15 bytes worth of extra
+    /// data that can be embedded.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_single_u128_with_rng_and_rewind_key`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_single_u128_with_rewind_key(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &Scalar,
+        n: usize,
+        pvt_rewind_key: &Scalar,
+        pvt_blinding_key: &Scalar,
+        proof_message: &[u8; 15],
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        RangeProof::prove_single_u128_with_rng_and_rewind_key(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            n,
+            &mut thread_rng(),
+            pvt_rewind_key,
+            pvt_blinding_key,
+            proof_message,
+        )
+    }
+
+    /// Create a rangeproof for a given pair of 128-bit value `v` and
+    /// blinding scalar `v_blinding`, which can be rewound with the
+    /// `rewind_keys` to recover the value, the blinding factor and the
+    /// embedded `proof_message`.
+    ///
+    /// The message can hold up to [`ProofMessage::capacity`] bytes for
+    /// the bitsize `n`.  The nonces which make the proof rewindable are
+    /// derived with the `nonce_generator`, usually a
+    /// [`Blake2bNonceGenerator`]; the proof must be rewound with the
+    /// same generator, see
+    /// [`RangeProof::rewind_single_get_commitment_data_with_keys`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_single_rewindable_with_rng<T: RngCore + CryptoRng, G: NonceGenerator>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &Scalar,
+        n: usize,
+        rewind_keys: &RewindKeys,
+        proof_message: &ProofMessage,
+        nonce_generator: &G,
+        rng: &mut T,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        RangeProof::prove_single_rewindable_extended_with_rng(
+            bp_gens,
+            &ExtendedPedersenGens::from(*pc_gens),
+            transcript,
+            v,
+            &[*v_blinding],
+            n,
+            rewind_keys,
+            proof_message,
+            nonce_generator,
+            rng,
+        )
+    }
+
+    /// Create a rangeproof for a given 128-bit value `v` in an extended
+    /// Pedersen commitment with the blinding factors `v_blinding`,
+    /// which can be rewound with the `rewind_keys` to recover the
+    /// value, all of the blinding factors and the embedded
+    /// `proof_message`, see
+    /// [`RangeProof::rewind_single_extended_get_commitment_data_with_keys`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_single_rewindable_extended_with_rng<T: RngCore + CryptoRng, G: NonceGenerator>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &ExtendedPedersenGens,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &[Scalar],
+        n: usize,
+        rewind_keys: &RewindKeys,
+        proof_message: &ProofMessage,
+        nonce_generator: &G,
+        rng: &mut T,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let party = party::Party::new_extended_with_rewind_keys(
+            bp_gens,
+            pc_gens,
+            v,
+            v_blinding,
+            n,
+            rewind_keys,
+            proof_message,
+            nonce_generator,
+        )?;
+        let (p, Vs) = RangeProof::prove_with_parties(
+            bp_gens,
+            pc_gens,
+            None,
+            transcript,
+            alloc::vec![party],
+            n,
+            rng,
+        )?;
+        Ok((p, Vs[0]))
+    }
+
+    /// Create a rangeproof for a given pair of 128-bit value `v` and
+    /// blinding scalar `v_blinding`, which can be rewound with the
+    /// `rewind_keys`.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_single_rewindable_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_single_rewindable<G: NonceGenerator>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &Scalar,
+        n: usize,
+        rewind_keys: &RewindKeys,
+        proof_message: &ProofMessage,
+        nonce_generator: &G,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        RangeProof::prove_single_rewindable_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            n,
+            rewind_keys,
+            proof_message,
+            nonce_generator,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create a rangeproof for a given 128-bit value `v` in an extended
+    /// Pedersen commitment, which can be rewound with the `rewind_keys`.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_single_rewindable_extended_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_single_rewindable_extended<G: NonceGenerator>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &ExtendedPedersenGens,
+        transcript: &mut Transcript,
+        v: u128,
+        v_blinding: &[Scalar],
+        n: usize,
+        rewind_keys: &RewindKeys,
+        proof_message: &ProofMessage,
+        nonce_generator: &G,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        RangeProof::prove_single_rewindable_extended_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            n,
+            rewind_keys,
+            proof_message,
+            nonce_generator,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Returns the size in bytes of the serialization of an `n`-bit
+    /// proof for `m` values, for commitments with the given extension
+    /// degree and, if `has_value_bases` is set, over distinct value
+    /// bases, which matches the length of [`RangeProof::to_bytes`].
+    pub fn serialized_size_for(
+        n: usize,
+        m: usize,
+        extension_degree: usize,
+        has_value_bases: bool,
+    ) -> Result<usize, ProofError> {
+        let lg_nm = padded_aggregation_size(n, m)?.trailing_zeros() as usize;
+        if extension_degree == 0 {
+            return Err(ProofError::InvalidExtensionDegree);
+        }
+        let t_x_shares = if has_value_bases { m } else { 0 };
+        let header_size = if extension_degree > 1 || has_value_bases {
+            32 + 8
+        } else {
+            0
+        };
+        Ok(header_size + (5 + 2 * extension_degree + t_x_shares + 2 * lg_nm + 2) * 32)
+    }
+
+    /// Returns the number of terms of the multiscalar multiplication
+    /// which verifies an `n`-bit proof for `m` values, with the same
+    /// inputs as [`RangeProof::serialized_size_for`].
+    ///
+    /// The multiplication has a point and a scalar for each of the
+    /// commitments of the proof and the values, for each of the value
+    /// and blinding bases, and for two generators per padded bit.  In a
+    /// batch, the terms for the generators are shared between the proofs.
+    pub fn verification_msm_size(
+        n: usize,
+        m: usize,
+        extension_degree: usize,
+        has_value_bases: bool,
+    ) -> Result<usize, ProofError> {
+        let padded_nm = padded_aggregation_size(n, m)?;
+        let lg_nm = padded_nm.trailing_zeros() as usize;
+        if extension_degree == 0 {
+            return Err(ProofError::InvalidExtensionDegree);
+        }
+        let value_bases = if has_value_bases { m } else { 0 };
+        // A, S, T_1, T_2, L, R, V, value bases, B, B_blinding, G, H
+        Ok(4 + 2 * lg_nm + m + value_bases + 1 + extension_degree + 2 * padded_nm)
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a
+    /// `RangeProof` over the value base `B` for commitments with an
+    /// extension degree of one.
+    pub fn from_bytes(slice: &[u8]) -> Result<RangeProof, ProofError> {
+        RangeProof::from_bytes_with_extension_degree(slice, 1)
+    }
+
+    /// Deserializes the proof for commitments with the given extension
+    /// degree from a byte slice.  The extension degree is part of the
+    /// encoding, and a proof for another degree is rejected.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `RangeProof`.
+    pub fn from_bytes_with_extension_degree(
+        slice: &[u8],
+        extension_degree: usize,
+    ) -> Result<RangeProof, ProofError> {
+        RangeProof::from_bytes_with_value_bases(slice, extension_degree, 0)
+    }
+
+    /// Deserializes a proof of `m` values over distinct value bases,
+    /// for commitments with the given extension degree, from a byte
+    /// slice.  Both are part of the encoding, and a proof for another
+    /// degree or number of values is rejected.  An `m` of zero reads a
+    /// proof over the single value base `B`.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `RangeProof`.
+    pub fn from_bytes_with_value_bases(
+        slice: &[u8],
+        extension_degree: usize,
+        m: usize,
+    ) -> Result<RangeProof, ProofError> {
+        if extension_degree == 0 {
+            return Err(ProofError::InvalidExtensionDegree);
+        }
+        let proof = RangeProof::read_bytes(slice)?;
+        if proof.extension_degree() != extension_degree || proof.t_x_shares.len() != m {
+            return Err(ProofError::FormatError);
+        }
+        Ok(proof)
+    }
+
+    /// Rewinds a rangeproof for a given value commitment \\(V\\),
+    /// returning the value, blinding factor and 23 bytes extra data
+    /// upon success.  The bitsize `n` must be at most 64; use
+    /// [`RangeProof::rewind_single_u128_get_commitment_data`] for
+    /// wider ranges.
+    ///
+    /// Only proofs for a single value can be rewound.  Whatever the
+    /// number of values, a proof has a fixed number of scalars which
+    /// the prover can choose freely: \\(\tilde{e}\\), \\(\tilde{t}\_x\\)
+    /// and the final scalars of the inner product proof.  Even if all
+    /// of the nonces were derived from the rewind keys, these could not
+    /// carry the independent blinding factors of every value in an
+    /// aggregated proof, so a rewindable party must not be aggregated,
+    /// see [`MPCError::InvalidRewindableAggregation`](crate::range_proof_mpc::MPCError::InvalidRewindableAggregation).
+    pub fn rewind_single_get_commitment_data(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitment: &CompressedRistretto,
+        n: usize,
+        rewind_nonce_1: &Scalar,
+        rewind_nonce_2: &Scalar,
+        blinding_nonce_1: &Scalar,
+        blinding_nonce_2: &Scalar,
+    ) -> Result<(u64, Scalar, [u8; 23]), ProofError> {
+        if n > 64 {
+            return Err(ProofError::InvalidBitsize);
+        }
+        let (data, _, z, x) = self.rewind_single_get_embedded_data(
+            bp_gens,
+            transcript,
+            value_commitment,
+            n,
+            rewind_nonce_1,
+            rewind_nonce_2,
+        )?;
+        let (value, proof_message) = split_rewind_data(&data);
+        let v_blinding = self.rewind_blinding_factors(
+            &ExtendedPedersenGens::from(*pc_gens),
+            value_commitment,
+            value.into(),
+            &x,
+            &z,
+            blinding_nonce_1,
+            blinding_nonce_2,
+        )?;
+        Ok((value, v_blinding[0], proof_message))
+    }
+
+    /// Rewinds a rangeproof for a given value commitment \\(V\\)
+    /// to get the value and 23 bytes extra data only. If the wrong
+    /// rewind_nonce is provided, garbage data will be returned; see
+    /// [`RangeProof::rewind_single_get_value_only_with_keys`] for
+    /// proofs which detect the wrong keys.
+    /// The bitsize `n` must be at most 64.
+    #[cfg(feature = "std")]
+    pub fn rewind_single_get_value_only(
+        &self,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: usize,
+        rewind_nonce_1: &Scalar,
+        rewind_nonce_2: &Scalar,
+    ) -> Result<(u64, [u8; 23]), ProofError> {
+        if n > 64 {
+            return Err(ProofError::InvalidBitsize);
+        }
+        let (data, _, _, _) = self.rewind_single_get_embedded_data(
+            bp_gens,
+            transcript,
+            V,
+            n,
+            rewind_nonce_1,
+            rewind_nonce_2,
+        )?;
+        Ok(split_rewind_data(&data))
+    }
+
+    /// Rewinds a rangeproof created with
+    /// [`RangeProof::prove_single_u128_with_rng_and_rewind_key`] for a
+    /// given value commitment \\(V\\), returning the 128-bit value,
+    /// blinding factor and 15 bytes extra data upon success.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rewind_single_u128_get_commitment_data(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitment: &CompressedRistretto,
+        n: usize,
+        rewind_nonce_1: &Scalar,
+        rewind_nonce_2: &Scalar,
+        blinding_nonce_1: &Scalar,
+        blinding_nonce_2: &Scalar,
+    ) -> Result<(u128, Scalar, [u8; 15]), ProofError> {
+        let (data, _, z, x) = self.rewind_single_get_embedded_data(
+            bp_gens,
+            transcript,
+            value_commitment,
+            n,
+            rewind_nonce_1,
+            rewind_nonce_2,
+        )?;
+        let (value, proof_message) = split_rewind_data_u128(&data);
+        let v_blinding = self.rewind_blinding_factors(
+            &ExtendedPedersenGens::from(*pc_gens),
+            value_commitment,
+            value.into(),
+            &x,
+            &z,
+            blinding_nonce_1,
+            blinding_nonce_2,
+        )?;
+        Ok((value, v_blinding[0], proof_message))
+    }
+
+    /// Rewinds a rangeproof created with
+    /// [`RangeProof::prove_single_u128_with_rng_and_rewind_key`] for a
+    /// given value commitment \\(V\\) to get the 128-bit value and 15
+    /// bytes extra data only. If the wrong rewind_nonce is provided,
+    /// garbage data will be returned.
+    #[cfg(feature = "std")]
+    pub fn rewind_single_u128_get_value_only(
+        &self,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: usize,
+        rewind_nonce_1: &Scalar,
+        rewind_nonce_2: &Scalar,
+    ) -> Result<(u128, [u8; 15]), ProofError> {
+        let (data, _, _, _) = self.rewind_single_get_embedded_data(
+            bp_gens,
+            transcript,
+            V,
+            n,
+            rewind_nonce_1,
+            rewind_nonce_2,
+        )?;
+        Ok(split_rewind_data_u128(&data))
+    }
+
+    /// Rewinds a rangeproof created with
+    /// [`RangeProof::prove_single_rewindable_with_rng`] for a given
+    /// value commitment \\(V\\), returning the value, blinding factor
+    /// and message upon success.  The message is returned at the full
+    /// [`ProofMessage::capacity`], padded with zeros.
+    ///
+    /// The `nonce_generator` must be the one the proof was created
+    /// with.  If the keys or the generator are wrong,
+    /// [`ProofError::InvalidRewindKeys`] is returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rewind_single_get_commitment_data_with_keys<G: NonceGenerator>(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitment: &CompressedRistretto,
+        n: usize,
+        rewind_keys: &RewindKeys,
+        nonce_generator: &G,
+    ) -> Result<(u128, Scalar, ProofMessage), ProofError> {
+        let (value, v_blinding, proof_message) = self.rewind_single_with_keys(
+            bp_gens,
+            &ExtendedPedersenGens::from(*pc_gens),
+            transcript,
+            value_commitment,
+            n,
+            rewind_keys,
+            &rewind_keys.public_keys(),
+            nonce_generator,
+        )?;
+        Ok((value, v_blinding[0], proof_message))
+    }
+
+    /// Rewinds a rangeproof created with
+    /// [`RangeProof::prove_single_rewindable_extended_with_rng`] for a
+    /// given extended Pedersen commitment \\(V\\), returning the value,
+    /// all of its blinding factors and the message upon success.
+    ///
+    /// The `nonce_generator` must be the one the proof was created
+    /// with.  If the keys or the generator are wrong,
+    /// [`ProofError::InvalidRewindKeys`] is returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rewind_single_extended_get_commitment_data_with_keys<G: NonceGenerator>(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &ExtendedPedersenGens,
+        transcript: &mut Transcript,
+        value_commitment: &CompressedRistretto,
+        n: usize,
+        rewind_keys: &RewindKeys,
+        nonce_generator: &G,
+    ) -> Result<(u128, Vec<Scalar>, ProofMessage), ProofError> {
+        self.rewind_single_with_keys(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitment,
+            n,
+            rewind_keys,
+            &rewind_keys.public_keys(),
+            nonce_generator,
+        )
+    }
+
+    /// Rewinds a rangeproof created with
+    /// [`RangeProof::prove_single_rewindable_with_rng`] for a given
+    /// value commitment \\(V\\) to get the value and message only,
+    /// using the public rewind keys.  If the keys or the generator are
+    /// wrong, [`ProofError::InvalidRewindKeys`] is returned, so this
+    /// can be used to find out whether a proof was created for the
+    /// keys without the private keys.
+    pub fn rewind_single_get_value_only_with_keys<G: NonceGenerator>(
+        &self,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: usize,
+        public_keys: &PublicRewindKeys,
+        nonce_generator: &G,
+    ) -> Result<(u128, ProofMessage), ProofError> {
+        let (rewind_nonce_1, rewind_nonce_2) = public_keys.rewind_nonces(nonce_generator, V);
+        let (data, y, z, x) = self.rewind_single_get_embedded_data(
+            bp_gens,
+            transcript,
+            V,
+            n,
+            &rewind_nonce_1,
+            &rewind_nonce_2,
+        )?;
+        if !has_rewind_tag(n, &data, &rewind_nonce_1, &rewind_nonce_2) {
+            return Err(ProofError::InvalidRewindKeys);
+        }
+        self.rewind_single_get_message(transcript, n, data, &y, &z, &x, &rewind_nonce_2)
+    }
+
+    /// Rewinds a rangeproof with the given keys, returning the value,
+    /// blinding factors and message.
+    ///
+    /// The secret nonces are only derived once the tag has shown that
+    /// the proof belongs to the keys, so that rejecting the proofs of
+    /// other keys is cheap; `public_keys` must belong to `rewind_keys`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn rewind_single_with_keys<G: NonceGenerator + ?Sized>(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &ExtendedPedersenGens,
+        transcript: &mut Transcript,
+        value_commitment: &CompressedRistretto,
+        n: usize,
+        rewind_keys: &RewindKeys,
+        public_keys: &PublicRewindKeys,
+        nonce_generator: &G,
+    ) -> Result<(u128, Vec<Scalar>, ProofMessage), ProofError> {
+        let (rewind_nonce_1, rewind_nonce_2) =
+            public_keys.rewind_nonces(nonce_generator, value_commitment);
+        let (data, y, z, x) = self.rewind_single_get_embedded_data(
+            bp_gens,
+            transcript,
+            value_commitment,
+            n,
+            &rewind_nonce_1,
+            &rewind_nonce_2,
+        )?;
+        if !has_rewind_tag(n, &data, &rewind_nonce_1, &rewind_nonce_2) {
+            return Err(ProofError::InvalidRewindKeys);
+        }
+        let (value, proof_message) =
+            self.rewind_single_get_message(transcript, n, data, &y, &z, &x, &rewind_nonce_2)?;
+        let v_blinding = self.rewind_blinding_factors(
+            pc_gens,
+            value_commitment,
+            value.into(),
+            &x,
+            &z,
+            &nonce_generator.secret_nonce(&rewind_keys.rewind_key, value_commitment),
+            &nonce_generator.secret_nonce(&rewind_keys.blinding_key, value_commitment),
+        )?;
+        Ok((value, v_blinding, proof_message))
+    }
+
+    /// Recovers the value and the full message of a rewound rangeproof
+    /// from the first word of embedded `data`, continuing the replay of
+    /// the `transcript` through the inner product proof to recover the
+    /// words embedded in \\(\mathbf{s}\_L, \mathbf{s}\_R\\).
+    #[allow(clippy::too_many_arguments)]
+    fn rewind_single_get_message(
+        &self,
+        transcript: &mut Transcript,
+        n: usize,
+        data: [u8; 32],
+        y: &Scalar,
+        z: &Scalar,
+        x: &Scalar,
+        rewind_nonce_2: &Scalar,
+    ) -> Result<(u128, ProofMessage), ProofError> {
+        let (value, _) = extract_message(n, &[data]);
+
+        self.append_proof_scalars(transcript);
+        transcript.challenge_scalar(b"w");
+        let (_, _, s) = self
+            .ipp_proof
+            .verification_scalars(n.next_power_of_two(), transcript)?;
+
+        let [word_1, word_2] = rewind::extract_extended_words(
+            n,
+            value,
+            y,
+            z,
+            x,
+            &s,
+            &self.ipp_proof.a,
+            &self.ipp_proof.b,
+            rewind_nonce_2,
+        );
+        Ok(extract_message(n, &[data, word_1, word_2]))
+    }
+
+    /// Rewinds a rangeproof for a given value commitment \\(V\\)
+    /// to retrieve the 32 bytes of embedded value and extra data
+    /// held by \\(\rho\\), and the challenge scalars y, z and x.
+    fn rewind_single_get_embedded_data(
+        &self,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        value_commitment: &CompressedRistretto,
+        n: usize,
+        rewind_nonce_1: &Scalar,
+        rewind_nonce_2: &Scalar,
+    ) -> Result<([u8; 32], Scalar, Scalar, Scalar), ProofError> {
+        // First, replay the "interactive" protocol using the proof
+        // data to recompute all challenges.
+        let padded_n = padded_bitsize(n).ok_or(ProofError::InvalidBitsize)?;
+        if bp_gens.gens_capacity < padded_n {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        if bp_gens.party_capacity < 1 {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        transcript.rangeproof_domain_sep(n as u64, 1u64);
+        transcript.append_point(b"V", value_commitment);
+        transcript.validate_and_append_point(b"A", &self.A)?;
+        transcript.validate_and_append_point(b"S", &self.S)?;
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+        transcript.validate_and_append_point(b"T_1", &self.T_1)?;
+        transcript.validate_and_append_point(b"T_2", &self.T_2)?;
+        let x = transcript.challenge_scalar(b"x");
+
+        // Extract s_blinding:
+        //   e_blinding = a_blinding + x * s_blinding
+        //   s_blinding = (e_blinding - a_blinding) * (1/x)
+        //   a_blinding: replaced by rewind_nonce_1
+        // Only the first blinding factors carry the embedded data.
+        let s_blinding = (self.e_blinding[0] - rewind_nonce_1) * x.invert();
+        // Extract the value and extra data
+        let xor_s_blinding = xor_32_bytes(&rewind_nonce_2.as_bytes(), &s_blinding.as_bytes());
+
+        Ok((xor_s_blinding, y, z, x))
+    }
+
+    /// Extracts the blinding factors of a rewound rangeproof, and checks
+    /// that they open the value commitment \\(V\\) to `value`.
+    #[allow(clippy::too_many_arguments)]
+    fn rewind_blinding_factors(
+        &self,
+        pc_gens: &ExtendedPedersenGens,
+        value_commitment: &CompressedRistretto,
+        value: Scalar,
+        x: &Scalar,
+        z: &Scalar,
+        blinding_nonce_1: &Scalar,
+        blinding_nonce_2: &Scalar,
+    ) -> Result<Vec<Scalar>, ProofError> {
+        let extension_degree = pc_gens.extension_degree();
+        if self.extension_degree() != extension_degree {
+            return Err(ProofError::InvalidExtensionDegree);
+        }
+
+        // Extract the blinding factors, for each extension degree:
+        //   t_x_blinding = z^2 * v_blinding + x * t_1_blinding + x^2 * t_2_blinding
+        //   v_blinding = (1 / z^2) * (t_x_blinding - x * t_1_blinding - x^2 * t_2_blinding)
+        //   t_1_blinding: derived from blinding_nonce_1
+        //   t_2_blinding: derived from blinding_nonce_2
+        let zz_inv = (z * z).invert();
+        let v_blinding: Vec<Scalar> = self
+            .t_x_blinding
+            .iter()
+            .zip(derive_extension_nonces(blinding_nonce_1, extension_degree))
+            .zip(derive_extension_nonces(blinding_nonce_2, extension_degree))
+            .map(|((t_x_blinding, t_1_blinding), t_2_blinding)| {
+                zz_inv * (t_x_blinding - x * t_1_blinding - x * x * t_2_blinding)
+            })
+            .collect();
+
+        //Verify if the correct value and blinding factors were extracted
+        let value_commitment_calculated = pc_gens.commit(value, &v_blinding)?.compress();
+        if value_commitment.as_bytes() != value_commitment_calculated.as_bytes() {
+            Err(ProofError::InvalidCommitmentExtracted)
+        } else {
+            Ok(v_blinding)
+        }
+    }
+}
+
+/// The byte which fills the first point-sized block of the encoding of a
+/// proof for extended commitments.  Such a block is not the compression
+/// of any point, so the encoding of a plain proof never starts with it.
+const ENCODING_MARKER: u8 = 0xff;
+
+impl<G: Group> RangeProof<G> {
+    /// Returns the extension degree of the commitments this proof is
+    /// for, which is the number of each of its blinding scalars.
+    pub fn extension_degree(&self) -> usize {
+        self.t_x_blinding.len()
+    }
+
+    /// Returns the size in bytes required to serialize the `RangeProof`.
+    pub fn serialized_size(&self) -> usize {
+        // Header, then points A, S, T1, T2, then 1 + 2d + m scalars tx, tx_bl, e_bl, tx_shares.
+        self.header_size()
+            + 4 * G::COMPRESSED_POINT_SIZE
+            + (1 + 2 * self.extension_degree() + self.t_x_shares.len()) * G::SCALAR_SIZE
+            + self.ipp_proof.serialized_size()
+    }
+
+    /// Serializes the proof into a byte array of \\(2 \lg n + 7 + 2d\\)
+    /// 32-byte elements, where \\(n\\) is the number of secret bits
+    /// and \\(d\\) is the extension degree, plus \\(m\\) elements
+    /// for a proof of \\(m\\) values over distinct value bases, and
+    /// a header of 40 bytes for either of those.
+    ///
+    /// # Layout
+    ///
+    /// The layout of the range proof encoding is:
+    ///
+    /// * for extended commitments or distinct value bases, a header of a
+    ///   point-sized block of `0xff` bytes followed by \\(d\\) and the
+    ///   number of shares of \\(t_x\\), each as a little-endian `u32`,
+    /// * four compressed Ristretto points \\(A,S,T_1,T_2\\),
+    /// * the scalar \\(t_x\\),
+    /// * \\(d\\) scalars \\(\tilde{t}_x\\) followed by \\(d\\) scalars \\(\tilde{e}\\),
+    /// * for proofs over distinct value bases, the \\(m\\) shares of \\(t_x\\),
+    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{n-1},R_{n-1}\\),
+    /// * two scalars \\(a, b\\).
+    ///
+    /// The points and scalars of other groups take
+    /// [`Group::COMPRESSED_POINT_SIZE`] and [`Group::SCALAR_SIZE`] bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        if self.header_size() > 0 {
+            buf.resize(G::COMPRESSED_POINT_SIZE, ENCODING_MARKER);
+            buf.extend_from_slice(&(self.extension_degree() as u32).to_le_bytes());
+            buf.extend_from_slice(&(self.t_x_shares.len() as u32).to_le_bytes());
+        }
+        buf.extend_from_slice(G::compressed_as_bytes(&self.A));
+        buf.extend_from_slice(G::compressed_as_bytes(&self.S));
+        buf.extend_from_slice(G::compressed_as_bytes(&self.T_1));
+        buf.extend_from_slice(G::compressed_as_bytes(&self.T_2));
+        buf.extend_from_slice(&self.t_x.to_bytes());
+        for t_x_blinding in self.t_x_blinding.iter() {
+            buf.extend_from_slice(&t_x_blinding.to_bytes());
+        }
+        for e_blinding in self.e_blinding.iter() {
+            buf.extend_from_slice(&e_blinding.to_bytes());
+        }
+        for t_x_share in self.t_x_shares.iter() {
+            buf.extend_from_slice(&t_x_share.to_bytes());
+        }
+        buf.extend(self.ipp_proof.to_bytes_iter());
+        buf
+    }
+
+    /// Returns the size of the header which starts the encoding of a
+    /// proof for extended commitments or distinct value bases, or zero
+    /// for a plain proof.
+    fn header_size(&self) -> usize {
+        if self.extension_degree() > 1 || !self.t_x_shares.is_empty() {
+            G::COMPRESSED_POINT_SIZE + 8
+        } else {
+            0
+        }
+    }
+
+    /// Deserializes a proof from a byte slice, reading its extension
+    /// degree and number of shares of \\(t_x\\) from its header, as
+    /// [`RangeProof::from_bytes_with_value_bases`] does for the proofs
+    /// of any group.
+    fn read_bytes(slice: &[u8]) -> Result<Self, ProofError> {
+        let point_size = G::COMPRESSED_POINT_SIZE;
+        let (extension_degree, m, slice) = if slice.len() >= point_size + 8
+            && slice[..point_size].iter().all(|&b| b == ENCODING_MARKER)
+        {
+            let read_u32 = |pos: usize| {
+                let mut bytes = [0u8; 4];
+                bytes.copy_from_slice(&slice[pos..pos + 4]);
+                u32::from_le_bytes(bytes) as usize
+            };
+            let extension_degree = read_u32(point_size);
+            let m = read_u32(point_size + 4);
+            // The header is only written for extended commitments or
+            // distinct value bases, and counts longer than the slice
+            // cannot be read from it.
+            if extension_degree == 0
+                || (extension_degree == 1 && m == 0)
+                || extension_degree > slice.len()
+                || m > slice.len()
+            {
+                return Err(ProofError::FormatError);
+            }
+            (extension_degree, m, &slice[point_size + 8..])
+        } else {
+            (1, 0, slice)
+        };
+
+        let scalar_size = G::SCALAR_SIZE;
+        let scalars_offset = 4 * point_size;
+        let ipp_offset = scalars_offset + (1 + 2 * extension_degree + m) * scalar_size;
+        if slice.len() < ipp_offset {
+            return Err(ProofError::FormatError);
+        }
+
+        let read_point = |i: usize| {
+            G::compressed_from_slice(&slice[i * point_size..(i + 1) * point_size])
+                .ok_or(ProofError::FormatError)
+        };
+        let read_scalar = |i: usize| {
+            let pos = scalars_offset + i * scalar_size;
+            G::Scalar::from_canonical_bytes(&slice[pos..pos + scalar_size])
+                .ok_or(ProofError::FormatError)
+        };
+
+        let A = read_point(0)?;
+        let S = read_point(1)?;
+        let T_1 = read_point(2)?;
+        let T_2 = read_point(3)?;
+
+        let t_x = read_scalar(0)?;
+        let proof_scalars = (0..2 * extension_degree + m)
+            .map(|i| read_scalar(1 + i))
+            .collect::<Result<Vec<_>, _>>()?;
+        let t_x_blinding = proof_scalars[..extension_degree].to_vec();
+        let e_blinding = proof_scalars[extension_degree..2 * extension_degree].to_vec();
+        let t_x_shares = proof_scalars[2 * extension_degree..].to_vec();
+
+        let ipp_proof = InnerProductProof::from_bytes(&slice[ipp_offset..])?;
+
+        Ok(RangeProof {
+            A,
+            S,
+            T_1,
+            T_2,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            t_x_shares,
+            ipp_proof,
+        })
+    }
+}
+
+impl<G: Group> Serialize for RangeProof<G> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de, G: Group> Deserialize<'de> for RangeProof<G> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RangeProofVisitor<G>(PhantomData<G>);
+
+        impl<'de, G: Group> Visitor<'de> for RangeProofVisitor<G> {
+            type Value = RangeProof<G>;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid RangeProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<RangeProof<G>, E>
+            where
+                E: serde::de::Error,
+            {
+                // Using Error::custom requires T: Display, which our error
+                // type only implements when it implements std::error::Error.
+                #[cfg(feature = "std")]
+                return RangeProof::read_bytes(v).map_err(serde::de::Error::custom);
+                // In no-std contexts, drop the error message.
+                #[cfg(not(feature = "std"))]
+                return RangeProof::read_bytes(v)
+                    .map_err(|_| serde::de::Error::custom("deserialization error"));
+            }
+        }
+
+        deserializer.deserialize_bytes(RangeProofVisitor(PhantomData))
+    }
+}
+
+/// Returns the smallest bitsize `n` such that
+/// \\(\text{max} - \text{min} < 2^n\\).
+fn interval_bitsize(min: u64, max: u64) -> Result<usize, ProofError> {
+    if min > max {
+        return Err(ProofError::InvalidInterval);
+    }
+    let width = 64 - (max - min).leading_zeros() as usize;
+    Ok(width.max(1))
+}
+
+/// Compute
+/// \\[
+/// \delta(y,z) = (z - z^{2}) \langle \mathbf{1}, {\mathbf{y}}^{n' \cdot m} \rangle - \sum_{j=0}^{m-1} z^{j+3} \cdot \langle \mathbf{1}, {\mathbf{2}}^{n} \rangle
+/// \\]
+/// where \\(n'\\) is \\(n\\) rounded up to the next power of two.
+fn delta<S: ScalarField>(n: usize, m: usize, y: &S, z: &S) -> S {
+    let sum_y = util::sum_of_powers(y, n.next_power_of_two() * m);
+    let sum_2 = util::sum_of_powers(&S::from(2u64), n);
+    let sum_z = util::sum_of_powers(z, m);
+    let z = *z;
+
+    (z - z * z) * sum_y - z * z * z * sum_2 * sum_z
+}
+
+/// Compute the share of \\(\delta(y,z)\\) of the party at position `j`,
+/// \\((z - z^{2}) y^{n' \cdot j} \langle \mathbf{1}, {\mathbf{y}}^{n'} \rangle - z^{j+3} \langle \mathbf{1}, {\mathbf{2}}^{n} \rangle\\).
+fn party_delta<S: ScalarField>(n: usize, j: usize, y: &S, z: &S) -> S {
+    let padded_n = n.next_power_of_two();
+    let sum_y = util::sum_of_powers(y, padded_n);
+    let sum_2 = util::sum_of_powers(&S::from(2u64), n);
+    let y_jn = util::scalar_exp_vartime(y, (j * padded_n) as u64);
+    let z_j = util::scalar_exp_vartime(z, j as u64);
+    let z = *z;
+
+    (z - z * z) * sum_y * y_jn - z * z * z * sum_2 * z_j
+}
+
+/// Checks that an `n`-bit proof for `m` values is supported, returning
+/// the number of padded bits of all values.
+fn padded_aggregation_size(n: usize, m: usize) -> Result<usize, ProofError> {
+    let padded_n = padded_bitsize(n).ok_or(ProofError::InvalidBitsize)?;
+    if m == 0 {
+        return Err(ProofError::InvalidAggregation);
+    }
+    Ok(padded_n * m.next_power_of_two())
+}
+
+/// Checks that `n` is a supported bitsize, returning the length of
+/// the bit vectors it is padded to, or `None` if it is unsupported.
+///
+/// Bitsizes which are not a power of two are padded with bits which
+/// are always zero and carry no weight in the value.
+pub(crate) fn padded_bitsize(n: usize) -> Option<usize> {
+    if (1..=128).contains(&n) {
+        Some(n.next_power_of_two())
+    } else {
+        None
+    }
+}
+
+/// Returns the powers \\(2^0, \ldots, 2^{n-1}\\), followed by zeros
+/// for the padding bits up to the padded bitsize.
+pub(crate) fn padded_powers_of_2<S: ScalarField>(n: usize) -> impl Iterator<Item = S> {
+    util::exp_iter(S::from(2u64))
+        .take(n)
+        .chain(iter::repeat(S::zero()))
+        .take(n.next_power_of_two())
+}
+
+/// Splits embedded rewind data into a 64-bit value and 23 bytes extra data.
+fn split_rewind_data(data: &[u8; 32]) -> (u64, [u8; 23]) {
+    let mut value_bytes = [0u8; 8];
+    value_bytes.copy_from_slice(&data[..8]);
+    let mut proof_message = [0u8; 23];
+    proof_message.copy_from_slice(&data[8..31]);
+    (u64::from_le_bytes(value_bytes), proof_message)
+}
+
+/// Splits embedded rewind data into a 128-bit value and 15 bytes extra data.
+fn split_rewind_data_u128(data: &[u8; 32]) -> (u128, [u8; 15]) {
+    let mut value_bytes = [0u8; 16];
+    value_bytes.copy_from_slice(&data[..16]);
+    let mut proof_message = [0u8; 15];
+    proof_message.copy_from_slice(&data[16..31]);
+    (u128::from_le_bytes(value_bytes), proof_message)
+}
+
+/// Calculate a rewind nonce from a private key and the value commitment.
+pub fn get_rewind_nonce_from_pvt_key(pvt_key: &Scalar, commitment: &CompressedRistretto) -> Scalar {
+    Blake2bNonceGenerator.rewind_nonce_from_pvt_key(pvt_key, commitment)
+}
+
+/// Calculate a rewind nonce from a public key and the value commitment.
+pub fn get_rewind_nonce_from_pub_key(
+    pub_key: &CompressedRistretto,
+    commitment: &CompressedRistretto,
+) -> Scalar {
+    Blake2bNonceGenerator.rewind_nonce(pub_key, commitment)
+}
+
+/// Calculate a secret nonce from a private key and the value commitment.
+pub fn get_secret_nonce_from_pvt_key(pvt_key: &Scalar, commitment: &CompressedRistretto) -> Scalar {
+    Blake2bNonceGenerator.secret_nonce(pvt_key, commitment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generators::PedersenGens;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use curve25519_dalek::traits::Identity;
+
+    #[test]
+    fn test_delta() {
+        let mut rng = rand::thread_rng();
+        let y = Scalar::random(&mut rng);
+        let z = Scalar::random(&mut rng);
+
+        // Choose n = 256 to ensure we overflow the group order during
+        // the computation, to check that that's done correctly
+        let n = 256;
+
+        // code copied from previous implementation
+        let z2 = z * z;
+        let z3 = z2 * z;
+        let mut power_g = Scalar::zero();
+        let mut exp_y = Scalar::one(); // start at y^0 = 1
+        let mut exp_2 = Scalar::one(); // start at 2^0 = 1
+        for _ in 0..n {
+            power_g += (z - z2) * exp_y - z3 * exp_2;
+
+            exp_y = exp_y * y; // y^i -> y^(i+1)
+            exp_2 = exp_2 + exp_2; // 2^i -> 2^(i+1)
+        }
+
+        assert_eq!(power_g, delta(n, 1, &y, &z),);
+    }
+
+    /// Given a bitsize `n`, test the following:
+    ///
+    /// 1. Generate `m` random values and create a proof they are all in range;
+    /// 2. Serialize to wire format;
+    /// 3. Deserialize from wire format;
+    /// 4. Verify the proof.
+    fn singleparty_create_and_verify_helper(n: usize, m: usize) {
+        // Split the test into two scopes, so that it's explicit what
+        // data is shared between the prover and the verifier.
+
+        // Use bincode for serialization
+        //use bincode; // already present in lib.rs
+
+        // Both prover and verifier have access to the generators and the proof
+        let max_bitsize = 64;
+        let max_parties = 8;
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(max_bitsize, max_parties);
+
+        // Prover's scope
+        let (proof_bytes, value_commitments) = {
+            use self::rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            // 0. Create witness data
+            let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
+            let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
+            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+
+            // 1. Create the proof
+            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+            let (proof, value_commitments) = RangeProof::prove_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                n,
+            )
+            .unwrap();
+
+            // 2. Return serialized proof and value commitments
+            (bincode::serialize(&proof).unwrap(), value_commitments)
+        };
+
+        // Verifier's scope
+        {
+            // 3. Deserialize
+            let proof: RangeProof = bincode::deserialize(&proof_bytes).unwrap();
+
+            // 4. Verify with the same customization label as above
+            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+            assert!(proof
+                .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_1() {
+        singleparty_create_and_verify_helper(32, 1);
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_2() {
+        singleparty_create_and_verify_helper(32, 2);
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_4() {
+        singleparty_create_and_verify_helper(32, 4);
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_8() {
+        singleparty_create_and_verify_helper(32, 8);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_1() {
+        singleparty_create_and_verify_helper(64, 1);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_2() {
+        singleparty_create_and_verify_helper(64, 2);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_4() {
+        singleparty_create_and_verify_helper(64, 4);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_8() {
+        singleparty_create_and_verify_helper(64, 8);
+    }
+
+    #[test]
+    fn create_and_verify_in_another_group() {
+        use crate::batch::BatchVerifier;
+        use crate::group::tests::{Fp, ToyGroup};
+
+        let n = 8;
+        let pc_gens = PedersenGens::<ToyGroup>::for_group();
+        let bp_gens = BulletproofGens::<ToyGroup>::for_group(n, 4);
+        let mut rng = rand::thread_rng();
+
+        // Three parties are padded with a dummy party
+        let values = [0u64, 42, 255];
+        let blindings: Vec<Fp> = values.iter().map(|_| Fp::random(&mut rng)).collect();
+        let (proof, value_commitments) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"AnotherGroupTest"),
+            &values,
+            &blindings,
+            n,
+        )
+        .unwrap();
+        assert_eq!(
+            value_commitments[1],
+            ToyGroup::compress(&pc_gens.commit(Fp::from(42u64), blindings[1]))
+        );
+
+        let proof: RangeProof<ToyGroup> =
+            bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+        assert!(proof
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"AnotherGroupTest"),
+                &value_commitments,
+                n,
+            )
+            .is_ok());
+
+        let mut batch = BatchVerifier::for_group(&bp_gens, &pc_gens);
+        batch
+            .add_range_proof(
+                &proof,
+                &mut Transcript::new(b"AnotherGroupTest"),
+                &value_commitments,
+                n,
+                &mut rng,
+            )
+            .unwrap();
+        assert!(batch.finish().is_ok());
+
+        // A proof for a value out of range does not verify, nor does a
+        // proof for other commitments
+        let (bad_proof, bad_commitment) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"AnotherGroupTest"),
+            256,
+            &blindings[0],
+            n,
+        )
+        .unwrap();
+        assert_eq!(
+            bad_proof.verify_single(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"AnotherGroupTest"),
+                &bad_commitment,
+                n,
+            ),
+            Err(ProofError::VerificationError)
+        );
+        let swapped = [
+            value_commitments[1],
+            value_commitments[0],
+            value_commitments[2],
+        ];
+        assert_eq!(
+            proof.verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"AnotherGroupTest"),
+                &swapped,
+                n,
+            ),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    fn batch_statements_helper(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        sizes: &[(usize, usize)],
+    ) -> Vec<(RangeProof, Vec<CompressedRistretto>, usize)> {
+        use self::rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        sizes
+            .iter()
+            .map(|&(n, m)| {
+                let values: Vec<u64> = (0..m)
+                    .map(|_| rng.gen_range(0..((1u128 << n) - 1) as u64))
+                    .collect();
+                let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+                let mut transcript = Transcript::new(b"BatchRangeProofTest");
+                let (proof, value_commitments) = RangeProof::prove_multiple(
+                    bp_gens,
+                    pc_gens,
+                    &mut transcript,
+                    &values,
+                    &blindings,
+                    n,
+                )
+                .unwrap();
+                (proof, value_commitments, n)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn size_and_cost_estimates() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 8);
+        let sizes = [(1, 1), (8, 3), (32, 2), (40, 1), (64, 8)];
+        let proofs = batch_statements_helper(&bp_gens, &pc_gens, &sizes);
+
+        let msm_size = |check: &DeferredCheck<Ristretto>| {
+            check.dynamic_points.len()
+                + 1
+                + check.B_blinding_scalars.len()
+                + check.G_scalars.iter().map(|G_j| G_j.len()).sum::<usize>()
+                + check.H_scalars.iter().map(|H_j| H_j.len()).sum::<usize>()
+        };
+
+        for (proof, value_commitments, n) in proofs.iter() {
+            let m = value_commitments.len();
+            assert_eq!(
+                RangeProof::serialized_size_for(*n, m, 1, false),
+                Ok(proof.to_bytes().len())
+            );
+            assert_eq!(proof.serialized_size(), proof.to_bytes().len());
+
+            let mut transcript = Transcript::new(b"BatchRangeProofTest");
+            let check = proof
+                .verification_check(
+                    &bp_gens,
+                    1,
+                    None,
+                    &mut transcript,
+                    value_commitments,
+                    *n,
+                    &mut rand::thread_rng(),
+                )
+                .unwrap();
+            assert_eq!(
+                RangeProof::verification_msm_size(*n, m, 1, false),
+                Ok(msm_size(&check))
+            );
+        }
+
+        // Extended commitments and distinct value bases
+        let mut rng = rand::thread_rng();
+        let n = 16;
+        for extension_degree in 1..=3 {
+            let pc_gens = ExtendedPedersenGens::new(extension_degree).unwrap();
+            for &m in &[1, 2, 3] {
+                let value_bases: Vec<RistrettoPoint> = (0..m)
+                    .map(|i| ExtendedPedersenGens::asset_value_base(&[i as u8]))
+                    .collect();
+                let values: Vec<u128> = (0..m).map(|i| 100 + i as u128).collect();
+                let blindings: Vec<Vec<Scalar>> = (0..m)
+                    .map(|_| {
+                        (0..extension_degree)
+                            .map(|_| Scalar::random(&mut rng))
+                            .collect()
+                    })
+                    .collect();
+                for &has_value_bases in &[false, true] {
+                    let value_bases = if has_value_bases {
+                        Some(&value_bases[..])
+                    } else {
+                        None
+                    };
+                    let mut transcript = Transcript::new(b"SizeEstimateTest");
+                    let (proof, value_commitments) = match value_bases {
+                        Some(value_bases) => RangeProof::prove_multiple_with_value_bases_with_rng(
+                            &bp_gens,
+                            &pc_gens,
+                            value_bases,
+                            &mut transcript,
+                            &values,
+                            &blindings,
+                            n,
+                            &mut rng,
+                        ),
+                        None => RangeProof::prove_multiple_extended_with_rng(
+                            &bp_gens,
+                            &pc_gens,
+                            &mut transcript,
+                            &values,
+                            &blindings,
+                            n,
+                            &mut rng,
+                        ),
+                    }
+                    .unwrap();
+                    assert_eq!(
+                        RangeProof::serialized_size_for(n, m, extension_degree, has_value_bases),
+                        Ok(proof.to_bytes().len())
+                    );
+
+                    let mut transcript = Transcript::new(b"SizeEstimateTest");
+                    let check = proof
+                        .verification_check(
+                            &bp_gens,
+                            extension_degree,
+                            value_bases,
+                            &mut transcript,
+                            &value_commitments,
+                            n,
+                            &mut rng,
+                        )
+                        .unwrap();
+                    assert_eq!(
+                        RangeProof::verification_msm_size(n, m, extension_degree, has_value_bases),
+                        Ok(msm_size(&check))
+                    );
+                }
+            }
+        }
+
+        assert_eq!(
+            RangeProof::serialized_size_for(0, 1, 1, false),
+            Err(ProofError::InvalidBitsize)
+        );
+        assert_eq!(
+            RangeProof::verification_msm_size(64, 0, 1, false),
+            Err(ProofError::InvalidAggregation)
+        );
+        assert_eq!(
+            RangeProof::serialized_size_for(64, 1, 0, false),
+            Err(ProofError::InvalidExtensionDegree)
+        );
+    }
+
+    #[test]
+    fn batch_verify_mixed_sizes() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 8);
+        let proofs = batch_statements_helper(
+            &bp_gens,
+            &pc_gens,
+            &[(8, 1), (64, 1), (32, 4), (16, 8), (64, 2), (8, 3)],
+        );
+
+        let mut transcripts = vec![Transcript::new(b"BatchRangeProofTest"); proofs.len()];
+        let statements = proofs.iter().zip(transcripts.iter_mut()).map(
+            |((proof, value_commitments, n), transcript)| RangeProofStatement {
+                proof,
+                value_commitments,
+                n: *n,
+                transcript,
+            },
+        );
+
+        assert!(RangeProof::verify_batch(&bp_gens, &pc_gens, statements).is_ok());
+    }
+
+    #[test]
+    fn batch_verify_detects_invalid_statement() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 8);
+        let mut proofs = batch_statements_helper(&bp_gens, &pc_gens, &[(32, 2), (64, 1), (8, 4)]);
+
+        // Swap the commitments of the last statement for commitments to other values
+        let (_, value_commitments, n) = proofs.pop().unwrap();
+        let (proof, _, _) =
+            batch_statements_helper(&bp_gens, &pc_gens, &[(n, value_commitments.len())])
+                .pop()
+                .unwrap();
+        proofs.push((proof, value_commitments, n));
+
+        let mut transcripts = vec![Transcript::new(b"BatchRangeProofTest"); proofs.len()];
+        let statements = proofs.iter().zip(transcripts.iter_mut()).map(
+            |((proof, value_commitments, n), transcript)| RangeProofStatement {
+                proof,
+                value_commitments,
+                n: *n,
+                transcript,
+            },
+        );
+
+        assert_eq!(
+            RangeProof::verify_batch(&bp_gens, &pc_gens, statements),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn batch_find_invalid_statements() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 8);
+        let sizes = [(8, 1), (16, 2), (32, 1), (8, 4), (64, 1), (16, 1), (8, 2)];
+        let mut proofs = batch_statements_helper(&bp_gens, &pc_gens, &sizes);
+        let find_invalid = |proofs: &[(RangeProof, Vec<CompressedRistretto>, usize)]| {
+            let mut transcripts = vec![Transcript::new(b"BatchRangeProofTest"); proofs.len()];
+            let statements = proofs.iter().zip(transcripts.iter_mut()).map(
+                |((proof, value_commitments, n), transcript)| RangeProofStatement {
+                    proof,
+                    value_commitments,
+                    n: *n,
+                    transcript,
+                },
+            );
+            RangeProof::find_invalid_in_batch(&bp_gens, &pc_gens, statements)
+        };
+
+        assert_eq!(find_invalid(&proofs), Vec::<usize>::new());
+
+        // Swap the commitments of two statements for commitments to other values
+        for &i in &[2, 5] {
+            let (n, m) = sizes[i];
+            proofs[i].1 = batch_statements_helper(&bp_gens, &pc_gens, &[(n, m)])
+                .pop()
+                .unwrap()
+                .1;
+        }
+        assert_eq!(find_invalid(&proofs), vec![2, 5]);
+
+        // Statements with the wrong number of commitments are reported as well
+        proofs[3].1.pop();
+        assert_eq!(find_invalid(&proofs), vec![2, 3, 5]);
+    }
+
+    #[test]
+    fn proofs_do_not_depend_on_parallelism() {
+        use rand_chacha::ChaChaRng;
+        use rand_core::SeedableRng;
+        use sha3::{Digest, Sha3_256};
+
+        // The same proof must be created with and without the `parallel`
+        // feature, which this test checks by pinning its digest.
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 4);
+        let mut rng = ChaChaRng::from_seed([7u8; 32]);
+        let values = [3u64, 1 << 10, 65535];
+        let blindings: Vec<Scalar> = (0..3).map(|_| Scalar::random(&mut rng)).collect();
+        let (proof, _) = RangeProof::prove_multiple_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"ParallelProofTest"),
+            &values,
+            &blindings,
+            16,
+            &mut rng,
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(Sha3_256::digest(&proof.to_bytes())),
+            "009b399ed27e9f973f1a765e9580465261ff78b1548f5eef2977f7cc04fe99f1"
+        );
+    }
+
+    /// A broken RNG which only returns zeros.
+    struct ZeroRng;
+
+    impl RngCore for ZeroRng {
+        fn next_u32(&mut self) -> u32 {
+            0
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for ZeroRng {}
+
+    #[test]
+    fn nonces_are_hedged_with_the_witness() {
+        use rand_core::SeedableRng;
+
+        fn prove<T: RngCore + CryptoRng>(v: u64, blinding: u64, rng: &mut T) -> RangeProof {
+            RangeProof::prove_single_with_rng(
+                &BulletproofGens::new(32, 1),
+                &PedersenGens::default(),
+                &mut Transcript::new(b"HedgedNonceTest"),
+                v,
+                &Scalar::from(blinding),
+                32,
+                rng,
+            )
+            .unwrap()
+            .0
+        }
+
+        // With a broken RNG, the nonces of S still differ between witnesses
+        let proof = prove(5, 7, &mut ZeroRng);
+        assert_ne!(proof.S, prove(6, 7, &mut ZeroRng).S);
+        assert_ne!(proof.S, prove(5, 8, &mut ZeroRng).S);
+
+        // A seeded RNG makes proofs reproducible, and they verify
+        let seeded = |seed: u8| prove(5, 7, &mut SeededRng::from_seed([seed; 32]));
+        let proof = seeded(1);
+        assert_eq!(proof.to_bytes(), seeded(1).to_bytes());
+        assert_ne!(proof.S, seeded(2).S);
+
+        let pc_gens = PedersenGens::default();
+        let V = pc_gens.commit(Scalar::from(5u64), Scalar::from(7u64));
+        assert!(proof
+            .verify_single(
+                &BulletproofGens::new(32, 1),
+                &pc_gens,
+                &mut Transcript::new(b"HedgedNonceTest"),
+                &V.compress(),
+                32,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn proofs_do_not_depend_on_msm_backend() {
+        use alloc::sync::Arc;
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        use rand_chacha::ChaChaRng;
+        use rand_core::SeedableRng;
+
+        use crate::msm::{MsmBackend, PippengerMsm};
+
+        /// Counts the variable time multiplications of the inner backend.
+        struct CountingMsm(PippengerMsm, Arc<AtomicUsize>);
+
+        impl MsmBackend for CountingMsm {
+            fn vartime_multiscalar_mul(
+                &self,
+                scalars: &[Scalar],
+                points: &[RistrettoPoint],
+            ) -> RistrettoPoint {
+                self.1.fetch_add(1, Ordering::SeqCst);
+                self.0.vartime_multiscalar_mul(scalars, points)
+            }
+        }
+
+        let pc_gens = PedersenGens::default();
+        let count = Arc::new(AtomicUsize::new(0));
+        let bp_gens = BulletproofGens::new(16, 4)
+            .with_msm_backend(CountingMsm(PippengerMsm::new(4), count.clone()));
+
+        // The same proof as with the default backend
+        let mut rng = ChaChaRng::from_seed([7u8; 32]);
+        let values = [3u64, 1 << 10, 65535];
+        let blindings: Vec<Scalar> = (0..3).map(|_| Scalar::random(&mut rng)).collect();
+        let mut expected_rng = rng.clone();
+        let (proof, value_commitments) = RangeProof::prove_multiple_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"ParallelProofTest"),
+            &values,
+            &blindings,
+            16,
+            &mut rng,
+        )
+        .unwrap();
+        let (expected, _) = RangeProof::prove_multiple_with_rng(
+            &BulletproofGens::new(16, 4),
+            &pc_gens,
+            &mut Transcript::new(b"ParallelProofTest"),
+            &values,
+            &blindings,
+            16,
+            &mut expected_rng,
+        )
+        .unwrap();
+        assert_eq!(proof.to_bytes(), expected.to_bytes());
+
+        // One multiplication for each round of the inner product proof
+        assert_eq!(count.swap(0, Ordering::SeqCst), 2 * 6);
+
+        assert!(proof
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"ParallelProofTest"),
+                &value_commitments,
+                16,
+            )
+            .is_ok());
+        assert!(count.load(Ordering::SeqCst) > 0);
+    }
+
+    #[test]
+    fn interval_proofs() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
+        let mut rng = rand::thread_rng();
+
+        for &(v, min, max) in &[
+            (1200u64, 1000u64, 1500u64),
+            (1000, 1000, 1500),
+            (1500, 1000, 1500),
+            (42, 42, 42),
+            (u64::MAX, 0, u64::MAX),
+            (1 << 40, 1 << 33, (1 << 40) + 1),
+        ] {
+            let blinding = Scalar::random(&mut rng);
+            let mut transcript = Transcript::new(b"IntervalProofTest");
+            let (proof, V) = RangeProof::prove_interval(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                v,
+                &blinding,
+                min,
+                max,
+            )
+            .unwrap();
+            assert_eq!(V, pc_gens.commit(Scalar::from(v), blinding).compress());
+
+            let mut transcript = Transcript::new(b"IntervalProofTest");
+            assert!(proof
+                .verify_interval(&bp_gens, &pc_gens, &mut transcript, &V, min, max)
+                .is_ok());
+
+            // The proof is bound to the interval it was created for
+            let mut transcript = Transcript::new(b"IntervalProofTest");
+            assert!(proof
+                .verify_interval(&bp_gens, &pc_gens, &mut transcript, &V, min, max - 1)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn interval_proof_errors() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
+        let blinding = Scalar::random(&mut rand::thread_rng());
+
+        let mut transcript = Transcript::new(b"IntervalProofTest");
+        assert_eq!(
+            RangeProof::prove_interval(&bp_gens, &pc_gens, &mut transcript, 5, &blinding, 6, 10)
+                .unwrap_err(),
+            ProofError::ValueOutOfRange
+        );
+        assert_eq!(
+            RangeProof::prove_interval(&bp_gens, &pc_gens, &mut transcript, 11, &blinding, 6, 10)
+                .unwrap_err(),
+            ProofError::ValueOutOfRange
+        );
+        assert_eq!(
+            RangeProof::prove_interval(&bp_gens, &pc_gens, &mut transcript, 7, &blinding, 10, 6)
+                .unwrap_err(),
+            ProofError::InvalidInterval
+        );
+    }
+
+    #[test]
+    fn minimum_value_promise() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+        let blinding = Scalar::random(&mut rand::thread_rng());
+
+        let (v, promise) = (1_000_000u64, 999_999u64);
+        let mut transcript = Transcript::new(b"MinimumValuePromiseTest");
+        let (proof, V) = RangeProof::prove_with_minimum_value_promise(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            v,
+            &blinding,
+            promise,
+            32,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"MinimumValuePromiseTest");
+        assert!(proof
+            .verify_with_minimum_value_promise(&bp_gens, &pc_gens, &mut transcript, &V, promise, 32)
+            .is_ok());
+
+        let mut transcript = Transcript::new(b"MinimumValuePromiseTest");
+        assert!(proof
+            .verify_with_minimum_value_promise(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &V,
+                promise + 2,
+                32
+            )
+            .is_err());
+
+        let mut transcript = Transcript::new(b"MinimumValuePromiseTest");
+        assert_eq!(
+            RangeProof::prove_with_minimum_value_promise(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                v,
+                &blinding,
+                v + 1,
+                32
+            )
+            .unwrap_err(),
+            ProofError::ValueOutOfRange
+        );
+        assert_eq!(
+            RangeProof::prove_with_minimum_value_promise(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                v,
+                &blinding,
+                0,
+                8
+            )
+            .unwrap_err(),
+            ProofError::ValueOutOfRange
+        );
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_3() {
+        singleparty_create_and_verify_helper(32, 3);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_5() {
+        singleparty_create_and_verify_helper(64, 5);
+    }
+
+    #[test]
+    fn create_and_verify_n_8_m_7() {
+        singleparty_create_and_verify_helper(8, 7);
+    }
+
+    #[test]
+    fn create_and_verify_n_1_m_2() {
+        singleparty_create_and_verify_helper(1, 2);
+    }
+
+    #[test]
+    fn create_and_verify_n_13_m_3() {
+        singleparty_create_and_verify_helper(13, 3);
+    }
+
+    #[test]
+    fn create_and_verify_n_40_m_1() {
+        singleparty_create_and_verify_helper(40, 1);
+    }
+
+    #[test]
+    fn bitsize_boundaries() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+        let mut rng = rand::thread_rng();
+
+        let prove_and_verify = |v: u64, n: usize, verifier_n: usize| {
+            let blinding = Scalar::random(&mut rand::thread_rng());
+            let mut transcript = Transcript::new(b"BitsizeBoundaryTest");
+            let (proof, V) =
+                RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, v, &blinding, n)
+                    .unwrap();
+            let mut transcript = Transcript::new(b"BitsizeBoundaryTest");
+            proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &V, verifier_n)
+        };
+
+        for n in [1, 2, 5, 13, 40, 63].iter().cloned() {
+            let max = (1u64 << n) - 1;
+            assert!(prove_and_verify(max, n, n).is_ok());
+            assert!(prove_and_verify(0, n, n).is_ok());
+            // The first value out of range is rejected, even though it
+            // fits in the bits padding n to a power of two.
+            assert_eq!(
+                prove_and_verify(max + 1, n, n),
+                Err(ProofError::VerificationError)
+            );
+            // The transcript is bound to the true bitsize.
+            assert_eq!(
+                prove_and_verify(max, n, n + 1),
+                Err(ProofError::VerificationError)
+            );
+        }
+        assert!(prove_and_verify(u64::MAX, 64, 64).is_ok());
+
+        for n in [0, 129].iter().cloned() {
+            let mut transcript = Transcript::new(b"BitsizeBoundaryTest");
+            let blinding = Scalar::random(&mut rng);
+            assert_eq!(
+                RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 0, &blinding, n)
+                    .unwrap_err(),
+                ProofError::InvalidBitsize
+            );
+        }
+    }
+
+    #[test]
+    fn create_and_verify_u128() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(128, 4);
+        let mut rng = rand::thread_rng();
+
+        let prove_and_verify = |values: &[u128], n: usize| {
+            let mut rng = rand::thread_rng();
+            let blindings: Vec<Scalar> = values.iter().map(|_| Scalar::random(&mut rng)).collect();
+            let mut transcript = Transcript::new(b"U128RangeProofTest");
+            let (proof, value_commitments) = RangeProof::prove_multiple_u128(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                values,
+                &blindings,
+                n,
+            )
+            .unwrap();
+            let mut transcript = Transcript::new(b"U128RangeProofTest");
+            proof.verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+        };
+
+        assert!(prove_and_verify(&[u128::MAX], 128).is_ok());
+        assert!(prove_and_verify(&[0, u128::MAX, 1 << 100], 128).is_ok());
+
+        for n in [65, 100, 127].iter().cloned() {
+            let max = (1u128 << n) - 1;
+            assert!(prove_and_verify(&[max], n).is_ok());
+            assert!(prove_and_verify(&[max, 12345, max - 1], n).is_ok());
+            assert_eq!(
+                prove_and_verify(&[max + 1], n),
+                Err(ProofError::VerificationError)
+            );
+        }
+
+        // Bitsizes above 128 are not supported
+        let mut transcript = Transcript::new(b"U128RangeProofTest");
+        assert_eq!(
+            RangeProof::prove_single_u128(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                0,
+                &Scalar::random(&mut rng),
+                129
+            )
+            .unwrap_err(),
+            ProofError::InvalidBitsize
+        );
+
+        // The generators must cover the padded bitsize
+        let small_gens = BulletproofGens::new(64, 1);
+        let mut transcript = Transcript::new(b"U128RangeProofTest");
+        assert_eq!(
+            RangeProof::prove_single_u128(
+                &small_gens,
+                &pc_gens,
+                &mut transcript,
+                0,
+                &Scalar::random(&mut rng),
+                65
+            )
+            .unwrap_err(),
+            ProofError::InvalidGeneratorsLength
+        );
+    }
+
+    #[test]
+    fn rewind_keys_are_not_accepted_in_blindings() {
+        use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
+        let mut rng = rand::thread_rng();
+
+        // Rewind keys used to be appended to the blindings after this
+        // separator; they are now only accepted through `RewindKeys`.
+        let blindings = [
+            Scalar::random(&mut rng),
+            Scalar::random(&mut rng),
+            Scalar::from_bits(*RISTRETTO_BASEPOINT_COMPRESSED.as_bytes()),
+            Scalar::random(&mut rng),
+            Scalar::random(&mut rng),
+            Scalar::zero(),
+        ];
+        let mut transcript = Transcript::new(b"AggregatedRewindTest");
+        assert_eq!(
+            RangeProof::prove_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &[1, 2],
+                &blindings,
+                64
+            )
+            .unwrap_err(),
+            ProofError::WrongNumBlindingFactors
+        );
+    }
+
+    #[test]
+    fn rewindable_parties_are_not_aggregated() {
+        use self::party::*;
+        use crate::errors::MPCError;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
+        let mut rng = rand::thread_rng();
+        let rewind_keys = RewindKeys::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+
+        let party = |rng: &mut rand::rngs::ThreadRng| {
+            Party::new_with_rewind_keys(
+                &bp_gens,
+                &pc_gens,
+                7,
+                Scalar::random(rng),
+                64,
+                &rewind_keys,
+                &ProofMessage::new(b"memo"),
+                &Blake2bNonceGenerator,
+            )
+            .unwrap()
+        };
+
+        assert!(party(&mut rng)
+            .assign_position_with_rng(0, &mut rng)
+            .is_ok());
+        assert_eq!(
+            party(&mut rng).assign_position_with_rng(1, &mut rng).err(),
+            Some(MPCError::InvalidRewindableAggregation)
+        );
+        assert_eq!(
+            ProofError::from(MPCError::InvalidRewindableAggregation),
+            ProofError::InvalidRewindableAggregation
+        );
+    }
+
+    /// Derives the nonces with a different personalisation, standing in
+    /// for an application specific key derivation.
+    struct TestNonceGenerator;
+
+    impl NonceGenerator for TestNonceGenerator {
+        fn rewind_nonce(
+            &self,
+            pub_key: &CompressedRistretto,
+            commitment: &CompressedRistretto,
+        ) -> Scalar {
+            let mut t = Transcript::new(b"TestRewindNonce");
+            t.append_point(b"K", pub_key);
+            t.append_point(b"V", commitment);
+            t.challenge_scalar(b"nonce")
+        }
+
+        fn secret_nonce(&self, pvt_key: &Scalar, commitment: &CompressedRistretto) -> Scalar {
+            let mut t = Transcript::new(b"TestSecretNonce");
+            t.append_scalar(b"k", pvt_key);
+            t.append_point(b"V", commitment);
+            t.challenge_scalar(b"nonce")
+        }
+    }
+
+    #[test]
+    fn rewind_with_typed_keys() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(128, 1);
+        let mut rng = rand::thread_rng();
+
+        let keys = RewindKeys::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let wrong_keys = RewindKeys::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+
+        for &(n, v) in &[
+            (1, 1u128),
+            (8, 255),
+            (40, 123456789),
+            (64, u64::MAX as u128),
+            (65, u64::MAX as u128 + 1),
+            (128, u128::MAX),
+        ] {
+            // Fill the whole capacity, including bytes with the top bit set
+            let capacity = ProofMessage::capacity(n);
+            let bytes: Vec<u8> = (0..capacity).map(|i| (255 - 3 * i) as u8).collect();
+            let message = ProofMessage::new(&bytes);
+            let blinding = Scalar::random(&mut rng);
+
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            let (proof, V) = RangeProof::prove_single_rewindable(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                v,
+                &blinding,
+                n,
+                &keys,
+                &message,
+                &TestNonceGenerator,
+            )
+            .unwrap();
+
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            assert!(proof
+                .verify_single(&bp_gens, &pc_gens, &mut transcript, &V, n)
+                .is_ok());
+
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            assert_eq!(
+                proof.rewind_single_get_value_only_with_keys(
+                    &bp_gens,
+                    &mut transcript,
+                    &V,
+                    n,
+                    &keys.public_keys(),
+                    &TestNonceGenerator,
+                ),
+                Ok((v, message.clone()))
+            );
+
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            assert_eq!(
+                proof.rewind_single_get_commitment_data_with_keys(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &V,
+                    n,
+                    &keys,
+                    &TestNonceGenerator,
+                ),
+                Ok((v, blinding, message))
+            );
+
+            // Neither other keys nor the default nonce derivation rewind the proof
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            assert_eq!(
+                proof.rewind_single_get_commitment_data_with_keys(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &V,
+                    n,
+                    &wrong_keys,
+                    &TestNonceGenerator,
+                ),
+                Err(ProofError::InvalidRewindKeys)
+            );
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            assert_eq!(
+                proof.rewind_single_get_value_only_with_keys(
+                    &bp_gens,
+                    &mut transcript,
+                    &V,
+                    n,
+                    &wrong_keys.public_keys(),
+                    &TestNonceGenerator,
+                ),
+                Err(ProofError::InvalidRewindKeys)
+            );
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            assert_eq!(
+                proof.rewind_single_get_commitment_data_with_keys(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &V,
+                    n,
+                    &keys,
+                    &Blake2bNonceGenerator,
+                ),
+                Err(ProofError::InvalidRewindKeys)
+            );
+        }
+    }
+
+    #[test]
+    fn value_only_rewinding_detects_foreign_proofs() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+        let mut rng = rand::thread_rng();
+        let keys = RewindKeys::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+
+        // A proof without rewind keys, and one with the unauthenticated
+        // layout of the legacy API, both for the same keys
+        let mut transcript = Transcript::new(b"TypedRewindTest");
+        let plain = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            7,
+            &Scalar::random(&mut rng),
+            64,
+        )
+        .unwrap();
+        let mut transcript = Transcript::new(b"TypedRewindTest");
+        let legacy = RangeProof::prove_single_with_rewind_key(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            7,
+            &Scalar::random(&mut rng),
+            64,
+            &keys.rewind_key,
+            &keys.blinding_key,
+            &[0xff; 23],
+        )
+        .unwrap();
+
+        for (proof, V) in &[plain, legacy] {
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            assert_eq!(
+                proof.rewind_single_get_value_only_with_keys(
+                    &bp_gens,
+                    &mut transcript,
+                    V,
+                    64,
+                    &keys.public_keys(),
+                    &Blake2bNonceGenerator,
+                ),
+                Err(ProofError::InvalidRewindKeys)
+            );
+        }
+    }
+
+    #[test]
+    fn proof_message_must_fit_the_bitsize() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(128, 1);
+        let mut rng = rand::thread_rng();
+        let keys = RewindKeys::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+
+        for &(n, capacity) in &[(1, 77), (32, 77), (64, 77), (65, 69), (128, 69)] {
+            assert_eq!(ProofMessage::capacity(n), capacity);
+            let message = ProofMessage::new(&[7u8; 86][..capacity + 1]);
+            let mut transcript = Transcript::new(b"TypedRewindTest");
+            assert_eq!(
+                RangeProof::prove_single_rewindable(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    1,
+                    &Scalar::random(&mut rng),
+                    n,
+                    &keys,
+                    &message,
+                    &Blake2bNonceGenerator,
+                )
+                .unwrap_err(),
+                ProofError::InvalidProofMessageLength
+            );
+        }
+    }
+
+    #[test]
+    fn aggregation_with_padded_bitsize() {
+        use self::dealer::*;
+        use self::party::*;
+        use crate::errors::MPCError;
+
+        // Common data - rewind functionality not used
+        let not_used = Scalar::default();
+
+        let m = 3;
+        let n = 20;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 4);
+
+        let run = |values: &[u64]| {
+            let mut rng = rand::thread_rng();
+            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+            let parties: Vec<_> = values
+                .iter()
+                .map(|v| {
+                    let v_blinding = Scalar::random(&mut rng);
+                    Party::new(
+                        &bp_gens, &pc_gens, *v, v_blinding, n, not_used, not_used, not_used,
+                    )
+                    .unwrap()
+                })
+                .collect();
+
+            let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+
+            let (parties, bit_commitments): (Vec<_>, Vec<_>) = parties
+                .into_iter()
+                .enumerate()
+                .map(|(j, p)| p.assign_position(j).unwrap())
+                .unzip();
+            let value_commitments: Vec<_> = bit_commitments.iter().map(|vc| vc.V_j).collect();
+
+            let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
+
+            let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+                .into_iter()
+                .map(|p| p.apply_challenge(&bit_challenge))
+                .unzip();
+
+            let (dealer, poly_challenge) =
+                dealer.receive_poly_commitments(poly_commitments).unwrap();
+
+            let proof_shares: Vec<_> = parties
+                .into_iter()
+                .map(|p| p.apply_challenge(&poly_challenge).unwrap())
+                .collect();
+
+            dealer
+                .receive_shares(&proof_shares)
+                .map(|proof| (proof, value_commitments))
+        };
+
+        let max = (1u64 << n) - 1;
+        let (proof, value_commitments) = run(&[max, 0, 12345]).unwrap();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+            .is_ok());
+
+        // A party whose value is just out of range is identified by the dealer
+        match run(&[max, max + 1, 12345]) {
+            Err(MPCError::MalformedProofShares { bad_shares }) => {
+                assert_eq!(bad_shares, vec![1]);
+            }
+            Err(_) => panic!("Got wrong error type from malformed shares"),
+            Ok(_) => panic!("The proof was malformed, but it was not detected"),
+        }
+    }
+
+    #[test]
+    fn aggregation_with_dummy_parties() {
+        use self::dealer::*;
+        use self::party::*;
+
+        // Common data - rewind functionality not used
+        let not_used = Scalar::default();
+
+        // Three parties are padded with one dummy party by the dealer.
+        let m = 3;
+        let n = 16;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, 4);
+
+        use self::rand::Rng;
+        let mut rng = rand::thread_rng();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+        let parties: Vec<_> = (0..m)
+            .map(|_| {
+                let v = rng.gen::<u16>() as u64;
+                let v_blinding = Scalar::random(&mut rng);
+                Party::new(
+                    &bp_gens, &pc_gens, v, v_blinding, n, not_used, not_used, not_used,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+
+        let (parties, bit_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .enumerate()
+            .map(|(j, p)| p.assign_position(j).unwrap())
+            .unzip();
+        let value_commitments: Vec<_> = bit_commitments.iter().map(|vc| vc.V_j).collect();
+
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
+
+        let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&bit_challenge))
+            .unzip();
+
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments).unwrap();
+
+        let proof_shares: Vec<_> = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&poly_challenge).unwrap())
+            .collect();
+
+        // The dealer verifies the aggregated proof before returning it
+        let proof = dealer.receive_shares(&proof_shares).unwrap();
+
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+            .is_ok());
+
+        // The proof does not verify as a proof for four parties
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        let mut padded_commitments = value_commitments.clone();
+        padded_commitments.push(CompressedRistretto::identity());
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &padded_commitments, n)
+            .is_err());
+    }
+
+    #[test]
+    fn create_and_verify_extended() {
+        let bp_gens = BulletproofGens::new(64, 4);
+        let mut rng = rand::thread_rng();
+
+        for extension_degree in 1..=3 {
+            let pc_gens = ExtendedPedersenGens::new(extension_degree).unwrap();
+            for &m in &[1, 3] {
+                let values: Vec<u128> = (0..m).map(|i| 1_000_000 + i as u128).collect();
+                let blindings: Vec<Vec<Scalar>> = (0..m)
+                    .map(|_| {
+                        (0..extension_degree)
+                            .map(|_| Scalar::random(&mut rng))
+                            .collect()
+                    })
+                    .collect();
+
+                let mut transcript = Transcript::new(b"ExtendedRangeProofTest");
+                let (proof, value_commitments) = RangeProof::prove_multiple_extended(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &values,
+                    &blindings,
+                    32,
+                )
+                .unwrap();
+                assert_eq!(proof.extension_degree(), extension_degree);
+                for ((V, &v), v_blinding) in value_commitments.iter().zip(&values).zip(&blindings) {
+                    assert_eq!(pc_gens.commit(v.into(), v_blinding).unwrap().compress(), *V);
+                }
+
+                let bytes = proof.to_bytes();
+                let header_size = if extension_degree > 1 { 40 } else { 0 };
+                assert_eq!(
+                    bytes.len(),
+                    header_size
+                        + (5 + 2 * extension_degree) * 32
+                        + proof.ipp_proof.serialized_size()
+                );
+                assert_eq!(bytes.len(), proof.serialized_size());
+                let proof =
+                    RangeProof::from_bytes_with_extension_degree(&bytes, extension_degree).unwrap();
+                // The extension degree is part of the serde encoding
+                let proof: RangeProof =
+                    bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+                assert_eq!(proof.to_bytes(), bytes);
+
+                let mut transcript = Transcript::new(b"ExtendedRangeProofTest");
+                assert!(proof
+                    .verify_multiple_extended(
+                        &bp_gens,
+                        &pc_gens,
+                        &mut transcript,
+                        &value_commitments,
+                        32
+                    )
+                    .is_ok());
+            }
+        }
+
+        // The extension degrees of the generators, blinding factors and
+        // proof must match
+        let pc_gens = ExtendedPedersenGens::new(2).unwrap();
+        let blinding = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+        let mut transcript = Transcript::new(b"ExtendedRangeProofTest");
+        let (proof, V) = RangeProof::prove_single_extended(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            42,
+            &blinding,
+            8,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"ExtendedRangeProofTest");
+        assert_eq!(
+            proof.verify_single_extended(
+                &bp_gens,
+                &ExtendedPedersenGens::new(3).unwrap(),
+                &mut transcript,
+                &V,
+                8
+            ),
+            Err(ProofError::InvalidExtensionDegree)
+        );
+        let mut transcript = Transcript::new(b"ExtendedRangeProofTest");
+        assert_eq!(
+            proof.verify_single(&bp_gens, &PedersenGens::default(), &mut transcript, &V, 8),
+            Err(ProofError::InvalidExtensionDegree)
+        );
+        let mut transcript = Transcript::new(b"ExtendedRangeProofTest");
+        assert_eq!(
+            RangeProof::prove_single_extended(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                42,
+                &blinding[..1],
+                8,
+            )
+            .unwrap_err(),
+            ProofError::InvalidExtensionDegree
+        );
+        assert_eq!(
+            RangeProof::from_bytes_with_extension_degree(&proof.to_bytes(), 0).unwrap_err(),
+            ProofError::InvalidExtensionDegree
+        );
+        for extension_degree in &[1, 3] {
+            assert_eq!(
+                RangeProof::from_bytes_with_extension_degree(&proof.to_bytes(), *extension_degree)
+                    .unwrap_err(),
+                ProofError::FormatError
+            );
+        }
+    }
+
+    #[test]
+    fn rewind_extended() {
+        let pc_gens = ExtendedPedersenGens::new(3).unwrap();
+        let bp_gens = BulletproofGens::new(64, 1);
+        let mut rng = rand::thread_rng();
+
+        let keys = RewindKeys::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let message = ProofMessage::new(&[7u8; 77]);
+        let blinding: Vec<Scalar> = (0..3).map(|_| Scalar::random(&mut rng)).collect();
+
+        let mut transcript = Transcript::new(b"ExtendedRewindTest");
+        let (proof, V) = RangeProof::prove_single_rewindable_extended(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            123456789,
+            &blinding,
+            64,
+            &keys,
+            &message,
+            &Blake2bNonceGenerator,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"ExtendedRewindTest");
+        assert!(proof
+            .verify_single_extended(&bp_gens, &pc_gens, &mut transcript, &V, 64)
+            .is_ok());
+
+        let mut transcript = Transcript::new(b"ExtendedRewindTest");
+        assert_eq!(
+            proof.rewind_single_extended_get_commitment_data_with_keys(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &V,
+                64,
+                &keys,
+                &Blake2bNonceGenerator,
+            ),
+            Ok((123456789, blinding, message.clone()))
+        );
+
+        let mut transcript = Transcript::new(b"ExtendedRewindTest");
+        assert_eq!(
+            proof.rewind_single_get_value_only_with_keys(
+                &bp_gens,
+                &mut transcript,
+                &V,
+                64,
+                &keys.public_keys(),
+                &Blake2bNonceGenerator,
+            ),
+            Ok((123456789, message))
+        );
+
+        // Rewinding with generators of another extension degree fails
+        let mut transcript = Transcript::new(b"ExtendedRewindTest");
+        assert_eq!(
+            proof
+                .rewind_single_get_commitment_data_with_keys(
+                    &bp_gens,
+                    &PedersenGens::default(),
+                    &mut transcript,
+                    &V,
+                    64,
+                    &keys,
+                    &Blake2bNonceGenerator,
+                )
+                .unwrap_err(),
+            ProofError::InvalidExtensionDegree
+        );
     }
-}
 
-impl Serialize for RangeProof {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_bytes(&self.to_bytes()[..])
-    }
-}
+    #[test]
+    fn aggregation_with_extended_commitments() {
+        use self::dealer::*;
+        use self::messages::*;
+        use self::party::*;
 
-impl<'de> Deserialize<'de> for RangeProof {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct RangeProofVisitor;
+        let m = 3;
+        let n = 16;
 
-        impl<'de> Visitor<'de> for RangeProofVisitor {
-            type Value = RangeProof;
+        let pc_gens = ExtendedPedersenGens::new(2).unwrap();
+        let bp_gens = BulletproofGens::new(n, 4);
 
-            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                formatter.write_str("a valid RangeProof")
-            }
+        use self::rand::Rng;
+        let mut rng = rand::thread_rng();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
 
-            fn visit_bytes<E>(self, v: &[u8]) -> Result<RangeProof, E>
-            where
-                E: serde::de::Error,
-            {
-                // Using Error::custom requires T: Display, which our error
-                // type only implements when it implements std::error::Error.
-                #[cfg(feature = "std")]
-                return RangeProof::from_bytes(v).map_err(serde::de::Error::custom);
-                // In no-std contexts, drop the error message.
-                #[cfg(not(feature = "std"))]
-                return RangeProof::from_bytes(v)
-                    .map_err(|_| serde::de::Error::custom("deserialization error"));
-            }
-        }
+        // A party needs one blinding factor per extension degree
+        assert_eq!(
+            Party::new_extended(&bp_gens, &pc_gens, 1, &[Scalar::one()], n).err(),
+            Some(crate::errors::MPCError::InvalidExtensionDegree)
+        );
 
-        deserializer.deserialize_bytes(RangeProofVisitor)
-    }
-}
+        let parties: Vec<_> = (0..m)
+            .map(|_| {
+                let v = rng.gen::<u16>() as u128;
+                let v_blinding = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+                Party::new_extended(&bp_gens, &pc_gens, v, &v_blinding, n).unwrap()
+            })
+            .collect();
 
-/// Compute
-/// \\[
-/// \delta(y,z) = (z - z^{2}) \langle \mathbf{1}, {\mathbf{y}}^{n \cdot m} \rangle - \sum_{j=0}^{m-1} z^{j+3} \cdot \langle \mathbf{1}, {\mathbf{2}}^{n \cdot m} \rangle
-/// \\]
-fn delta(n: usize, m: usize, y: &Scalar, z: &Scalar) -> Scalar {
-    let sum_y = util::sum_of_powers(y, n * m);
-    let sum_2 = util::sum_of_powers(&Scalar::from(2u64), n);
-    let sum_z = util::sum_of_powers(z, m);
+        let dealer = Dealer::new_extended(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
 
-    (z - z * z) * sum_y - z * z * z * sum_2 * sum_z
-}
+        let (parties, bit_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .enumerate()
+            .map(|(j, p)| p.assign_position(j).unwrap())
+            .unzip();
+        let value_commitments: Vec<_> = bit_commitments.iter().map(|vc| vc.V_j).collect();
 
-/// Calculate a rewind nonce from a private key and the value commitment.
-pub fn get_rewind_nonce_from_pvt_key(pvt_key: &Scalar, commitment: &CompressedRistretto) -> Scalar {
-    let pub_key = (pvt_key * &RISTRETTO_BASEPOINT_TABLE).compress();
-    get_rewind_nonce_from_pub_key(&pub_key, commitment)
-}
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
 
-/// Calculate a rewind nonce from a public key and the value commitment.
-pub fn get_rewind_nonce_from_pub_key(
-    pub_key: &CompressedRistretto,
-    commitment: &CompressedRistretto,
-) -> Scalar {
-    let rewind_nonce_initial =
-        Blake2b::with_params(&pub_key.to_bytes().as_ref(), &[], "Rewind sep 1".as_bytes())
-            .finalize();
-    let rewind_nonce_data = [
-        rewind_nonce_initial.to_vec().as_slice(),
-        commitment.to_bytes().as_ref(),
-    ]
-    .concat();
-    let rewind_nonce_final = Blake2b::with_params(
-        &Blake2b::digest(&rewind_nonce_data),
-        &[],
-        "Rewind sep 2".as_bytes(),
-    );
-    Scalar::from_hash(rewind_nonce_final)
-}
+        let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&bit_challenge))
+            .unzip();
 
-/// Calculate a secret nonce from a private key and the value commitment.
-pub fn get_secret_nonce_from_pvt_key(pvt_key: &Scalar, commitment: &CompressedRistretto) -> Scalar {
-    let secret_nonce_initial =
-        Blake2b::with_params(&pvt_key.to_bytes().as_ref(), &[], "Secret sep 1".as_bytes())
-            .finalize();
-    let secret_nonce_data = [
-        secret_nonce_initial.to_vec().as_slice(),
-        commitment.to_bytes().as_ref(),
-    ]
-    .concat();
-    let secret_nonce_final = Blake2b::with_params(
-        &Blake2b::digest(&secret_nonce_data),
-        &[],
-        "Secret sep 2".as_bytes(),
-    );
-    Scalar::from_hash(secret_nonce_final)
-}
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let proof_shares: Vec<_> = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&poly_challenge).unwrap())
+            .collect();
 
-    use crate::generators::PedersenGens;
+        // Extended shares are sent as `ExtendedProofShare`s
+        assert!(bincode::serialize(&proof_shares[0]).is_err());
+        let proof_shares: Vec<ProofShare> = proof_shares
+            .into_iter()
+            .map(|share| {
+                let bytes = bincode::serialize(&ExtendedProofShare(share)).unwrap();
+                bincode::deserialize::<ExtendedProofShare>(&bytes)
+                    .unwrap()
+                    .0
+            })
+            .collect();
 
-    #[test]
-    fn test_delta() {
-        let mut rng = rand::thread_rng();
-        let y = Scalar::random(&mut rng);
-        let z = Scalar::random(&mut rng);
+        // The dealer verifies the aggregated proof before returning it
+        let proof = dealer.receive_shares(&proof_shares).unwrap();
 
-        // Choose n = 256 to ensure we overflow the group order during
-        // the computation, to check that that's done correctly
-        let n = 256;
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        assert!(proof
+            .verify_multiple_extended(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+            .is_ok());
+    }
 
-        // code copied from previous implementation
-        let z2 = z * z;
-        let z3 = z2 * z;
-        let mut power_g = Scalar::zero();
-        let mut exp_y = Scalar::one(); // start at y^0 = 1
-        let mut exp_2 = Scalar::one(); // start at 2^0 = 1
-        for _ in 0..n {
-            power_g += (z - z2) * exp_y - z3 * exp_2;
+    #[test]
+    fn plain_proof_shares_keep_their_serialization() {
+        use self::dealer::*;
+        use self::messages::*;
+        use self::party::*;
 
-            exp_y = exp_y * y; // y^i -> y^(i+1)
-            exp_2 = exp_2 + exp_2; // 2^i -> 2^(i+1)
+        /// The layout of a proof share before extended commitments.
+        #[derive(Serialize)]
+        struct PlainProofShare {
+            t_x: Scalar,
+            t_x_blinding: Scalar,
+            e_blinding: Scalar,
+            l_vec: Vec<Scalar>,
+            r_vec: Vec<Scalar>,
         }
 
-        assert_eq!(power_g, delta(n, 1, &y, &z),);
-    }
+        let m = 2;
+        let n = 8;
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, m);
+        let mut rng = rand::thread_rng();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
 
-    /// Given a bitsize `n`, test the following:
-    ///
-    /// 1. Generate `m` random values and create a proof they are all in range;
-    /// 2. Serialize to wire format;
-    /// 3. Deserialize from wire format;
-    /// 4. Verify the proof.
-    fn singleparty_create_and_verify_helper(n: usize, m: usize) {
-        // Split the test into two scopes, so that it's explicit what
-        // data is shared between the prover and the verifier.
+        let parties: Vec<_> = (0..m)
+            .map(|v| {
+                let v_blinding = [Scalar::random(&mut rng)];
+                Party::new_extended(&bp_gens, &pc_gens.into(), v as u128, &v_blinding, n).unwrap()
+            })
+            .collect();
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+        let (parties, bit_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .enumerate()
+            .map(|(j, p)| p.assign_position(j).unwrap())
+            .unzip();
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
+        let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&bit_challenge))
+            .unzip();
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments).unwrap();
 
-        // Use bincode for serialization
-        //use bincode; // already present in lib.rs
+        // The shares serialize with a single scalar per blinding factor
+        let proof_shares: Vec<ProofShare> = parties
+            .into_iter()
+            .map(|p| {
+                let share = p.apply_challenge(&poly_challenge).unwrap();
+                let bytes = bincode::serialize(&share).unwrap();
+                let plain_share = PlainProofShare {
+                    t_x: share.t_x,
+                    t_x_blinding: share.t_x_blinding[0],
+                    e_blinding: share.e_blinding[0],
+                    l_vec: share.l_vec.clone(),
+                    r_vec: share.r_vec.clone(),
+                };
+                assert_eq!(bytes, bincode::serialize(&plain_share).unwrap());
+                bincode::deserialize(&bytes).unwrap()
+            })
+            .collect();
 
-        // Both prover and verifier have access to the generators and the proof
-        let max_bitsize = 64;
-        let max_parties = 8;
-        let pc_gens = PedersenGens::default();
-        let bp_gens = BulletproofGens::new(max_bitsize, max_parties);
+        assert!(dealer.receive_shares(&proof_shares).is_ok());
+    }
 
-        // Prover's scope
-        let (proof_bytes, value_commitments) = {
-            use self::rand::Rng;
-            let mut rng = rand::thread_rng();
+    #[test]
+    fn create_and_verify_with_value_bases() {
+        let n = 32;
+        let pc_gens = ExtendedPedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, 4);
+        let mut rng = rand::thread_rng();
 
-            // 0. Create witness data
-            let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
-            let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
-            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+        // Two assets, the first of which is also spent under a blinded tag
+        let asset_a = ExtendedPedersenGens::asset_value_base(b"asset A");
+        let asset_b = ExtendedPedersenGens::asset_value_base(b"asset B");
+        let tag_blinding = Scalar::random(&mut rng);
+        let value_bases = [
+            asset_a,
+            asset_b,
+            pc_gens.blind_value_base(&asset_a, &tag_blinding),
+        ];
+        let values = [7u128, 1 << 31, 4242];
+        let blindings: Vec<Vec<Scalar>> = (0..3).map(|_| vec![Scalar::random(&mut rng)]).collect();
+
+        let mut transcript = Transcript::new(b"ValueBasesTest");
+        let (proof, value_commitments) = RangeProof::prove_multiple_with_value_bases(
+            &bp_gens,
+            &pc_gens,
+            &value_bases,
+            &mut transcript,
+            &values,
+            &blindings,
+            n,
+        )
+        .unwrap();
 
-            // 1. Create the proof
-            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
-            let (proof, value_commitments) = RangeProof::prove_multiple(
+        // The blinded tag commits to the value under the asset's base
+        let blinding = blindings[2][0] + Scalar::from(values[2]) * tag_blinding;
+        assert_eq!(
+            value_commitments[2],
+            pc_gens
+                .with_value_base(asset_a)
+                .commit(Scalar::from(values[2]), &[blinding])
+                .unwrap()
+                .compress()
+        );
+
+        let proof = RangeProof::from_bytes_with_value_bases(&proof.to_bytes(), 1, 3).unwrap();
+        let mut transcript = Transcript::new(b"ValueBasesTest");
+        assert!(proof
+            .verify_multiple_with_value_bases(
                 &bp_gens,
                 &pc_gens,
+                &value_bases,
                 &mut transcript,
-                &values,
-                &blindings,
-                n,
+                &value_commitments,
+                n
             )
-            .unwrap();
+            .is_ok());
 
-            // 2. Return serialized proof and value commitments
-            (bincode::serialize(&proof).unwrap(), value_commitments)
-        };
+        // The values must be proven under the bases they were committed with
+        let swapped_bases = [value_bases[1], value_bases[0], value_bases[2]];
+        let mut transcript = Transcript::new(b"ValueBasesTest");
+        assert_eq!(
+            proof.verify_multiple_with_value_bases(
+                &bp_gens,
+                &pc_gens,
+                &swapped_bases,
+                &mut transcript,
+                &value_commitments,
+                n
+            ),
+            Err(ProofError::VerificationError)
+        );
 
-        // Verifier's scope
-        {
-            // 3. Deserialize
-            let proof: RangeProof = bincode::deserialize(&proof_bytes).unwrap();
+        let mut transcript = Transcript::new(b"ValueBasesTest");
+        assert_eq!(
+            proof.verify_multiple_extended(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &value_commitments,
+                n
+            ),
+            Err(ProofError::InvalidValueBases)
+        );
+    }
 
-            // 4. Verify with the same customization label as above
-            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+    #[test]
+    fn value_base_proofs_round_trip() {
+        let n = 16;
+        let bp_gens = BulletproofGens::new(n, 4);
+        let mut rng = rand::thread_rng();
 
-            assert!(proof
-                .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
-                .is_ok());
+        for extension_degree in 1..=2 {
+            let pc_gens = ExtendedPedersenGens::new(extension_degree).unwrap();
+            // An even number of shares reads as whole points of the
+            // inner product proof if the encoding does not count them
+            for &m in &[1, 2, 4] {
+                let value_bases: Vec<RistrettoPoint> = (0..m)
+                    .map(|i| ExtendedPedersenGens::asset_value_base(&[i as u8]))
+                    .collect();
+                let values: Vec<u128> = (0..m).map(|i| 1000 + i as u128).collect();
+                let blindings: Vec<Vec<Scalar>> = (0..m)
+                    .map(|_| {
+                        (0..extension_degree)
+                            .map(|_| Scalar::random(&mut rng))
+                            .collect()
+                    })
+                    .collect();
+
+                let mut transcript = Transcript::new(b"ValueBasesRoundTripTest");
+                let (proof, value_commitments) = RangeProof::prove_multiple_with_value_bases(
+                    &bp_gens,
+                    &pc_gens,
+                    &value_bases,
+                    &mut transcript,
+                    &values,
+                    &blindings,
+                    n,
+                )
+                .unwrap();
+
+                let bytes = proof.to_bytes();
+                assert_eq!(bytes.len(), proof.serialized_size());
+                let from_bytes =
+                    RangeProof::from_bytes_with_value_bases(&bytes, extension_degree, m).unwrap();
+                let from_serde: RangeProof =
+                    bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+                for proof in &[from_bytes, from_serde] {
+                    assert_eq!(proof.to_bytes(), bytes);
+                    let mut transcript = Transcript::new(b"ValueBasesRoundTripTest");
+                    assert!(proof
+                        .verify_multiple_with_value_bases(
+                            &bp_gens,
+                            &pc_gens,
+                            &value_bases,
+                            &mut transcript,
+                            &value_commitments,
+                            n
+                        )
+                        .is_ok());
+                }
+
+                // Readers expecting another shape of proof reject it
+                assert_eq!(
+                    RangeProof::from_bytes_with_value_bases(&bytes, extension_degree, 0)
+                        .unwrap_err(),
+                    ProofError::FormatError
+                );
+                assert_eq!(
+                    RangeProof::from_bytes_with_value_bases(&bytes, extension_degree, m + 1)
+                        .unwrap_err(),
+                    ProofError::FormatError
+                );
+                assert_eq!(
+                    RangeProof::from_bytes(&bytes).unwrap_err(),
+                    ProofError::FormatError
+                );
+            }
         }
     }
 
     #[test]
-    fn create_and_verify_n_32_m_1() {
-        singleparty_create_and_verify_helper(32, 1);
-    }
+    fn aggregation_with_value_bases_detects_wrong_base() {
+        use self::dealer::*;
+        use self::party::*;
 
-    #[test]
-    fn create_and_verify_n_32_m_2() {
-        singleparty_create_and_verify_helper(32, 2);
-    }
+        use crate::errors::MPCError;
 
-    #[test]
-    fn create_and_verify_n_32_m_4() {
-        singleparty_create_and_verify_helper(32, 4);
-    }
+        let m = 3;
+        let n = 16;
 
-    #[test]
-    fn create_and_verify_n_32_m_8() {
-        singleparty_create_and_verify_helper(32, 8);
-    }
+        let pc_gens = ExtendedPedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, 4);
+        let value_bases: Vec<_> = [b"asset A", b"asset B", b"asset C"]
+            .iter()
+            .map(|asset_id| ExtendedPedersenGens::asset_value_base(*asset_id))
+            .collect();
 
-    #[test]
-    fn create_and_verify_n_64_m_1() {
-        singleparty_create_and_verify_helper(64, 1);
-    }
+        let mut rng = rand::thread_rng();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
 
-    #[test]
-    fn create_and_verify_n_64_m_2() {
-        singleparty_create_and_verify_helper(64, 2);
-    }
+        // The party at position 1 commits with the value base of another asset
+        let parties: Vec<_> = (0..m)
+            .map(|j| {
+                let value_base = value_bases[if j == 1 { 2 } else { j }];
+                let v_blinding = [Scalar::random(&mut rng)];
+                Party::new_with_value_base(&bp_gens, &pc_gens, value_base, 1000, &v_blinding, n)
+                    .unwrap()
+            })
+            .collect();
 
-    #[test]
-    fn create_and_verify_n_64_m_4() {
-        singleparty_create_and_verify_helper(64, 4);
+        let dealer =
+            Dealer::new_with_value_bases(&bp_gens, &pc_gens, &value_bases, &mut transcript, n)
+                .unwrap();
+
+        let (parties, bit_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .enumerate()
+            .map(|(j, p)| p.assign_position(j).unwrap())
+            .unzip();
+
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
+
+        let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&bit_challenge))
+            .unzip();
+
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments).unwrap();
+
+        let proof_shares: Vec<_> = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&poly_challenge).unwrap())
+            .collect();
+
+        match dealer.receive_shares(&proof_shares) {
+            Err(MPCError::MalformedProofShares { bad_shares }) => {
+                assert_eq!(bad_shares, vec![1]);
+            }
+            Err(_) => panic!("Got wrong error type from malformed shares"),
+            Ok(_) => panic!("The proof was malformed, but it was not detected"),
+        }
     }
 
     #[test]
-    fn create_and_verify_n_64_m_8() {
-        singleparty_create_and_verify_helper(64, 8);
+    fn party_nonces_are_hedged_with_the_witness() {
+        use self::dealer::*;
+        use self::party::*;
+
+        let n = 16;
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, 2);
+
+        // Runs the protocol between a dealer and two parties which draw
+        // their nonces from a broken RNG
+        let aggregate = |values: [u64; 2], blindings: [Scalar; 2]| {
+            let mut transcript = Transcript::new(b"HedgedPartyTest");
+            let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, 2).unwrap();
+            let (parties, bit_commitments): (Vec<_>, Vec<_>) = (0..2)
+                .map(|j| {
+                    Party::new_extended(
+                        &bp_gens,
+                        &pc_gens.into(),
+                        values[j].into(),
+                        &blindings[j..j + 1],
+                        n,
+                    )
+                    .unwrap()
+                    .assign_position_with_rng(j, &mut ZeroRng)
+                    .unwrap()
+                })
+                .unzip();
+            let (dealer, bit_challenge) = dealer
+                .receive_bit_commitments(bit_commitments.clone())
+                .unwrap();
+            let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+                .into_iter()
+                .map(|p| p.apply_challenge_with_rng(&bit_challenge, &mut ZeroRng))
+                .unzip();
+            let (dealer, poly_challenge) =
+                dealer.receive_poly_commitments(poly_commitments).unwrap();
+            let shares: Vec<_> = parties
+                .into_iter()
+                .map(|p| p.apply_challenge(&poly_challenge).unwrap())
+                .collect();
+            let proof = dealer.receive_shares(&shares).unwrap();
+            (
+                bit_commitments,
+                bit_challenge,
+                poly_challenge,
+                shares,
+                proof,
+            )
+        };
+
+        let values = [5, 5];
+        let blindings = [Scalar::from(7u64), Scalar::from(8u64)];
+        let (bit_commitments, bit_challenge, poly_challenge, shares, proof) =
+            aggregate(values, blindings);
+
+        let mut transcript = Transcript::new(b"HedgedPartyTest");
+        let value_commitments: Vec<_> = bit_commitments.iter().map(|c| c.V_j).collect();
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+            .is_ok());
+
+        // Without hedging, every nonce of a party would be the scalar
+        // drawn from the broken RNG, and its blinding factor could be
+        // solved for from its share of t_x_blinding
+        let nonce = Scalar::random(&mut ZeroRng);
+        let (x, z) = (poly_challenge.x, bit_challenge.z);
+        for (j, share) in shares.iter().enumerate() {
+            let zz = z * z * util::scalar_exp_vartime(&z, j as u64);
+            let recovered = (share.t_x_blinding[0] - x * nonce - x * x * nonce) * zz.invert();
+            assert_ne!(recovered, blindings[j]);
+        }
+
+        // The nonces differ between positions, and between witnesses
+        assert_ne!(bit_commitments[0].S_j, bit_commitments[1].S_j);
+        let (other_bit_commitments, ..) = aggregate([5, 6], blindings);
+        assert_eq!(bit_commitments[0].S_j, other_bit_commitments[0].S_j);
+        assert_ne!(bit_commitments[1].S_j, other_bit_commitments[1].S_j);
+        let (other_bit_commitments, ..) = aggregate(values, [blindings[0], Scalar::from(9u64)]);
+        assert_ne!(bit_commitments[1].S_j, other_bit_commitments[1].S_j);
     }
 
     #[test]
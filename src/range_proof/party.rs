@@ -14,21 +14,28 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use core::iter;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::MultiscalarMul;
+use merlin::{Transcript, TranscriptRng};
 use rand_core::{CryptoRng, RngCore};
 use zeroize::Zeroize;
 
 use crate::errors::MPCError;
-use crate::generators::{BulletproofGens, PedersenGens};
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+use crate::group::{Group, Ristretto, ScalarField};
+use crate::msm;
+use crate::transcript::TranscriptProtocol;
 use crate::util;
 
 #[cfg(feature = "std")]
 use rand::thread_rng;
 
 use super::messages::*;
-use crate::range_proof::{get_rewind_nonce_from_pvt_key, get_secret_nonce_from_pvt_key};
+use super::rewind::{
+    add_rewind_tag, derive_blinding_vectors, derive_extension_nonces, embed_message,
+    rewind_value_bytes, Blake2bNonceGenerator, NonceGenerator, ProofMessage, RewindKeys,
+    RewindNonces,
+};
+use crate::range_proof::padded_bitsize;
 use crate::util::{add_bytes_to_word, xor_32_bytes};
 
 /// Used to construct a party for the aggregated rangeproof MPC protocol.
@@ -36,6 +43,13 @@ pub struct Party {}
 
 impl Party {
     /// Constructs a `PartyAwaitingPosition` with the given rangeproof parameters.
+    ///
+    /// A party with rewind keys must be the only party in the
+    /// aggregation, since the proof of several parties cannot be
+    /// rewound, see
+    /// [`RangeProof::rewind_single_get_commitment_data`](crate::RangeProof::rewind_single_get_commitment_data).
+    /// Assigning it any position but the first fails with
+    /// [`MPCError::InvalidRewindableAggregation`].
     pub fn new<'a>(
         bp_gens: &'a BulletproofGens,
         pc_gens: &'a PedersenGens,
@@ -46,30 +60,168 @@ impl Party {
         pvt_blinding_key: Scalar,
         proof_message: Scalar,
     ) -> Result<PartyAwaitingPosition<'a>, MPCError> {
-        if !(n == 8 || n == 16 || n == 32 || n == 64) {
-            return Err(MPCError::InvalidBitsize);
+        Party::new_u128(
+            bp_gens,
+            pc_gens,
+            v.into(),
+            v_blinding,
+            n,
+            pvt_rewind_key,
+            pvt_blinding_key,
+            proof_message,
+        )
+    }
+
+    /// Constructs a `PartyAwaitingPosition` with the given rangeproof
+    /// parameters for a 128-bit value, which allows bitsizes `n` up to 128.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_u128<'a>(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        v: u128,
+        v_blinding: Scalar,
+        n: usize,
+        pvt_rewind_key: Scalar,
+        pvt_blinding_key: Scalar,
+        proof_message: Scalar,
+    ) -> Result<PartyAwaitingPosition<'a>, MPCError> {
+        let mut party = Party::new_internal(
+            bp_gens,
+            ExtendedPedersenGens::from(*pc_gens),
+            v,
+            alloc::vec![v_blinding],
+            n,
+        )?;
+        if pvt_rewind_key != Scalar::default() {
+            let keys = RewindKeys::new(pvt_rewind_key, pvt_blinding_key);
+            let nonces = keys.nonces(&Blake2bNonceGenerator, &party.V);
+            party.rewind = RewindBlindings::new(
+                &party,
+                &nonces,
+                [proof_message, Scalar::zero(), Scalar::zero()],
+            );
         }
-        if bp_gens.gens_capacity < n {
+        Ok(party)
+    }
+
+    /// Constructs a `PartyAwaitingPosition` with the given rangeproof
+    /// parameters for a value in an extended Pedersen commitment, with
+    /// one blinding factor per extension degree of the `pc_gens`.
+    pub fn new_extended<'a, G: Group>(
+        bp_gens: &'a BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        v: u128,
+        v_blinding: &[G::Scalar],
+        n: usize,
+    ) -> Result<PartyAwaitingPosition<'a, G>, MPCError> {
+        Party::new_internal(bp_gens, pc_gens.clone(), v, v_blinding.to_vec(), n)
+    }
+
+    /// Constructs a `PartyAwaitingPosition` with the given rangeproof
+    /// parameters for a value committed to with its own `value_base`,
+    /// such as the value base of an asset or a blinded asset tag,
+    /// instead of the `B` of the `pc_gens`.
+    ///
+    /// The dealer must be created with
+    /// [`Dealer::new_with_value_bases`](super::dealer::Dealer::new_with_value_bases),
+    /// holding the same value base at the position of this party.
+    pub fn new_with_value_base<'a, G: Group>(
+        bp_gens: &'a BulletproofGens<G>,
+        pc_gens: &ExtendedPedersenGens<G>,
+        value_base: G::Point,
+        v: u128,
+        v_blinding: &[G::Scalar],
+        n: usize,
+    ) -> Result<PartyAwaitingPosition<'a, G>, MPCError> {
+        Party::new_internal(
+            bp_gens,
+            pc_gens.with_value_base(value_base),
+            v,
+            v_blinding.to_vec(),
+            n,
+        )
+    }
+
+    /// Constructs a `PartyAwaitingPosition` whose proof can be rewound
+    /// with the given `rewind_keys`, embedding the `proof_message`.
+    ///
+    /// The nonces which make the proof rewindable are derived with the
+    /// given `nonce_generator`; the same generator must be used to
+    /// rewind the proof.  The message must not be longer than
+    /// [`ProofMessage::capacity`] for the bitsize `n`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_rewind_keys<'a, G: NonceGenerator>(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        v: u128,
+        v_blinding: Scalar,
+        n: usize,
+        rewind_keys: &RewindKeys,
+        proof_message: &ProofMessage,
+        nonce_generator: &G,
+    ) -> Result<PartyAwaitingPosition<'a>, MPCError> {
+        Party::new_extended_with_rewind_keys(
+            bp_gens,
+            &ExtendedPedersenGens::from(*pc_gens),
+            v,
+            &[v_blinding],
+            n,
+            rewind_keys,
+            proof_message,
+            nonce_generator,
+        )
+    }
+
+    /// Constructs a `PartyAwaitingPosition` for a value in an extended
+    /// Pedersen commitment whose proof can be rewound with the given
+    /// `rewind_keys`, embedding the `proof_message`.
+    ///
+    /// The blinding factors of \\(T\_1\\) and \\(T\_2\\) beyond the
+    /// first are derived from the secret nonces, so that all of the
+    /// blinding factors of the value can be recovered when rewinding.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_extended_with_rewind_keys<'a, G: NonceGenerator>(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &ExtendedPedersenGens,
+        v: u128,
+        v_blinding: &[Scalar],
+        n: usize,
+        rewind_keys: &RewindKeys,
+        proof_message: &ProofMessage,
+        nonce_generator: &G,
+    ) -> Result<PartyAwaitingPosition<'a>, MPCError> {
+        let words = embed_message(n, proof_message).ok_or(MPCError::InvalidProofMessageLength)?;
+        let mut party = Party::new_internal(bp_gens, pc_gens.clone(), v, v_blinding.to_vec(), n)?;
+        let nonces = rewind_keys.nonces(nonce_generator, &party.V);
+        // The tag depends on the value commitment through the nonces
+        let word = add_rewind_tag(n, words[0], &nonces.rewind_nonce_1, &nonces.rewind_nonce_2);
+        party.rewind = RewindBlindings::new(
+            &party,
+            &nonces,
+            [
+                Scalar::from_bits(word),
+                Scalar::from_bits(words[1]),
+                Scalar::from_bits(words[2]),
+            ],
+        );
+        Ok(party)
+    }
+
+    fn new_internal<'a, G: Group>(
+        bp_gens: &'a BulletproofGens<G>,
+        pc_gens: ExtendedPedersenGens<G>,
+        v: u128,
+        v_blinding: Vec<G::Scalar>,
+        n: usize,
+    ) -> Result<PartyAwaitingPosition<'a, G>, MPCError> {
+        let padded_n = padded_bitsize(n).ok_or(MPCError::InvalidBitsize)?;
+        if bp_gens.gens_capacity < padded_n {
             return Err(MPCError::InvalidGeneratorsLength);
         }
 
-        let V = pc_gens.commit(v.into(), v_blinding).compress();
-        let (rewind_nonce_1, rewind_nonce_2, blinding_nonce_1, blinding_nonce_2) =
-            if pvt_rewind_key == Scalar::default() {
-                (
-                    Scalar::default(),
-                    Scalar::default(),
-                    Scalar::default(),
-                    Scalar::default(),
-                )
-            } else {
-                (
-                    get_rewind_nonce_from_pvt_key(&pvt_rewind_key, &V),
-                    get_rewind_nonce_from_pvt_key(&pvt_blinding_key, &V),
-                    get_secret_nonce_from_pvt_key(&pvt_rewind_key, &V),
-                    get_secret_nonce_from_pvt_key(&pvt_blinding_key, &V),
-                )
-            };
+        let V = pc_gens
+            .commit(util::scalar_from_u128(v), &v_blinding)
+            .map_err(|_| MPCError::InvalidExtensionDegree)?;
 
         Ok(PartyAwaitingPosition {
             bp_gens,
@@ -77,95 +229,198 @@ impl Party {
             n,
             v,
             v_blinding,
-            V,
-            rewind_nonce_1,
-            rewind_nonce_2,
-            blinding_nonce_1,
-            blinding_nonce_2,
-            proof_message,
+            V: G::compress(&V),
+            rewind: RewindBlindings::default(),
         })
     }
 }
 
+/// The blinding factors of a rewindable party, which are derived from
+/// its rewind nonces instead of drawn at random, so that the value,
+/// message and blinding factors can be recovered from the proof.
+///
+/// Each blinding factor is only derived if its nonce is not zero.
+struct RewindBlindings<G: Group> {
+    /// The first blinding factor of \\(A\\)
+    a_blinding: Option<G::Scalar>,
+    /// The first blinding factor of \\(S\\), and the vectors
+    /// \\(\mathbf{s}\_L, \mathbf{s}\_R\\)
+    #[allow(clippy::type_complexity)]
+    s_blinding: Option<(G::Scalar, Vec<G::Scalar>, Vec<G::Scalar>)>,
+    /// The blinding factors of \\(T\_1\\)
+    t_1_blinding: Option<Vec<G::Scalar>>,
+    /// The blinding factors of \\(T\_2\\)
+    t_2_blinding: Option<Vec<G::Scalar>>,
+}
+
+impl<G: Group> Default for RewindBlindings<G> {
+    fn default() -> Self {
+        RewindBlindings {
+            a_blinding: None,
+            s_blinding: None,
+            t_1_blinding: None,
+            t_2_blinding: None,
+        }
+    }
+}
+
+impl RewindBlindings<Ristretto> {
+    /// Derives the blinding factors of the `party` from its rewind
+    /// `nonces`, embedding the value and the `proof_message`.
+    fn new(
+        party: &PartyAwaitingPosition,
+        nonces: &RewindNonces,
+        proof_message: [Scalar; 3],
+    ) -> Self {
+        let padded_n = party.n.next_power_of_two();
+        let extension_degree = party.pc_gens.extension_degree();
+        let nonzero = |nonce: &Scalar| *nonce != Scalar::default();
+
+        let s_blinding = Some(&nonces.rewind_nonce_2)
+            .filter(|n| nonzero(n))
+            .map(|nonce| {
+                // The value takes the first 8 bytes, or 16 bytes for
+                // bitsizes above 64, followed by the extra data
+                let value_bytes = rewind_value_bytes(party.n);
+                let value_and_extra_data = add_bytes_to_word(
+                    *proof_message[0].as_bytes(),
+                    &party.v.to_le_bytes()[..value_bytes],
+                    0,
+                );
+                let xor = xor_32_bytes(
+                    &Scalar::from_bits(value_and_extra_data).as_bytes(),
+                    &nonce.as_bytes(),
+                );
+                // The rest of the message is added to the first entries
+                let (mut s_L, mut s_R) = derive_blinding_vectors(nonce, padded_n);
+                s_L[0] += proof_message[1];
+                s_R[0] += proof_message[2];
+                (Scalar::from_bits(xor), s_L, s_R)
+            });
+
+        RewindBlindings {
+            a_blinding: Some(nonces.rewind_nonce_1).filter(nonzero),
+            s_blinding,
+            t_1_blinding: Some(&nonces.blinding_nonce_1)
+                .filter(|n| nonzero(n))
+                .map(|nonce| derive_extension_nonces(nonce, extension_degree)),
+            t_2_blinding: Some(&nonces.blinding_nonce_2)
+                .filter(|n| nonzero(n))
+                .map(|nonce| derive_extension_nonces(nonce, extension_degree)),
+        }
+    }
+}
+
+impl<G: Group> Drop for RewindBlindings<G> {
+    fn drop(&mut self) {
+        self.a_blinding.zeroize();
+        if let Some((s_blinding, s_L, s_R)) = self.s_blinding.as_mut() {
+            s_blinding.zeroize();
+            s_L.zeroize();
+            s_R.zeroize();
+        }
+        self.t_1_blinding.zeroize();
+        self.t_2_blinding.zeroize();
+    }
+}
+
 /// A party waiting for the dealer to assign their position in the aggregation.
-pub struct PartyAwaitingPosition<'a> {
-    bp_gens: &'a BulletproofGens,
-    pc_gens: &'a PedersenGens,
+pub struct PartyAwaitingPosition<'a, G: Group = Ristretto> {
+    bp_gens: &'a BulletproofGens<G>,
+    pc_gens: ExtendedPedersenGens<G>,
     n: usize,
-    v: u64,
-    v_blinding: Scalar,
-    V: CompressedRistretto,
-    rewind_nonce_1: Scalar,
-    rewind_nonce_2: Scalar,
-    blinding_nonce_1: Scalar,
-    blinding_nonce_2: Scalar,
-    proof_message: Scalar,
+    v: u128,
+    v_blinding: Vec<G::Scalar>,
+    V: G::CompressedPoint,
+    rewind: RewindBlindings<G>,
 }
 
-impl<'a> PartyAwaitingPosition<'a> {
+impl<'a, G: Group> PartyAwaitingPosition<'a, G> {
     /// Assigns a position in the aggregated proof to this party,
     /// allowing the party to commit to the bits of their value.
     #[cfg(feature = "std")]
     pub fn assign_position(
         self,
         j: usize,
-    ) -> Result<(PartyAwaitingBitChallenge<'a>, BitCommitment), MPCError> {
+    ) -> Result<(PartyAwaitingBitChallenge<G>, BitCommitment<G>), MPCError> {
         self.assign_position_with_rng(j, &mut thread_rng())
     }
 
     /// Assigns a position in the aggregated proof to this party,
     /// allowing the party to commit to the bits of their value.
+    ///
+    /// The nonces are drawn from `rng` hedged with the party's value
+    /// and blinding factors, so that a broken `rng` does not leak them.
+    ///
+    /// A party with rewind keys can only take the first position,
+    /// as the proof could not be rewound if it were aggregated.
     pub fn assign_position_with_rng<T: RngCore + CryptoRng>(
-        self,
+        mut self,
         j: usize,
         rng: &mut T,
-    ) -> Result<(PartyAwaitingBitChallenge<'a>, BitCommitment), MPCError> {
+    ) -> Result<(PartyAwaitingBitChallenge<G>, BitCommitment<G>), MPCError> {
         if self.bp_gens.party_capacity <= j {
             return Err(MPCError::InvalidGeneratorsLength);
         }
+        if j != 0 && self.rewind.a_blinding.is_some() {
+            return Err(MPCError::InvalidRewindableAggregation);
+        }
 
-        let bp_share = self.bp_gens.share(j);
+        let mut transcript = Transcript::new(b"RangeProofPartyNonces");
+        transcript.append_u64(b"j", j as u64);
+        transcript.append_group_point::<G>(b"V", &self.V);
+        let rng = &mut hedged_rng::<G, T>(transcript, self.v, &self.v_blinding, rng);
 
-        let a_blinding = if self.rewind_nonce_1 == Scalar::default() {
-            Scalar::random(rng)
-        } else {
-            self.rewind_nonce_1
-        };
-        // Compute A = <a_L, G> + <a_R, H> + a_blinding * B_blinding
-        let mut A = self.pc_gens.B_blinding * a_blinding;
+        let bp_share = self.bp_gens.share(j);
+        let padded_n = self.n.next_power_of_two();
+
+        let extension_degree = self.pc_gens.extension_degree();
+        // Only the first blinding factors of A and S carry rewind data
+        let a_blinding: Vec<G::Scalar> = iter::once(
+            self.rewind
+                .a_blinding
+                .unwrap_or_else(|| G::Scalar::random(rng)),
+        )
+        .chain((1..extension_degree).map(|_| G::Scalar::random(rng)))
+        .collect();
+        // Compute A = <a_L, G> + <a_R, H> + <a_blinding, B_blinding>
+        let mut A = G::multiscalar_mul(&a_blinding, &self.pc_gens.B_blinding);
 
         use subtle::{Choice, ConditionallySelectable};
         let mut i = 0;
-        for (G_i, H_i) in bp_share.G(self.n).zip(bp_share.H(self.n)) {
+        for (G_i, H_i) in bp_share.G(padded_n).zip(bp_share.H(padded_n)) {
             // If v_i = 0, we add a_L[i] * G[i] + a_R[i] * H[i] = - H[i]
             // If v_i = 1, we add a_L[i] * G[i] + a_R[i] * H[i] =   G[i]
-            let v_i = Choice::from(((self.v >> i) & 1) as u8);
-            let mut point = -H_i;
+            // The padding bits beyond n are always 0.
+            let v_i = Choice::from(bit(self.v, i, self.n) as u8);
+            let mut point = -*H_i;
             point.conditional_assign(G_i, v_i);
             A += point;
             i += 1;
         }
 
-        let s_blinding = if self.rewind_nonce_2 == Scalar::default() {
-            Scalar::random(rng)
-        } else {
-            let value_and_extra_data =
-                add_bytes_to_word(*self.proof_message.as_bytes(), &self.v.to_le_bytes(), 0);
-            let xor = xor_32_bytes(
-                &Scalar::from_bits(value_and_extra_data).as_bytes(),
-                &self.rewind_nonce_2.as_bytes(),
-            );
-            Scalar::from_bits(xor)
+        let (s_blinding_1, rewind_s_vecs) = match self.rewind.s_blinding.take() {
+            Some((s_blinding_1, s_L, s_R)) => (s_blinding_1, Some((s_L, s_R))),
+            None => (G::Scalar::random(rng), None),
         };
-        let s_L: Vec<Scalar> = (0..self.n).map(|_| Scalar::random(rng)).collect();
-        let s_R: Vec<Scalar> = (0..self.n).map(|_| Scalar::random(rng)).collect();
-
-        // Compute S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
-        let S = RistrettoPoint::multiscalar_mul(
-            iter::once(&s_blinding).chain(s_L.iter()).chain(s_R.iter()),
-            iter::once(&self.pc_gens.B_blinding)
-                .chain(bp_share.G(self.n))
-                .chain(bp_share.H(self.n)),
+        let s_blinding: Vec<G::Scalar> = iter::once(s_blinding_1)
+            .chain((1..extension_degree).map(|_| G::Scalar::random(rng)))
+            .collect();
+        let (s_L, s_R) = rewind_s_vecs.unwrap_or_else(|| {
+            let s_L: Vec<G::Scalar> = (0..padded_n).map(|_| G::Scalar::random(rng)).collect();
+            let s_R: Vec<G::Scalar> = (0..padded_n).map(|_| G::Scalar::random(rng)).collect();
+            (s_L, s_R)
+        });
+
+        // Compute S = <s_L, G> + <s_R, H> + <s_blinding, B_blinding>
+        let S = msm::multiscalar_mul(
+            self.bp_gens.msm_backend(),
+            s_blinding.iter().chain(s_L.iter()).chain(s_R.iter()),
+            self.pc_gens
+                .B_blinding
+                .iter()
+                .chain(bp_share.G(padded_n))
+                .chain(bp_share.H(padded_n)),
         );
 
         // Return next state and all commitments
@@ -177,22 +432,22 @@ impl<'a> PartyAwaitingPosition<'a> {
         let next_state = PartyAwaitingBitChallenge {
             n: self.n,
             v: self.v,
-            v_blinding: self.v_blinding,
-            pc_gens: self.pc_gens,
+            v_blinding: self.v_blinding.clone(),
+            pc_gens: self.pc_gens.clone(),
             j,
             a_blinding,
             s_blinding,
             s_L,
             s_R,
-            blinding_nonce_1: self.blinding_nonce_1,
-            blinding_nonce_2: self.blinding_nonce_2,
+            t_1_blinding: self.rewind.t_1_blinding.take(),
+            t_2_blinding: self.rewind.t_2_blinding.take(),
         };
         Ok((next_state, bit_commitment))
     }
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
-impl<'a> Drop for PartyAwaitingPosition<'a> {
+impl<'a, G: Group> Drop for PartyAwaitingPosition<'a, G> {
     fn drop(&mut self) {
         self.v.zeroize();
         self.v_blinding.zeroize();
@@ -201,52 +456,66 @@ impl<'a> Drop for PartyAwaitingPosition<'a> {
 
 /// A party which has committed to the bits of its value
 /// and is waiting for the aggregated value challenge from the dealer.
-pub struct PartyAwaitingBitChallenge<'a> {
+pub struct PartyAwaitingBitChallenge<G: Group = Ristretto> {
     n: usize, // bitsize of the range
-    v: u64,
-    v_blinding: Scalar,
+    v: u128,
+    v_blinding: Vec<G::Scalar>,
     j: usize,
-    pc_gens: &'a PedersenGens,
-    a_blinding: Scalar,
-    s_blinding: Scalar,
-    s_L: Vec<Scalar>,
-    s_R: Vec<Scalar>,
-    blinding_nonce_1: Scalar,
-    blinding_nonce_2: Scalar,
+    pc_gens: ExtendedPedersenGens<G>,
+    a_blinding: Vec<G::Scalar>,
+    s_blinding: Vec<G::Scalar>,
+    s_L: Vec<G::Scalar>,
+    s_R: Vec<G::Scalar>,
+    t_1_blinding: Option<Vec<G::Scalar>>,
+    t_2_blinding: Option<Vec<G::Scalar>>,
 }
 
-impl<'a> PartyAwaitingBitChallenge<'a> {
+impl<G: Group> PartyAwaitingBitChallenge<G> {
     /// Receive a [`BitChallenge`] from the dealer and use it to
     /// compute commitments to the party's polynomial coefficients.
     #[cfg(feature = "std")]
     pub fn apply_challenge(
         self,
-        vc: &BitChallenge,
-    ) -> (PartyAwaitingPolyChallenge, PolyCommitment) {
+        vc: &BitChallenge<G>,
+    ) -> (PartyAwaitingPolyChallenge<G>, PolyCommitment<G>) {
         self.apply_challenge_with_rng(vc, &mut thread_rng())
     }
 
     /// Receive a [`BitChallenge`] from the dealer and use it to
     /// compute commitments to the party's polynomial coefficients.
+    ///
+    /// As for [`PartyAwaitingPosition::assign_position_with_rng`], the
+    /// nonces are drawn from `rng` hedged with the party's witness.
     pub fn apply_challenge_with_rng<T: RngCore + CryptoRng>(
-        self,
-        vc: &BitChallenge,
+        mut self,
+        vc: &BitChallenge<G>,
         rng: &mut T,
-    ) -> (PartyAwaitingPolyChallenge, PolyCommitment) {
+    ) -> (PartyAwaitingPolyChallenge<G>, PolyCommitment<G>) {
+        let mut transcript = Transcript::new(b"RangeProofPartyNonces");
+        transcript.append_u64(b"j", self.j as u64);
+        transcript.append_group_scalar::<G>(b"y", &vc.y);
+        transcript.append_group_scalar::<G>(b"z", &vc.z);
+        let rng = &mut hedged_rng::<G, T>(transcript, self.v, &self.v_blinding, rng);
+
         let n = self.n;
-        let offset_y = util::scalar_exp_vartime(&vc.y, (self.j * n) as u64);
+        let padded_n = n.next_power_of_two();
+        let offset_y = util::scalar_exp_vartime(&vc.y, (self.j * padded_n) as u64);
         let offset_z = util::scalar_exp_vartime(&vc.z, self.j as u64);
 
         // Calculate t by calculating vectors l0, l1, r0, r1 and multiplying
-        let mut l_poly = util::VecPoly1::zero(n);
-        let mut r_poly = util::VecPoly1::zero(n);
+        let mut l_poly = util::VecPoly1::zero(padded_n);
+        let mut r_poly = util::VecPoly1::zero(padded_n);
 
         let offset_zz = vc.z * vc.z * offset_z;
         let mut exp_y = offset_y; // start at y^j
-        let mut exp_2 = Scalar::one(); // start at 2^0 = 1
-        for i in 0..n {
-            let a_L_i = Scalar::from((self.v >> i) & 1);
-            let a_R_i = a_L_i - Scalar::one();
+        let mut exp_2 = G::Scalar::one(); // start at 2^0 = 1
+        for i in 0..padded_n {
+            // The padding bits beyond n are weighted by 0 instead of 2^i.
+            if i == n {
+                exp_2 = G::Scalar::zero();
+            }
+            let a_L_i = G::Scalar::from(bit(self.v, i, n));
+            let a_R_i = a_L_i - G::Scalar::one();
 
             l_poly.0[i] = a_L_i - vc.z;
             l_poly.1[i] = self.s_L[i];
@@ -260,18 +529,19 @@ impl<'a> PartyAwaitingBitChallenge<'a> {
         let t_poly = l_poly.inner_product(&r_poly);
 
         // Generate x by committing to T_1, T_2 (line 49-54)
-        let t_1_blinding = if self.blinding_nonce_1 == Scalar::default() {
-            Scalar::random(rng)
-        } else {
-            self.blinding_nonce_1
-        };
-        let t_2_blinding = if self.blinding_nonce_2 == Scalar::default() {
-            Scalar::random(rng)
-        } else {
-            self.blinding_nonce_2
-        };
-        let T_1 = self.pc_gens.commit(t_poly.1, t_1_blinding);
-        let T_2 = self.pc_gens.commit(t_poly.2, t_2_blinding);
+        let extension_degree = self.pc_gens.extension_degree();
+        let t_1_blinding = self.t_1_blinding.take().unwrap_or_else(|| {
+            (0..extension_degree)
+                .map(|_| G::Scalar::random(rng))
+                .collect()
+        });
+        let t_2_blinding = self.t_2_blinding.take().unwrap_or_else(|| {
+            (0..extension_degree)
+                .map(|_| G::Scalar::random(rng))
+                .collect()
+        });
+        let T_1 = commit_extended(&self.pc_gens, &t_poly.1, &t_1_blinding);
+        let T_2 = commit_extended(&self.pc_gens, &t_poly.2, &t_2_blinding);
 
         let poly_commitment = PolyCommitment {
             T_1_j: T_1,
@@ -279,9 +549,9 @@ impl<'a> PartyAwaitingBitChallenge<'a> {
         };
 
         let papc = PartyAwaitingPolyChallenge {
-            v_blinding: self.v_blinding,
-            a_blinding: self.a_blinding,
-            s_blinding: self.s_blinding,
+            v_blinding: self.v_blinding.clone(),
+            a_blinding: self.a_blinding.clone(),
+            s_blinding: self.s_blinding.clone(),
             offset_zz,
             l_poly,
             r_poly,
@@ -295,7 +565,7 @@ impl<'a> PartyAwaitingBitChallenge<'a> {
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
-impl<'a> Drop for PartyAwaitingBitChallenge<'a> {
+impl<G: Group> Drop for PartyAwaitingBitChallenge<G> {
     fn drop(&mut self) {
         self.v.zeroize();
         self.v_blinding.zeroize();
@@ -308,42 +578,52 @@ impl<'a> Drop for PartyAwaitingBitChallenge<'a> {
         for e in self.s_R.iter_mut() {
             e.zeroize();
         }
+        self.t_1_blinding.zeroize();
+        self.t_2_blinding.zeroize();
     }
 }
 
 /// A party which has committed to their polynomial coefficents
 /// and is waiting for the polynomial challenge from the dealer.
-pub struct PartyAwaitingPolyChallenge {
-    offset_zz: Scalar,
-    l_poly: util::VecPoly1,
-    r_poly: util::VecPoly1,
-    t_poly: util::Poly2,
-    v_blinding: Scalar,
-    a_blinding: Scalar,
-    s_blinding: Scalar,
-    t_1_blinding: Scalar,
-    t_2_blinding: Scalar,
+pub struct PartyAwaitingPolyChallenge<G: Group = Ristretto> {
+    offset_zz: G::Scalar,
+    l_poly: util::VecPoly1<G::Scalar>,
+    r_poly: util::VecPoly1<G::Scalar>,
+    t_poly: util::Poly2<G::Scalar>,
+    v_blinding: Vec<G::Scalar>,
+    a_blinding: Vec<G::Scalar>,
+    s_blinding: Vec<G::Scalar>,
+    t_1_blinding: Vec<G::Scalar>,
+    t_2_blinding: Vec<G::Scalar>,
 }
 
-impl PartyAwaitingPolyChallenge {
+impl<G: Group> PartyAwaitingPolyChallenge<G> {
     /// Receive a [`PolyChallenge`] from the dealer and compute the
     /// party's proof share.
-    pub fn apply_challenge(self, pc: &PolyChallenge) -> Result<ProofShare, MPCError> {
+    pub fn apply_challenge(self, pc: &PolyChallenge<G>) -> Result<ProofShare<G>, MPCError> {
         // Prevent a malicious dealer from annihilating the blinding
         // factors by supplying a zero challenge.
-        if pc.x == Scalar::zero() {
+        if pc.x == G::Scalar::zero() {
             return Err(MPCError::MaliciousDealer);
         }
 
-        let t_blinding_poly = util::Poly2(
-            self.offset_zz * self.v_blinding,
-            self.t_1_blinding,
-            self.t_2_blinding,
-        );
-
         let t_x = self.t_poly.eval(pc.x);
-        let t_x_blinding = t_blinding_poly.eval(pc.x);
-        let e_blinding = self.a_blinding + self.s_blinding * &pc.x;
+        let t_x_blinding = (0..self.v_blinding.len())
+            .map(|k| {
+                let t_blinding_poly = util::Poly2(
+                    self.offset_zz * self.v_blinding[k],
+                    self.t_1_blinding[k],
+                    self.t_2_blinding[k],
+                );
+                t_blinding_poly.eval(pc.x)
+            })
+            .collect();
+        let e_blinding = self
+            .a_blinding
+            .iter()
+            .zip(self.s_blinding.iter())
+            .map(|(a_blinding, s_blinding)| *a_blinding + *s_blinding * pc.x)
+            .collect();
         let l_vec = self.l_poly.eval(pc.x);
         let r_vec = self.r_poly.eval(pc.x);
 
@@ -358,7 +638,7 @@ impl PartyAwaitingPolyChallenge {
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
-impl Drop for PartyAwaitingPolyChallenge {
+impl<G: Group> Drop for PartyAwaitingPolyChallenge<G> {
     fn drop(&mut self) {
         self.v_blinding.zeroize();
         self.a_blinding.zeroize();
@@ -370,3 +650,46 @@ impl Drop for PartyAwaitingPolyChallenge {
         // are cleared within their own Drop impls.
     }
 }
+
+/// Computes the commitment \\(v B + \\langle \\mathbf{k}, \\tilde{\\mathbf{B}} \\rangle\\)
+/// for blinding factors `blinding` matching the extension degree.
+/// Returns the generator of the nonces of a step of the protocol, keyed
+/// with the public data of the step in `transcript`, the party's value
+/// and blinding factors and the external `rng`, so that the nonces stay
+/// secret even if `rng` is broken.
+fn hedged_rng<G: Group, T: RngCore + CryptoRng>(
+    transcript: Transcript,
+    v: u128,
+    v_blinding: &[G::Scalar],
+    rng: &mut T,
+) -> TranscriptRng {
+    let builder = transcript
+        .build_rng()
+        .rekey_with_witness_bytes(b"v", &v.to_le_bytes());
+    v_blinding
+        .iter()
+        .fold(builder, |builder, v_blinding| {
+            builder.rekey_with_witness_bytes(b"v_blinding", &v_blinding.to_bytes())
+        })
+        .finalize(rng)
+}
+
+fn commit_extended<G: Group>(
+    pc_gens: &ExtendedPedersenGens<G>,
+    v: &G::Scalar,
+    blinding: &[G::Scalar],
+) -> G::Point {
+    G::multiscalar_mul(
+        iter::once(v).chain(blinding.iter()),
+        iter::once(&pc_gens.B).chain(pc_gens.B_blinding.iter()),
+    )
+}
+
+/// Returns bit `i` of `v`, or 0 if `i` is a padding position at or beyond `n`.
+fn bit(v: u128, i: usize, n: usize) -> u64 {
+    if i < n {
+        ((v >> i) & 1) as u64
+    } else {
+        0
+    }
+}
@@ -0,0 +1,509 @@
+//! The `rewind` module contains the types used to create range proofs
+//! which can later be rewound, to recover the value, blinding factor
+//! and an embedded message with the rewind keys.
+//!
+//! A rewindable proof replaces the prover's random blinding scalars
+//! by nonces derived from the rewind keys and the value commitment:
+//!
+//! * \\(\alpha\\) (the blinding of \\(A\\)) is the rewind nonce of the rewind key;
+//! * \\(\rho\\) (the blinding of \\(S\\)) is the value and message, masked
+//!   with the rewind nonce of the blinding key;
+//! * \\(\tau\_1, \tau\_2\\) are the secret nonces of the rewind and blinding keys;
+//! * \\(\mathbf{s}\_L, \mathbf{s}\_R\\) are derived from the rewind nonce of
+//!   the blinding key, and their first entries also carry the message.
+//!
+//! The first entries of \\(\mathbf{s}\_L, \mathbf{s}\_R\\) are recovered
+//! from the final scalars \\(a, b\\) of the inner product proof, which
+//! are linear in them once the value and the other entries are known.
+//!
+//! Anyone holding the public rewind keys can recover the value and
+//! message, while recovering the blinding factor requires the
+//! private keys.  The way the nonces are derived can be customized
+//! with a [`NonceGenerator`].
+//!
+//! The value is followed by a tag derived from the rewind nonces, so
+//! that rewinding with keys which do not belong to a proof fails with
+//! [`ProofError::InvalidRewindKeys`](crate::ProofError::InvalidRewindKeys)
+//! instead of returning garbage, even without the private keys.
+//!
+//! # Aggregated proofs
+//!
+//! Only proofs of a single value can be rewound, whether an aggregated
+//! proof is created by one prover or through the dealer and parties.
+//! The blinding factor \\(\gamma\_j\\) of the \\(j\\)-th value only
+//! enters the proof through
+//! \\(\tilde{t}\_x = \sum\_j z^{2+j} \gamma\_j + x \tau\_{1,j} + x^2 \tau\_{2,j}\\):
+//! the points \\(A, S, T\_1, T\_2\\), the inner product proof and the
+//! shares of \\(t\_x\\) of proofs over distinct value bases do not
+//! depend on it, and the slices of \\(\mathbf{l}, \mathbf{r}\\) of each
+//! value are folded into the inner product proof rather than carried
+//! by it.  Even with the nonces of every value derived from the rewind
+//! keys, \\(\tilde{t}\_x\\) only yields one linear combination of the
+//! \\(m\\) blinding factors, and the other scalars the prover can
+//! choose, \\(\tilde{e}\\) and the final scalars \\(a, b\\), hold three
+//! more scalars for the whole proof.  The blinding factors of all of
+//! the values, with their values and messages, do not fit into these,
+//! so a rewindable party cannot be aggregated.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use blake2::{Blake2b, Digest};
+use core::iter;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use zeroize::Zeroize;
+
+use super::padded_powers_of_2;
+use crate::transcript::TranscriptProtocol;
+
+/// Derives the nonces which make a range proof rewindable from the
+/// rewind keys and the value commitment.
+pub trait NonceGenerator {
+    /// Derives a rewind nonce from a public key and the value
+    /// commitment.  Anyone knowing the public key can compute it.
+    fn rewind_nonce(
+        &self,
+        pub_key: &CompressedRistretto,
+        commitment: &CompressedRistretto,
+    ) -> Scalar;
+
+    /// Derives a secret nonce from a private key and the value
+    /// commitment.  Only the owner of the private key can compute it.
+    fn secret_nonce(&self, pvt_key: &Scalar, commitment: &CompressedRistretto) -> Scalar;
+
+    /// Derives the rewind nonce of the public key belonging to `pvt_key`.
+    fn rewind_nonce_from_pvt_key(
+        &self,
+        pvt_key: &Scalar,
+        commitment: &CompressedRistretto,
+    ) -> Scalar {
+        let pub_key = (pvt_key * &RISTRETTO_BASEPOINT_TABLE).compress();
+        self.rewind_nonce(&pub_key, commitment)
+    }
+}
+
+/// The default [`NonceGenerator`], which derives the nonces with
+/// Blake2b using fixed personalisation strings.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Blake2bNonceGenerator;
+
+impl NonceGenerator for Blake2bNonceGenerator {
+    fn rewind_nonce(
+        &self,
+        pub_key: &CompressedRistretto,
+        commitment: &CompressedRistretto,
+    ) -> Scalar {
+        let rewind_nonce_initial =
+            Blake2b::with_params(pub_key.to_bytes().as_ref(), &[], "Rewind sep 1".as_bytes())
+                .finalize();
+        let rewind_nonce_data = [
+            rewind_nonce_initial.to_vec().as_slice(),
+            commitment.to_bytes().as_ref(),
+        ]
+        .concat();
+        let rewind_nonce_final = Blake2b::with_params(
+            &Blake2b::digest(&rewind_nonce_data),
+            &[],
+            "Rewind sep 2".as_bytes(),
+        );
+        Scalar::from_hash(rewind_nonce_final)
+    }
+
+    fn secret_nonce(&self, pvt_key: &Scalar, commitment: &CompressedRistretto) -> Scalar {
+        let secret_nonce_initial =
+            Blake2b::with_params(pvt_key.to_bytes().as_ref(), &[], "Secret sep 1".as_bytes())
+                .finalize();
+        let secret_nonce_data = [
+            secret_nonce_initial.to_vec().as_slice(),
+            commitment.to_bytes().as_ref(),
+        ]
+        .concat();
+        let secret_nonce_final = Blake2b::with_params(
+            &Blake2b::digest(&secret_nonce_data),
+            &[],
+            "Secret sep 2".as_bytes(),
+        );
+        Scalar::from_hash(secret_nonce_final)
+    }
+}
+
+/// The private keys with which a range proof is made rewindable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RewindKeys {
+    /// The private rewind key.
+    pub rewind_key: Scalar,
+    /// The private blinding key.
+    pub blinding_key: Scalar,
+}
+
+impl RewindKeys {
+    /// Constructs the rewind keys from the two private keys.
+    pub fn new(rewind_key: Scalar, blinding_key: Scalar) -> Self {
+        RewindKeys {
+            rewind_key,
+            blinding_key,
+        }
+    }
+
+    /// Returns the public keys, which can be shared with a third party
+    /// to let it recover the value and message, but not the blinding
+    /// factor.
+    pub fn public_keys(&self) -> PublicRewindKeys {
+        PublicRewindKeys {
+            rewind_key: (&self.rewind_key * &RISTRETTO_BASEPOINT_TABLE).compress(),
+            blinding_key: (&self.blinding_key * &RISTRETTO_BASEPOINT_TABLE).compress(),
+        }
+    }
+
+    /// Derives all of the nonces for rewinding the proof of `commitment`.
+    pub fn nonces<G: NonceGenerator + ?Sized>(
+        &self,
+        nonce_generator: &G,
+        commitment: &CompressedRistretto,
+    ) -> RewindNonces {
+        RewindNonces {
+            rewind_nonce_1: nonce_generator.rewind_nonce_from_pvt_key(&self.rewind_key, commitment),
+            rewind_nonce_2: nonce_generator
+                .rewind_nonce_from_pvt_key(&self.blinding_key, commitment),
+            blinding_nonce_1: nonce_generator.secret_nonce(&self.rewind_key, commitment),
+            blinding_nonce_2: nonce_generator.secret_nonce(&self.blinding_key, commitment),
+        }
+    }
+}
+
+/// Overwrite secrets with null bytes when they go out of scope.
+impl Drop for RewindKeys {
+    fn drop(&mut self) {
+        self.rewind_key.zeroize();
+        self.blinding_key.zeroize();
+    }
+}
+
+/// The public keys belonging to a set of [`RewindKeys`], which allow
+/// the value and message of a proof to be recovered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PublicRewindKeys {
+    /// The public rewind key.
+    pub rewind_key: CompressedRistretto,
+    /// The public blinding key.
+    pub blinding_key: CompressedRistretto,
+}
+
+impl PublicRewindKeys {
+    /// Derives the two rewind nonces for rewinding the value and
+    /// message of the proof of `commitment`.
+    pub fn rewind_nonces<G: NonceGenerator + ?Sized>(
+        &self,
+        nonce_generator: &G,
+        commitment: &CompressedRistretto,
+    ) -> (Scalar, Scalar) {
+        (
+            nonce_generator.rewind_nonce(&self.rewind_key, commitment),
+            nonce_generator.rewind_nonce(&self.blinding_key, commitment),
+        )
+    }
+}
+
+/// The nonces used to create and rewind the proof of one value commitment.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RewindNonces {
+    /// The rewind nonce of the rewind key, used as the blinding of \\(A\\).
+    pub rewind_nonce_1: Scalar,
+    /// The rewind nonce of the blinding key, which masks the value and message.
+    pub rewind_nonce_2: Scalar,
+    /// The secret nonce of the rewind key, used as \\(\tau\_1\\).
+    pub blinding_nonce_1: Scalar,
+    /// The secret nonce of the blinding key, used as \\(\tau\_2\\).
+    pub blinding_nonce_2: Scalar,
+}
+
+/// A message embedded in a rewindable range proof.
+///
+/// The message is split over three scalars of 31 bytes each, the
+/// first of which also holds the value and an authentication tag of
+/// 8 bytes, so the capacity for the
+/// message depends on the bitsize of the proof, see
+/// [`ProofMessage::capacity`].  A rewound message is always returned
+/// at full capacity, padded with zeros.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofMessage(Vec<u8>);
+
+impl ProofMessage {
+    /// Constructs a message from the given bytes.
+    pub fn new(bytes: &[u8]) -> Self {
+        ProofMessage(bytes.to_vec())
+    }
+
+    /// Returns the bytes of the message.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the number of bytes of the message.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the message is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the maximum length of a message embedded in an `n`-bit
+    /// proof: 77 bytes for bitsizes up to 64, or 69 bytes above that.
+    pub fn capacity(n: usize) -> usize {
+        REWIND_WORDS * REWIND_WORD_BYTES - rewind_value_bytes(n) - REWIND_TAG_BYTES
+    }
+}
+
+impl From<&[u8]> for ProofMessage {
+    fn from(bytes: &[u8]) -> Self {
+        ProofMessage::new(bytes)
+    }
+}
+
+/// Returns the number of bytes of the embedded rewind data taken up
+/// by the value for an `n`-bit proof: 8 bytes for bitsizes up to 64,
+/// or 16 bytes above that.
+pub(crate) fn rewind_value_bytes(n: usize) -> usize {
+    if n > 64 {
+        16
+    } else {
+        8
+    }
+}
+
+/// The number of scalars carrying the value and message.
+const REWIND_WORDS: usize = 3;
+
+/// The number of bytes of the value and message held by each scalar,
+/// which keeps every word below \\(2\^{248}\\) so that it survives the
+/// reduction modulo the group order.
+const REWIND_WORD_BYTES: usize = 31;
+
+/// The number of bytes of the tag following the value, which
+/// authenticates the rewind nonces.
+const REWIND_TAG_BYTES: usize = 8;
+
+/// Lays out the value of an `n`-bit proof, its tag and `message` in
+/// three words, for \\(\rho\\) and the first entries of
+/// \\(\mathbf{s}\_L, \mathbf{s}\_R\\).  The value and tag bytes are left
+/// as zeros to be filled in by the prover.  Returns `None` if the
+/// message does not fit.
+pub(crate) fn embed_message(n: usize, message: &ProofMessage) -> Option<[[u8; 32]; 3]> {
+    if message.len() > ProofMessage::capacity(n) {
+        return None;
+    }
+    let mut data = [0u8; REWIND_WORDS * REWIND_WORD_BYTES];
+    let offset = rewind_value_bytes(n) + REWIND_TAG_BYTES;
+    data[offset..offset + message.len()].copy_from_slice(message.as_bytes());
+
+    let mut words = [[0u8; 32]; REWIND_WORDS];
+    for (word, chunk) in words.iter_mut().zip(data.chunks(REWIND_WORD_BYTES)) {
+        word[..REWIND_WORD_BYTES].copy_from_slice(chunk);
+    }
+    Some(words)
+}
+
+/// Splits the words of embedded rewind data of an `n`-bit proof into
+/// the value and the message, skipping the tag.  Given only the first
+/// word, only the part of the message held by \\(\rho\\) is returned.
+pub(crate) fn extract_message(n: usize, words: &[[u8; 32]]) -> (u128, ProofMessage) {
+    let value_bytes = rewind_value_bytes(n);
+    let mut value = [0u8; 16];
+    value[..value_bytes].copy_from_slice(&words[0][..value_bytes]);
+
+    let mut message = words[0][value_bytes + REWIND_TAG_BYTES..REWIND_WORD_BYTES].to_vec();
+    for word in &words[1..] {
+        message.extend_from_slice(&word[..REWIND_WORD_BYTES]);
+    }
+    (u128::from_le_bytes(value), ProofMessage(message))
+}
+
+/// Derives the tag which authenticates the rewind nonces of a proof.
+fn rewind_tag(rewind_nonce_1: &Scalar, rewind_nonce_2: &Scalar) -> [u8; REWIND_TAG_BYTES] {
+    let mut transcript = Transcript::new(b"Bulletproofs rewind tag");
+    transcript.append_scalar(b"rewind_nonce_1", rewind_nonce_1);
+    transcript.append_scalar(b"rewind_nonce_2", rewind_nonce_2);
+    let mut tag = [0u8; REWIND_TAG_BYTES];
+    transcript.challenge_bytes(b"tag", &mut tag);
+    tag
+}
+
+/// Writes the tag of the rewind nonces after the value in the first
+/// word of embedded rewind data of an `n`-bit proof.
+pub(crate) fn add_rewind_tag(
+    n: usize,
+    mut word: [u8; 32],
+    rewind_nonce_1: &Scalar,
+    rewind_nonce_2: &Scalar,
+) -> [u8; 32] {
+    let offset = rewind_value_bytes(n);
+    word[offset..offset + REWIND_TAG_BYTES]
+        .copy_from_slice(&rewind_tag(rewind_nonce_1, rewind_nonce_2));
+    word
+}
+
+/// Checks that the first word of rewound data of an `n`-bit proof
+/// holds the tag of the rewind nonces it was rewound with.
+pub(crate) fn has_rewind_tag(
+    n: usize,
+    word: &[u8; 32],
+    rewind_nonce_1: &Scalar,
+    rewind_nonce_2: &Scalar,
+) -> bool {
+    let offset = rewind_value_bytes(n);
+    word[offset..offset + REWIND_TAG_BYTES] == rewind_tag(rewind_nonce_1, rewind_nonce_2)
+}
+
+/// Derives the vectors \\(\mathbf{s}\_L, \mathbf{s}\_R\\) of a rewindable
+/// proof from the rewind nonce `seed`, before the message is added to
+/// their first entries.
+pub(crate) fn derive_blinding_vectors(
+    seed: &Scalar,
+    padded_n: usize,
+) -> (Vec<Scalar>, Vec<Scalar>) {
+    let mut transcript = Transcript::new(b"Bulletproofs rewindable blinding vectors");
+    transcript.append_scalar(b"seed", seed);
+    let s_L = (0..padded_n)
+        .map(|_| transcript.challenge_scalar(b"s_L"))
+        .collect();
+    let s_R = (0..padded_n)
+        .map(|_| transcript.challenge_scalar(b"s_R"))
+        .collect();
+    (s_L, s_R)
+}
+
+/// Derives the blinding factors of \\(T\_1\\) or \\(T\_2\\) of a
+/// rewindable proof with the given extension degree from the secret
+/// `nonce`.  The first one is the nonce itself, so that proofs with an
+/// extension degree of one are unchanged.
+pub(crate) fn derive_extension_nonces(nonce: &Scalar, extension_degree: usize) -> Vec<Scalar> {
+    let mut transcript = Transcript::new(b"Bulletproofs rewindable extension nonces");
+    transcript.append_scalar(b"nonce", nonce);
+    iter::once(*nonce)
+        .chain((1..extension_degree).map(|_| transcript.challenge_scalar(b"nonce")))
+        .collect()
+}
+
+/// Recovers the two words of the message embedded in the first entries
+/// of \\(\mathbf{s}\_L, \mathbf{s}\_R\\) of a single-value `n`-bit proof
+/// of the value `v`, from the final scalars `a`, `b` and the challenge
+/// scalars `s` of its inner product proof.
+///
+/// The inner product proof folds \\(\mathbf{l}, \mathbf{r}\\) into
+/// \\(a = \sum\_i l\_i s\_i^{-1}\\) and \\(b = \sum\_i r\_i s\_i\\), so
+/// the words are the differences from these sums computed without them.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn extract_extended_words(
+    n: usize,
+    v: u128,
+    y: &Scalar,
+    z: &Scalar,
+    x: &Scalar,
+    s: &[Scalar],
+    a: &Scalar,
+    b: &Scalar,
+    seed: &Scalar,
+) -> [[u8; 32]; 2] {
+    let padded_n = s.len();
+    let (s_L, s_R) = derive_blinding_vectors(seed, padded_n);
+    let zz = z * z;
+
+    let mut a_derived = Scalar::zero();
+    let mut b_derived = Scalar::zero();
+    let mut exp_y = Scalar::one();
+    for (i, exp_2) in padded_powers_of_2::<Scalar>(n).enumerate() {
+        let a_L_i = if i < n {
+            Scalar::from(((v >> i) & 1) as u64)
+        } else {
+            Scalar::zero()
+        };
+        let a_R_i = a_L_i - Scalar::one();
+        let l_i = a_L_i - z + x * s_L[i];
+        let r_i = exp_y * (a_R_i + z + x * s_R[i]) + zz * exp_2;
+        a_derived += l_i * s[padded_n - 1 - i];
+        b_derived += r_i * s[i];
+        exp_y *= y;
+    }
+
+    [
+        ((a - a_derived) * (x * s[padded_n - 1]).invert()).to_bytes(),
+        ((b - b_derived) * (x * s[0]).invert()).to_bytes(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_layout_round_trips() {
+        for n in [8, 64, 65, 128].iter().cloned() {
+            let capacity = ProofMessage::capacity(n);
+            let message = ProofMessage::new(&[0xab; 93][..capacity]);
+            let words = embed_message(n, &message).unwrap();
+            let offset = rewind_value_bytes(n) + REWIND_TAG_BYTES;
+            assert!(words[0][..offset].iter().all(|b| *b == 0));
+            assert!(words.iter().all(|word| word[31] == 0));
+            assert_eq!(extract_message(n, &words), (0, message));
+
+            let too_long = ProofMessage::new(&[0xab; 93][..capacity + 1]);
+            assert!(embed_message(n, &too_long).is_none());
+        }
+
+        // Short messages are padded with zeros, and the first word on
+        // its own holds the start of the message
+        let words = embed_message(64, &ProofMessage::new(&[1, 2, 3])).unwrap();
+        let mut expected = vec![1, 2, 3];
+        expected.resize(77, 0);
+        assert_eq!(extract_message(64, &words).1.as_bytes(), &expected[..]);
+        assert_eq!(
+            extract_message(64, &words[..1]).1.as_bytes(),
+            &expected[..15]
+        );
+    }
+
+    #[test]
+    fn rewind_tag_authenticates_the_nonces() {
+        use rand::thread_rng;
+
+        let nonce_1 = Scalar::random(&mut thread_rng());
+        let nonce_2 = Scalar::random(&mut thread_rng());
+        let value = 0xffu128 << 56;
+        for n in [64, 128].iter().cloned() {
+            let mut word = embed_message(n, &ProofMessage::new(b"memo")).unwrap()[0];
+            word[..rewind_value_bytes(n)]
+                .copy_from_slice(&value.to_le_bytes()[..rewind_value_bytes(n)]);
+            let word = add_rewind_tag(n, word, &nonce_1, &nonce_2);
+
+            assert!(has_rewind_tag(n, &word, &nonce_1, &nonce_2));
+            assert!(!has_rewind_tag(n, &word, &nonce_2, &nonce_1));
+            assert!(!has_rewind_tag(n, &word, &nonce_1, &Scalar::one()));
+            assert_eq!(extract_message(n, &[word]).0, value);
+            assert_eq!(&extract_message(n, &[word]).1.as_bytes()[..4], b"memo");
+        }
+    }
+
+    #[test]
+    fn public_keys_derive_the_same_rewind_nonces() {
+        use rand::thread_rng;
+
+        let keys = RewindKeys::new(
+            Scalar::random(&mut thread_rng()),
+            Scalar::random(&mut thread_rng()),
+        );
+        let commitment =
+            (&Scalar::random(&mut thread_rng()) * &RISTRETTO_BASEPOINT_TABLE).compress();
+        let nonces = keys.nonces(&Blake2bNonceGenerator, &commitment);
+        assert_eq!(
+            keys.public_keys()
+                .rewind_nonces(&Blake2bNonceGenerator, &commitment),
+            (nonces.rewind_nonce_1, nonces.rewind_nonce_2)
+        );
+        assert_ne!(nonces.rewind_nonce_1, nonces.blinding_nonce_1);
+    }
+}
@@ -0,0 +1,79 @@
+//! The `rng` module contains a [`SeededRng`], a deterministic random
+//! number generator for creating reproducible range proofs.
+//!
+//! Range proofs hedge the nonces drawn from the caller's RNG with the
+//! transcript and the witness, so a proof created with a [`SeededRng`]
+//! depends only on the seed, the transcript and the values and blinding
+//! factors.  This is meant for test vectors: a seed which is reused for
+//! different transcripts or witnesses gives no more protection than the
+//! hedging alone.
+
+use merlin::Transcript;
+use rand_core::{impls, CryptoRng, Error, RngCore, SeedableRng};
+
+/// A deterministic random number generator, which expands a 32 byte
+/// seed with a Merlin transcript.
+///
+/// ```
+/// # extern crate tari_bulletproofs;
+/// # use tari_bulletproofs::range_proof::SeededRng;
+/// # use tari_bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+/// # use curve25519_dalek::scalar::Scalar;
+/// # use merlin::Transcript;
+/// # use rand_core::SeedableRng;
+/// # fn main() {
+/// let pc_gens = PedersenGens::default();
+/// let bp_gens = BulletproofGens::new(64, 1);
+///
+/// let prove = || {
+///     RangeProof::prove_single_with_rng(
+///         &bp_gens,
+///         &pc_gens,
+///         &mut Transcript::new(b"doctest example"),
+///         1037578891,
+///         &Scalar::from(7u64),
+///         32,
+///         &mut SeededRng::from_seed([1u8; 32]),
+///     )
+///     .unwrap()
+/// };
+///
+/// // The same seed creates the same proof
+/// assert_eq!(prove().0.to_bytes(), prove().0.to_bytes());
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SeededRng {
+    transcript: Transcript,
+}
+
+impl SeedableRng for SeededRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut transcript = Transcript::new(b"Bulletproofs SeededRng");
+        transcript.append_message(b"seed", &seed);
+        SeededRng { transcript }
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.transcript.challenge_bytes(b"bytes", dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for SeededRng {}
@@ -0,0 +1,251 @@
+//! The `scanner` module contains a [`RewindScanner`], which finds the
+//! outputs belonging to a wallet among many rewindable range proofs by
+//! trial-rewinding each of them with the wallet's keys.
+//!
+//! Most outputs do not belong to the wallet, so the scanner derives the
+//! public keys once, and rejects an output with only its two rewind
+//! nonces and its authentication tag.  The secret nonces, the message
+//! and the blinding factor are only recovered for the outputs which
+//! pass this check.
+
+extern crate alloc;
+
+#[cfg(feature = "parallel")]
+use alloc::vec::Vec;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::rewind::{
+    Blake2bNonceGenerator, NonceGenerator, ProofMessage, PublicRewindKeys, RewindKeys,
+};
+use super::RangeProof;
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+
+/// An output recovered by a [`RewindScanner`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecoveredOutput {
+    /// The position of the output in the scanned outputs.
+    pub index: usize,
+    /// The value of the output.
+    pub value: u128,
+    /// The blinding factor of the value commitment.
+    pub blinding: Scalar,
+    /// The message embedded in the proof, at full capacity.
+    pub message: ProofMessage,
+}
+
+/// Rewinds the proofs of many outputs with one set of rewind keys,
+/// to recover the outputs created for these keys.
+///
+/// The proofs must have been created with
+/// [`RangeProof::prove_single_rewindable_with_rng`] for the bitsize
+/// `n` of the scanner, and the same nonce generator.  Each output is
+/// given with the label of the transcript its proof was created with.
+pub struct RewindScanner<'a, G: NonceGenerator = Blake2bNonceGenerator> {
+    bp_gens: &'a BulletproofGens,
+    pc_gens: ExtendedPedersenGens,
+    n: usize,
+    rewind_keys: RewindKeys,
+    public_keys: PublicRewindKeys,
+    nonce_generator: G,
+}
+
+impl<'a> RewindScanner<'a> {
+    /// Constructs a scanner for proofs of bitsize `n` whose nonces are
+    /// derived with the default [`Blake2bNonceGenerator`].
+    pub fn new(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        n: usize,
+        rewind_keys: RewindKeys,
+    ) -> Self {
+        RewindScanner::with_nonce_generator(bp_gens, pc_gens, n, rewind_keys, Blake2bNonceGenerator)
+    }
+}
+
+impl<'a, G: NonceGenerator> RewindScanner<'a, G> {
+    /// Constructs a scanner for proofs of bitsize `n` whose nonces are
+    /// derived with the given `nonce_generator`.
+    pub fn with_nonce_generator(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        n: usize,
+        rewind_keys: RewindKeys,
+        nonce_generator: G,
+    ) -> Self {
+        let public_keys = rewind_keys.public_keys();
+        RewindScanner {
+            bp_gens,
+            pc_gens: ExtendedPedersenGens::from(*pc_gens),
+            n,
+            rewind_keys,
+            public_keys,
+            nonce_generator,
+        }
+    }
+
+    /// Rewinds the proof of a single output, returning its value,
+    /// blinding factor and message.
+    ///
+    /// Returns [`ProofError::InvalidRewindKeys`] if the output does not
+    /// belong to the keys of the scanner.
+    pub fn rewind(
+        &self,
+        commitment: &CompressedRistretto,
+        proof: &RangeProof,
+        transcript_label: &'static [u8],
+    ) -> Result<(u128, Scalar, ProofMessage), ProofError> {
+        let mut transcript = Transcript::new(transcript_label);
+        let (value, blinding, message) = proof.rewind_single_with_keys(
+            self.bp_gens,
+            &self.pc_gens,
+            &mut transcript,
+            commitment,
+            self.n,
+            &self.rewind_keys,
+            &self.public_keys,
+            &self.nonce_generator,
+        )?;
+        Ok((value, blinding[0], message))
+    }
+
+    /// Scans the given `(commitment, proof, transcript label)` outputs,
+    /// yielding the outputs which belong to the keys of the scanner in
+    /// order.  Outputs which do not belong to the keys, or whose proofs
+    /// are malformed, are skipped.
+    pub fn scan<'s, I>(&'s self, outputs: I) -> impl Iterator<Item = RecoveredOutput> + 's
+    where
+        I: IntoIterator<Item = (&'s CompressedRistretto, &'s RangeProof, &'static [u8])>,
+        I::IntoIter: 's,
+    {
+        outputs
+            .into_iter()
+            .enumerate()
+            .filter_map(move |(index, (commitment, proof, label))| {
+                self.recover(index, commitment, proof, label)
+            })
+    }
+
+    /// Scans the given outputs like [`RewindScanner::scan`], spreading
+    /// the work over the threads of the rayon thread pool.
+    #[cfg(feature = "parallel")]
+    pub fn scan_parallel(
+        &self,
+        outputs: &[(&CompressedRistretto, &RangeProof, &'static [u8])],
+    ) -> Vec<RecoveredOutput>
+    where
+        G: Sync,
+    {
+        outputs
+            .par_iter()
+            .enumerate()
+            .filter_map(|(index, &(commitment, proof, label))| {
+                self.recover(index, commitment, proof, label)
+            })
+            .collect()
+    }
+
+    fn recover(
+        &self,
+        index: usize,
+        commitment: &CompressedRistretto,
+        proof: &RangeProof,
+        transcript_label: &'static [u8],
+    ) -> Option<RecoveredOutput> {
+        let (value, blinding, message) = self.rewind(commitment, proof, transcript_label).ok()?;
+        Some(RecoveredOutput {
+            index,
+            value,
+            blinding,
+            message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::thread_rng;
+
+    #[test]
+    fn scan_finds_own_outputs() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+        let mut rng = thread_rng();
+
+        let keys = RewindKeys::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let other_keys = RewindKeys::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let labels: [&'static [u8]; 2] = [b"OutputA", b"OutputB"];
+
+        // Outputs 1 and 4 belong to other keys, and output 3 is not rewindable
+        let mut outputs = Vec::new();
+        let mut expected = Vec::new();
+        for index in 0..6 {
+            let value = 1000 + index as u64;
+            let blinding = Scalar::random(&mut rng);
+            let label = labels[index % 2];
+            let message = ProofMessage::new(&[index as u8; 40]);
+            let mut transcript = Transcript::new(label);
+            let (proof, commitment) = match index {
+                3 => RangeProof::prove_single(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    value,
+                    &blinding,
+                    64,
+                ),
+                _ => RangeProof::prove_single_rewindable(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    value.into(),
+                    &blinding,
+                    64,
+                    if index == 1 || index == 4 {
+                        &other_keys
+                    } else {
+                        &keys
+                    },
+                    &message,
+                    &Blake2bNonceGenerator,
+                ),
+            }
+            .unwrap();
+            if index != 1 && index != 3 && index != 4 {
+                let mut full_message = message.as_bytes().to_vec();
+                full_message.resize(ProofMessage::capacity(64), 0);
+                expected.push(RecoveredOutput {
+                    index,
+                    value: value.into(),
+                    blinding,
+                    message: ProofMessage::new(&full_message),
+                });
+            }
+            outputs.push((commitment, proof, label));
+        }
+
+        let scanner = RewindScanner::new(&bp_gens, &pc_gens, 64, keys);
+        let recovered: Vec<_> = scanner
+            .scan(outputs.iter().map(|(c, p, l)| (c, p, *l)))
+            .collect();
+        assert_eq!(recovered, expected);
+
+        // The wrong transcript label does not rewind the proof
+        assert!(scanner
+            .rewind(&outputs[0].0, &outputs[0].1, labels[1])
+            .is_err());
+
+        #[cfg(feature = "parallel")]
+        {
+            let items: Vec<_> = outputs.iter().map(|(c, p, l)| (c, p, *l)).collect();
+            assert_eq!(scanner.scan_parallel(&items), expected);
+        }
+    }
+}
@@ -0,0 +1,922 @@
+//! The `session` module drives the [`dealer`](super::dealer) and
+//! [`party`](super::party) state machines of the aggregated multiparty
+//! computation protocol over a [`Transport`].
+//!
+//! The drivers serialize the protocol messages into [`Frame`]s, which
+//! carry the [`SessionId`] of the aggregation and the position of the
+//! party in it, and take care of routing the frames, of checking that
+//! they arrive in the order of the protocol, and of timing out parties
+//! which do not answer.
+//!
+//! Two transports are provided: a [`ChannelTransport`] between threads
+//! of one process, and a [`TcpTransport`] for aggregating the proof
+//! of parties running in different processes.
+//!
+//! ```
+//! # extern crate tari_bulletproofs;
+//! # use tari_bulletproofs::range_proof_mpc::dealer::Dealer;
+//! # use tari_bulletproofs::range_proof_mpc::party::Party;
+//! # use tari_bulletproofs::range_proof_mpc::session::{
+//! #     ChannelTransport, DealerSession, PartySession, SessionId,
+//! # };
+//! # use tari_bulletproofs::{BulletproofGens, PedersenGens};
+//! # use curve25519_dalek::scalar::Scalar;
+//! # use merlin::Transcript;
+//! # use std::thread;
+//! # fn main() {
+//! let session_id = SessionId::random(&mut rand::thread_rng());
+//! let values = [3u64, 1037578891];
+//!
+//! let mut parties = Vec::new();
+//! let mut dealer_transports = Vec::new();
+//! for (j, &v) in values.iter().enumerate() {
+//!     let (dealer_end, party_end) = ChannelTransport::pair();
+//!     dealer_transports.push(dealer_end);
+//!     parties.push(thread::spawn(move || {
+//!         let pc_gens = PedersenGens::default();
+//!         let bp_gens = BulletproofGens::new(32, 2);
+//!         let party = Party::new_extended(
+//!             &bp_gens,
+//!             &pc_gens.into(),
+//!             v.into(),
+//!             &[Scalar::random(&mut rand::thread_rng())],
+//!             32,
+//!         )
+//!         .unwrap();
+//!         PartySession::new(session_id, party, j, party_end).run()
+//!     }));
+//! }
+//!
+//! let pc_gens = PedersenGens::default();
+//! let bp_gens = BulletproofGens::new(32, 2);
+//! let mut transcript = Transcript::new(b"doctest example");
+//! let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, 32, 2).unwrap();
+//! let (proof, commitments) = DealerSession::new(session_id, dealer, dealer_transports)
+//!     .run()
+//!     .unwrap();
+//!
+//! for party in parties {
+//!     party.join().unwrap().unwrap();
+//! }
+//! let mut transcript = Transcript::new(b"doctest example");
+//! assert!(proof
+//!     .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &commitments, 32)
+//!     .is_ok());
+//! # }
+//! ```
+
+use std::cmp;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::thread_rng;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::errors::SessionError;
+use crate::range_proof::RangeProof;
+use crate::util;
+
+use super::dealer::DealerAwaitingBitCommitments;
+use super::messages::*;
+use super::party::PartyAwaitingPosition;
+
+/// The time the drivers wait for each round of the protocol, unless
+/// another timeout is set.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The largest frame a [`TcpTransport`] accepts, in bytes.
+pub const MAX_FRAME_SIZE: usize = 1 << 20;
+
+/// Identifies one run of the aggregated multiparty computation
+/// protocol, so that frames of different runs cannot be mixed up.
+///
+/// The session ID is not bound to the proof: callers which want the
+/// proof to depend on it should append it to the transcript.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SessionId(pub [u8; 16]);
+
+impl SessionId {
+    /// Draws a random session ID.
+    pub fn random<T: RngCore + CryptoRng>(rng: &mut T) -> SessionId {
+        let mut bytes = [0u8; 16];
+        rng.fill_bytes(&mut bytes);
+        SessionId(bytes)
+    }
+}
+
+/// A message of the aggregated multiparty computation protocol.
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// A party's [`BitCommitment`], sent to the dealer.
+    BitCommitment(BitCommitment),
+    /// The dealer's [`BitChallenge`], sent to every party.
+    BitChallenge(BitChallenge),
+    /// A party's [`PolyCommitment`], sent to the dealer.
+    PolyCommitment(PolyCommitment),
+    /// The dealer's [`PolyChallenge`], sent to every party.
+    PolyChallenge(PolyChallenge),
+    /// A party's [`ProofShare`], sent to the dealer.
+    ProofShare(ProofShare),
+}
+
+impl Message {
+    fn tag(&self) -> u8 {
+        match self {
+            Message::BitCommitment(_) => 0,
+            Message::BitChallenge(_) => 1,
+            Message::PolyCommitment(_) => 2,
+            Message::PolyChallenge(_) => 3,
+            Message::ProofShare(_) => 4,
+        }
+    }
+}
+
+/// A [`Message`] framed with the [`SessionId`] of the aggregation and
+/// the position of the party sending or receiving it.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    /// The session the message belongs to
+    pub session_id: SessionId,
+    /// The position of the party in the aggregation
+    pub position: usize,
+    /// The protocol message
+    pub message: Message,
+}
+
+impl Frame {
+    /// Serializes the frame into a byte array.
+    ///
+    /// # Layout
+    ///
+    /// The layout of the frame encoding is:
+    ///
+    /// * the 16 byte session ID,
+    /// * the position as a little-endian `u64`,
+    /// * a one byte message tag, from `0` for a [`BitCommitment`] to
+    ///   `4` for a [`ProofShare`], in the order of the protocol,
+    /// * the points and canonical scalars of the message, 32 bytes
+    ///   each, in the order of their fields.  The four vectors of a
+    ///   [`ProofShare`] are preceded by their lengths as little-endian
+    ///   `u64`s.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(25 + 3 * 32);
+        buf.extend_from_slice(&self.session_id.0);
+        buf.extend_from_slice(&(self.position as u64).to_le_bytes());
+        buf.push(self.message.tag());
+        match &self.message {
+            Message::BitCommitment(bc) => {
+                buf.extend_from_slice(bc.V_j.as_bytes());
+                buf.extend_from_slice(bc.A_j.compress().as_bytes());
+                buf.extend_from_slice(bc.S_j.compress().as_bytes());
+            }
+            Message::BitChallenge(bc) => {
+                buf.extend_from_slice(bc.y.as_bytes());
+                buf.extend_from_slice(bc.z.as_bytes());
+            }
+            Message::PolyCommitment(pc) => {
+                buf.extend_from_slice(pc.T_1_j.compress().as_bytes());
+                buf.extend_from_slice(pc.T_2_j.compress().as_bytes());
+            }
+            Message::PolyChallenge(pc) => {
+                buf.extend_from_slice(pc.x.as_bytes());
+            }
+            Message::ProofShare(share) => {
+                buf.extend_from_slice(share.t_x.as_bytes());
+                for scalars in &[
+                    &share.t_x_blinding,
+                    &share.e_blinding,
+                    &share.l_vec,
+                    &share.r_vec,
+                ] {
+                    buf.extend_from_slice(&(scalars.len() as u64).to_le_bytes());
+                    for scalar in scalars.iter() {
+                        buf.extend_from_slice(scalar.as_bytes());
+                    }
+                }
+            }
+        }
+        buf
+    }
+
+    /// Deserializes the frame from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a
+    /// `Frame`, for instance if it contains an invalid point or a
+    /// non-canonical scalar.
+    pub fn from_bytes(slice: &[u8]) -> Result<Frame, SessionError> {
+        let mut reader = FrameReader(slice);
+
+        let mut session_id = [0u8; 16];
+        session_id.copy_from_slice(reader.take(16)?);
+        let position =
+            usize::try_from(reader.read_u64()?).map_err(|_| SessionError::FormatError)?;
+        let message = match reader.take(1)?[0] {
+            0 => Message::BitCommitment(BitCommitment {
+                V_j: reader.read_compressed()?,
+                A_j: reader.read_point()?,
+                S_j: reader.read_point()?,
+            }),
+            1 => Message::BitChallenge(BitChallenge {
+                y: reader.read_scalar()?,
+                z: reader.read_scalar()?,
+            }),
+            2 => Message::PolyCommitment(PolyCommitment {
+                T_1_j: reader.read_point()?,
+                T_2_j: reader.read_point()?,
+            }),
+            3 => Message::PolyChallenge(PolyChallenge {
+                x: reader.read_scalar()?,
+            }),
+            4 => Message::ProofShare(ProofShare {
+                t_x: reader.read_scalar()?,
+                t_x_blinding: reader.read_scalars()?,
+                e_blinding: reader.read_scalars()?,
+                l_vec: reader.read_scalars()?,
+                r_vec: reader.read_scalars()?,
+            }),
+            _ => return Err(SessionError::FormatError),
+        };
+
+        if !reader.0.is_empty() {
+            return Err(SessionError::FormatError);
+        }
+
+        Ok(Frame {
+            session_id: SessionId(session_id),
+            position,
+            message,
+        })
+    }
+}
+
+/// Takes care of counting the bytes of a frame being parsed.
+struct FrameReader<'a>(&'a [u8]);
+
+impl<'a> FrameReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SessionError> {
+        if self.0.len() < len {
+            return Err(SessionError::FormatError);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn read_u64(&mut self) -> Result<u64, SessionError> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn read_compressed(&mut self) -> Result<CompressedRistretto, SessionError> {
+        Ok(CompressedRistretto(util::read32(self.take(32)?)))
+    }
+
+    fn read_point(&mut self) -> Result<RistrettoPoint, SessionError> {
+        self.read_compressed()?
+            .decompress()
+            .ok_or(SessionError::FormatError)
+    }
+
+    fn read_scalar(&mut self) -> Result<Scalar, SessionError> {
+        Scalar::from_canonical_bytes(util::read32(self.take(32)?)).ok_or(SessionError::FormatError)
+    }
+
+    fn read_scalars(&mut self) -> Result<Vec<Scalar>, SessionError> {
+        let len = usize::try_from(self.read_u64()?).map_err(|_| SessionError::FormatError)?;
+        // Check the length against the remaining bytes before allocating
+        if len > self.0.len() / 32 {
+            return Err(SessionError::FormatError);
+        }
+        (0..len).map(|_| self.read_scalar()).collect()
+    }
+}
+
+/// A bidirectional link between the dealer and one party, carrying
+/// [`Frame`]s in the order they were sent.
+pub trait Transport {
+    /// Sends a frame to the other end.
+    fn send(&mut self, frame: &Frame) -> Result<(), SessionError>;
+
+    /// Receives the next frame from the other end, failing with
+    /// [`SessionError::Timeout`] if the whole frame has not arrived by
+    /// the `deadline`.
+    fn receive(&mut self, deadline: Instant) -> Result<Frame, SessionError>;
+}
+
+/// A [`Transport`] between two threads of one process, which passes
+/// the encoded frames over a pair of channels.
+pub struct ChannelTransport {
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+}
+
+impl ChannelTransport {
+    /// Creates the two connected ends of a transport.
+    pub fn pair() -> (ChannelTransport, ChannelTransport) {
+        let (a_sender, b_receiver) = mpsc::channel();
+        let (b_sender, a_receiver) = mpsc::channel();
+        (
+            ChannelTransport {
+                sender: a_sender,
+                receiver: a_receiver,
+            },
+            ChannelTransport {
+                sender: b_sender,
+                receiver: b_receiver,
+            },
+        )
+    }
+}
+
+impl Transport for ChannelTransport {
+    fn send(&mut self, frame: &Frame) -> Result<(), SessionError> {
+        self.sender
+            .send(frame.to_bytes())
+            .map_err(|_| SessionError::Disconnected)
+    }
+
+    fn receive(&mut self, deadline: Instant) -> Result<Frame, SessionError> {
+        match self
+            .receiver
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            Ok(bytes) => Frame::from_bytes(&bytes),
+            Err(RecvTimeoutError::Timeout) => Err(SessionError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(SessionError::Disconnected),
+        }
+    }
+}
+
+/// A [`Transport`] over a TCP connection, which prefixes each encoded
+/// frame with its length as a little-endian `u32`.
+///
+/// A frame which times out halfway through leaves the connection in
+/// an unknown state, so the connection should not be reused after a
+/// [`SessionError::Timeout`].
+pub struct TcpTransport {
+    stream: TcpStream,
+}
+
+impl TcpTransport {
+    /// Wraps an established TCP connection.
+    pub fn new(stream: TcpStream) -> Result<TcpTransport, SessionError> {
+        stream.set_nodelay(true)?;
+        Ok(TcpTransport { stream })
+    }
+
+    /// Connects to a dealer listening at `addr`.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<TcpTransport, SessionError> {
+        TcpTransport::new(TcpStream::connect(addr)?)
+    }
+
+    /// Accepts the connection of the next party on the `listener`.
+    pub fn accept(listener: &TcpListener) -> Result<TcpTransport, SessionError> {
+        let (stream, _) = listener.accept()?;
+        TcpTransport::new(stream)
+    }
+
+    /// Fills `buf` from the connection before the `deadline`.  The read
+    /// timeout of the socket only bounds each read, so it is shortened
+    /// to the time left before every one of them, and a peer trickling
+    /// the bytes in cannot stretch the frame past the deadline.
+    fn read_exact_before(
+        &mut self,
+        mut buf: &mut [u8],
+        deadline: Instant,
+    ) -> Result<(), SessionError> {
+        while !buf.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(SessionError::Timeout);
+            }
+            // A zero read timeout is rejected by the socket
+            let timeout = cmp::max(deadline - now, Duration::from_millis(1));
+            self.stream.set_read_timeout(Some(timeout))?;
+            match self.stream.read(buf) {
+                Ok(0) => return Err(SessionError::Disconnected),
+                Ok(read) => buf = &mut buf[read..],
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, frame: &Frame) -> Result<(), SessionError> {
+        let bytes = frame.to_bytes();
+        if bytes.len() > MAX_FRAME_SIZE {
+            return Err(SessionError::FormatError);
+        }
+        self.stream.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        self.stream.write_all(&bytes)?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    fn receive(&mut self, deadline: Instant) -> Result<Frame, SessionError> {
+        let mut len = [0u8; 4];
+        self.read_exact_before(&mut len, deadline)?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_FRAME_SIZE {
+            return Err(SessionError::FormatError);
+        }
+        let mut bytes = vec![0u8; len];
+        self.read_exact_before(&mut bytes, deadline)?;
+        Frame::from_bytes(&bytes)
+    }
+}
+
+/// Receives the next frame of the session from the `transport`,
+/// before the `deadline`.
+fn receive_frame<T: Transport>(
+    transport: &mut T,
+    session_id: SessionId,
+    deadline: Instant,
+) -> Result<Frame, SessionError> {
+    let frame = transport.receive(deadline)?;
+    if frame.session_id != session_id {
+        return Err(SessionError::WrongSession);
+    }
+    Ok(frame)
+}
+
+/// A dealer driving the aggregated multiparty computation protocol
+/// with one [`Transport`] per party.
+///
+/// The transports may be given in any order: each party names its
+/// position in the frame of its [`BitCommitment`], and the transport
+/// it arrived on is used for the rest of the session.
+pub struct DealerSession<'a, 'b, T: Transport> {
+    session_id: SessionId,
+    dealer: DealerAwaitingBitCommitments<'a, 'b>,
+    transports: Vec<T>,
+    timeout: Duration,
+}
+
+impl<'a, 'b, T: Transport> DealerSession<'a, 'b, T> {
+    /// Creates a session for the `dealer`, which must be coordinating
+    /// as many parties as there are `transports`.
+    pub fn new(
+        session_id: SessionId,
+        dealer: DealerAwaitingBitCommitments<'a, 'b>,
+        transports: Vec<T>,
+    ) -> Self {
+        DealerSession {
+            session_id,
+            dealer,
+            transports,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Sets the time to wait for the messages of all parties in each
+    /// round of the protocol.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs the protocol to completion, returning the aggregated proof
+    /// and the value commitments of the parties, in the order of their
+    /// positions.
+    pub fn run(self) -> Result<(RangeProof, Vec<CompressedRistretto>), SessionError> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Runs the protocol to completion, returning the aggregated proof
+    /// and the value commitments of the parties, in the order of their
+    /// positions.
+    ///
+    /// The proof is verified with the `rng` before it is returned; see
+    /// [`receive_shares_with_rng`](super::dealer::DealerAwaitingProofShares::receive_shares_with_rng).
+    pub fn run_with_rng<R: RngCore + CryptoRng>(
+        self,
+        rng: &mut R,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), SessionError> {
+        let DealerSession {
+            session_id,
+            dealer,
+            mut transports,
+            timeout,
+        } = self;
+        let m = transports.len();
+
+        // The bit commitments determine which transport carries which position
+        let mut routes = vec![None; m];
+        let mut bit_commitments = vec![None; m];
+        let deadline = Instant::now() + timeout;
+        for (i, transport) in transports.iter_mut().enumerate() {
+            let frame = receive_frame(transport, session_id, deadline)?;
+            let j = frame.position;
+            match frame.message {
+                Message::BitCommitment(bc) if j < m && routes[j].is_none() => {
+                    routes[j] = Some(i);
+                    bit_commitments[j] = Some(bc);
+                }
+                _ => return Err(SessionError::UnexpectedMessage { position: j }),
+            }
+        }
+        // With one frame from each of the `m` transports for distinct
+        // positions below `m`, every position is routed.
+        let routes: Vec<usize> = routes.into_iter().flatten().collect();
+        let bit_commitments: Vec<BitCommitment> = bit_commitments.into_iter().flatten().collect();
+        let value_commitments = bit_commitments.iter().map(|bc| bc.V_j).collect();
+
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments)?;
+        broadcast(&mut transports, &routes, session_id, || {
+            Message::BitChallenge(bit_challenge)
+        })?;
+
+        let poly_commitments =
+            gather(
+                &mut transports,
+                &routes,
+                session_id,
+                timeout,
+                |message| match message {
+                    Message::PolyCommitment(pc) => Some(pc),
+                    _ => None,
+                },
+            )?;
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments)?;
+        broadcast(&mut transports, &routes, session_id, || {
+            Message::PolyChallenge(poly_challenge)
+        })?;
+
+        let proof_shares =
+            gather(
+                &mut transports,
+                &routes,
+                session_id,
+                timeout,
+                |message| match message {
+                    Message::ProofShare(share) => Some(share),
+                    _ => None,
+                },
+            )?;
+        let proof = dealer.receive_shares_with_rng(&proof_shares, rng)?;
+
+        Ok((proof, value_commitments))
+    }
+}
+
+/// Sends a message to the party at each position.
+fn broadcast<T: Transport, F: Fn() -> Message>(
+    transports: &mut [T],
+    routes: &[usize],
+    session_id: SessionId,
+    message: F,
+) -> Result<(), SessionError> {
+    for (j, &i) in routes.iter().enumerate() {
+        transports[i].send(&Frame {
+            session_id,
+            position: j,
+            message: message(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Receives the next message of the party at each position, in the
+/// order of the positions.
+fn gather<T: Transport, M, F: Fn(Message) -> Option<M>>(
+    transports: &mut [T],
+    routes: &[usize],
+    session_id: SessionId,
+    timeout: Duration,
+    extract: F,
+) -> Result<Vec<M>, SessionError> {
+    let deadline = Instant::now() + timeout;
+    routes
+        .iter()
+        .enumerate()
+        .map(|(j, &i)| {
+            let frame = receive_frame(&mut transports[i], session_id, deadline)?;
+            if frame.position != j {
+                return Err(SessionError::UnexpectedMessage {
+                    position: frame.position,
+                });
+            }
+            extract(frame.message).ok_or(SessionError::UnexpectedMessage { position: j })
+        })
+        .collect()
+}
+
+/// A party driving the aggregated multiparty computation protocol
+/// over a [`Transport`] to the dealer.
+pub struct PartySession<'a, T: Transport> {
+    session_id: SessionId,
+    party: PartyAwaitingPosition<'a>,
+    position: usize,
+    transport: T,
+    timeout: Duration,
+}
+
+impl<'a, T: Transport> PartySession<'a, T> {
+    /// Creates a session for the `party`, which takes the `position`
+    /// in the aggregation.  The positions are agreed on by the dealer
+    /// and the parties beforehand.
+    pub fn new(
+        session_id: SessionId,
+        party: PartyAwaitingPosition<'a>,
+        position: usize,
+        transport: T,
+    ) -> Self {
+        PartySession {
+            session_id,
+            party,
+            position,
+            transport,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Sets the time to wait for each challenge of the dealer.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs the protocol to completion, sending the party's proof
+    /// share to the dealer.
+    pub fn run(self) -> Result<(), SessionError> {
+        self.run_with_rng(&mut thread_rng())
+    }
+
+    /// Runs the protocol to completion, sending the party's proof
+    /// share to the dealer.
+    pub fn run_with_rng<R: RngCore + CryptoRng>(self, rng: &mut R) -> Result<(), SessionError> {
+        let PartySession {
+            session_id,
+            party,
+            position,
+            mut transport,
+            timeout,
+        } = self;
+        let frame = |message| Frame {
+            session_id,
+            position,
+            message,
+        };
+
+        let (party, bit_commitment) = party.assign_position_with_rng(position, rng)?;
+        transport.send(&frame(Message::BitCommitment(bit_commitment)))?;
+
+        let bit_challenge =
+            match receive_from_dealer(&mut transport, session_id, position, timeout)? {
+                Message::BitChallenge(bc) => bc,
+                _ => return Err(SessionError::UnexpectedMessage { position }),
+            };
+        let (party, poly_commitment) = party.apply_challenge_with_rng(&bit_challenge, rng);
+        transport.send(&frame(Message::PolyCommitment(poly_commitment)))?;
+
+        let poly_challenge =
+            match receive_from_dealer(&mut transport, session_id, position, timeout)? {
+                Message::PolyChallenge(pc) => pc,
+                _ => return Err(SessionError::UnexpectedMessage { position }),
+            };
+        let proof_share = party.apply_challenge(&poly_challenge)?;
+        transport.send(&frame(Message::ProofShare(proof_share)))
+    }
+}
+
+/// Receives the next message from the dealer for the party at `position`.
+fn receive_from_dealer<T: Transport>(
+    transport: &mut T,
+    session_id: SessionId,
+    position: usize,
+    timeout: Duration,
+) -> Result<Message, SessionError> {
+    let frame = receive_frame(transport, session_id, Instant::now() + timeout)?;
+    if frame.position != position {
+        return Err(SessionError::UnexpectedMessage {
+            position: frame.position,
+        });
+    }
+    Ok(frame.message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    use merlin::Transcript;
+
+    use crate::errors::MPCError;
+    use crate::generators::{BulletproofGens, PedersenGens};
+    use crate::range_proof::dealer::Dealer;
+    use crate::range_proof::party::Party;
+
+    const N: usize = 32;
+
+    /// Spawns a thread running the party at position `j` over the `transport`.
+    fn spawn_party<T: Transport + Send + 'static>(
+        session_id: SessionId,
+        j: usize,
+        v: u64,
+        transport: T,
+    ) -> thread::JoinHandle<Result<(), SessionError>> {
+        thread::spawn(move || {
+            let pc_gens = PedersenGens::default();
+            let bp_gens = BulletproofGens::new(N, 4);
+            let v_blinding = Scalar::random(&mut thread_rng());
+            let party =
+                Party::new_extended(&bp_gens, &pc_gens.into(), v.into(), &[v_blinding], N).unwrap();
+            PartySession::new(session_id, party, j, transport).run()
+        })
+    }
+
+    /// Runs the dealer over the `transports` and verifies the proof.
+    fn run_dealer<T: Transport>(
+        session_id: SessionId,
+        transports: Vec<T>,
+        timeout: Duration,
+    ) -> Result<(), SessionError> {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(N, 4);
+        let m = transports.len();
+
+        let mut transcript = Transcript::new(b"SessionTest");
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, N, m)?;
+        let (proof, value_commitments) = DealerSession::new(session_id, dealer, transports)
+            .with_timeout(timeout)
+            .run()?;
+
+        let mut transcript = Transcript::new(b"SessionTest");
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, N)
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_over_channels() {
+        let session_id = SessionId::random(&mut thread_rng());
+        let values = [0u64, 1, 12345, u32::MAX as u64];
+
+        // The transports are handed to the dealer out of position order
+        let mut parties = Vec::new();
+        let mut transports = Vec::new();
+        for (j, &v) in values.iter().enumerate().rev() {
+            let (dealer_end, party_end) = ChannelTransport::pair();
+            transports.push(dealer_end);
+            parties.push(spawn_party(session_id, j, v, party_end));
+        }
+
+        assert_eq!(run_dealer(session_id, transports, DEFAULT_TIMEOUT), Ok(()));
+        for party in parties {
+            assert_eq!(party.join().unwrap(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn aggregate_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let session_id = SessionId::random(&mut thread_rng());
+
+        let parties: Vec<_> = [7u64, 1037578891, 42]
+            .iter()
+            .enumerate()
+            .map(|(j, &v)| spawn_party(session_id, j, v, TcpTransport::connect(addr).unwrap()))
+            .collect();
+        let transports = (0..parties.len())
+            .map(|_| TcpTransport::accept(&listener))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(run_dealer(session_id, transports, DEFAULT_TIMEOUT), Ok(()));
+        for party in parties {
+            assert_eq!(party.join().unwrap(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn frames_are_checked() {
+        let session_id = SessionId([1u8; 16]);
+        let other_session_id = SessionId([2u8; 16]);
+
+        // A frame of another session is rejected
+        let (mut dealer_end, party_end) = ChannelTransport::pair();
+        let party = spawn_party(other_session_id, 0, 1, party_end);
+        assert_eq!(
+            receive_frame(
+                &mut dealer_end,
+                session_id,
+                Instant::now() + DEFAULT_TIMEOUT
+            )
+            .err(),
+            Some(SessionError::WrongSession)
+        );
+        drop(dealer_end);
+        assert_eq!(party.join().unwrap(), Err(SessionError::Disconnected));
+
+        // A party claiming a position taken by another is rejected
+        let (dealer_end_0, party_end_0) = ChannelTransport::pair();
+        let (dealer_end_1, party_end_1) = ChannelTransport::pair();
+        let parties = vec![
+            spawn_party(session_id, 0, 1, party_end_0),
+            spawn_party(session_id, 0, 2, party_end_1),
+        ];
+        assert_eq!(
+            run_dealer(
+                session_id,
+                vec![dealer_end_0, dealer_end_1],
+                DEFAULT_TIMEOUT
+            ),
+            Err(SessionError::UnexpectedMessage { position: 0 })
+        );
+        for party in parties {
+            assert_eq!(party.join().unwrap(), Err(SessionError::Disconnected));
+        }
+
+        // A dealer expecting more parties than it has transports fails
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(N, 4);
+        let mut transcript = Transcript::new(b"SessionTest");
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, N, 2).unwrap();
+        let (dealer_end, party_end) = ChannelTransport::pair();
+        let party = spawn_party(session_id, 0, 1, party_end);
+        assert_eq!(
+            DealerSession::new(session_id, dealer, vec![dealer_end])
+                .run()
+                .err(),
+            Some(SessionError::ProtocolError(
+                MPCError::WrongNumBitCommitments
+            ))
+        );
+        assert_eq!(party.join().unwrap(), Err(SessionError::Disconnected));
+
+        // Truncated and trailing bytes are rejected
+        let frame = Frame {
+            session_id,
+            position: 3,
+            message: Message::PolyChallenge(PolyChallenge { x: Scalar::one() }),
+        };
+        let bytes = frame.to_bytes();
+        assert!(Frame::from_bytes(&bytes).is_ok());
+        assert!(Frame::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Frame::from_bytes(&[&bytes[..], &[0u8]].concat()).is_err());
+    }
+
+    #[test]
+    fn trickling_party_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let session_id = SessionId::random(&mut thread_rng());
+
+        // The party sends a valid frame, one byte at a time, so that
+        // each read of the dealer returns well within the timeout
+        let frame = Frame {
+            session_id,
+            position: 0,
+            message: Message::PolyChallenge(PolyChallenge { x: Scalar::one() }),
+        };
+        let bytes = frame.to_bytes();
+        let bytes = [&(bytes.len() as u32).to_le_bytes()[..], &bytes[..]].concat();
+        let party = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            for byte in bytes.iter() {
+                if stream.write_all(&[*byte]).is_err() {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+
+        let mut transport = TcpTransport::accept(&listener).unwrap();
+        let start = Instant::now();
+        assert_eq!(
+            receive_frame(
+                &mut transport,
+                session_id,
+                start + Duration::from_millis(200)
+            )
+            .err(),
+            Some(SessionError::Timeout)
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+        drop(transport);
+        party.join().unwrap();
+    }
+
+    #[test]
+    fn silent_party_times_out() {
+        let session_id = SessionId::random(&mut thread_rng());
+        let (dealer_end, _party_end) = ChannelTransport::pair();
+        assert_eq!(
+            run_dealer(session_id, vec![dealer_end], Duration::from_millis(10)),
+            Err(SessionError::Timeout)
+        );
+    }
+}
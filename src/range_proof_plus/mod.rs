@@ -0,0 +1,1042 @@
+#![allow(non_snake_case)]
+//! Range proofs using the Bulletproofs+ weighted inner product argument.
+//!
+//! Bulletproofs+ ([Chung, Han, Ju, Kim, Seo, 2020][bpp]) replaces the
+//! inner product argument of Bulletproofs with a zero-knowledge
+//! weighted inner product (WIP) argument.  The resulting range proofs
+//! are three group elements smaller than a [`RangeProof`](crate::RangeProof)
+//! and are verified with a single, slightly smaller multiscalar
+//! multiplication.
+//!
+//! The proofs use the same [`BulletproofGens`] and [`PedersenGens`]
+//! as [`RangeProof`](crate::RangeProof), but a different transcript
+//! domain separator, so that a proof of one kind can never be
+//! mistaken for a proof of the other.
+//!
+//! [bpp]: https://eprint.iacr.org/2020/735
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate rand;
+
+#[cfg(feature = "std")]
+use self::rand::thread_rng;
+use alloc::vec::Vec;
+
+use core::iter;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, MultiscalarMul};
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+use crate::batch::DeferredCheck;
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+use crate::range_proof::{padded_bitsize, padded_powers_of_2};
+use crate::transcript::TranscriptProtocol;
+use crate::util;
+
+/// The `RangeProofPlus` struct represents a Bulletproofs+ proof that
+/// one or more values are in a range.
+///
+/// Like [`RangeProof`](crate::RangeProof), the bitsize of the range
+/// and the commitments to the values are not included in the proof,
+/// and must be known to the verifier.  The bitsize `n` can be any
+/// number from 1 to 128, and aggregations of any size `m` are padded to
+/// a power of two with dummy commitments to zero.
+///
+/// Aggregated proofs are created by a single prover who knows all of
+/// the values; there is no multiparty protocol for `RangeProofPlus`.
+#[derive(Clone, Debug)]
+pub struct RangeProofPlus {
+    /// Commitment to the bits of the values
+    A: CompressedRistretto,
+    /// Commitment to the blinding factors of the final WIP round
+    A1: CompressedRistretto,
+    /// Commitment to the cross term of the final WIP round
+    B: CompressedRistretto,
+    /// Response for the \\(\mathbf{a}\\) side of the final WIP round
+    r1: Scalar,
+    /// Response for the \\(\mathbf{b}\\) side of the final WIP round
+    s1: Scalar,
+    /// Response for the blinding factor of the final WIP round
+    d1: Scalar,
+    /// Left commitments of the WIP folding rounds
+    L_vec: Vec<CompressedRistretto>,
+    /// Right commitments of the WIP folding rounds
+    R_vec: Vec<CompressedRistretto>,
+}
+
+impl RangeProofPlus {
+    /// Create a Bulletproofs+ rangeproof for a given pair of value `v`
+    /// and blinding scalar `v_blinding`.
+    /// This is a convenience wrapper around [`RangeProofPlus::prove_multiple_with_rng`].
+    pub fn prove_single_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProofPlus, CompressedRistretto), ProofError> {
+        let (p, Vs) = RangeProofPlus::prove_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            &[v],
+            &[*v_blinding],
+            n,
+            rng,
+        )?;
+        Ok((p, Vs[0]))
+    }
+
+    /// Create a Bulletproofs+ rangeproof for a given pair of value `v`
+    /// and blinding scalar `v_blinding`.
+    /// This is a convenience wrapper around [`RangeProofPlus::prove_single_with_rng`],
+    /// passing in a threadsafe RNG.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// use rand::thread_rng;
+    ///
+    /// extern crate curve25519_dalek;
+    /// use curve25519_dalek::scalar::Scalar;
+    ///
+    /// extern crate merlin;
+    /// use merlin::Transcript;
+    ///
+    /// extern crate tari_bulletproofs;
+    /// use tari_bulletproofs::{BulletproofGens, PedersenGens, RangeProofPlus};
+    ///
+    /// # fn main() {
+    /// let pc_gens = PedersenGens::default();
+    /// let bp_gens = BulletproofGens::new(64, 1);
+    ///
+    /// // A secret value we want to prove lies in the range [0, 2^32)
+    /// let secret_value = 1037578891u64;
+    /// let blinding = Scalar::random(&mut thread_rng());
+    ///
+    /// let mut prover_transcript = Transcript::new(b"doctest example");
+    /// let (proof, committed_value) = RangeProofPlus::prove_single(
+    ///     &bp_gens,
+    ///     &pc_gens,
+    ///     &mut prover_transcript,
+    ///     secret_value,
+    ///     &blinding,
+    ///     32,
+    /// ).expect("A real program could handle errors");
+    ///
+    /// // Verification requires a transcript with identical initial state:
+    /// let mut verifier_transcript = Transcript::new(b"doctest example");
+    /// assert!(
+    ///     proof
+    ///         .verify_single(&bp_gens, &pc_gens, &mut verifier_transcript, &committed_value, 32)
+    ///         .is_ok()
+    /// );
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn prove_single(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        n: usize,
+    ) -> Result<(RangeProofPlus, CompressedRistretto), ProofError> {
+        RangeProofPlus::prove_single_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            v_blinding,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create an aggregated Bulletproofs+ rangeproof for a set of
+    /// values, returning the proof and the commitments to the values.
+    pub fn prove_multiple_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProofPlus, Vec<CompressedRistretto>), ProofError> {
+        if values.len() != blindings.len() {
+            return Err(ProofError::WrongNumBlindingFactors);
+        }
+        let m = values.len();
+        let (padded_n, padded_m) = check_sizes(bp_gens, n, m)?;
+        let nm = padded_n * padded_m;
+
+        transcript.rangeproof_plus_domain_sep(n as u64, m as u64);
+
+        let value_commitments: Vec<CompressedRistretto> = values
+            .iter()
+            .zip(blindings.iter())
+            .map(|(v, v_blinding)| pc_gens.commit(Scalar::from(*v), *v_blinding).compress())
+            .collect();
+        for V in value_commitments.iter() {
+            transcript.append_point(b"V", V);
+        }
+        for _ in m..padded_m {
+            transcript.append_point(b"V", &CompressedRistretto::identity());
+        }
+
+        // Draw the nonces from a generator hedged with the transcript
+        // and the values and blinding factors, as for `RangeProof`, so
+        // that a broken or reused `rng` does not leak the blindings.
+        let mut rng = values
+            .iter()
+            .zip(blindings.iter())
+            .fold(transcript.build_rng(), |builder, (v, v_blinding)| {
+                builder
+                    .rekey_with_witness_bytes(b"v", &v.to_le_bytes())
+                    .rekey_with_witness_bytes(b"v_blinding", v_blinding.as_bytes())
+            })
+            .finalize(rng);
+
+        // Commit to the bits of the values: a_L is the concatenation
+        // of the bit vectors, and a_R = a_L - 1.  The padding bits
+        // beyond n and the dummy values padding the aggregation are zero.
+        let mut a_L: Vec<Scalar> = values
+            .iter()
+            .copied()
+            .chain((m..padded_m).map(|_| 0))
+            .flat_map(|v| {
+                (0..padded_n).map(move |i| {
+                    Scalar::from(if i < n {
+                        (u128::from(v) >> i) as u64 & 1
+                    } else {
+                        0
+                    })
+                })
+            })
+            .collect();
+        let mut a_R: Vec<Scalar> = a_L.iter().map(|a_L_i| a_L_i - Scalar::one()).collect();
+        let mut alpha = Scalar::random(&mut rng);
+
+        let G: Vec<RistrettoPoint> = bp_gens.G(padded_n, padded_m).cloned().collect();
+        let H: Vec<RistrettoPoint> = bp_gens.H(padded_n, padded_m).cloned().collect();
+
+        let A = RistrettoPoint::multiscalar_mul(
+            a_L.iter().chain(a_R.iter()).chain(iter::once(&alpha)),
+            G.iter()
+                .chain(H.iter())
+                .chain(iter::once(&pc_gens.B_blinding)),
+        )
+        .compress();
+        transcript.append_point(b"A", &A);
+
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        // Fold the range statement into a single WIP statement for
+        // a_hat_L = a_L - z * 1 and a_hat_R = a_R + d o y^(N-i) + z * 1.
+        let d = d_vec(n, padded_m, &z);
+        let exp_y: Vec<Scalar> = util::exp_iter(y).take(nm + 2).collect();
+        let mut a_hat_L: Vec<Scalar> = a_L.iter().map(|a_L_i| a_L_i - z).collect();
+        let mut a_hat_R: Vec<Scalar> = a_R
+            .iter()
+            .zip(d.iter())
+            .enumerate()
+            .map(|(i, (a_R_i, d_i))| a_R_i + d_i * exp_y[nm - i] + z)
+            .collect();
+        let mut alpha_hat = alpha
+            + exp_y[nm + 1]
+                * util::exp_iter(z * z)
+                    .skip(1)
+                    .zip(blindings.iter())
+                    .map(|(z_exp, v_blinding)| z_exp * v_blinding)
+                    .sum::<Scalar>();
+
+        let proof = RangeProofPlus::prove_wip(
+            transcript,
+            pc_gens,
+            G,
+            H,
+            &mut a_hat_L,
+            &mut a_hat_R,
+            &mut alpha_hat,
+            &y,
+            A,
+            &mut rng,
+        );
+
+        for e in a_L.iter_mut().chain(a_R.iter_mut()) {
+            e.zeroize();
+        }
+        alpha.zeroize();
+
+        Ok((proof, value_commitments))
+    }
+
+    /// Create an aggregated Bulletproofs+ rangeproof for a set of values.
+    /// This is a convenience wrapper around [`RangeProofPlus::prove_multiple_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_multiple(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: usize,
+    ) -> Result<(RangeProofPlus, Vec<CompressedRistretto>), ProofError> {
+        RangeProofPlus::prove_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            values,
+            blindings,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Runs the zero-knowledge weighted inner product argument for
+    /// the statement
+    /// \\[
+    /// \hat{A} = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{b}, \mathbf{H} \rangle
+    ///           + (\mathbf{a} \odot_y \mathbf{b}) B + \alpha \tilde{B},
+    /// \\]
+    /// where \\(\mathbf{a} \odot_y \mathbf{b} = \sum_i a_i b_i y^{i+1}\\).
+    /// The witness vectors are overwritten as they are folded, and are
+    /// zeroized before returning.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_wip<T: RngCore + CryptoRng>(
+        transcript: &mut Transcript,
+        pc_gens: &PedersenGens,
+        mut G_vec: Vec<RistrettoPoint>,
+        mut H_vec: Vec<RistrettoPoint>,
+        a_vec: &mut [Scalar],
+        b_vec: &mut [Scalar],
+        alpha: &mut Scalar,
+        y: &Scalar,
+        A: CompressedRistretto,
+        rng: &mut T,
+    ) -> RangeProofPlus {
+        let mut G = &mut G_vec[..];
+        let mut H = &mut H_vec[..];
+        let mut a = &mut a_vec[..];
+        let mut b = &mut b_vec[..];
+
+        let mut n = G.len();
+        debug_assert!(n.is_power_of_two());
+
+        let y_inv = y.invert();
+        let lg_n = n.trailing_zeros() as usize;
+        let mut L_vec = Vec::with_capacity(lg_n);
+        let mut R_vec = Vec::with_capacity(lg_n);
+
+        while n != 1 {
+            n /= 2;
+            let (a_L, a_R) = a.split_at_mut(n);
+            let (b_L, b_R) = b.split_at_mut(n);
+            let (G_L, G_R) = G.split_at_mut(n);
+            let (H_L, H_R) = H.split_at_mut(n);
+
+            let y_n = util::scalar_exp_vartime(y, n as u64);
+            let y_n_inv = util::scalar_exp_vartime(&y_inv, n as u64);
+
+            let c_L = weighted_inner_product(a_L, b_R, y);
+            let c_R = y_n * weighted_inner_product(a_R, b_L, y);
+            let mut d_L = Scalar::random(rng);
+            let mut d_R = Scalar::random(rng);
+
+            let L = RistrettoPoint::multiscalar_mul(
+                a_L.iter()
+                    .map(|a_L_i| a_L_i * y_n_inv)
+                    .chain(b_R.iter().cloned())
+                    .chain(iter::once(c_L))
+                    .chain(iter::once(d_L)),
+                G_R.iter()
+                    .chain(H_L.iter())
+                    .chain(iter::once(&pc_gens.B))
+                    .chain(iter::once(&pc_gens.B_blinding)),
+            )
+            .compress();
+
+            let R = RistrettoPoint::multiscalar_mul(
+                a_R.iter()
+                    .map(|a_R_i| a_R_i * y_n)
+                    .chain(b_L.iter().cloned())
+                    .chain(iter::once(c_R))
+                    .chain(iter::once(d_R)),
+                G_L.iter()
+                    .chain(H_R.iter())
+                    .chain(iter::once(&pc_gens.B))
+                    .chain(iter::once(&pc_gens.B_blinding)),
+            )
+            .compress();
+
+            L_vec.push(L);
+            R_vec.push(R);
+
+            transcript.append_point(b"L", &L);
+            transcript.append_point(b"R", &R);
+
+            let e = transcript.challenge_scalar(b"e");
+            let e_inv = e.invert();
+
+            for i in 0..n {
+                a_L[i] = a_L[i] * e + e_inv * y_n * a_R[i];
+                b_L[i] = b_L[i] * e_inv + e * b_R[i];
+                G_L[i] = RistrettoPoint::multiscalar_mul(&[e_inv, e * y_n_inv], &[G_L[i], G_R[i]]);
+                H_L[i] = RistrettoPoint::multiscalar_mul(&[e, e_inv], &[H_L[i], H_R[i]]);
+            }
+            *alpha += e * e * d_L + e_inv * e_inv * d_R;
+
+            d_L.zeroize();
+            d_R.zeroize();
+            for e in a_R.iter_mut().chain(b_R.iter_mut()) {
+                e.zeroize();
+            }
+
+            a = a_L;
+            b = b_L;
+            G = G_L;
+            H = H_L;
+        }
+
+        // Final round: a, b and the generators are single elements.
+        let mut r = Scalar::random(rng);
+        let mut s = Scalar::random(rng);
+        let mut delta = Scalar::random(rng);
+        let mut eta = Scalar::random(rng);
+
+        let A1 = RistrettoPoint::multiscalar_mul(
+            &[r, s, y * (r * b[0] + s * a[0]), delta],
+            &[G[0], H[0], pc_gens.B, pc_gens.B_blinding],
+        )
+        .compress();
+        let B =
+            RistrettoPoint::multiscalar_mul(&[r * y * s, eta], &[pc_gens.B, pc_gens.B_blinding])
+                .compress();
+
+        transcript.append_point(b"A1", &A1);
+        transcript.append_point(b"B", &B);
+
+        let e = transcript.challenge_scalar(b"e");
+
+        let proof = RangeProofPlus {
+            A,
+            A1,
+            B,
+            r1: r + a[0] * e,
+            s1: s + b[0] * e,
+            d1: eta + delta * e + *alpha * e * e,
+            L_vec,
+            R_vec,
+        };
+
+        r.zeroize();
+        s.zeroize();
+        delta.zeroize();
+        eta.zeroize();
+        a[0].zeroize();
+        b[0].zeroize();
+        alpha.zeroize();
+
+        proof
+    }
+
+    /// Verifies a Bulletproofs+ rangeproof for a given value commitment \\(V\\).
+    ///
+    /// This is a convenience wrapper around `verify_multiple` for the `m=1` case.
+    pub fn verify_single(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple(bp_gens, pc_gens, transcript, &[*V], n)
+    }
+
+    /// Verifies an aggregated Bulletproofs+ rangeproof for the given
+    /// value commitments.
+    ///
+    /// The verification equation of a single proof needs no random
+    /// weights, so unlike [`RangeProof`](crate::RangeProof) this takes
+    /// no RNG, and is available without the `std` feature.
+    pub fn verify_multiple(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        let check = self.verification_check(bp_gens, transcript, value_commitments, n)?;
+
+        if check.verify(bp_gens, &ExtendedPedersenGens::from(*pc_gens)) {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Replays the proof transcript and constructs the verification
+    /// equation for this proof, without evaluating it.
+    fn verification_check(
+        &self,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<DeferredCheck, ProofError> {
+        let m = value_commitments.len();
+        let (padded_n, padded_m) = check_sizes(bp_gens, n, m)?;
+        let nm = padded_n * padded_m;
+
+        let lg_nm = self.L_vec.len();
+        if self.R_vec.len() != lg_nm || nm != 1 << lg_nm {
+            return Err(ProofError::VerificationError);
+        }
+
+        transcript.rangeproof_plus_domain_sep(n as u64, m as u64);
+
+        for V in value_commitments.iter() {
+            // Allow the commitments to be zero (0 value, 0 blinding)
+            transcript.append_point(b"V", V);
+        }
+        for _ in m..padded_m {
+            transcript.append_point(b"V", &CompressedRistretto::identity());
+        }
+
+        transcript.validate_and_append_point(b"A", &self.A)?;
+
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        let mut challenges = Vec::with_capacity(lg_nm);
+        for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            transcript.validate_and_append_point(b"L", L)?;
+            transcript.validate_and_append_point(b"R", R)?;
+            challenges.push(transcript.challenge_scalar(b"e"));
+        }
+
+        transcript.validate_and_append_point(b"A1", &self.A1)?;
+        transcript.validate_and_append_point(b"B", &self.B)?;
+
+        let e = transcript.challenge_scalar(b"e");
+        let e_sq = e * e;
+
+        // Compute the scalars s_i = prod_k e_k^{+-1} for the folded
+        // generators, in the same way as for the inner product proof.
+        let mut challenges_inv = challenges.clone();
+        let allinv = Scalar::batch_invert(&mut challenges_inv);
+        let challenges_sq: Vec<Scalar> = challenges.iter().map(|e_k| e_k * e_k).collect();
+        let challenges_inv_sq: Vec<Scalar> = challenges_inv.iter().map(|e_k| e_k * e_k).collect();
+
+        let mut s = Vec::with_capacity(nm);
+        s.push(allinv);
+        for i in 1..nm {
+            let lg_i = (32 - 1 - (i as u32).leading_zeros()) as usize;
+            let k = 1 << lg_i;
+            // The challenges are stored in "creation order" as [e_k,...,e_1],
+            // so e_{lg(i)+1} = is indexed by (lg_nm-1) - lg_i
+            s.push(s[i - k] * challenges_sq[(lg_nm - 1) - lg_i]);
+        }
+
+        let d = d_vec(n, padded_m, &z);
+        let exp_y: Vec<Scalar> = util::exp_iter(y).take(nm + 2).collect();
+        let minus_e_sq_z = -e_sq * z;
+        let r1_e = self.r1 * e;
+        let s1_e = self.s1 * e;
+
+        let g: Vec<Scalar> = s
+            .iter()
+            .zip(util::exp_iter(y.invert()))
+            .map(|(s_i, exp_y_inv)| minus_e_sq_z - r1_e * exp_y_inv * s_i)
+            .collect();
+        let h: Vec<Scalar> = s
+            .iter()
+            .rev()
+            .zip(d.iter())
+            .enumerate()
+            .map(|(i, (s_i_inv, d_i))| e_sq * (z + d_i * exp_y[nm - i]) - s1_e * s_i_inv)
+            .collect();
+
+        // zeta = (z - z^2) * sum_{i=1}^{N} y^i - z * y^{N+1} * sum_i d_i
+        let sum_d = util::sum_of_powers(&Scalar::from(2u64), n)
+            * util::exp_iter(z * z).skip(1).take(padded_m).sum::<Scalar>();
+        let zeta = (z - z * z) * y * util::sum_of_powers(&y, nm) - z * exp_y[nm + 1] * sum_d;
+
+        let value_commitment_scalars = util::exp_iter(z * z)
+            .skip(1)
+            .take(m)
+            .map(|z_exp| e_sq * exp_y[nm + 1] * z_exp);
+
+        let dynamic_points = iter::once(&self.A)
+            .chain(iter::once(&self.A1))
+            .chain(iter::once(&self.B))
+            .chain(self.L_vec.iter())
+            .chain(self.R_vec.iter())
+            .chain(value_commitments.iter())
+            .map(|P| P.decompress().ok_or(ProofError::VerificationError))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DeferredCheck {
+            dynamic_scalars: iter::once(e_sq)
+                .chain(iter::once(e))
+                .chain(iter::once(Scalar::one()))
+                .chain(challenges_sq.iter().map(|e_k_sq| e_sq * e_k_sq))
+                .chain(challenges_inv_sq.iter().map(|e_k_inv_sq| e_sq * e_k_inv_sq))
+                .chain(value_commitment_scalars)
+                .collect(),
+            dynamic_points,
+            B_scalar: e_sq * zeta - self.r1 * y * self.s1,
+            B_blinding_scalars: alloc::vec![-self.d1],
+            G_scalars: g.chunks(padded_n).map(|g_j| g_j.to_vec()).collect(),
+            H_scalars: h.chunks(padded_n).map(|h_j| h_j.to_vec()).collect(),
+        })
+    }
+
+    /// Returns the size in bytes required to serialize the `RangeProofPlus`.
+    pub fn serialized_size(&self) -> usize {
+        (6 + 2 * self.L_vec.len()) * 32
+    }
+
+    /// Serializes the proof into a byte array of \\(2 \lg n + 6\\)
+    /// 32-byte elements, where \\(n\\) is the number of secret bits.
+    ///
+    /// # Layout
+    ///
+    /// The layout of the range proof encoding is:
+    ///
+    /// * three compressed Ristretto points \\(A,A_1,B\\),
+    /// * three scalars \\(r_1, s_1, d_1\\),
+    /// * \\(\lg n\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{\lg n-1},R_{\lg n-1}\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        buf.extend_from_slice(self.A.as_bytes());
+        buf.extend_from_slice(self.A1.as_bytes());
+        buf.extend_from_slice(self.B.as_bytes());
+        buf.extend_from_slice(self.r1.as_bytes());
+        buf.extend_from_slice(self.s1.as_bytes());
+        buf.extend_from_slice(self.d1.as_bytes());
+        for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            buf.extend_from_slice(L.as_bytes());
+            buf.extend_from_slice(R.as_bytes());
+        }
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `RangeProofPlus`.
+    pub fn from_bytes(mut slice: &[u8]) -> Result<RangeProofPlus, ProofError> {
+        if slice.len() < 6 * 32 {
+            return Err(ProofError::FormatError);
+        }
+        // The L and R points come in pairs of 64 bytes each.
+        let rounds = &slice[6 * 32..];
+        if !rounds.chunks_exact(64).remainder().is_empty() {
+            return Err(ProofError::FormatError);
+        }
+        let lg_n = rounds.len() / 64;
+        if lg_n >= 32 {
+            return Err(ProofError::FormatError);
+        }
+
+        // This macro takes care of counting bytes in the slice
+        macro_rules! read32 {
+            () => {{
+                let tmp = util::read32(slice);
+                slice = &slice[32..];
+                tmp
+            }};
+        }
+
+        let A = CompressedRistretto(read32!());
+        let A1 = CompressedRistretto(read32!());
+        let B = CompressedRistretto(read32!());
+
+        let r1 = Scalar::from_canonical_bytes(read32!()).ok_or(ProofError::FormatError)?;
+        let s1 = Scalar::from_canonical_bytes(read32!()).ok_or(ProofError::FormatError)?;
+        let d1 = Scalar::from_canonical_bytes(read32!()).ok_or(ProofError::FormatError)?;
+
+        let mut L_vec = Vec::with_capacity(lg_n);
+        let mut R_vec = Vec::with_capacity(lg_n);
+        for _ in 0..lg_n {
+            L_vec.push(CompressedRistretto(read32!()));
+            R_vec.push(CompressedRistretto(read32!()));
+        }
+
+        Ok(RangeProofPlus {
+            A,
+            A1,
+            B,
+            r1,
+            s1,
+            d1,
+            L_vec,
+            R_vec,
+        })
+    }
+}
+
+impl Serialize for RangeProofPlus {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for RangeProofPlus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RangeProofPlusVisitor;
+
+        impl<'de> Visitor<'de> for RangeProofPlusVisitor {
+            type Value = RangeProofPlus;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid RangeProofPlus")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<RangeProofPlus, E>
+            where
+                E: serde::de::Error,
+            {
+                // Using Error::custom requires T: Display, which our error
+                // type only implements when it implements std::error::Error.
+                #[cfg(feature = "std")]
+                return RangeProofPlus::from_bytes(v).map_err(serde::de::Error::custom);
+                // In no-std contexts, drop the error message.
+                #[cfg(not(feature = "std"))]
+                return RangeProofPlus::from_bytes(v)
+                    .map_err(|_| serde::de::Error::custom("deserialization error"));
+            }
+        }
+
+        deserializer.deserialize_bytes(RangeProofPlusVisitor)
+    }
+}
+
+/// Checks that an `n`-bit, `m`-party proof is supported and fits in
+/// the generators, and returns the bitsize and aggregation size padded
+/// to powers of two.
+fn check_sizes(
+    bp_gens: &BulletproofGens,
+    n: usize,
+    m: usize,
+) -> Result<(usize, usize), ProofError> {
+    let padded_n = padded_bitsize(n).ok_or(ProofError::InvalidBitsize)?;
+    if m == 0 {
+        return Err(ProofError::InvalidAggregation);
+    }
+    let padded_m = m.next_power_of_two();
+    if bp_gens.gens_capacity < padded_n || bp_gens.party_capacity < padded_m {
+        return Err(ProofError::InvalidGeneratorsLength);
+    }
+    Ok((padded_n, padded_m))
+}
+
+/// Compute the vector
+/// \\[
+/// \mathbf{d} = z^{2} \cdot \mathbf{2}^{n} \Vert z^{4} \cdot \mathbf{2}^{n} \Vert \cdots \Vert z^{2m} \cdot \mathbf{2}^{n},
+/// \\]
+/// where each \\(\mathbf{2}^{n}\\) is padded with zeros to the padded bitsize.
+fn d_vec(n: usize, m: usize, z: &Scalar) -> Vec<Scalar> {
+    let powers_of_2: Vec<Scalar> = padded_powers_of_2(n).collect();
+    util::exp_iter(z * z)
+        .skip(1)
+        .take(m)
+        .flat_map(|exp_z| powers_of_2.iter().map(move |exp_2| exp_2 * exp_z))
+        .collect()
+}
+
+/// Computes the weighted inner product
+/// \\[
+/// \mathbf{a} \odot_y \mathbf{b} = \sum_{i=0}^{n-1} a_i \cdot b_i \cdot y^{i+1}.
+/// \\]
+fn weighted_inner_product(a: &[Scalar], b: &[Scalar], y: &Scalar) -> Scalar {
+    a.iter()
+        .zip(b.iter())
+        .zip(util::exp_iter(*y).skip(1))
+        .map(|((a_i, b_i), exp_y)| a_i * b_i * exp_y)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::range_proof::RangeProof;
+
+    fn create_and_verify_helper(n: usize, m: usize) {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 8);
+
+        // Prover's scope
+        let (proof_bytes, value_commitments) = {
+            use self::rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
+            let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
+            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+
+            let mut transcript = Transcript::new(b"RangeProofPlusTest");
+            let (proof, value_commitments) = RangeProofPlus::prove_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                n,
+            )
+            .unwrap();
+            assert_eq!(proof.to_bytes().len(), proof.serialized_size());
+
+            (bincode::serialize(&proof).unwrap(), value_commitments)
+        };
+
+        // Verifier's scope
+        {
+            let proof: RangeProofPlus = bincode::deserialize(&proof_bytes).unwrap();
+
+            let mut transcript = Transcript::new(b"RangeProofPlusTest");
+            assert!(proof
+                .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+                .is_ok());
+
+            // A different statement must not verify
+            let mut transcript = Transcript::new(b"RangeProofPlusTest");
+            let mut value_commitments = value_commitments;
+            value_commitments[0] = pc_gens.commit(Scalar::one(), Scalar::one()).compress();
+            assert!(proof
+                .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn create_and_verify_n_8_m_1() {
+        create_and_verify_helper(8, 1);
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_2() {
+        create_and_verify_helper(32, 2);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_1() {
+        create_and_verify_helper(64, 1);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_8() {
+        create_and_verify_helper(64, 8);
+    }
+
+    #[test]
+    fn create_and_verify_n_16_m_3() {
+        create_and_verify_helper(16, 3);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_5() {
+        create_and_verify_helper(64, 5);
+    }
+
+    #[test]
+    fn create_and_verify_n_1_m_1() {
+        create_and_verify_helper(1, 1);
+    }
+
+    #[test]
+    fn create_and_verify_n_40_m_3() {
+        create_and_verify_helper(40, 3);
+    }
+
+    #[test]
+    fn bitsize_boundaries() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+
+        let prove_and_verify = |v: u64, n: usize| {
+            let blinding = Scalar::random(&mut thread_rng());
+            let mut transcript = Transcript::new(b"RangeProofPlusTest");
+            let (proof, V) =
+                RangeProofPlus::prove_single(&bp_gens, &pc_gens, &mut transcript, v, &blinding, n)
+                    .unwrap();
+            let mut transcript = Transcript::new(b"RangeProofPlusTest");
+            proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &V, n)
+        };
+
+        for n in [1, 7, 40, 63].iter().cloned() {
+            let max = (1u64 << n) - 1;
+            assert!(prove_and_verify(max, n).is_ok());
+            assert_eq!(
+                prove_and_verify(max + 1, n),
+                Err(ProofError::VerificationError)
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_value_fails() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+        let blinding = Scalar::random(&mut thread_rng());
+
+        let mut transcript = Transcript::new(b"RangeProofPlusTest");
+        let (proof, V) = RangeProofPlus::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            1 << 32,
+            &blinding,
+            32,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"RangeProofPlusTest");
+        assert_eq!(
+            proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &V, 32),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn smaller_than_rangeproof() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+        let blinding = Scalar::random(&mut thread_rng());
+
+        let mut transcript = Transcript::new(b"RangeProofPlusTest");
+        let (plus, _) =
+            RangeProofPlus::prove_single(&bp_gens, &pc_gens, &mut transcript, 7, &blinding, 64)
+                .unwrap();
+        let mut transcript = Transcript::new(b"RangeProofPlusTest");
+        let (proof, _) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 7, &blinding, 64)
+                .unwrap();
+
+        assert_eq!(plus.to_bytes().len() + 3 * 32, proof.to_bytes().len());
+    }
+
+    #[test]
+    fn nonces_are_hedged_with_the_witness() {
+        use rand_core::{impls, Error, SeedableRng};
+
+        use crate::range_proof::rng::SeededRng;
+
+        /// A broken RNG which only returns zeros.
+        struct ZeroRng;
+
+        impl RngCore for ZeroRng {
+            fn next_u32(&mut self) -> u32 {
+                0
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                0
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                impls::fill_bytes_via_next(self, dest)
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+
+        impl CryptoRng for ZeroRng {}
+
+        fn prove<T: RngCore + CryptoRng>(
+            transcript_label: &'static [u8],
+            v: u64,
+            blinding: u64,
+            rng: &mut T,
+        ) -> RangeProofPlus {
+            RangeProofPlus::prove_single_with_rng(
+                &BulletproofGens::new(32, 1),
+                &PedersenGens::default(),
+                &mut Transcript::new(transcript_label),
+                v,
+                &Scalar::from(blinding),
+                32,
+                rng,
+            )
+            .unwrap()
+            .0
+        }
+
+        // With a broken RNG, the final round nonces of B still differ
+        // between witnesses
+        let proof = prove(b"HedgedNonceTest", 5, 7, &mut ZeroRng);
+        assert_ne!(proof.B, prove(b"HedgedNonceTest", 6, 7, &mut ZeroRng).B);
+        assert_ne!(proof.B, prove(b"HedgedNonceTest", 5, 8, &mut ZeroRng).B);
+
+        // A seeded RNG makes proofs reproducible, and they verify
+        let seeded = |label: &'static [u8], seed: u8| {
+            prove(label, 5, 7, &mut SeededRng::from_seed([seed; 32]))
+        };
+        let proof = seeded(b"HedgedNonceTest", 1);
+        assert_eq!(proof.to_bytes(), seeded(b"HedgedNonceTest", 1).to_bytes());
+        assert_ne!(proof.B, seeded(b"HedgedNonceTest", 2).B);
+
+        // A reused seed still gives fresh nonces for another transcript
+        assert_ne!(proof.A, seeded(b"OtherHedgedNonceTest", 1).A);
+
+        let pc_gens = PedersenGens::default();
+        let V = pc_gens.commit(Scalar::from(5u64), Scalar::from(7u64));
+        assert!(proof
+            .verify_single(
+                &BulletproofGens::new(32, 1),
+                &pc_gens,
+                &mut Transcript::new(b"HedgedNonceTest"),
+                &V.compress(),
+                32,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn malformed_bytes_are_rejected() {
+        assert_eq!(
+            RangeProofPlus::from_bytes(&[0u8; 5 * 32]).unwrap_err(),
+            ProofError::FormatError
+        );
+        assert_eq!(
+            RangeProofPlus::from_bytes(&[0u8; 7 * 32]).unwrap_err(),
+            ProofError::FormatError
+        );
+        assert_eq!(
+            RangeProofPlus::from_bytes(&[0u8; 6 * 32 + 1]).unwrap_err(),
+            ProofError::FormatError
+        );
+        // Non-canonical scalar r1
+        let mut bytes = [0u8; 6 * 32];
+        bytes[3 * 32..4 * 32].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            RangeProofPlus::from_bytes(&bytes).unwrap_err(),
+            ProofError::FormatError
+        );
+    }
+}
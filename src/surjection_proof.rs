@@ -0,0 +1,607 @@
+#![allow(non_snake_case)]
+//! Asset surjection proofs for confidential assets.
+//!
+//! An output of a confidential asset transaction commits to its value
+//! with a blinded asset tag \\(H' = H + r \tilde{B}\\) as value base,
+//! see [`ExtendedPedersenGens::blind_value_base`].  A surjection proof
+//! shows that the tag of an output is a re-blinding of one of the
+//! tags of the inputs, \\(H' - H\_k = r' \tilde{B}\\), without
+//! revealing which input \\(k\\) it is.
+//!
+//! The proof is a one-out-of-\\(n\\) proof of knowledge of the
+//! discrete logarithm of one of the differences \\(H' - H\_i\\) with
+//! respect to the blinding base \\(\tilde{B}\\) of the
+//! [`PedersenGens`], in which the prover simulates the Schnorr proofs
+//! for all of the other inputs.  The nonce commitments are kept in
+//! the proof, so that all of its equations are linear and many proofs
+//! can be verified with a single multiscalar multiplication.
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate rand;
+
+#[cfg(feature = "std")]
+use self::rand::thread_rng;
+use alloc::vec::Vec;
+
+use core::iter;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+use crate::batch::{self, DeferredCheck};
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, ExtendedPedersenGens, PedersenGens};
+use crate::transcript::TranscriptProtocol;
+use crate::util;
+
+/// The `SurjectionProof` struct represents a proof that an output
+/// asset tag is a re-blinding of one of a list of input asset tags.
+///
+/// The input tags and the output tag are not included in the proof,
+/// and must be known to the verifier.
+#[derive(Clone, Debug)]
+pub struct SurjectionProof {
+    /// Commitments to the nonces, one per input tag
+    R_vec: Vec<CompressedRistretto>,
+    /// Challenges of all but the last input tag, whose challenge is
+    /// determined by the others
+    c_vec: Vec<Scalar>,
+    /// Responses, one per input tag
+    s_vec: Vec<Scalar>,
+}
+
+/// A single statement in a batch of surjection proofs to be verified
+/// with [`SurjectionProof::verify_batch`].
+///
+/// Each statement carries its own transcript, which must be in the
+/// same state as the one used by the prover.
+pub struct SurjectionProofStatement<'a> {
+    /// The surjection proof to verify.
+    pub proof: &'a SurjectionProof,
+    /// The asset tags of the inputs.
+    pub input_tags: &'a [CompressedRistretto],
+    /// The asset tag of the output.
+    pub output_tag: &'a CompressedRistretto,
+    /// The verifier's transcript for this proof.
+    pub transcript: &'a mut Transcript,
+}
+
+impl SurjectionProof {
+    /// Create a proof that the `output_tag` is a re-blinding of the
+    /// input tag at position `input_index` in `input_tags`, that is
+    /// \\(H' = H\_k + r \tilde{B}\\) where \\(r\\) is the `blinding`.
+    /// For tags blinded from the same value base, the `blinding` is the
+    /// difference between the blinding factors of the output and the
+    /// input.
+    ///
+    /// Returns [`ProofError::InvalidSurjection`] if the output tag is
+    /// not such a re-blinding of the input tag.
+    pub fn prove_with_rng<T: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        input_tags: &[CompressedRistretto],
+        output_tag: &CompressedRistretto,
+        input_index: usize,
+        blinding: &Scalar,
+        rng: &mut T,
+    ) -> Result<SurjectionProof, ProofError> {
+        let n = input_tags.len();
+        if input_index >= n {
+            return Err(ProofError::InvalidSurjection);
+        }
+        let differences = tag_differences(input_tags, output_tag)?;
+        if differences[input_index] != blinding * pc_gens.B_blinding {
+            return Err(ProofError::InvalidSurjection);
+        }
+
+        transcript.surjection_domain_sep(n as u64);
+        append_tags(transcript, input_tags, output_tag);
+
+        // Simulate the proofs of the other inputs with random
+        // challenges and responses, and commit to a nonce for ours.
+        let mut nonce = Scalar::random(rng);
+        let mut c_vec: Vec<Scalar> = Vec::with_capacity(n);
+        let mut s_vec: Vec<Scalar> = Vec::with_capacity(n);
+        let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(n);
+        for (i, P_i) in differences.iter().enumerate() {
+            let R_i = if i == input_index {
+                c_vec.push(Scalar::zero());
+                s_vec.push(Scalar::zero());
+                nonce * pc_gens.B_blinding
+            } else {
+                let c_i = Scalar::random(rng);
+                let s_i = Scalar::random(rng);
+                c_vec.push(c_i);
+                s_vec.push(s_i);
+                s_i * pc_gens.B_blinding - c_i * P_i
+            }
+            .compress();
+            transcript.append_point(b"R", &R_i);
+            R_vec.push(R_i);
+        }
+
+        let c = transcript.challenge_scalar(b"c");
+
+        // Our challenge completes the sum of the challenges to c
+        let c_k = c - c_vec.iter().sum::<Scalar>();
+        c_vec[input_index] = c_k;
+        s_vec[input_index] = nonce + c_k * blinding;
+        nonce.zeroize();
+
+        c_vec.pop();
+        Ok(SurjectionProof {
+            R_vec,
+            c_vec,
+            s_vec,
+        })
+    }
+
+    /// Create a proof that the `output_tag` is a re-blinding of the
+    /// input tag at position `input_index` in `input_tags`.
+    ///
+    /// This is a convenience wrapper around [`SurjectionProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove(
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        input_tags: &[CompressedRistretto],
+        output_tag: &CompressedRistretto,
+        input_index: usize,
+        blinding: &Scalar,
+    ) -> Result<SurjectionProof, ProofError> {
+        SurjectionProof::prove_with_rng(
+            pc_gens,
+            transcript,
+            input_tags,
+            output_tag,
+            input_index,
+            blinding,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Verifies that the `output_tag` is a re-blinding of one of the
+    /// `input_tags`.
+    pub fn verify_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        input_tags: &[CompressedRistretto],
+        output_tag: &CompressedRistretto,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let check = self.verification_check(transcript, input_tags, output_tag, rng)?;
+
+        // The check has no terms for the Bulletproofs generators
+        if check.verify(
+            &BulletproofGens::new(0, 0),
+            &ExtendedPedersenGens::from(*pc_gens),
+        ) {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Verifies that the `output_tag` is a re-blinding of one of the
+    /// `input_tags`.
+    ///
+    /// This is a convenience wrapper around [`SurjectionProof::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify(
+        &self,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        input_tags: &[CompressedRistretto],
+        output_tag: &CompressedRistretto,
+    ) -> Result<(), ProofError> {
+        self.verify_with_rng(
+            pc_gens,
+            transcript,
+            input_tags,
+            output_tag,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Verifies a batch of independent surjection proofs at once.
+    ///
+    /// The verification equations of the proofs are weighted by random
+    /// scalars and checked with a single multiscalar multiplication.
+    /// If the batch fails to verify, adding the statements to a
+    /// [`BatchVerifier`](crate::BatchVerifier) and calling
+    /// [`find_invalid`](crate::BatchVerifier::find_invalid) identifies
+    /// the invalid ones by bisecting the batch.
+    pub fn verify_batch_with_rng<'a, I, T>(
+        pc_gens: &PedersenGens,
+        statements: I,
+        rng: &mut T,
+    ) -> Result<(), ProofError>
+    where
+        I: IntoIterator<Item = SurjectionProofStatement<'a>>,
+        T: RngCore + CryptoRng,
+    {
+        let mut checks = Vec::new();
+        for statement in statements {
+            let check = statement.proof.verification_check(
+                statement.transcript,
+                statement.input_tags,
+                statement.output_tag,
+                rng,
+            )?;
+            checks.push(check);
+        }
+
+        // The equations of each proof are already weighted at random
+        if batch::verify_batch(
+            checks.iter().map(|check| (Scalar::one(), check)),
+            &BulletproofGens::new(0, 0),
+            &ExtendedPedersenGens::from(*pc_gens),
+        ) {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Verifies a batch of independent surjection proofs at once.
+    ///
+    /// This is a convenience wrapper around [`SurjectionProof::verify_batch_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_batch<'a, I>(pc_gens: &PedersenGens, statements: I) -> Result<(), ProofError>
+    where
+        I: IntoIterator<Item = SurjectionProofStatement<'a>>,
+    {
+        SurjectionProof::verify_batch_with_rng(pc_gens, statements, &mut thread_rng())
+    }
+
+    /// Replays the proof transcript and constructs the verification
+    /// equation for this proof, without evaluating it.
+    ///
+    /// The equation for each input tag, \\(s\_i \tilde{B} = R\_i + c\_i (H' - H\_i)\\),
+    /// is weighted by its own random scalar.
+    pub(crate) fn verification_check<T: RngCore + CryptoRng>(
+        &self,
+        transcript: &mut Transcript,
+        input_tags: &[CompressedRistretto],
+        output_tag: &CompressedRistretto,
+        rng: &mut T,
+    ) -> Result<DeferredCheck, ProofError> {
+        let n = input_tags.len();
+        if n == 0 || self.R_vec.len() != n {
+            return Err(ProofError::VerificationError);
+        }
+
+        transcript.surjection_domain_sep(n as u64);
+        append_tags(transcript, input_tags, output_tag);
+        for R_i in self.R_vec.iter() {
+            transcript.validate_and_append_point(b"R", R_i)?;
+        }
+
+        let c = transcript.challenge_scalar(b"c");
+        let c_last = c - self.c_vec.iter().sum::<Scalar>();
+        let c_vec = self.c_vec.iter().cloned().chain(iter::once(c_last));
+
+        let weights: Vec<Scalar> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let weighted_c: Vec<Scalar> = weights.iter().zip(c_vec).map(|(w, c_i)| w * c_i).collect();
+
+        let dynamic_points = self
+            .R_vec
+            .iter()
+            .chain(iter::once(output_tag))
+            .chain(input_tags.iter())
+            .map(|P| P.decompress().ok_or(ProofError::VerificationError))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DeferredCheck {
+            dynamic_scalars: weights
+                .iter()
+                .map(|w| -w)
+                .chain(iter::once(-weighted_c.iter().sum::<Scalar>()))
+                .chain(weighted_c.iter().cloned())
+                .collect(),
+            dynamic_points,
+            B_scalar: Scalar::zero(),
+            B_blinding_scalars: alloc::vec![weights
+                .iter()
+                .zip(self.s_vec.iter())
+                .map(|(w, s_i)| w * s_i)
+                .sum()],
+            G_scalars: Vec::new(),
+            H_scalars: Vec::new(),
+        })
+    }
+
+    /// Returns the number of input tags this proof is for.
+    pub fn input_count(&self) -> usize {
+        self.R_vec.len()
+    }
+
+    /// Returns the size in bytes required to serialize the `SurjectionProof`.
+    pub fn serialized_size(&self) -> usize {
+        (3 * self.R_vec.len() - 1) * 32
+    }
+
+    /// Serializes the proof into a byte array of \\(3n - 1\\) 32-byte
+    /// elements, where \\(n\\) is the number of input tags.
+    ///
+    /// # Layout
+    ///
+    /// The layout of the surjection proof encoding is:
+    ///
+    /// * \\(n\\) compressed Ristretto points \\(R\_0,\dots,R\_{n-1}\\),
+    /// * \\(n - 1\\) scalars \\(c\_0,\dots,c\_{n-2}\\),
+    /// * \\(n\\) scalars \\(s\_0,\dots,s\_{n-1}\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        for R_i in self.R_vec.iter() {
+            buf.extend_from_slice(R_i.as_bytes());
+        }
+        for c_i in self.c_vec.iter() {
+            buf.extend_from_slice(c_i.as_bytes());
+        }
+        for s_i in self.s_vec.iter() {
+            buf.extend_from_slice(s_i.as_bytes());
+        }
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `SurjectionProof`.
+    pub fn from_bytes(slice: &[u8]) -> Result<SurjectionProof, ProofError> {
+        if slice.len() % 32 != 0 {
+            return Err(ProofError::FormatError);
+        }
+        let num_elements = slice.len() / 32;
+        if num_elements % 3 != 2 {
+            return Err(ProofError::FormatError);
+        }
+        let n = (num_elements + 1) / 3;
+
+        let mut elements = slice.chunks_exact(32).map(util::read32);
+        let R_vec: Vec<CompressedRistretto> =
+            elements.by_ref().take(n).map(CompressedRistretto).collect();
+        let scalars = elements
+            .map(|bytes| Scalar::from_canonical_bytes(bytes).ok_or(ProofError::FormatError))
+            .collect::<Result<Vec<_>, _>>()?;
+        let c_vec = scalars[..n - 1].to_vec();
+        let s_vec = scalars[n - 1..].to_vec();
+
+        Ok(SurjectionProof {
+            R_vec,
+            c_vec,
+            s_vec,
+        })
+    }
+}
+
+impl Serialize for SurjectionProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for SurjectionProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SurjectionProofVisitor;
+
+        impl<'de> Visitor<'de> for SurjectionProofVisitor {
+            type Value = SurjectionProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid SurjectionProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<SurjectionProof, E>
+            where
+                E: serde::de::Error,
+            {
+                // Using Error::custom requires T: Display, which our error
+                // type only implements when it implements std::error::Error.
+                #[cfg(feature = "std")]
+                return SurjectionProof::from_bytes(v).map_err(serde::de::Error::custom);
+                // In no-std contexts, drop the error message.
+                #[cfg(not(feature = "std"))]
+                return SurjectionProof::from_bytes(v)
+                    .map_err(|_| serde::de::Error::custom("deserialization error"));
+            }
+        }
+
+        deserializer.deserialize_bytes(SurjectionProofVisitor)
+    }
+}
+
+/// Appends the input tags and the output tag to the `transcript`.
+fn append_tags(
+    transcript: &mut Transcript,
+    input_tags: &[CompressedRistretto],
+    output_tag: &CompressedRistretto,
+) {
+    for input_tag in input_tags.iter() {
+        transcript.append_point(b"input_tag", input_tag);
+    }
+    transcript.append_point(b"output_tag", output_tag);
+}
+
+/// Computes the differences \\(H' - H\_i\\) between the output tag and
+/// each of the input tags.
+fn tag_differences(
+    input_tags: &[CompressedRistretto],
+    output_tag: &CompressedRistretto,
+) -> Result<Vec<RistrettoPoint>, ProofError> {
+    let output_tag = output_tag.decompress().ok_or(ProofError::FormatError)?;
+    input_tags
+        .iter()
+        .map(|input_tag| {
+            input_tag
+                .decompress()
+                .map(|input_tag| output_tag - input_tag)
+                .ok_or(ProofError::FormatError)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::Rng;
+
+    /// Creates blinded tags for `n` inputs of distinct assets, and an
+    /// output tag re-blinding the input at `input_index`, returning the
+    /// tags and the blinding of the output relative to that input.
+    fn tags_helper(
+        pc_gens: &PedersenGens,
+        n: usize,
+        input_index: usize,
+    ) -> (Vec<CompressedRistretto>, CompressedRistretto, Scalar) {
+        let mut rng = rand::thread_rng();
+        let ext_gens = ExtendedPedersenGens::from(*pc_gens);
+        let assets: Vec<RistrettoPoint> = (0..n)
+            .map(|_| ExtendedPedersenGens::asset_value_base(&rng.gen::<[u8; 32]>()))
+            .collect();
+        let input_blindings: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let input_tags = assets
+            .iter()
+            .zip(input_blindings.iter())
+            .map(|(asset, r)| ext_gens.blind_value_base(asset, r).compress())
+            .collect();
+        let output_blinding = Scalar::random(&mut rng);
+        let output_tag = ext_gens
+            .blind_value_base(&assets[input_index], &output_blinding)
+            .compress();
+        (
+            input_tags,
+            output_tag,
+            output_blinding - input_blindings[input_index],
+        )
+    }
+
+    #[test]
+    fn create_and_verify() {
+        let pc_gens = PedersenGens::default();
+
+        for &(n, input_index) in &[(1, 0), (2, 0), (2, 1), (5, 3), (8, 7)] {
+            let (input_tags, output_tag, blinding) = tags_helper(&pc_gens, n, input_index);
+
+            let mut transcript = Transcript::new(b"SurjectionProofTest");
+            let proof = SurjectionProof::prove(
+                &pc_gens,
+                &mut transcript,
+                &input_tags,
+                &output_tag,
+                input_index,
+                &blinding,
+            )
+            .unwrap();
+            assert_eq!(proof.input_count(), n);
+
+            let proof_bytes = bincode::serialize(&proof).unwrap();
+            let proof: SurjectionProof = bincode::deserialize(&proof_bytes).unwrap();
+            assert_eq!(proof.to_bytes().len(), proof.serialized_size());
+
+            let mut transcript = Transcript::new(b"SurjectionProofTest");
+            assert!(proof
+                .verify(&pc_gens, &mut transcript, &input_tags, &output_tag)
+                .is_ok());
+
+            // The proof does not hold for other input tags
+            let (other_tags, _, _) = tags_helper(&pc_gens, n, input_index);
+            let mut transcript = Transcript::new(b"SurjectionProofTest");
+            assert_eq!(
+                proof.verify(&pc_gens, &mut transcript, &other_tags, &output_tag),
+                Err(ProofError::VerificationError)
+            );
+        }
+    }
+
+    #[test]
+    fn cannot_prove_unrelated_output() {
+        let pc_gens = PedersenGens::default();
+        let (input_tags, output_tag, blinding) = tags_helper(&pc_gens, 3, 1);
+
+        let mut transcript = Transcript::new(b"SurjectionProofTest");
+        for &input_index in &[0, 2, 3] {
+            assert_eq!(
+                SurjectionProof::prove(
+                    &pc_gens,
+                    &mut transcript,
+                    &input_tags,
+                    &output_tag,
+                    input_index,
+                    &blinding,
+                )
+                .err(),
+                Some(ProofError::InvalidSurjection)
+            );
+        }
+    }
+
+    #[test]
+    fn batch_verify() {
+        let pc_gens = PedersenGens::default();
+
+        let statements: Vec<_> = [(3, 2), (1, 0), (4, 1)]
+            .iter()
+            .map(|&(n, input_index)| {
+                let (input_tags, output_tag, blinding) = tags_helper(&pc_gens, n, input_index);
+                let mut transcript = Transcript::new(b"SurjectionBatchTest");
+                let proof = SurjectionProof::prove(
+                    &pc_gens,
+                    &mut transcript,
+                    &input_tags,
+                    &output_tag,
+                    input_index,
+                    &blinding,
+                )
+                .unwrap();
+                (proof, input_tags, output_tag)
+            })
+            .collect();
+
+        let verify = |output_tags: &[CompressedRistretto]| {
+            let mut transcripts: Vec<_> = statements
+                .iter()
+                .map(|_| Transcript::new(b"SurjectionBatchTest"))
+                .collect();
+            SurjectionProof::verify_batch(
+                &pc_gens,
+                statements
+                    .iter()
+                    .zip(output_tags.iter())
+                    .zip(transcripts.iter_mut())
+                    .map(|(((proof, input_tags, _), output_tag), transcript)| {
+                        SurjectionProofStatement {
+                            proof,
+                            input_tags,
+                            output_tag,
+                            transcript,
+                        }
+                    }),
+            )
+        };
+
+        let output_tags: Vec<_> = statements.iter().map(|(_, _, tag)| *tag).collect();
+        assert!(verify(&output_tags).is_ok());
+
+        // Swapping the output tags of two statements fails the batch
+        let swapped = [output_tags[0], output_tags[2], output_tags[1]];
+        assert_eq!(verify(&swapped), Err(ProofError::VerificationError));
+    }
+}
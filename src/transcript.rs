@@ -5,14 +5,29 @@ use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 
 use crate::errors::ProofError;
+use crate::group::{Group, Ristretto, ScalarField};
 
 pub trait TranscriptProtocol {
     /// Append a domain separator for an `n`-bit, `m`-party range proof.
     fn rangeproof_domain_sep(&mut self, n: u64, m: u64);
 
+    /// Append a domain separator for an `n`-bit, `m`-party Bulletproofs+ range proof.
+    fn rangeproof_plus_domain_sep(&mut self, n: u64, m: u64);
+
+    /// Append a domain separator for a proof that a value lies in the
+    /// interval `[min, max]`.
+    fn interval_domain_sep(&mut self, min: u64, max: u64);
+
+    /// Append a domain separator for an `n`-bit proof that a value is
+    /// at least `min`.
+    fn minimum_value_domain_sep(&mut self, min: u64, n: u64);
+
     /// Append a domain separator for a length-`n` inner product proof.
     fn innerproduct_domain_sep(&mut self, n: u64);
 
+    /// Append a domain separator for a surjection proof over `n` input tags.
+    fn surjection_domain_sep(&mut self, n: u64);
+
     /// Append a domain separator for a constraint system.
     fn r1cs_domain_sep(&mut self);
 
@@ -38,6 +53,23 @@ pub trait TranscriptProtocol {
 
     /// Compute a `label`ed challenge variable.
     fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+
+    /// Append a `scalar` of the group `G` with the given `label`.
+    fn append_group_scalar<G: Group>(&mut self, label: &'static [u8], scalar: &G::Scalar);
+
+    /// Append a `point` of the group `G` with the given `label`.
+    fn append_group_point<G: Group>(&mut self, label: &'static [u8], point: &G::CompressedPoint);
+
+    /// Check that a point of the group `G` is not the identity, then
+    /// append it to the transcript.  Otherwise, return an error.
+    fn validate_and_append_group_point<G: Group>(
+        &mut self,
+        label: &'static [u8],
+        point: &G::CompressedPoint,
+    ) -> Result<(), ProofError>;
+
+    /// Compute a `label`ed challenge variable in the scalars of the group `G`.
+    fn challenge_group_scalar<G: Group>(&mut self, label: &'static [u8]) -> G::Scalar;
 }
 
 impl TranscriptProtocol for Transcript {
@@ -47,11 +79,34 @@ impl TranscriptProtocol for Transcript {
         self.append_u64(b"m", m);
     }
 
+    fn rangeproof_plus_domain_sep(&mut self, n: u64, m: u64) {
+        self.append_message(b"dom-sep", b"rangeproof+ v1");
+        self.append_u64(b"n", n);
+        self.append_u64(b"m", m);
+    }
+
+    fn interval_domain_sep(&mut self, min: u64, max: u64) {
+        self.append_message(b"dom-sep", b"interval v1");
+        self.append_u64(b"min", min);
+        self.append_u64(b"max", max);
+    }
+
+    fn minimum_value_domain_sep(&mut self, min: u64, n: u64) {
+        self.append_message(b"dom-sep", b"minimum value v1");
+        self.append_u64(b"min", min);
+        self.append_u64(b"n", n);
+    }
+
     fn innerproduct_domain_sep(&mut self, n: u64) {
         self.append_message(b"dom-sep", b"ipp v1");
         self.append_u64(b"n", n);
     }
 
+    fn surjection_domain_sep(&mut self, n: u64) {
+        self.append_message(b"dom-sep", b"surjection v1");
+        self.append_u64(b"n", n);
+    }
+
     fn r1cs_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"r1cs v1");
     }
@@ -65,11 +120,11 @@ impl TranscriptProtocol for Transcript {
     }
 
     fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
-        self.append_message(label, scalar.as_bytes());
+        self.append_group_scalar::<Ristretto>(label, scalar);
     }
 
     fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto) {
-        self.append_message(label, point.as_bytes());
+        self.append_group_point::<Ristretto>(label, point);
     }
 
     fn validate_and_append_point(
@@ -77,19 +132,37 @@ impl TranscriptProtocol for Transcript {
         label: &'static [u8],
         point: &CompressedRistretto,
     ) -> Result<(), ProofError> {
-        use curve25519_dalek::traits::IsIdentity;
+        self.validate_and_append_group_point::<Ristretto>(label, point)
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        self.challenge_group_scalar::<Ristretto>(label)
+    }
 
-        if point.is_identity() {
+    fn append_group_scalar<G: Group>(&mut self, label: &'static [u8], scalar: &G::Scalar) {
+        self.append_message(label, &scalar.to_bytes());
+    }
+
+    fn append_group_point<G: Group>(&mut self, label: &'static [u8], point: &G::CompressedPoint) {
+        self.append_message(label, G::compressed_as_bytes(point));
+    }
+
+    fn validate_and_append_group_point<G: Group>(
+        &mut self,
+        label: &'static [u8],
+        point: &G::CompressedPoint,
+    ) -> Result<(), ProofError> {
+        if G::is_identity(point) {
             Err(ProofError::VerificationError)
         } else {
-            Ok(self.append_message(label, point.as_bytes()))
+            Ok(self.append_message(label, G::compressed_as_bytes(point)))
         }
     }
 
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+    fn challenge_group_scalar<G: Group>(&mut self, label: &'static [u8]) -> G::Scalar {
         let mut buf = [0u8; 64];
         self.challenge_bytes(label, &mut buf);
 
-        Scalar::from_bytes_mod_order_wide(&buf)
+        G::Scalar::from_bytes_mod_order_wide(&buf)
     }
 }
@@ -6,51 +6,61 @@ extern crate alloc;
 use alloc::vec;
 use alloc::vec::Vec;
 use curve25519_dalek::scalar::Scalar;
-use zeroize::Zeroize;
 
+use crate::group::ScalarField;
 use crate::inner_product_proof::inner_product;
 use std::cmp::{max, min};
 
+/// Turns a collection into an iterator over its items, which runs in
+/// parallel on the rayon thread pool with the `parallel` feature.
+macro_rules! maybe_par_iter {
+    ($items:expr) => {{
+        #[cfg(feature = "parallel")]
+        {
+            rayon::iter::IntoParallelIterator::into_par_iter($items)
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            IntoIterator::into_iter($items)
+        }
+    }};
+}
+
 /// Represents a degree-1 vector polynomial \\(\mathbf{a} + \mathbf{b} \cdot x\\).
-pub struct VecPoly1(pub Vec<Scalar>, pub Vec<Scalar>);
+pub struct VecPoly1<S: ScalarField = Scalar>(pub Vec<S>, pub Vec<S>);
 
 /// Represents a degree-3 vector polynomial
 /// \\(\mathbf{a} + \mathbf{b} \cdot x + \mathbf{c} \cdot x^2 + \mathbf{d} \cdot x^3 \\).
 #[cfg(feature = "yoloproofs")]
-pub struct VecPoly3(
-    pub Vec<Scalar>,
-    pub Vec<Scalar>,
-    pub Vec<Scalar>,
-    pub Vec<Scalar>,
-);
+pub struct VecPoly3<S: ScalarField = Scalar>(pub Vec<S>, pub Vec<S>, pub Vec<S>, pub Vec<S>);
 
 /// Represents a degree-2 scalar polynomial \\(a + b \cdot x + c \cdot x^2\\)
-pub struct Poly2(pub Scalar, pub Scalar, pub Scalar);
+pub struct Poly2<S: ScalarField = Scalar>(pub S, pub S, pub S);
 
 /// Represents a degree-6 scalar polynomial, without the zeroth degree
 /// \\(a \cdot x + b \cdot x^2 + c \cdot x^3 + d \cdot x^4 + e \cdot x^5 + f \cdot x^6\\)
 #[cfg(feature = "yoloproofs")]
-pub struct Poly6 {
-    pub t1: Scalar,
-    pub t2: Scalar,
-    pub t3: Scalar,
-    pub t4: Scalar,
-    pub t5: Scalar,
-    pub t6: Scalar,
+pub struct Poly6<S: ScalarField = Scalar> {
+    pub t1: S,
+    pub t2: S,
+    pub t3: S,
+    pub t4: S,
+    pub t5: S,
+    pub t6: S,
 }
 
-/// Provides an iterator over the powers of a `Scalar`.
+/// Provides an iterator over the powers of a scalar.
 ///
 /// This struct is created by the `exp_iter` function.
-pub struct ScalarExp {
-    x: Scalar,
-    next_exp_x: Scalar,
+pub struct ScalarExp<S: ScalarField = Scalar> {
+    x: S,
+    next_exp_x: S,
 }
 
-impl Iterator for ScalarExp {
-    type Item = Scalar;
+impl<S: ScalarField> Iterator for ScalarExp<S> {
+    type Item = S;
 
-    fn next(&mut self) -> Option<Scalar> {
+    fn next(&mut self) -> Option<S> {
         let exp_x = self.next_exp_x;
         self.next_exp_x *= self.x;
         Some(exp_x)
@@ -62,29 +72,29 @@ impl Iterator for ScalarExp {
 }
 
 /// Return an iterator of the powers of `x`.
-pub fn exp_iter(x: Scalar) -> ScalarExp {
-    let next_exp_x = Scalar::one();
+pub fn exp_iter<S: ScalarField>(x: S) -> ScalarExp<S> {
+    let next_exp_x = S::one();
     ScalarExp { x, next_exp_x }
 }
 
-pub fn add_vec(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
+pub fn add_vec<S: ScalarField>(a: &[S], b: &[S]) -> Vec<S> {
     if a.len() != b.len() {
         // throw some error
         //println!("lengths of vectors don't match for vector addition");
     }
-    let mut out = vec![Scalar::zero(); b.len()];
+    let mut out = vec![S::zero(); b.len()];
     for i in 0..a.len() {
         out[i] = a[i] + b[i];
     }
     out
 }
 
-impl VecPoly1 {
+impl<S: ScalarField> VecPoly1<S> {
     pub fn zero(n: usize) -> Self {
-        VecPoly1(vec![Scalar::zero(); n], vec![Scalar::zero(); n])
+        VecPoly1(vec![S::zero(); n], vec![S::zero(); n])
     }
 
-    pub fn inner_product(&self, rhs: &VecPoly1) -> Poly2 {
+    pub fn inner_product(&self, rhs: &VecPoly1<S>) -> Poly2<S> {
         // Uses Karatsuba's method
         let l = self;
         let r = rhs;
@@ -100,9 +110,9 @@ impl VecPoly1 {
         Poly2(t0, t1, t2)
     }
 
-    pub fn eval(&self, x: Scalar) -> Vec<Scalar> {
+    pub fn eval(&self, x: S) -> Vec<S> {
         let n = self.0.len();
-        let mut out = vec![Scalar::zero(); n];
+        let mut out = vec![S::zero(); n];
         for i in 0..n {
             out[i] = self.0[i] + self.1[i] * x;
         }
@@ -111,13 +121,13 @@ impl VecPoly1 {
 }
 
 #[cfg(feature = "yoloproofs")]
-impl VecPoly3 {
+impl<S: ScalarField> VecPoly3<S> {
     pub fn zero(n: usize) -> Self {
         VecPoly3(
-            vec![Scalar::zero(); n],
-            vec![Scalar::zero(); n],
-            vec![Scalar::zero(); n],
-            vec![Scalar::zero(); n],
+            vec![S::zero(); n],
+            vec![S::zero(); n],
+            vec![S::zero(); n],
+            vec![S::zero(); n],
         )
     }
 
@@ -125,7 +135,7 @@ impl VecPoly3 {
     /// - `lhs.0` is zero;
     /// - `rhs.2` is zero;
     /// This is the case in the constraint system proof.
-    pub fn special_inner_product(lhs: &Self, rhs: &Self) -> Poly6 {
+    pub fn special_inner_product(lhs: &Self, rhs: &Self) -> Poly6<S> {
         // TODO: make checks that l_poly.0 and r_poly.2 are zero.
 
         let t1 = inner_product(&lhs.1, &rhs.0);
@@ -145,9 +155,9 @@ impl VecPoly3 {
         }
     }
 
-    pub fn eval(&self, x: Scalar) -> Vec<Scalar> {
+    pub fn eval(&self, x: S) -> Vec<S> {
         let n = self.0.len();
-        let mut out = vec![Scalar::zero(); n];
+        let mut out = vec![S::zero(); n];
         for i in 0..n {
             out[i] = self.0[i] + x * (self.1[i] + x * (self.2[i] + x * self.3[i]));
         }
@@ -155,20 +165,20 @@ impl VecPoly3 {
     }
 }
 
-impl Poly2 {
-    pub fn eval(&self, x: Scalar) -> Scalar {
+impl<S: ScalarField> Poly2<S> {
+    pub fn eval(&self, x: S) -> S {
         self.0 + x * (self.1 + x * self.2)
     }
 }
 
 #[cfg(feature = "yoloproofs")]
-impl Poly6 {
-    pub fn eval(&self, x: Scalar) -> Scalar {
+impl<S: ScalarField> Poly6<S> {
+    pub fn eval(&self, x: S) -> S {
         x * (self.t1 + x * (self.t2 + x * (self.t3 + x * (self.t4 + x * (self.t5 + x * self.t6)))))
     }
 }
 
-impl Drop for VecPoly1 {
+impl<S: ScalarField> Drop for VecPoly1<S> {
     fn drop(&mut self) {
         for e in self.0.iter_mut() {
             e.zeroize();
@@ -179,7 +189,7 @@ impl Drop for VecPoly1 {
     }
 }
 
-impl Drop for Poly2 {
+impl<S: ScalarField> Drop for Poly2<S> {
     fn drop(&mut self) {
         self.0.zeroize();
         self.1.zeroize();
@@ -188,7 +198,7 @@ impl Drop for Poly2 {
 }
 
 #[cfg(feature = "yoloproofs")]
-impl Drop for VecPoly3 {
+impl<S: ScalarField> Drop for VecPoly3<S> {
     fn drop(&mut self) {
         for e in self.0.iter_mut() {
             e.zeroize();
@@ -206,7 +216,7 @@ impl Drop for VecPoly3 {
 }
 
 #[cfg(feature = "yoloproofs")]
-impl Drop for Poly6 {
+impl<S: ScalarField> Drop for Poly6<S> {
     fn drop(&mut self) {
         self.t1.zeroize();
         self.t2.zeroize();
@@ -217,11 +227,17 @@ impl Drop for Poly6 {
     }
 }
 
+/// Converts a 128-bit value to a scalar, from its two 64-bit halves.
+pub fn scalar_from_u128<S: ScalarField>(v: u128) -> S {
+    let two_32 = S::from(1u64 << 32);
+    S::from((v >> 64) as u64) * two_32 * two_32 + S::from(v as u64)
+}
+
 /// Raises `x` to the power `n` using binary exponentiation,
 /// with (1 to 2)*lg(n) scalar multiplications.
 /// TODO: a consttime version of this would be awfully similar to a Montgomery ladder.
-pub fn scalar_exp_vartime(x: &Scalar, mut n: u64) -> Scalar {
-    let mut result = Scalar::one();
+pub fn scalar_exp_vartime<S: ScalarField>(x: &S, mut n: u64) -> S {
+    let mut result = S::one();
     let mut aux = *x; // x, x^2, x^4, x^8, ...
     while n > 0 {
         let bit = n & 1;
@@ -238,15 +254,15 @@ pub fn scalar_exp_vartime(x: &Scalar, mut n: u64) -> Scalar {
 /// If `n` is a power of 2, it uses the efficient algorithm with `2*lg n` multiplications and additions.
 /// If `n` is not a power of 2, it uses the slow algorithm with `n` multiplications and additions.
 /// In the Bulletproofs case, all calls to `sum_of_powers` should have `n` as a power of 2.
-pub fn sum_of_powers(x: &Scalar, n: usize) -> Scalar {
+pub fn sum_of_powers<S: ScalarField>(x: &S, n: usize) -> S {
     if !n.is_power_of_two() {
         return sum_of_powers_slow(x, n);
     }
     if n == 0 || n == 1 {
-        return Scalar::from(n as u64);
+        return S::from(n as u64);
     }
     let mut m = n;
-    let mut result = Scalar::one() + x;
+    let mut result = S::one() + *x;
     let mut factor = *x;
     while m > 2 {
         factor = factor * factor;
@@ -257,7 +273,7 @@ pub fn sum_of_powers(x: &Scalar, n: usize) -> Scalar {
 }
 
 // takes the sum of all of the powers of x, up to n
-fn sum_of_powers_slow(x: &Scalar, n: usize) -> Scalar {
+fn sum_of_powers_slow<S: ScalarField>(x: &S, n: usize) -> S {
     exp_iter(*x).take(n).sum()
 }
 
@@ -294,6 +310,7 @@ pub fn bits_to_usize(bits: &[u8]) -> usize {
 
 /// Converts up to 16 bytes of a little endian byte vector to usize,
 /// from start byte to end byte inclusive.
+#[allow(dead_code)]
 pub fn bytes_to_usize(bytes: &[u8], start_byte: usize, end_byte: usize) -> usize {
     // bytes to bits to vector
     let bits = bytes_to_bits(&bytes);
@@ -341,6 +358,7 @@ pub fn add_bytes_to_word(mut word: [u8; 32], bytes: &[u8], array_position: usize
 #[cfg(test)]
 mod tests {
     use super::*;
+    use zeroize::Zeroize;
 
     #[test]
     fn exp_2_is_powers_of_2() {
@@ -0,0 +1,118 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use tari_bulletproofs::range_proof_mpc::{dealer::Dealer, party::Party};
+use tari_bulletproofs::{BulletproofGens, PedersenGens};
+
+/// An allocator which keeps track of the peak number of bytes allocated.
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+/// Returns the peak number of bytes allocated by `f` on top of the
+/// bytes allocated before it is called.
+fn peak_allocation<F: FnOnce()>(f: F) -> usize {
+    let start = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(start, Ordering::SeqCst);
+    f();
+    PEAK.load(Ordering::SeqCst) - start
+}
+
+#[test]
+fn aggregated_proof_peak_memory() {
+    let (n, m) = (64, 64);
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, m);
+    let not_used = Scalar::default();
+    let mut transcript = Transcript::new(b"MemoryTest");
+
+    let parties: Vec<_> = (0..m as u64)
+        .map(|v| {
+            Party::new(
+                &bp_gens,
+                &pc_gens,
+                v,
+                Scalar::from(v),
+                n,
+                not_used,
+                not_used,
+                not_used,
+            )
+            .unwrap()
+        })
+        .collect();
+    let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+
+    let (parties, bit_commitments): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .enumerate()
+        .map(|(j, p)| p.assign_position(j).unwrap())
+        .unzip();
+    let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
+
+    let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.apply_challenge(&bit_challenge))
+        .unzip();
+    let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments).unwrap();
+
+    let proof_shares: Vec<_> = parties
+        .into_iter()
+        .map(|p| p.apply_challenge(&poly_challenge).unwrap())
+        .collect();
+
+    // Before the inner product prover borrowed the parties' generators
+    // and vectors, assembling the proof allocated about four times the
+    // size of the generators; it now stays under one and a half times.
+    // With the `parallel` feature, the two multiscalar multiplications of
+    // each round can run at the same time, which doubles their buffers,
+    // but the proof still stays under twice the size of the generators.
+    let mut proof = None;
+    let peak =
+        peak_allocation(|| proof = Some(dealer.receive_trusted_shares(&proof_shares).unwrap()));
+    let generators = 2 * n * m * std::mem::size_of::<RistrettoPoint>();
+    let bound = if cfg!(feature = "parallel") {
+        2 * generators
+    } else {
+        3 * generators / 2
+    };
+    assert!(peak < bound, "peak allocation of {} bytes", peak);
+
+    let value_commitments: Vec<_> = (0..m as u64)
+        .map(|v| pc_gens.commit(Scalar::from(v), Scalar::from(v)).compress())
+        .collect();
+    assert!(proof
+        .unwrap()
+        .verify_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"MemoryTest"),
+            &value_commitments,
+            n
+        )
+        .is_ok());
+}
@@ -6,7 +6,7 @@ use merlin::Transcript;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use tari_bulletproofs::r1cs::*;
-use tari_bulletproofs::{BulletproofGens, PedersenGens};
+use tari_bulletproofs::{BatchVerifier, BulletproofGens, PedersenGens, RangeProof};
 
 // Shuffle gadget (documented in markdown file)
 
@@ -359,6 +359,142 @@ fn example_gadget_serialization_test() {
     assert!(example_gadget_roundtrip_serialization_helper(3, 4, 6, 1, 40, 10).is_err());
 }
 
+#[test]
+fn proof_size_estimates() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+
+    // The example gadget has one multiplier and no randomized constraints
+    let (proof, commitments) = example_gadget_proof(&pc_gens, &bp_gens, 3, 4, 6, 1, 40, 9).unwrap();
+    let mut transcript = Transcript::new(b"R1CSExampleGadget");
+    let mut verifier = Verifier::new(&mut transcript);
+    let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+    example_gadget(
+        &mut verifier,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    let metrics = verifier.metrics();
+    assert_eq!(metrics.multipliers, 1);
+    assert_eq!(metrics.commitments, commitments.len());
+    assert_eq!(
+        R1CSProof::serialized_size_for(&metrics, 0),
+        proof.to_bytes().len()
+    );
+    assert_eq!(
+        R1CSProof::verification_msm_size(&metrics, 0),
+        6 + 5 + 5 + 2 + 2
+    );
+
+    // The counts can also be given without building the constraint system
+    let counted = Metrics::new(1, 1, 1, 0, commitments.len());
+    assert_eq!(
+        R1CSProof::serialized_size_for(&counted, 0),
+        proof.to_bytes().len()
+    );
+
+    // The shuffle of k values allocates 2(k-1) multipliers in the randomized phase
+    for k in [2, 5, 9].iter() {
+        let input: Vec<Scalar> = (0..*k).map(|i| Scalar::from(i as u64)).collect();
+        let mut output = input.clone();
+        output.reverse();
+        let mut transcript = Transcript::new(b"ShuffleProofTest");
+        let (proof, in_commitments, out_commitments) =
+            ShuffleProof::prove(&pc_gens, &bp_gens, &mut transcript, &input, &output).unwrap();
+
+        let mut transcript = Transcript::new(b"ShuffleProofTest");
+        let mut verifier = Verifier::new(&mut transcript);
+        let x: Vec<_> = in_commitments.iter().map(|V| verifier.commit(*V)).collect();
+        let y: Vec<_> = out_commitments
+            .iter()
+            .map(|V| verifier.commit(*V))
+            .collect();
+        ShuffleProof::gadget(&mut verifier, x, y).unwrap();
+        let metrics = verifier.metrics();
+        assert_eq!(metrics.multipliers, 0);
+        assert_eq!(metrics.phase_two_constraints, 1);
+        assert_eq!(
+            R1CSProof::serialized_size_for(&metrics, 2 * (k - 1)),
+            proof.0.to_bytes().len()
+        );
+    }
+}
+
+// Adds an example gadget proof verified against each of `c2s` and a
+// range proof to a batch, and passes the batch to `finish`.
+fn example_gadget_batch_helper<R>(c2s: &[u64], finish: impl FnOnce(BatchVerifier) -> R) -> R {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+    let mut rng = thread_rng();
+
+    let (r1cs_proof, commitments) =
+        example_gadget_proof(&pc_gens, &bp_gens, 3, 4, 6, 1, 40, 9).unwrap();
+    let (range_proof, value_commitment) = RangeProof::prove_single(
+        &bp_gens,
+        &pc_gens,
+        &mut Transcript::new(b"BatchVerifierTest"),
+        1037578891,
+        &Scalar::random(&mut rng),
+        32,
+    )
+    .unwrap();
+
+    let mut batch = BatchVerifier::new(&bp_gens, &pc_gens);
+
+    for c2 in c2s {
+        let mut transcript = Transcript::new(b"R1CSExampleGadget");
+        let mut verifier = Verifier::new(&mut transcript);
+        let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+        example_gadget(
+            &mut verifier,
+            vars[0].into(),
+            vars[1].into(),
+            vars[2].into(),
+            vars[3].into(),
+            vars[4].into(),
+            Scalar::from(*c2).into(),
+        );
+        batch.add_r1cs_proof(verifier, &r1cs_proof).unwrap();
+    }
+
+    batch
+        .add_range_proof(
+            &range_proof,
+            &mut Transcript::new(b"BatchVerifierTest"),
+            &[value_commitment],
+            32,
+            &mut rng,
+        )
+        .unwrap();
+    assert_eq!(batch.len(), c2s.len() + 1);
+
+    finish(batch)
+}
+
+#[test]
+fn batch_verify_range_and_r1cs_proofs() {
+    // (3 + 4) * (6 + 1) = (40 + 9)
+    assert!(example_gadget_batch_helper(&[9], |batch| batch.finish()).is_ok());
+    // (3 + 4) * (6 + 1) != (40 + 10), so the whole batch fails
+    assert!(example_gadget_batch_helper(&[10], |batch| batch.finish()).is_err());
+}
+
+#[test]
+fn batch_find_invalid_r1cs_proofs() {
+    assert_eq!(
+        example_gadget_batch_helper(&[9, 9, 9], |batch| batch.find_invalid()),
+        Vec::<usize>::new()
+    );
+    assert_eq!(
+        example_gadget_batch_helper(&[9, 10, 9, 9, 11], |batch| batch.find_invalid()),
+        vec![1, 4]
+    );
+}
+
 // Range Proof gadget
 
 /// Enforces that the quantity of v is in the range [0, 2^n).
@@ -320,3 +320,142 @@ fn range_proof_rewind() {
         Err(ProofError::InvalidCommitmentExtracted)
     );
 }
+
+#[test]
+fn range_proof_rewind_u128() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+
+    let pvt_rewind_key = Scalar::random(&mut thread_rng());
+    let pvt_blinding_key = Scalar::random(&mut thread_rng());
+
+    let confidential_value = u128::MAX - 123456789;
+    let blinding_factor = Scalar::random(&mut thread_rng());
+    // The 128-bit value leaves room for 15 bytes of extra data
+    let proof_message: [u8; 15] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+    let mut prover_transcript = Transcript::new(b"Bulletproof-Rewind Test");
+    let (proof, committed_value) = RangeProof::prove_single_u128_with_rewind_key(
+        &bp_gens,
+        &pc_gens,
+        &mut prover_transcript,
+        confidential_value,
+        &blinding_factor,
+        128,
+        &pvt_rewind_key,
+        &pvt_blinding_key,
+        &proof_message,
+    )
+    .expect("A real program could handle errors");
+
+    let mut verifier_transcript = Transcript::new(b"Bulletproof-Rewind Test");
+    assert!(proof
+        .verify_single(
+            &bp_gens,
+            &pc_gens,
+            &mut verifier_transcript,
+            &committed_value,
+            128
+        )
+        .is_ok());
+
+    let pub_rewind_key_1 =
+        RistrettoPoint::from(&pvt_rewind_key * &RISTRETTO_BASEPOINT_TABLE).compress();
+    let pub_rewind_key_2 =
+        RistrettoPoint::from(&pvt_blinding_key * &RISTRETTO_BASEPOINT_TABLE).compress();
+    let rewind_nonce_1 = get_rewind_nonce_from_pub_key(&pub_rewind_key_1, &committed_value);
+    let rewind_nonce_2 = get_rewind_nonce_from_pub_key(&pub_rewind_key_2, &committed_value);
+    let blinding_nonce_1 = get_secret_nonce_from_pvt_key(&pvt_rewind_key, &committed_value);
+    let blinding_nonce_2 = get_secret_nonce_from_pvt_key(&pvt_blinding_key, &committed_value);
+
+    let mut rewind_transcript = Transcript::new(b"Bulletproof-Rewind Test");
+    assert_eq!(
+        proof.rewind_single_u128_get_value_only(
+            &bp_gens,
+            &mut rewind_transcript,
+            &committed_value,
+            128,
+            &rewind_nonce_1,
+            &rewind_nonce_2,
+        ),
+        Ok((confidential_value, proof_message))
+    );
+
+    let mut rewind_transcript = Transcript::new(b"Bulletproof-Rewind Test");
+    assert_eq!(
+        proof.rewind_single_u128_get_commitment_data(
+            &bp_gens,
+            &pc_gens,
+            &mut rewind_transcript,
+            &committed_value,
+            128,
+            &rewind_nonce_1,
+            &rewind_nonce_2,
+            &blinding_nonce_1,
+            &blinding_nonce_2,
+        ),
+        Ok((confidential_value, blinding_factor, proof_message))
+    );
+
+    // The 64-bit rewind functions cannot hold a 128-bit value
+    let mut rewind_transcript = Transcript::new(b"Bulletproof-Rewind Test");
+    assert_eq!(
+        proof.rewind_single_get_commitment_data(
+            &bp_gens,
+            &pc_gens,
+            &mut rewind_transcript,
+            &committed_value,
+            128,
+            &rewind_nonce_1,
+            &rewind_nonce_2,
+            &blinding_nonce_1,
+            &blinding_nonce_2,
+        ),
+        Err(ProofError::InvalidBitsize)
+    );
+    let mut prover_transcript = Transcript::new(b"Bulletproof-Rewind Test");
+    assert_eq!(
+        RangeProof::prove_single_with_rewind_key(
+            &bp_gens,
+            &pc_gens,
+            &mut prover_transcript,
+            123456789,
+            &blinding_factor,
+            128,
+            &pvt_rewind_key,
+            &pvt_blinding_key,
+            &[0u8; 23],
+        )
+        .unwrap_err(),
+        ProofError::InvalidBitsize
+    );
+
+    // A 128-bit rewindable proof of a smaller bitsize can also be rewound
+    let mut prover_transcript = Transcript::new(b"Bulletproof-Rewind Test");
+    let (proof, committed_value) = RangeProof::prove_single_u128_with_rewind_key(
+        &bp_gens,
+        &pc_gens,
+        &mut prover_transcript,
+        123456789,
+        &blinding_factor,
+        32,
+        &pvt_rewind_key,
+        &pvt_blinding_key,
+        &proof_message,
+    )
+    .expect("A real program could handle errors");
+    let rewind_nonce_1 = get_rewind_nonce_from_pub_key(&pub_rewind_key_1, &committed_value);
+    let rewind_nonce_2 = get_rewind_nonce_from_pub_key(&pub_rewind_key_2, &committed_value);
+    let mut rewind_transcript = Transcript::new(b"Bulletproof-Rewind Test");
+    assert_eq!(
+        proof.rewind_single_u128_get_value_only(
+            &bp_gens,
+            &mut rewind_transcript,
+            &committed_value,
+            32,
+            &rewind_nonce_1,
+            &rewind_nonce_2,
+        ),
+        Ok((123456789, proof_message))
+    );
+}